@@ -1,11 +1,13 @@
 package org.linebender.android.viewdemo;
 
 import android.app.Activity;
+import android.content.Intent;
 import android.os.Bundle;
-import android.view.View;
 import android.widget.FrameLayout;
 
 public final class DemoActivity extends Activity {
+    private DemoView mView;
+
     static {
          System.loadLibrary("main");
     }
@@ -13,7 +15,8 @@ public final class DemoActivity extends Activity {
     @Override
     public void onCreate(Bundle state) {
         super.onCreate(state);
-        View view = new DemoView(this);
+        DemoView view = new DemoView(this);
+        mView = view;
         view.setLayoutParams(
                 new FrameLayout.LayoutParams(
                         FrameLayout.LayoutParams.MATCH_PARENT,
@@ -25,4 +28,31 @@ public final class DemoActivity extends Activity {
         setContentView(layout);
         view.requestFocus();
     }
+
+    @Override
+    public void onMultiWindowModeChanged(boolean isInMultiWindowMode) {
+        super.onMultiWindowModeChanged(isInMultiWindowMode);
+        mView.onMultiWindowModeChanged(isInMultiWindowMode);
+    }
+
+    @Override
+    public void onPictureInPictureModeChanged(boolean isInPictureInPictureMode) {
+        super.onPictureInPictureModeChanged(isInPictureInPictureMode);
+        mView.onPictureInPictureModeChanged(isInPictureInPictureMode);
+    }
+
+    @Override
+    protected void onActivityResult(int requestCode, int resultCode, Intent data) {
+        if (!mView.onActivityResult(requestCode, resultCode, data)) {
+            super.onActivityResult(requestCode, resultCode, data);
+        }
+    }
+
+    @Override
+    public void onRequestPermissionsResult(
+            int requestCode, String[] permissions, int[] grantResults) {
+        if (!mView.onRequestPermissionsResult(requestCode, permissions, grantResults)) {
+            super.onRequestPermissionsResult(requestCode, permissions, grantResults);
+        }
+    }
 }
@@ -0,0 +1,54 @@
+//! Benchmarks for the cache of Java strings returned to keyboards.
+//!
+//! The crate only builds for Android, so build this with
+//! `cargo bench --bench text_cache --target aarch64-linux-android --no-run`
+//! and run the resulting binary on a device with `adb shell`.
+
+use std::{hint::black_box, time::Instant};
+
+#[allow(dead_code)]
+#[path = "../src/text_cache.rs"]
+mod text_cache;
+
+use text_cache::*;
+
+// Stands in for a global reference, counting how many were created.
+fn lookup(cache: &mut TextCache<u32>, created: &mut u32, text: &str) -> bool {
+    match cache.lookup(1, TextQuery::BeforeCursor, text) {
+        TextLookup::Hit(_) => true,
+        TextLookup::Repeat(slot) => {
+            *created += 1;
+            *slot = Some(*created);
+            false
+        }
+        TextLookup::Miss => false,
+    }
+}
+
+fn bench(name: &str, texts: &[String]) {
+    const ROUNDS: usize = 200;
+    let mut cache = TextCache::new();
+    let mut created = 0;
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for text in texts {
+            black_box(lookup(&mut cache, &mut created, black_box(text)));
+        }
+    }
+    let per_lookup = start.elapsed() / (ROUNDS * texts.len()) as u32;
+    println!("{name}: {per_lookup:?} per lookup, {created} strings kept");
+}
+
+fn main() {
+    let paragraph = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+    // A keyboard typically asks for the same text three times per
+    // keystroke.
+    let repeated: Vec<String> = (0..paragraph.len())
+        .flat_map(|n| std::iter::repeat_n(paragraph[..n].to_string(), 3))
+        .collect();
+    bench("repeated queries", &repeated);
+    let changing: Vec<String> = (0..paragraph.len())
+        .map(|n| paragraph[..n].to_string())
+        .collect();
+    bench("changing text", &changing);
+}
@@ -0,0 +1,96 @@
+use android_view::ndk::event::Keycode;
+use bevy::input::keyboard::{Key, KeyCode, NativeKey, NativeKeyCode};
+use smol_str::SmolStr;
+
+pub(crate) fn bevy_key_code(key_code: Keycode, scan_code: u32) -> KeyCode {
+    match key_code {
+        Keycode::DpadUp => KeyCode::ArrowUp,
+        Keycode::DpadDown => KeyCode::ArrowDown,
+        Keycode::DpadLeft => KeyCode::ArrowLeft,
+        Keycode::DpadRight => KeyCode::ArrowRight,
+        Keycode::Enter | Keycode::DpadCenter => KeyCode::Enter,
+        Keycode::NumpadEnter => KeyCode::NumpadEnter,
+        Keycode::Del => KeyCode::Backspace,
+        Keycode::ForwardDel => KeyCode::Delete,
+        Keycode::Tab => KeyCode::Tab,
+        Keycode::Escape => KeyCode::Escape,
+        Keycode::Space => KeyCode::Space,
+        Keycode::MoveHome => KeyCode::Home,
+        Keycode::MoveEnd => KeyCode::End,
+        Keycode::PageUp => KeyCode::PageUp,
+        Keycode::PageDown => KeyCode::PageDown,
+        Keycode::Insert => KeyCode::Insert,
+        Keycode::ShiftLeft => KeyCode::ShiftLeft,
+        Keycode::ShiftRight => KeyCode::ShiftRight,
+        Keycode::CtrlLeft => KeyCode::ControlLeft,
+        Keycode::CtrlRight => KeyCode::ControlRight,
+        Keycode::AltLeft => KeyCode::AltLeft,
+        Keycode::AltRight => KeyCode::AltRight,
+        Keycode::MetaLeft => KeyCode::SuperLeft,
+        Keycode::MetaRight => KeyCode::SuperRight,
+        Keycode::Keycode0 => KeyCode::Digit0,
+        Keycode::Keycode1 => KeyCode::Digit1,
+        Keycode::Keycode2 => KeyCode::Digit2,
+        Keycode::Keycode3 => KeyCode::Digit3,
+        Keycode::Keycode4 => KeyCode::Digit4,
+        Keycode::Keycode5 => KeyCode::Digit5,
+        Keycode::Keycode6 => KeyCode::Digit6,
+        Keycode::Keycode7 => KeyCode::Digit7,
+        Keycode::Keycode8 => KeyCode::Digit8,
+        Keycode::Keycode9 => KeyCode::Digit9,
+        Keycode::A => KeyCode::KeyA,
+        Keycode::B => KeyCode::KeyB,
+        Keycode::C => KeyCode::KeyC,
+        Keycode::D => KeyCode::KeyD,
+        Keycode::E => KeyCode::KeyE,
+        Keycode::F => KeyCode::KeyF,
+        Keycode::G => KeyCode::KeyG,
+        Keycode::H => KeyCode::KeyH,
+        Keycode::I => KeyCode::KeyI,
+        Keycode::J => KeyCode::KeyJ,
+        Keycode::K => KeyCode::KeyK,
+        Keycode::L => KeyCode::KeyL,
+        Keycode::M => KeyCode::KeyM,
+        Keycode::N => KeyCode::KeyN,
+        Keycode::O => KeyCode::KeyO,
+        Keycode::P => KeyCode::KeyP,
+        Keycode::Q => KeyCode::KeyQ,
+        Keycode::R => KeyCode::KeyR,
+        Keycode::S => KeyCode::KeyS,
+        Keycode::T => KeyCode::KeyT,
+        Keycode::U => KeyCode::KeyU,
+        Keycode::V => KeyCode::KeyV,
+        Keycode::W => KeyCode::KeyW,
+        Keycode::X => KeyCode::KeyX,
+        Keycode::Y => KeyCode::KeyY,
+        Keycode::Z => KeyCode::KeyZ,
+        _ => KeyCode::Unidentified(NativeKeyCode::Android(scan_code)),
+    }
+}
+
+pub(crate) fn bevy_logical_key(key_code: Keycode, c: Option<char>, scan_code: u32) -> Key {
+    match key_code {
+        Keycode::DpadUp => Key::ArrowUp,
+        Keycode::DpadDown => Key::ArrowDown,
+        Keycode::DpadLeft => Key::ArrowLeft,
+        Keycode::DpadRight => Key::ArrowRight,
+        Keycode::Enter | Keycode::NumpadEnter | Keycode::DpadCenter => Key::Enter,
+        Keycode::Del => Key::Backspace,
+        Keycode::ForwardDel => Key::Delete,
+        Keycode::Tab => Key::Tab,
+        Keycode::Escape => Key::Escape,
+        Keycode::Space => Key::Space,
+        Keycode::MoveHome => Key::Home,
+        Keycode::MoveEnd => Key::End,
+        Keycode::PageUp => Key::PageUp,
+        Keycode::PageDown => Key::PageDown,
+        Keycode::ShiftLeft | Keycode::ShiftRight => Key::Shift,
+        Keycode::CtrlLeft | Keycode::CtrlRight => Key::Control,
+        Keycode::AltLeft | Keycode::AltRight => Key::Alt,
+        Keycode::MetaLeft | Keycode::MetaRight => Key::Super,
+        _ => match c.filter(|c| !c.is_control()) {
+            Some(c) => Key::Character(SmolStr::new(c.to_string())),
+            None => Key::Unidentified(NativeKey::Android(scan_code)),
+        },
+    }
+}
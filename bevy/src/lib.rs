@@ -0,0 +1,477 @@
+//! Host a Bevy [`App`] in an Android view, so that a Bevy scene can be
+//! part of an ordinary layout rather than taking over a whole
+//! `NativeActivity`.
+//!
+//! Build the app as usual, with Bevy's `bevy_winit` feature off, and pass
+//! it to [`new_view_peer`] from the view class's `newViewPeer`
+//! implementation. The view's surface becomes the primary window, and the
+//! app is updated once per frame on the UI thread while the surface
+//! exists. Touch, key, focus and IME callbacks become the same Bevy events
+//! that `bevy_winit` sends, and setting [`Window::ime_enabled`] shows the
+//! soft keyboard.
+//!
+//! Bevy's renderer finishes initializing only once it has a window, so the
+//! app's `Startup` systems run on the first frame after the surface is
+//! created.
+
+use android_view::{
+    jni::{
+        JNIEnv,
+        sys::{jint, jlong},
+    },
+    ndk::event::{Axis, KeyAction, Keycode, MotionAction},
+    *,
+};
+use bevy::{
+    app::{App, PluginsState},
+    ecs::{entity::Entity, query::With},
+    input::{
+        ButtonState,
+        keyboard::KeyboardInput,
+        touch::{ForceTouch, TouchInput, TouchPhase},
+    },
+    math::Vec2,
+    window::{
+        Ime, PrimaryWindow, RawHandleWrapper, RawHandleWrapperHolder, Window, WindowFocused,
+        WindowResized, WindowWrapper,
+    },
+};
+use std::borrow::Cow;
+
+mod keys;
+use keys::*;
+
+fn scale_factor<'local>(env: &mut JNIEnv<'local>, android_ctx: &Context<'local>) -> f32 {
+    let res = android_ctx.resources(env);
+    let metrics = res.display_metrics(env);
+    metrics.density(env)
+}
+
+fn show_soft_input<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) {
+    let imm = view.input_method_manager(env);
+    imm.restart_input(env, view);
+    imm.show_soft_input(env, view, 0);
+}
+
+fn hide_soft_input<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) {
+    let imm = view.input_method_manager(env);
+    if let Some(window_token) = view.window_token(env) {
+        imm.hide_soft_input_from_window(env, &window_token, 0);
+    }
+}
+
+struct BevyViewPeer {
+    app: App,
+    window: Entity,
+    handle_holder: RawHandleWrapperHolder,
+    // Whether the plugins have finished building, after which the app
+    // can be updated.
+    running: bool,
+    has_surface: bool,
+    scale_factor: f32,
+    soft_input_shown: bool,
+    // The text being composed by the keyboard, if any.
+    composing: Option<String>,
+}
+
+impl BevyViewPeer {
+    fn position(&self, x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y) / self.scale_factor
+    }
+
+    fn window_mut(&mut self) -> bevy::ecs::world::Mut<'_, Window> {
+        self.app.world_mut().get_mut::<Window>(self.window).unwrap()
+    }
+
+    fn update(&mut self, ctx: &mut CallbackCtx) {
+        if !self.running {
+            // Plugins such as the renderer finish building asynchronously,
+            // and the renderer waits for the window.
+            match self.app.plugins_state() {
+                PluginsState::Adding => return,
+                PluginsState::Ready => {
+                    self.app.finish();
+                    self.app.cleanup();
+                }
+                PluginsState::Finished => self.app.cleanup(),
+                PluginsState::Cleaned => {}
+            }
+            self.running = true;
+        }
+        self.app.update();
+        if self.app.should_exit().is_some() {
+            // There's no activity to finish, so the view just stops
+            // updating; the app should remove it from the layout.
+            ctx.view.remove_frame_callback(&mut ctx.env);
+            self.has_surface = false;
+            return;
+        }
+
+        let ime_enabled = self.window_mut().ime_enabled;
+        if ime_enabled != self.soft_input_shown {
+            self.soft_input_shown = ime_enabled;
+            self.composing = None;
+            if ime_enabled {
+                ctx.push_static_deferred_callback(show_soft_input);
+            } else {
+                ctx.push_static_deferred_callback(hide_soft_input);
+            }
+        }
+    }
+
+    fn on_key_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        if matches!(
+            key_code,
+            Keycode::Back | Keycode::VolumeUp | Keycode::VolumeDown | Keycode::VolumeMute
+        ) {
+            return false;
+        }
+        let state = match event.action(&mut ctx.env) {
+            KeyAction::Down => ButtonState::Pressed,
+            KeyAction::Up => ButtonState::Released,
+            _ => return false,
+        };
+        let repeat = event.repeat_count(&mut ctx.env) > 0;
+        let scan_code = event.scan_code(&mut ctx.env) as u32;
+        let c = event.unicode_char(&mut ctx.env);
+        let logical_key = bevy_logical_key(key_code, c, scan_code);
+        let text = match (&logical_key, state) {
+            (bevy::input::keyboard::Key::Character(text), ButtonState::Pressed) => {
+                Some(text.clone())
+            }
+            _ => None,
+        };
+        self.app.world_mut().send_event(KeyboardInput {
+            key_code: bevy_key_code(key_code, scan_code),
+            logical_key,
+            state,
+            text,
+            repeat,
+            window: self.window,
+        });
+        true
+    }
+
+    fn send_ime(&mut self, ime: Ime) {
+        self.app.world_mut().send_event(ime);
+    }
+
+    fn commit(&mut self, text: &str) {
+        let window = self.window;
+        if self.composing.take().is_none() {
+            self.send_ime(Ime::Enabled { window });
+        }
+        self.send_ime(Ime::Commit {
+            window,
+            value: text.to_string(),
+        });
+    }
+}
+
+impl ViewPeer for BevyViewPeer {
+    fn on_key_down<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        self.on_key_event(ctx, key_code, event)
+    }
+
+    fn on_key_up<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        self.on_key_event(ctx, key_code, event)
+    }
+
+    fn on_touch_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        let phase = match event.action_masked(&mut ctx.env) {
+            MotionAction::Down | MotionAction::PointerDown => TouchPhase::Started,
+            MotionAction::Up | MotionAction::PointerUp => TouchPhase::Ended,
+            MotionAction::Move => TouchPhase::Moved,
+            MotionAction::Cancel => TouchPhase::Canceled,
+            _ => return false,
+        };
+        let action_index = event.action_index(&mut ctx.env);
+        // Only the pointer that went down or up changes phase; the others
+        // are reported as moved, as `bevy_winit` does.
+        for index in 0..event.pointer_count(&mut ctx.env) {
+            let phase = match phase {
+                TouchPhase::Started | TouchPhase::Ended if index != action_index => {
+                    TouchPhase::Moved
+                }
+                phase => phase,
+            };
+            let id = event.pointer_id(&mut ctx.env, index);
+            let x = event.x_at(&mut ctx.env, index);
+            let y = event.y_at(&mut ctx.env, index);
+            let pressure = event.axis(&mut ctx.env, Axis::Pressure, index);
+            let touch = TouchInput {
+                phase,
+                position: self.position(x, y),
+                window: self.window,
+                force: Some(ForceTouch::Normalized(pressure as f64)),
+                id: id as u64,
+            };
+            self.app.world_mut().send_event(touch);
+        }
+        true
+    }
+
+    fn on_focus_changed<'local>(
+        &mut self,
+        _ctx: &mut CallbackCtx<'local>,
+        gain_focus: bool,
+        _direction: jint,
+        _previously_focused_rect: Option<&Rect<'local>>,
+    ) {
+        self.window_mut().focused = gain_focus;
+        let window = self.window;
+        self.app.world_mut().send_event(WindowFocused {
+            window,
+            focused: gain_focus,
+        });
+    }
+
+    fn surface_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+        _format: jint,
+        width: jint,
+        height: jint,
+    ) {
+        let android_ctx = ctx.view.context(&mut ctx.env);
+        self.scale_factor = scale_factor(&mut ctx.env, &android_ctx);
+        let native_window = holder.surface(&mut ctx.env).to_native_window(&mut ctx.env);
+        let handle = RawHandleWrapper::new(&WindowWrapper::new(ViewWindow::new(native_window)))
+            .expect("Error getting window handle");
+        *self.handle_holder.0.lock().unwrap() = Some(handle.clone());
+        let scale_factor = self.scale_factor;
+        let mut window = self.window_mut();
+        window.resolution.set_scale_factor(scale_factor);
+        window
+            .resolution
+            .set_physical_resolution(width as u32, height as u32);
+        let (width, height) = (window.width(), window.height());
+        let entity = self.window;
+        let world = self.app.world_mut();
+        world.entity_mut(entity).insert(handle);
+        world.send_event(WindowResized {
+            window: entity,
+            width,
+            height,
+        });
+        self.has_surface = true;
+        ctx.view.post_frame_callback(&mut ctx.env);
+    }
+
+    fn surface_destroyed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        _holder: &SurfaceHolder<'local>,
+    ) {
+        // As `bevy_winit` does when the app is suspended, remove the
+        // handle, and update once so the renderer drops its surface
+        // before the window goes away.
+        *self.handle_holder.0.lock().unwrap() = None;
+        let entity = self.window;
+        self.app
+            .world_mut()
+            .entity_mut(entity)
+            .remove::<RawHandleWrapper>();
+        self.has_surface = false;
+        ctx.view.remove_frame_callback(&mut ctx.env);
+        if self.running {
+            self.app.update();
+        }
+    }
+
+    fn do_frame(&mut self, ctx: &mut CallbackCtx, _frame_time_nanos: jlong) {
+        if !self.has_surface {
+            return;
+        }
+        self.update(ctx);
+        if self.has_surface {
+            ctx.view.post_frame_callback(&mut ctx.env);
+        }
+    }
+
+    fn as_input_connection(&mut self) -> Option<&mut dyn InputConnection> {
+        Some(self)
+    }
+}
+
+impl InputConnection for BevyViewPeer {
+    fn on_create_input_connection<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        out_attrs: &EditorInfo<'local>,
+    ) {
+        out_attrs.populate(
+            &mut ctx.env,
+            &EditorAttributes {
+                input_type: InputType::CLASS_TEXT,
+                ime_options: ImeOptions::FLAG_NO_FULLSCREEN | ImeOptions::FLAG_NO_EXTRACT_UI,
+                ..Default::default()
+            },
+        );
+        self.composing = None;
+    }
+
+    fn text_before_cursor<'slf>(
+        &'slf mut self,
+        _ctx: &mut CallbackCtx,
+        _n: jint,
+    ) -> Option<Cow<'slf, str>> {
+        Some(Cow::Borrowed(self.composing.as_deref().unwrap_or("")))
+    }
+
+    fn text_after_cursor<'slf>(
+        &'slf mut self,
+        _ctx: &mut CallbackCtx,
+        _n: jint,
+    ) -> Option<Cow<'slf, str>> {
+        Some(Cow::Borrowed(""))
+    }
+
+    fn selected_text<'slf>(&'slf mut self, _ctx: &mut CallbackCtx) -> Option<Cow<'slf, str>> {
+        None
+    }
+
+    fn cursor_caps_mode(&mut self, _ctx: &mut CallbackCtx, _req_modes: CapsMode) -> CapsMode {
+        CapsMode::empty()
+    }
+
+    fn delete_surrounding_text(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        _before_length: jint,
+        _after_length: jint,
+    ) -> bool {
+        // Bevy's IME events have no way to delete committed text, so
+        // keyboards fall back to sending backspace key events.
+        false
+    }
+
+    fn delete_surrounding_text_in_code_points(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        _before_length: jint,
+        _after_length: jint,
+    ) -> bool {
+        false
+    }
+
+    fn set_composing_text(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        text: &str,
+        _new_cursor_position: jint,
+    ) -> bool {
+        let window = self.window;
+        if self.composing.is_none() {
+            self.send_ime(Ime::Enabled { window });
+        }
+        self.composing = Some(text.to_string());
+        let cursor = (!text.is_empty()).then_some((text.len(), text.len()));
+        self.send_ime(Ime::Preedit {
+            window,
+            value: text.to_string(),
+            cursor,
+        });
+        true
+    }
+
+    fn set_composing_region(&mut self, _ctx: &mut CallbackCtx, _start: jint, _end: jint) -> bool {
+        false
+    }
+
+    fn finish_composing_text(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        if let Some(text) = self.composing.clone() {
+            self.commit(&text);
+        }
+        true
+    }
+
+    fn commit_text(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        text: &str,
+        _new_cursor_position: jint,
+    ) -> bool {
+        self.commit(text);
+        true
+    }
+
+    fn set_selection(&mut self, _ctx: &mut CallbackCtx, _start: jint, _end: jint) -> bool {
+        false
+    }
+
+    fn perform_editor_action(&mut self, _ctx: &mut CallbackCtx, _editor_action: jint) -> bool {
+        false
+    }
+
+    fn begin_batch_edit(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        true
+    }
+
+    fn end_batch_edit(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        false
+    }
+
+    fn send_key_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        let key_code = event.key_code(&mut ctx.env);
+        self.on_key_event(ctx, key_code, event)
+    }
+
+    fn request_cursor_updates(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        _cursor_update_mode: jint,
+    ) -> bool {
+        false
+    }
+}
+
+/// Register a peer that hosts `app`, for the view class's `newViewPeer`
+/// implementation. The app must have a primary window, as Bevy's
+/// `WindowPlugin` creates by default.
+pub fn new_view_peer<'local>(
+    env: &mut JNIEnv<'local>,
+    android_ctx: &Context<'local>,
+    mut app: App,
+) -> jlong {
+    let world = app.world_mut();
+    let (window, handle_holder) = world
+        .query_filtered::<(Entity, &RawHandleWrapperHolder), With<PrimaryWindow>>()
+        .single(world)
+        .map(|(entity, holder)| (entity, holder.clone()))
+        .expect("The app has no primary window");
+    register_view_peer(BevyViewPeer {
+        app,
+        window,
+        handle_holder,
+        running: false,
+        has_surface: false,
+        scale_factor: scale_factor(env, android_ctx),
+        soft_input_shown: false,
+        composing: None,
+    })
+}
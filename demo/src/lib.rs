@@ -327,6 +327,18 @@ impl DemoViewPeer {
         let mut drv = self.editor.driver();
         drv.move_to_byte(cursor_pos);
     }
+
+    fn apply_deletion(&mut self, deletion: SurroundingTextDeletion) {
+        // Delete after the selection first, so the range before it
+        // stays valid.
+        let mut drv = self.editor.driver();
+        if let Some(len) = NonZeroUsize::new(deletion.after.len()) {
+            drv.delete_bytes_after_selection(len);
+        }
+        if let Some(len) = NonZeroUsize::new(deletion.before.len()) {
+            drv.delete_bytes_before_selection(len);
+        }
+    }
 }
 
 impl ViewPeer for DemoViewPeer {
@@ -585,25 +597,26 @@ impl InputConnection for DemoViewPeer {
         ctx: &mut CallbackCtx<'local>,
         out_attrs: &EditorInfo<'local>,
     ) {
-        out_attrs.set_input_type(
-            &mut ctx.env,
-            INPUT_TYPE_CLASS_TEXT
-                | INPUT_TYPE_TEXT_FLAG_CAP_SENTENCES
-                | INPUT_TYPE_TEXT_FLAG_AUTO_CORRECT
-                | INPUT_TYPE_TEXT_FLAG_MULTI_LINE,
-        );
-        out_attrs.set_ime_options(
-            &mut ctx.env,
-            IME_FLAG_NO_FULLSCREEN | IME_FLAG_NO_EXTRACT_UI | IME_FLAG_NO_ENTER_ACTION,
-        );
         let selection = self.editor.editor().raw_selection().text_range();
         let sel_start = self.editor.utf8_to_utf16_index(selection.start);
         let sel_end = self.editor.utf8_to_utf16_index(selection.end);
-        out_attrs.set_initial_sel_start(&mut ctx.env, sel_start as jint);
-        out_attrs.set_initial_sel_end(&mut ctx.env, sel_end as jint);
         let text = self.editor.editor().raw_text();
-        let initial_caps_mode = caps_mode(&mut ctx.env, text, sel_start, CAP_MODE_SENTENCES);
-        out_attrs.set_initial_caps_mode(&mut ctx.env, initial_caps_mode);
+        let initial_caps_mode = caps_mode(&mut ctx.env, text, sel_start, CapsMode::SENTENCES);
+        out_attrs.populate(
+            &mut ctx.env,
+            &EditorAttributes {
+                input_type: InputType::CLASS_TEXT
+                    | InputType::TEXT_FLAG_CAP_SENTENCES
+                    | InputType::TEXT_FLAG_AUTO_CORRECT
+                    | InputType::TEXT_FLAG_MULTI_LINE,
+                ime_options: ImeOptions::FLAG_NO_FULLSCREEN
+                    | ImeOptions::FLAG_NO_EXTRACT_UI
+                    | ImeOptions::FLAG_NO_ENTER_ACTION,
+                initial_selection: Some((sel_start as jint, sel_end as jint)),
+                initial_caps_mode,
+                ..Default::default()
+            },
+        );
         self.editor.driver().clear_compose();
         self.enqueue_render_if_needed(ctx);
         self.ime_active = true;
@@ -658,7 +671,7 @@ impl InputConnection for DemoViewPeer {
         self.editor.editor().selected_text().map(Cow::Borrowed)
     }
 
-    fn cursor_caps_mode(&mut self, ctx: &mut CallbackCtx, req_modes: u32) -> u32 {
+    fn cursor_caps_mode(&mut self, ctx: &mut CallbackCtx, req_modes: CapsMode) -> CapsMode {
         let editor = self.editor.editor();
         let text = editor.raw_text();
         let offset = editor.raw_selection().focus().index();
@@ -672,31 +685,14 @@ impl InputConnection for DemoViewPeer {
         before_length: jint,
         after_length: jint,
     ) -> bool {
-        if before_length > 0 {
-            let sel_range = self.editor.editor().raw_selection().text_range();
-            let sel_start_utf16 = self.editor.utf8_to_utf16_index(sel_range.start);
-            let before_start_utf16 = sel_start_utf16.saturating_sub(before_length as usize);
-            let before_start = self.editor.utf16_to_utf8_index(before_start_utf16);
-            if let Some(len) = NonZeroUsize::new(sel_range.start - before_start) {
-                let mut drv = self.editor.driver();
-                drv.delete_bytes_before_selection(len);
-            }
-        }
-        if after_length > 0 {
-            let sel_range = self.editor.editor().raw_selection().text_range();
-            let sel_end_utf16 = self.editor.utf8_to_utf16_index(sel_range.end);
-            let len_utf16 = self
-                .editor
-                .utf8_to_utf16_index(self.editor.editor().raw_text().len());
-            let after_end_utf16 = sel_end_utf16
-                .saturating_add(after_length as usize)
-                .min(len_utf16);
-            let after_end = self.editor.utf16_to_utf8_index(after_end_utf16);
-            if let Some(len) = NonZeroUsize::new(after_end - sel_range.end) {
-                let mut drv = self.editor.driver();
-                drv.delete_bytes_after_selection(len);
-            }
-        }
+        let editor = self.editor.editor();
+        let deletion = surrounding_text_deletion(
+            editor.raw_text(),
+            editor.raw_selection().text_range(),
+            before_length,
+            after_length,
+        );
+        self.apply_deletion(deletion);
         self.enqueue_render_if_needed(ctx);
         true
     }
@@ -707,31 +703,14 @@ impl InputConnection for DemoViewPeer {
         before_length: jint,
         after_length: jint,
     ) -> bool {
-        if before_length > 0 {
-            let sel_range = self.editor.editor().raw_selection().text_range();
-            let sel_start_usv = self.editor.utf8_to_usv_index(sel_range.start);
-            let before_start_usv = sel_start_usv.saturating_sub(before_length as usize);
-            let before_start = self.editor.usv_to_utf8_index(before_start_usv);
-            if let Some(len) = NonZeroUsize::new(sel_range.start - before_start) {
-                let mut drv = self.editor.driver();
-                drv.delete_bytes_before_selection(len);
-            }
-        }
-        if after_length > 0 {
-            let sel_range = self.editor.editor().raw_selection().text_range();
-            let sel_end_usv = self.editor.utf8_to_usv_index(sel_range.end);
-            let len_usv = self
-                .editor
-                .utf8_to_usv_index(self.editor.editor().raw_text().len());
-            let after_end_usv = sel_end_usv
-                .saturating_add(after_length as usize)
-                .min(len_usv);
-            let after_end = self.editor.usv_to_utf8_index(after_end_usv);
-            if let Some(len) = NonZeroUsize::new(after_end - sel_range.end) {
-                let mut drv = self.editor.driver();
-                drv.delete_bytes_after_selection(len);
-            }
-        }
+        let editor = self.editor.editor();
+        let deletion = surrounding_text_deletion_in_code_points(
+            editor.raw_text(),
+            editor.raw_selection().text_range(),
+            before_length,
+            after_length,
+        );
+        self.apply_deletion(deletion);
         self.enqueue_render_if_needed(ctx);
         true
     }
@@ -889,6 +868,7 @@ pub unsafe extern "system" fn JNI_OnLoad(vm: *mut RawJavaVM, _: *mut c_void) ->
         &mut env,
         "org/linebender/android/viewdemo/DemoView",
         new_view_peer,
+        Vec::new(),
     );
     JNI_VERSION_1_6
 }
@@ -96,32 +96,6 @@ impl Editor {
         utf8_len_so_far
     }
 
-    pub fn utf8_to_usv_index(&self, utf8_index: usize) -> usize {
-        let mut usv_len_so_far = 0usize;
-        let mut utf8_len_so_far = 0usize;
-        for c in self.editor.raw_text().chars() {
-            if utf8_len_so_far >= utf8_index {
-                break;
-            }
-            usv_len_so_far += 1;
-            utf8_len_so_far += c.len_utf8();
-        }
-        usv_len_so_far
-    }
-
-    pub fn usv_to_utf8_index(&self, usv_index: usize) -> usize {
-        let mut usv_len_so_far = 0usize;
-        let mut utf8_len_so_far = 0usize;
-        for c in self.editor.raw_text().chars() {
-            if usv_len_so_far >= usv_index {
-                break;
-            }
-            usv_len_so_far += 1;
-            utf8_len_so_far += c.len_utf8();
-        }
-        utf8_len_so_far
-    }
-
     pub fn cursor_reset(&mut self) {
         self.start_time = Some(Instant::now());
         // TODO: for real world use, this should be reading from the system settings
@@ -0,0 +1,69 @@
+use android_view::ndk::event::{Keycode, MetaState};
+use egui::{Key, Modifiers};
+
+pub(crate) fn egui_key(key_code: Keycode) -> Option<Key> {
+    Some(match key_code {
+        Keycode::DpadUp => Key::ArrowUp,
+        Keycode::DpadDown => Key::ArrowDown,
+        Keycode::DpadLeft => Key::ArrowLeft,
+        Keycode::DpadRight => Key::ArrowRight,
+        Keycode::Enter | Keycode::NumpadEnter | Keycode::DpadCenter => Key::Enter,
+        Keycode::Del => Key::Backspace,
+        Keycode::ForwardDel => Key::Delete,
+        Keycode::Tab => Key::Tab,
+        Keycode::Escape => Key::Escape,
+        Keycode::Space => Key::Space,
+        Keycode::MoveHome => Key::Home,
+        Keycode::MoveEnd => Key::End,
+        Keycode::PageUp => Key::PageUp,
+        Keycode::PageDown => Key::PageDown,
+        Keycode::Insert => Key::Insert,
+        Keycode::Keycode0 => Key::Num0,
+        Keycode::Keycode1 => Key::Num1,
+        Keycode::Keycode2 => Key::Num2,
+        Keycode::Keycode3 => Key::Num3,
+        Keycode::Keycode4 => Key::Num4,
+        Keycode::Keycode5 => Key::Num5,
+        Keycode::Keycode6 => Key::Num6,
+        Keycode::Keycode7 => Key::Num7,
+        Keycode::Keycode8 => Key::Num8,
+        Keycode::Keycode9 => Key::Num9,
+        Keycode::A => Key::A,
+        Keycode::B => Key::B,
+        Keycode::C => Key::C,
+        Keycode::D => Key::D,
+        Keycode::E => Key::E,
+        Keycode::F => Key::F,
+        Keycode::G => Key::G,
+        Keycode::H => Key::H,
+        Keycode::I => Key::I,
+        Keycode::J => Key::J,
+        Keycode::K => Key::K,
+        Keycode::L => Key::L,
+        Keycode::M => Key::M,
+        Keycode::N => Key::N,
+        Keycode::O => Key::O,
+        Keycode::P => Key::P,
+        Keycode::Q => Key::Q,
+        Keycode::R => Key::R,
+        Keycode::S => Key::S,
+        Keycode::T => Key::T,
+        Keycode::U => Key::U,
+        Keycode::V => Key::V,
+        Keycode::W => Key::W,
+        Keycode::X => Key::X,
+        Keycode::Y => Key::Y,
+        Keycode::Z => Key::Z,
+        _ => return None,
+    })
+}
+
+pub(crate) fn egui_modifiers(meta_state: MetaState) -> Modifiers {
+    Modifiers {
+        alt: meta_state.alt_on(),
+        ctrl: meta_state.ctrl_on(),
+        shift: meta_state.shift_on(),
+        mac_cmd: false,
+        command: meta_state.ctrl_on(),
+    }
+}
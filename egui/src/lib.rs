@@ -0,0 +1,657 @@
+//! Run an [`egui`] app in an Android view, rendering with `wgpu` through
+//! `egui-wgpu`.
+//!
+//! Implement [`EguiApp`], or pass a closure, and return the result of
+//! [`new_view_peer`] from the view class's `newViewPeer` implementation.
+//! Touch, hover, scroll and key callbacks become `egui` input events.
+//! When a text field has focus, the soft keyboard is shown, and its edits
+//! arrive through the view's `InputConnection` as `egui` IME events.
+//!
+//! The view doesn't expose the text field's contents to the keyboard, since
+//! `egui` doesn't make them available; keyboards fall back to key events
+//! for deletion, and their suggestions only see the word being composed.
+
+use android_view::{
+    jni::{
+        JNIEnv,
+        sys::{jint, jlong},
+    },
+    ndk::event::{Axis, KeyAction, Keycode, MotionAction},
+    *,
+};
+use egui::{Event, ImeEvent, PointerButton, Pos2, RawInput, TouchPhase, ViewportId};
+use egui_wgpu::wgpu;
+use std::{borrow::Cow, time::Instant};
+
+mod keys;
+use keys::*;
+
+/// An app built with `egui`.
+pub trait EguiApp {
+    /// Build the UI for one frame.
+    fn update(&mut self, ctx: &egui::Context);
+}
+
+impl<F: FnMut(&egui::Context)> EguiApp for F {
+    fn update(&mut self, ctx: &egui::Context) {
+        self(ctx)
+    }
+}
+
+fn scale_factor<'local>(env: &mut JNIEnv<'local>, android_ctx: &Context<'local>) -> f32 {
+    let res = android_ctx.resources(env);
+    let metrics = res.display_metrics(env);
+    metrics.density(env)
+}
+
+fn show_soft_input<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) {
+    let imm = view.input_method_manager(env);
+    imm.restart_input(env, view);
+    imm.show_soft_input(env, view, 0);
+}
+
+fn hide_soft_input<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) {
+    let imm = view.input_method_manager(env);
+    if let Some(window_token) = view.window_token(env) {
+        imm.hide_soft_input_from_window(env, &window_token, 0);
+    }
+}
+
+struct RenderState {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    renderer: egui_wgpu::Renderer,
+}
+
+impl RenderState {
+    fn new(instance: &wgpu::Instance, window: ViewWindow, width: u32, height: u32) -> Self {
+        let surface = instance
+            .create_surface(window)
+            .expect("Error creating surface");
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            compatible_surface: Some(&surface),
+            ..Default::default()
+        }))
+        .expect("No compatible adapter");
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+                .expect("No compatible device");
+        let mut config = surface
+            .get_default_config(&adapter, width, height)
+            .expect("Surface isn't supported by the adapter");
+        // egui blends in gamma space, so it wants a non-sRGB target.
+        let capabilities = surface.get_capabilities(&adapter);
+        if let Some(format) = capabilities.formats.iter().find(|format| !format.is_srgb()) {
+            config.format = *format;
+        }
+        surface.configure(&device, &config);
+        let renderer = egui_wgpu::Renderer::new(&device, config.format, None, 1, false);
+        Self {
+            device,
+            queue,
+            surface,
+            config,
+            renderer,
+        }
+    }
+
+    fn render(&mut self, ctx: &egui::Context, output: egui::FullOutput) {
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [self.config.width, self.config.height],
+            pixels_per_point: output.pixels_per_point,
+        };
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer
+                .update_texture(&self.device, &self.queue, *id, delta);
+        }
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("egui"),
+            });
+        let command_buffers = self.renderer.update_buffers(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &paint_jobs,
+            &screen,
+        );
+        let frame = self
+            .surface
+            .get_current_texture()
+            .expect("failed to get surface texture");
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        {
+            let mut pass = encoder
+                .begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("egui"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                })
+                .forget_lifetime();
+            self.renderer.render(&mut pass, &paint_jobs, &screen);
+        }
+        self.queue
+            .submit(command_buffers.into_iter().chain([encoder.finish()]));
+        frame.present();
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
+
+struct EguiViewPeer<App: EguiApp> {
+    app: App,
+    egui_ctx: egui::Context,
+    instance: wgpu::Instance,
+    render_state: Option<RenderState>,
+    raw_input: RawInput,
+    start_time: Instant,
+    pixels_per_point: f32,
+    // The pointer that egui sees as the mouse, which is the first finger
+    // down until it's lifted.
+    primary_pointer: Option<jint>,
+    soft_input_shown: bool,
+    // The text being composed by the keyboard, if any.
+    composing: Option<String>,
+}
+
+impl<App: EguiApp> EguiViewPeer<App> {
+    fn pos(&self, x: f32, y: f32) -> Pos2 {
+        Pos2::new(x / self.pixels_per_point, y / self.pixels_per_point)
+    }
+
+    fn push_event(&mut self, ctx: &mut CallbackCtx, event: Event) {
+        self.raw_input.events.push(event);
+        if self.render_state.is_some() {
+            ctx.view.post_frame_callback(&mut ctx.env);
+        }
+    }
+
+    fn redraw(&mut self, ctx: &mut CallbackCtx) {
+        let Some(render_state) = &mut self.render_state else {
+            return;
+        };
+        let mut raw_input = self.raw_input.take();
+        raw_input.time = Some(self.start_time.elapsed().as_secs_f64());
+        raw_input.screen_rect = Some(egui::Rect::from_min_size(
+            Pos2::ZERO,
+            egui::vec2(
+                render_state.config.width as f32,
+                render_state.config.height as f32,
+            ) / self.pixels_per_point,
+        ));
+        raw_input
+            .viewports
+            .entry(ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+        let app = &mut self.app;
+        let output = self
+            .egui_ctx
+            .run(raw_input, |egui_ctx| app.update(egui_ctx));
+
+        let wants_soft_input = output.platform_output.ime.is_some();
+        if wants_soft_input != self.soft_input_shown {
+            self.soft_input_shown = wants_soft_input;
+            self.composing = None;
+            if wants_soft_input {
+                ctx.push_static_deferred_callback(show_soft_input);
+            } else {
+                ctx.push_static_deferred_callback(hide_soft_input);
+            }
+        }
+
+        let repaint_delay = output
+            .viewport_output
+            .get(&ViewportId::ROOT)
+            .map(|viewport| viewport.repaint_delay);
+        render_state.render(&self.egui_ctx, output);
+        match repaint_delay {
+            Some(delay) if delay.is_zero() => ctx.view.post_frame_callback(&mut ctx.env),
+            Some(delay) if delay.as_secs() < 60 * 60 => {
+                ctx.view.remove_delayed_callbacks(&mut ctx.env);
+                ctx.view
+                    .post_delayed(&mut ctx.env, delay.as_millis() as jlong);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_key_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        let pressed = event.action(&mut ctx.env) == KeyAction::Down;
+        let repeat = event.repeat_count(&mut ctx.env) > 0;
+        let modifiers = egui_modifiers(event.meta_state(&mut ctx.env));
+        self.raw_input.modifiers = modifiers;
+        let mut handled = false;
+        if let Some(key) = egui_key(key_code) {
+            self.push_event(
+                ctx,
+                Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat,
+                    modifiers,
+                },
+            );
+            handled = true;
+        }
+        if pressed
+            && !modifiers.ctrl
+            && !modifiers.alt
+            && let Some(c) = event.unicode_char(&mut ctx.env).filter(|c| !c.is_control())
+        {
+            self.push_event(ctx, Event::Text(c.to_string()));
+            handled = true;
+        }
+        handled
+    }
+
+    fn press_key(&mut self, ctx: &mut CallbackCtx, key: egui::Key) {
+        for pressed in [true, false] {
+            self.push_event(
+                ctx,
+                Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat: false,
+                    modifiers: egui::Modifiers::NONE,
+                },
+            );
+        }
+    }
+
+    fn set_composing(&mut self, ctx: &mut CallbackCtx, text: &str) {
+        if self.composing.is_none() {
+            self.push_event(ctx, Event::Ime(ImeEvent::Enabled));
+        }
+        self.composing = Some(text.to_string());
+        self.push_event(ctx, Event::Ime(ImeEvent::Preedit(text.to_string())));
+    }
+
+    fn commit(&mut self, ctx: &mut CallbackCtx, text: &str) {
+        if self.composing.take().is_some() {
+            self.push_event(ctx, Event::Ime(ImeEvent::Commit(text.to_string())));
+            self.push_event(ctx, Event::Ime(ImeEvent::Disabled));
+        } else if !text.is_empty() {
+            self.push_event(ctx, Event::Text(text.to_string()));
+        }
+    }
+}
+
+impl<App: EguiApp + 'static> ViewPeer for EguiViewPeer<App> {
+    fn on_key_down<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        self.on_key_event(ctx, key_code, event)
+    }
+
+    fn on_key_up<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        self.on_key_event(ctx, key_code, event)
+    }
+
+    fn on_touch_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        let action = event.action_masked(&mut ctx.env);
+        let phase = match action {
+            MotionAction::Down | MotionAction::PointerDown => TouchPhase::Start,
+            MotionAction::Up | MotionAction::PointerUp => TouchPhase::End,
+            MotionAction::Move => TouchPhase::Move,
+            MotionAction::Cancel => TouchPhase::Cancel,
+            _ => return false,
+        };
+        let device_id = egui::TouchDeviceId(event.device_id(&mut ctx.env) as u64);
+        let action_index = event.action_index(&mut ctx.env);
+        let modifiers = egui_modifiers(event.meta_state(&mut ctx.env));
+        // Only the pointer that went down or up changes phase; the others
+        // haven't moved.
+        let indices = match phase {
+            TouchPhase::Start | TouchPhase::End => action_index..action_index + 1,
+            TouchPhase::Move | TouchPhase::Cancel => 0..event.pointer_count(&mut ctx.env),
+        };
+        for index in indices {
+            let id = event.pointer_id(&mut ctx.env, index);
+            let x = event.x_at(&mut ctx.env, index);
+            let y = event.y_at(&mut ctx.env, index);
+            let pos = self.pos(x, y);
+            let force = Some(event.axis(&mut ctx.env, Axis::Pressure, index));
+            self.push_event(
+                ctx,
+                Event::Touch {
+                    device_id,
+                    id: egui::TouchId(id as u64),
+                    phase,
+                    pos,
+                    force,
+                },
+            );
+
+            if phase == TouchPhase::Start && self.primary_pointer.is_none() {
+                self.primary_pointer = Some(id);
+                self.push_event(ctx, Event::PointerMoved(pos));
+                self.push_event(
+                    ctx,
+                    Event::PointerButton {
+                        pos,
+                        button: PointerButton::Primary,
+                        pressed: true,
+                        modifiers,
+                    },
+                );
+            } else if self.primary_pointer == Some(id) {
+                match phase {
+                    TouchPhase::Move => self.push_event(ctx, Event::PointerMoved(pos)),
+                    TouchPhase::End => {
+                        self.primary_pointer = None;
+                        self.push_event(
+                            ctx,
+                            Event::PointerButton {
+                                pos,
+                                button: PointerButton::Primary,
+                                pressed: false,
+                                modifiers,
+                            },
+                        );
+                        self.push_event(ctx, Event::PointerGone);
+                    }
+                    TouchPhase::Cancel => {
+                        self.primary_pointer = None;
+                        self.push_event(ctx, Event::PointerGone);
+                    }
+                    TouchPhase::Start => {}
+                }
+            }
+        }
+        true
+    }
+
+    fn on_generic_motion_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        if event.action_masked(&mut ctx.env) != MotionAction::Scroll {
+            return false;
+        }
+        // Android's horizontal axis is positive toward the right, but
+        // egui's delta is the direction the content moves.
+        let h = event.axis(&mut ctx.env, Axis::Hscroll, 0);
+        let v = event.axis(&mut ctx.env, Axis::Vscroll, 0);
+        let modifiers = egui_modifiers(event.meta_state(&mut ctx.env));
+        self.push_event(
+            ctx,
+            Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: egui::vec2(-h, v),
+                modifiers,
+            },
+        );
+        true
+    }
+
+    fn on_hover_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        match event.action_masked(&mut ctx.env) {
+            MotionAction::HoverEnter | MotionAction::HoverMove => {
+                let x = event.x(&mut ctx.env);
+                let y = event.y(&mut ctx.env);
+                let pos = self.pos(x, y);
+                self.push_event(ctx, Event::PointerMoved(pos));
+                true
+            }
+            MotionAction::HoverExit => {
+                self.push_event(ctx, Event::PointerGone);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn on_focus_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        gain_focus: bool,
+        _direction: jint,
+        _previously_focused_rect: Option<&Rect<'local>>,
+    ) {
+        self.raw_input.focused = gain_focus;
+        self.push_event(ctx, Event::WindowFocused(gain_focus));
+    }
+
+    fn surface_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+        _format: jint,
+        width: jint,
+        height: jint,
+    ) {
+        let android_ctx = ctx.view.context(&mut ctx.env);
+        self.pixels_per_point = scale_factor(&mut ctx.env, &android_ctx);
+        let window = holder.surface(&mut ctx.env).to_native_window(&mut ctx.env);
+        // Drop the old surface, if any, that owned the native window
+        // before creating a new one. Otherwise, we crash with
+        // ERROR_NATIVE_WINDOW_IN_USE_KHR.
+        self.render_state = None;
+        self.render_state = Some(RenderState::new(
+            &self.instance,
+            ViewWindow::new(window),
+            width as u32,
+            height as u32,
+        ));
+        self.redraw(ctx);
+    }
+
+    fn surface_destroyed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        _holder: &SurfaceHolder<'local>,
+    ) {
+        self.render_state = None;
+        ctx.view.remove_frame_callback(&mut ctx.env);
+        ctx.view.remove_delayed_callbacks(&mut ctx.env);
+    }
+
+    fn do_frame(&mut self, ctx: &mut CallbackCtx, _frame_time_nanos: jlong) {
+        self.redraw(ctx);
+    }
+
+    fn delayed_callback(&mut self, ctx: &mut CallbackCtx) {
+        self.redraw(ctx);
+    }
+
+    fn as_input_connection(&mut self) -> Option<&mut dyn InputConnection> {
+        Some(self)
+    }
+}
+
+impl<App: EguiApp + 'static> InputConnection for EguiViewPeer<App> {
+    fn on_create_input_connection<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        out_attrs: &EditorInfo<'local>,
+    ) {
+        out_attrs.populate(
+            &mut ctx.env,
+            &EditorAttributes {
+                input_type: InputType::CLASS_TEXT,
+                ime_options: ImeOptions::FLAG_NO_FULLSCREEN | ImeOptions::FLAG_NO_EXTRACT_UI,
+                ..Default::default()
+            },
+        );
+        self.composing = None;
+    }
+
+    fn text_before_cursor<'slf>(
+        &'slf mut self,
+        _ctx: &mut CallbackCtx,
+        _n: jint,
+    ) -> Option<Cow<'slf, str>> {
+        Some(Cow::Borrowed(self.composing.as_deref().unwrap_or("")))
+    }
+
+    fn text_after_cursor<'slf>(
+        &'slf mut self,
+        _ctx: &mut CallbackCtx,
+        _n: jint,
+    ) -> Option<Cow<'slf, str>> {
+        Some(Cow::Borrowed(""))
+    }
+
+    fn selected_text<'slf>(&'slf mut self, _ctx: &mut CallbackCtx) -> Option<Cow<'slf, str>> {
+        None
+    }
+
+    fn cursor_caps_mode(&mut self, _ctx: &mut CallbackCtx, _req_modes: CapsMode) -> CapsMode {
+        CapsMode::empty()
+    }
+
+    fn delete_surrounding_text(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        before_length: jint,
+        after_length: jint,
+    ) -> bool {
+        // egui can only delete through keys, one character at a time.
+        for _ in 0..before_length {
+            self.press_key(ctx, egui::Key::Backspace);
+        }
+        for _ in 0..after_length {
+            self.press_key(ctx, egui::Key::Delete);
+        }
+        true
+    }
+
+    fn delete_surrounding_text_in_code_points(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        before_length: jint,
+        after_length: jint,
+    ) -> bool {
+        self.delete_surrounding_text(ctx, before_length, after_length)
+    }
+
+    fn set_composing_text(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        text: &str,
+        _new_cursor_position: jint,
+    ) -> bool {
+        self.set_composing(ctx, text);
+        true
+    }
+
+    fn set_composing_region(&mut self, _ctx: &mut CallbackCtx, _start: jint, _end: jint) -> bool {
+        // The text outside the composition isn't known, so it can't
+        // become composing text.
+        false
+    }
+
+    fn finish_composing_text(&mut self, ctx: &mut CallbackCtx) -> bool {
+        if let Some(text) = self.composing.clone() {
+            self.commit(ctx, &text);
+        }
+        true
+    }
+
+    fn commit_text(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        text: &str,
+        _new_cursor_position: jint,
+    ) -> bool {
+        self.commit(ctx, text);
+        true
+    }
+
+    fn set_selection(&mut self, _ctx: &mut CallbackCtx, _start: jint, _end: jint) -> bool {
+        false
+    }
+
+    fn perform_editor_action(&mut self, ctx: &mut CallbackCtx, _editor_action: jint) -> bool {
+        self.press_key(ctx, egui::Key::Enter);
+        true
+    }
+
+    fn begin_batch_edit(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        true
+    }
+
+    fn end_batch_edit(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        false
+    }
+
+    fn send_key_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        let key_code = event.key_code(&mut ctx.env);
+        self.on_key_event(ctx, key_code, event)
+    }
+
+    fn request_cursor_updates(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        _cursor_update_mode: jint,
+    ) -> bool {
+        false
+    }
+}
+
+/// Create and register a peer that runs `app`, for the view class's
+/// `newViewPeer` implementation.
+pub fn new_view_peer<'local>(
+    env: &mut JNIEnv<'local>,
+    android_ctx: &Context<'local>,
+    app: impl EguiApp + 'static,
+) -> jlong {
+    let pixels_per_point = scale_factor(env, android_ctx);
+    register_view_peer(EguiViewPeer {
+        app,
+        egui_ctx: egui::Context::default(),
+        instance: wgpu::Instance::new(&wgpu::InstanceDescriptor::default()),
+        render_state: None,
+        raw_input: RawInput::default(),
+        start_time: Instant::now(),
+        pixels_per_point,
+        primary_pointer: None,
+        soft_input_shown: false,
+        composing: None,
+    })
+}
@@ -0,0 +1,90 @@
+/*
+ * C interface to the android-view crate, enabled by its `capi` feature.
+ * See the documentation of the crate's `capi` module for details.
+ */
+
+#ifndef ANDROID_VIEW_H
+#define ANDROID_VIEW_H
+
+#include <jni.h>
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The context of a callback, which is only valid during that callback. */
+typedef struct AndroidViewCallbackCtx AndroidViewCallbackCtx;
+
+/*
+ * The callbacks of a view peer. Any callback may be null, in which case
+ * the default behavior applies. Set `size` to
+ * `sizeof(AndroidViewPeerCallbacks)`; new fields are only ever added at
+ * the end.
+ */
+typedef struct AndroidViewPeerCallbacks {
+    size_t size;
+    void *user_data;
+    /* Called when the peer is dropped, after the view is detached. */
+    void (*destroy)(void *user_data);
+    /* Return true and store the measured size to override the default. */
+    bool (*on_measure)(void *user_data, AndroidViewCallbackCtx *ctx, jint width_spec,
+                       jint height_spec, jint *out_width, jint *out_height);
+    void (*on_layout)(void *user_data, AndroidViewCallbackCtx *ctx, bool changed, jint left,
+                      jint top, jint right, jint bottom);
+    void (*on_size_changed)(void *user_data, AndroidViewCallbackCtx *ctx, jint w, jint h,
+                            jint oldw, jint oldh);
+    bool (*on_key_down)(void *user_data, AndroidViewCallbackCtx *ctx, jint key_code,
+                        jobject event);
+    bool (*on_key_up)(void *user_data, AndroidViewCallbackCtx *ctx, jint key_code,
+                      jobject event);
+    bool (*on_touch_event)(void *user_data, AndroidViewCallbackCtx *ctx, jobject event);
+    bool (*on_generic_motion_event)(void *user_data, AndroidViewCallbackCtx *ctx,
+                                    jobject event);
+    bool (*on_hover_event)(void *user_data, AndroidViewCallbackCtx *ctx, jobject event);
+    /* `previously_focused_rect` is null if there's none. */
+    void (*on_focus_changed)(void *user_data, AndroidViewCallbackCtx *ctx, bool gain_focus,
+                             jint direction, jobject previously_focused_rect);
+    void (*on_window_focus_changed)(void *user_data, AndroidViewCallbackCtx *ctx,
+                                    bool has_window_focus);
+    void (*on_attached_to_window)(void *user_data, AndroidViewCallbackCtx *ctx);
+    void (*on_detached_from_window)(void *user_data, AndroidViewCallbackCtx *ctx);
+    void (*surface_created)(void *user_data, AndroidViewCallbackCtx *ctx, jobject holder);
+    void (*surface_changed)(void *user_data, AndroidViewCallbackCtx *ctx, jobject holder,
+                            jint format, jint width, jint height);
+    void (*surface_destroyed)(void *user_data, AndroidViewCallbackCtx *ctx, jobject holder);
+    void (*do_frame)(void *user_data, AndroidViewCallbackCtx *ctx, jlong frame_time_nanos);
+    void (*delayed_callback)(void *user_data, AndroidViewCallbackCtx *ctx);
+} AndroidViewPeerCallbacks;
+
+/*
+ * Register a peer implemented by the given callbacks, returning its ID
+ * for `newViewPeer` to return. The table is copied; the user data is owned
+ * by the peer from then on, and passed to `destroy` when it's dropped.
+ */
+jlong android_view_register_view_peer(const AndroidViewPeerCallbacks *callbacks);
+
+/*
+ * Register the native methods of the `RustView` subclass with the given
+ * name, such as "com/example/MyView", with `new_peer` implementing its
+ * `newViewPeer` method. Typically called from `JNI_OnLoad`.
+ */
+void android_view_register_view_class(JNIEnv *env, const char *class_name,
+                                      jlong (JNICALL *new_peer)(JNIEnv *env, jobject view,
+                                                                jobject context));
+
+/* The JNIEnv of the current callback. */
+JNIEnv *android_view_ctx_env(AndroidViewCallbackCtx *ctx);
+
+/* The RustView receiving the current callback, as a local reference. */
+jobject android_view_ctx_view(AndroidViewCallbackCtx *ctx);
+
+/* The ID of the peer receiving the current callback. */
+jlong android_view_ctx_peer_id(AndroidViewCallbackCtx *ctx);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ANDROID_VIEW_H */
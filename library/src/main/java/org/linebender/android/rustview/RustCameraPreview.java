@@ -0,0 +1,183 @@
+package org.linebender.android.rustview;
+
+import android.hardware.camera2.CameraAccessException;
+import android.hardware.camera2.CameraCaptureSession;
+import android.hardware.camera2.CameraCharacteristics;
+import android.hardware.camera2.CameraDevice;
+import android.hardware.camera2.CameraManager;
+import android.hardware.camera2.CaptureRequest;
+import android.hardware.camera2.params.StreamConfigurationMap;
+import android.os.Handler;
+import android.os.Looper;
+import android.util.Log;
+import android.util.Size;
+import android.view.Surface;
+import android.view.SurfaceHolder;
+import java.util.Collections;
+
+/**
+ * Streams a Camera2 preview into a {@link RustView}'s surface. While the
+ * preview is running, the camera owns the surface, so the peer must not
+ * render to it. The app must hold the {@code CAMERA} permission.
+ */
+public final class RustCameraPreview {
+    private static final String TAG = "RustCameraPreview";
+
+    private final RustView mView;
+    private final CameraManager mManager;
+    private final Handler mHandler = new Handler(Looper.getMainLooper());
+    private CameraDevice mDevice;
+    private CameraCaptureSession mSession;
+
+    public RustCameraPreview(RustView view) {
+        mView = view;
+        mManager = view.getContext().getSystemService(CameraManager.class);
+    }
+
+    public String[] getCameraIds() {
+        try {
+            return mManager.getCameraIdList();
+        } catch (CameraAccessException e) {
+            Log.e(TAG, "failed to list cameras", e);
+            return new String[0];
+        }
+    }
+
+    /** Returns the ID of the first camera facing the given direction, or null. */
+    public String findCamera(int lensFacing) {
+        for (String id : getCameraIds()) {
+            try {
+                Integer facing =
+                        mManager.getCameraCharacteristics(id)
+                                .get(CameraCharacteristics.LENS_FACING);
+                if (facing != null && facing == lensFacing) {
+                    return id;
+                }
+            } catch (CameraAccessException e) {
+                Log.e(TAG, "failed to get camera characteristics", e);
+            }
+        }
+        return null;
+    }
+
+    /**
+     * Picks the largest preview size that fits within the given bounds and
+     * sets the surface to that size. The surface is then resized, so
+     * {@code surfaceChanged} is called again with the chosen size.
+     * Returns the size as {width, height}, or null on failure.
+     */
+    public int[] configureSize(String cameraId, int maxWidth, int maxHeight) {
+        StreamConfigurationMap map;
+        try {
+            map =
+                    mManager.getCameraCharacteristics(cameraId)
+                            .get(CameraCharacteristics.SCALER_STREAM_CONFIGURATION_MAP);
+        } catch (CameraAccessException e) {
+            Log.e(TAG, "failed to get camera characteristics", e);
+            return null;
+        }
+        if (map == null) {
+            return null;
+        }
+        Size best = null;
+        for (Size size : map.getOutputSizes(SurfaceHolder.class)) {
+            if (size.getWidth() > maxWidth || size.getHeight() > maxHeight) {
+                continue;
+            }
+            if (best == null
+                    || (long) size.getWidth() * size.getHeight()
+                            > (long) best.getWidth() * best.getHeight()) {
+                best = size;
+            }
+        }
+        if (best == null) {
+            return null;
+        }
+        mView.getHolder().setFixedSize(best.getWidth(), best.getHeight());
+        return new int[] {best.getWidth(), best.getHeight()};
+    }
+
+    /** Opens the camera and starts streaming to the view's current surface. */
+    public boolean start(String cameraId) {
+        stop();
+        final Surface surface = mView.getHolder().getSurface();
+        if (!surface.isValid()) {
+            return false;
+        }
+        try {
+            mManager.openCamera(
+                    cameraId,
+                    new CameraDevice.StateCallback() {
+                        @Override
+                        public void onOpened(CameraDevice device) {
+                            mDevice = device;
+                            startSession(device, surface);
+                        }
+
+                        @Override
+                        public void onDisconnected(CameraDevice device) {
+                            device.close();
+                            if (mDevice == device) {
+                                mDevice = null;
+                            }
+                        }
+
+                        @Override
+                        public void onError(CameraDevice device, int error) {
+                            Log.e(TAG, "camera error " + error);
+                            onDisconnected(device);
+                        }
+                    },
+                    mHandler);
+        } catch (CameraAccessException | SecurityException e) {
+            Log.e(TAG, "failed to open camera", e);
+            return false;
+        }
+        return true;
+    }
+
+    private void startSession(final CameraDevice device, final Surface surface) {
+        try {
+            device.createCaptureSession(
+                    Collections.singletonList(surface),
+                    new CameraCaptureSession.StateCallback() {
+                        @Override
+                        public void onConfigured(CameraCaptureSession session) {
+                            if (mDevice != device) {
+                                session.close();
+                                return;
+                            }
+                            mSession = session;
+                            try {
+                                CaptureRequest.Builder builder =
+                                        device.createCaptureRequest(CameraDevice.TEMPLATE_PREVIEW);
+                                builder.addTarget(surface);
+                                session.setRepeatingRequest(builder.build(), null, mHandler);
+                            } catch (CameraAccessException e) {
+                                Log.e(TAG, "failed to start preview", e);
+                            }
+                        }
+
+                        @Override
+                        public void onConfigureFailed(CameraCaptureSession session) {
+                            Log.e(TAG, "failed to configure capture session");
+                        }
+                    },
+                    mHandler);
+        } catch (CameraAccessException e) {
+            Log.e(TAG, "failed to create capture session", e);
+        }
+    }
+
+    /** Stops the preview and closes the camera. Call this when the surface is destroyed. */
+    public void stop() {
+        if (mSession != null) {
+            mSession.close();
+            mSession = null;
+        }
+        if (mDevice != null) {
+            mDevice.close();
+            mDevice = null;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+package org.linebender.android.rustview;
+
+import android.graphics.Paint;
+import android.text.Spanned;
+import android.view.inputmethod.EditorInfo;
+import androidx.emoji2.text.EmojiCompat;
+import androidx.emoji2.text.EmojiSpan;
+
+/**
+ * Exposes Jetpack EmojiCompat to Rust code. This is optional; apps that use
+ * it must depend on {@code androidx.emoji2:emoji2} themselves. When the library
+ * is missing or hasn't finished loading, the methods fall back to the
+ * platform's fonts.
+ */
+public final class RustEmojiCompat {
+    private static final Paint sPaint = new Paint();
+
+    private RustEmojiCompat() {}
+
+    public static boolean isLoaded() {
+        try {
+            return EmojiCompat.isConfigured()
+                    && EmojiCompat.get().getLoadState() == EmojiCompat.LOAD_STATE_SUCCEEDED;
+        } catch (NoClassDefFoundError e) {
+            return false;
+        }
+    }
+
+    /**
+     * Returns the start and end offsets, in UTF-16 code units, of each emoji
+     * that EmojiCompat would draw with its own font, as consecutive pairs.
+     */
+    public static int[] process(String text) {
+        if (!isLoaded()) {
+            return new int[0];
+        }
+        CharSequence processed = EmojiCompat.get().process(text);
+        if (!(processed instanceof Spanned)) {
+            return new int[0];
+        }
+        Spanned spanned = (Spanned) processed;
+        EmojiSpan[] spans = spanned.getSpans(0, spanned.length(), EmojiSpan.class);
+        int[] result = new int[spans.length * 2];
+        for (int i = 0; i < spans.length; i++) {
+            result[i * 2] = spanned.getSpanStart(spans[i]);
+            result[i * 2 + 1] = spanned.getSpanEnd(spans[i]);
+        }
+        return result;
+    }
+
+    public static boolean hasGlyph(String text) {
+        if (sPaint.hasGlyph(text)) {
+            return true;
+        }
+        return isLoaded()
+                && EmojiCompat.get().getEmojiMatch(text, Integer.MAX_VALUE)
+                        == EmojiCompat.EMOJI_SUPPORTED;
+    }
+
+    static void updateEditorInfo(EditorInfo outAttrs) {
+        if (isLoaded()) {
+            EmojiCompat.get().updateEditorInfo(outAttrs);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+package org.linebender.android.rustview;
+
+import android.app.Activity;
+import androidx.core.util.Consumer;
+import androidx.window.java.layout.WindowInfoTrackerCallbackAdapter;
+import androidx.window.layout.DisplayFeature;
+import androidx.window.layout.FoldingFeature;
+import androidx.window.layout.WindowInfoTracker;
+import androidx.window.layout.WindowLayoutInfo;
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * Forwards folding features reported by Jetpack WindowManager to a
+ * {@link RustView}. This bridge is optional; apps that use it must depend on
+ * {@code androidx.window:window-java} themselves. Call {@link #start} from the
+ * activity's {@code onStart} and {@link #stop} from its {@code onStop}.
+ */
+public final class RustFoldingFeatureTracker implements Consumer<WindowLayoutInfo> {
+    private final RustView mView;
+    private final WindowInfoTrackerCallbackAdapter mAdapter;
+
+    public RustFoldingFeatureTracker(Activity activity, RustView view) {
+        mView = view;
+        mAdapter = new WindowInfoTrackerCallbackAdapter(WindowInfoTracker.getOrCreate(activity));
+    }
+
+    public void start(Activity activity) {
+        mAdapter.addWindowLayoutInfoListener(activity, activity.getMainExecutor(), this);
+    }
+
+    public void stop() {
+        mAdapter.removeWindowLayoutInfoListener(this);
+    }
+
+    @Override
+    public void accept(WindowLayoutInfo info) {
+        List<FoldingFeature> features = new ArrayList<>();
+        for (DisplayFeature feature : info.getDisplayFeatures()) {
+            if (feature instanceof FoldingFeature) {
+                features.add((FoldingFeature) feature);
+            }
+        }
+        // Must match the decoding in fold.rs.
+        int[] data = new int[features.size() * 8];
+        int i = 0;
+        for (FoldingFeature feature : features) {
+            data[i++] = feature.getBounds().left;
+            data[i++] = feature.getBounds().top;
+            data[i++] = feature.getBounds().right;
+            data[i++] = feature.getBounds().bottom;
+            data[i++] = feature.getState() == FoldingFeature.State.HALF_OPENED ? 1 : 0;
+            data[i++] = feature.getOrientation() == FoldingFeature.Orientation.HORIZONTAL ? 1 : 0;
+            data[i++] = feature.getOcclusionType() == FoldingFeature.OcclusionType.FULL ? 1 : 0;
+            data[i++] = feature.isSeparating() ? 1 : 0;
+        }
+        mView.onFoldingFeaturesChanged(data);
+    }
+}
@@ -1,10 +1,13 @@
 package org.linebender.android.rustview;
 
+import android.os.Build;
 import android.os.Bundle;
 import android.os.Handler;
+import android.os.LocaleList;
 import android.view.KeyEvent;
 import android.view.inputmethod.CompletionInfo;
 import android.view.inputmethod.CorrectionInfo;
+import android.view.inputmethod.EditorInfo;
 import android.view.inputmethod.ExtractedText;
 import android.view.inputmethod.ExtractedTextRequest;
 import android.view.inputmethod.InputConnection;
@@ -21,6 +24,28 @@ class RustInputConnection implements InputConnection {
         return mView.mViewPeer;
     }
 
+    // Called from Rust with all of the attributes at once, so that filling
+    // in the EditorInfo takes one JNI call rather than one per field.
+    static void populateEditorInfo(
+            EditorInfo outAttrs,
+            int inputType,
+            int imeOptions,
+            int initialSelStart,
+            int initialSelEnd,
+            int initialCapsMode,
+            CharSequence hintText,
+            String hintLocales) {
+        outAttrs.inputType = inputType;
+        outAttrs.imeOptions = imeOptions;
+        outAttrs.initialSelStart = initialSelStart;
+        outAttrs.initialSelEnd = initialSelEnd;
+        outAttrs.initialCapsMode = initialCapsMode;
+        outAttrs.hintText = hintText;
+        if (hintLocales != null && Build.VERSION.SDK_INT >= Build.VERSION_CODES.N) {
+            outAttrs.hintLocales = LocaleList.forLanguageTags(hintLocales);
+        }
+    }
+
     @Override
     public CharSequence getTextBeforeCursor(int n, int flags) {
         return mView.getTextBeforeCursorNative(getViewPeer(), n);
@@ -43,7 +68,19 @@ class RustInputConnection implements InputConnection {
 
     @Override
     public ExtractedText getExtractedText(ExtractedTextRequest request, int flags) {
-        return null;
+        int[] offsets = new int[3];
+        String text = mView.getExtractedTextNative(getViewPeer(), offsets);
+        if (text == null) {
+            return null;
+        }
+        ExtractedText result = new ExtractedText();
+        result.text = text;
+        result.startOffset = offsets[0];
+        result.selectionStart = offsets[1];
+        result.selectionEnd = offsets[2];
+        result.partialStartOffset = -1;
+        result.partialEndOffset = -1;
+        return result;
     }
 
     @Override
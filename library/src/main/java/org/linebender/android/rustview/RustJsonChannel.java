@@ -0,0 +1,75 @@
+package org.linebender.android.rustview;
+
+import java.nio.charset.StandardCharsets;
+import org.json.JSONException;
+import org.json.JSONObject;
+
+/**
+ * A channel to a {@link RustView}'s peer whose messages and replies are
+ * JSON objects, encoded as UTF-8, for hybrid apps that would rather not
+ * define a binary format. A reply that isn't a valid JSON object, or that's
+ * null, is passed on as null.
+ */
+public final class RustJsonChannel {
+    /** Receives the messages that the peer sends on this channel. */
+    public interface Handler {
+        /** Handle a message, returning the reply, which may be null. */
+        JSONObject onMessage(JSONObject message);
+    }
+
+    /** Receives the peer's reply to a message. */
+    public interface ReplyCallback {
+        void onReply(JSONObject reply);
+    }
+
+    private final RustView mView;
+    private final String mName;
+
+    public RustJsonChannel(RustView view, String name) {
+        mView = view;
+        mName = name;
+    }
+
+    private static byte[] encode(JSONObject object) {
+        return object == null ? null : object.toString().getBytes(StandardCharsets.UTF_8);
+    }
+
+    private static JSONObject decode(byte[] bytes) {
+        if (bytes == null) {
+            return null;
+        }
+        try {
+            return new JSONObject(new String(bytes, StandardCharsets.UTF_8));
+        } catch (JSONException e) {
+            return null;
+        }
+    }
+
+    /** Send a message to the peer, with a callback for its reply, which may be null. */
+    public void send(JSONObject message, final ReplyCallback callback) {
+        RustView.ChannelReply reply = null;
+        if (callback != null) {
+            reply = new RustView.ChannelReply() {
+                @Override
+                public void reply(byte[] message) {
+                    callback.onReply(decode(message));
+                }
+            };
+        }
+        mView.sendChannelMessage(mName, encode(message), reply);
+    }
+
+    /** Set or, if {@code handler} is null, remove the handler for this channel. */
+    public void setHandler(final Handler handler) {
+        if (handler == null) {
+            mView.setChannelHandler(mName, null);
+            return;
+        }
+        mView.setChannelHandler(mName, new RustView.ChannelHandler() {
+            @Override
+            public void onMessage(byte[] message, RustView.ChannelReply reply) {
+                reply.reply(encode(handler.onMessage(decode(message))));
+            }
+        });
+    }
+}
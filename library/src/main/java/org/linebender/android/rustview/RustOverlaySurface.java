@@ -0,0 +1,66 @@
+package org.linebender.android.rustview;
+
+import android.graphics.PixelFormat;
+import android.view.SurfaceHolder;
+import android.view.SurfaceView;
+import android.view.ViewGroup;
+
+/**
+ * An extra surface layered over a {@link RustView}, such as a low-latency
+ * layer for ink or a text cursor over slower-updating content. It's added
+ * to the view's parent, right after the view, while the view is attached,
+ * and shares the view's layout params, so the parent must stack its
+ * children, as {@code FrameLayout} does. It doesn't take touches or focus;
+ * input goes to the view underneath.
+ *
+ * <p>Overlays sit above the view's own surface and, unless created above the
+ * window, below the window's other content. The order of several overlays
+ * at the same level isn't defined.
+ */
+final class RustOverlaySurface extends SurfaceView implements SurfaceHolder.Callback {
+    private final RustView mOwner;
+    private final int mId;
+
+    RustOverlaySurface(RustView owner, int id, boolean aboveWindow) {
+        super(owner.getContext());
+        mOwner = owner;
+        mId = id;
+        setFocusable(false);
+        if (aboveWindow) {
+            setZOrderOnTop(true);
+        } else {
+            setZOrderMediaOverlay(true);
+        }
+        getHolder().setFormat(PixelFormat.TRANSLUCENT);
+        getHolder().addCallback(this);
+    }
+
+    void attach() {
+        if (getParent() != null || !(mOwner.getParent() instanceof ViewGroup)) {
+            return;
+        }
+        ViewGroup parent = (ViewGroup) mOwner.getParent();
+        parent.addView(this, parent.indexOfChild(mOwner) + 1, mOwner.getLayoutParams());
+    }
+
+    void detach() {
+        if (getParent() instanceof ViewGroup) {
+            ((ViewGroup) getParent()).removeView(this);
+        }
+    }
+
+    @Override
+    public void surfaceCreated(SurfaceHolder holder) {
+        mOwner.onOverlaySurfaceCreated(mId, holder);
+    }
+
+    @Override
+    public void surfaceChanged(SurfaceHolder holder, int format, int width, int height) {
+        mOwner.onOverlaySurfaceChanged(mId, holder, format, width, height);
+    }
+
+    @Override
+    public void surfaceDestroyed(SurfaceHolder holder) {
+        mOwner.onOverlaySurfaceDestroyed(mId, holder);
+    }
+}
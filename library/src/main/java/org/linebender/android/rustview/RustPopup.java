@@ -0,0 +1,62 @@
+package org.linebender.android.rustview;
+
+import android.view.Gravity;
+import android.view.View;
+import android.widget.PopupWindow;
+
+final class RustPopup implements PopupWindow.OnDismissListener {
+    private final RustView mAnchor;
+    private final int mId;
+    private final PopupWindow mWindow;
+
+    RustPopup(RustView anchor, View content, int width, int height, int id) {
+        mAnchor = anchor;
+        mId = id;
+        mWindow = new PopupWindow(content, width, height, false);
+        mWindow.setOnDismissListener(this);
+    }
+
+    void showAsDropDown(int xOffset, int yOffset) {
+        mWindow.showAsDropDown(mAnchor, xOffset, yOffset, Gravity.TOP | Gravity.START);
+    }
+
+    void showAtAnchorOffset(int x, int y) {
+        int[] location = new int[2];
+        mAnchor.getLocationInWindow(location);
+        mWindow.showAtLocation(
+                mAnchor, Gravity.TOP | Gravity.START, location[0] + x, location[1] + y);
+    }
+
+    void updateAtAnchorOffset(int x, int y, int width, int height) {
+        int[] location = new int[2];
+        mAnchor.getLocationInWindow(location);
+        mWindow.update(location[0] + x, location[1] + y, width, height);
+    }
+
+    void setDismissOnOutsideTouch(boolean dismiss) {
+        mWindow.setOutsideTouchable(dismiss);
+        mWindow.update();
+    }
+
+    void setFocusable(boolean focusable) {
+        mWindow.setFocusable(focusable);
+        mWindow.update();
+    }
+
+    void setElevation(float elevation) {
+        mWindow.setElevation(elevation);
+    }
+
+    boolean isShowing() {
+        return mWindow.isShowing();
+    }
+
+    void dismiss() {
+        mWindow.dismiss();
+    }
+
+    @Override
+    public void onDismiss() {
+        mAnchor.onPopupDismissed(mId);
+    }
+}
@@ -0,0 +1,127 @@
+package org.linebender.android.rustview;
+
+import android.os.Bundle;
+import android.os.Parcel;
+import java.lang.ref.WeakReference;
+import java.util.HashMap;
+import java.util.Iterator;
+import java.util.Map;
+
+/**
+ * Saves and restores the state of the whole app's Rust views and app-wide Rust state, so that it
+ * survives the system killing the process in the background. Call {@link #save} from the
+ * activity's {@code onSaveInstanceState} and {@link #restore} from its {@code onCreate}. All
+ * methods must be called on the UI thread.
+ */
+public final class RustProcessState {
+    private static final String KEY_VIEWS = "org.linebender.android.rustview.PROCESS_VIEWS";
+    private static final String KEY_HOOKS = "org.linebender.android.rustview.PROCESS_HOOKS";
+
+    private static final HashMap<String, WeakReference<RustView>> sViews = new HashMap<>();
+    // State restored for keys whose views haven't been created yet.
+    private static final HashMap<String, Bundle> sPendingViewStates = new HashMap<>();
+
+    private RustProcessState() {}
+
+    private static native void saveHooksNative(Bundle outState);
+
+    private static native void restoreHookNative(String key, byte[] state);
+
+    private static native void resetHooksNative();
+
+    static void registerView(String key, RustView view) {
+        sViews.put(key, new WeakReference<>(view));
+        Bundle state = sPendingViewStates.remove(key);
+        if (state != null) {
+            view.restoreState(state);
+        }
+    }
+
+    /** Save the state of every keyed view and every registered Rust hook into {@code outState}. */
+    public static void save(Bundle outState) {
+        Bundle views = new Bundle();
+        for (Map.Entry<String, Bundle> entry : sPendingViewStates.entrySet()) {
+            views.putBundle(entry.getKey(), entry.getValue());
+        }
+        Iterator<Map.Entry<String, WeakReference<RustView>>> it = sViews.entrySet().iterator();
+        while (it.hasNext()) {
+            Map.Entry<String, WeakReference<RustView>> entry = it.next();
+            RustView view = entry.getValue().get();
+            if (view == null) {
+                it.remove();
+                continue;
+            }
+            // A detached view no longer has a peer to save its state.
+            if (!view.isAttachedToWindow()) {
+                continue;
+            }
+            Bundle state = new Bundle();
+            view.saveState(state);
+            views.putBundle(entry.getKey(), state);
+        }
+        outState.putBundle(KEY_VIEWS, views);
+        Bundle hooks = new Bundle();
+        saveHooksNative(hooks);
+        outState.putBundle(KEY_HOOKS, hooks);
+    }
+
+    /**
+     * Restore state saved with {@link #save}, possibly by an earlier process. Does nothing if
+     * {@code state} is null, as it is when the activity is first created.
+     */
+    public static void restore(Bundle state) {
+        if (state == null) {
+            return;
+        }
+        Bundle hooks = state.getBundle(KEY_HOOKS);
+        if (hooks != null) {
+            for (String key : hooks.keySet()) {
+                restoreHookNative(key, hooks.getByteArray(key));
+            }
+        }
+        Bundle views = state.getBundle(KEY_VIEWS);
+        if (views != null) {
+            for (String key : views.keySet()) {
+                Bundle viewState = views.getBundle(key);
+                WeakReference<RustView> ref = sViews.get(key);
+                RustView view = ref != null ? ref.get() : null;
+                if (view != null && !view.isAttachedToWindow()) {
+                    view.restoreState(viewState);
+                } else {
+                    sPendingViewStates.put(key, viewState);
+                }
+            }
+        }
+    }
+
+    /**
+     * For instrumented tests, run the sequence that process death would: save the state,
+     * round-trip it through a {@link Parcel} so that only what the system keeps survives, forget
+     * the keyed views, and reset every Rust hook to its initial state. The test should then
+     * recreate the activity, for example with {@code ActivityScenario.recreate}, and pass the
+     * returned state to {@link #restore} in place of the saved instance state.
+     *
+     * <p>This can't drop state that the app keeps elsewhere, such as in Java statics or Rust
+     * globals without a hook, so a test that passes here may still lose that state for real.
+     */
+    public static Bundle simulateProcessDeath() {
+        Bundle state = new Bundle();
+        save(state);
+        Parcel parcel = Parcel.obtain();
+        try {
+            state.writeToParcel(parcel, 0);
+            byte[] bytes = parcel.marshall();
+            parcel.recycle();
+            parcel = Parcel.obtain();
+            parcel.unmarshall(bytes, 0, bytes.length);
+            parcel.setDataPosition(0);
+            state = parcel.readBundle(RustProcessState.class.getClassLoader());
+        } finally {
+            parcel.recycle();
+        }
+        sViews.clear();
+        sPendingViewStates.clear();
+        resetHooksNative();
+        return state;
+    }
+}
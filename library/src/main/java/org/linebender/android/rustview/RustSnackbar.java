@@ -0,0 +1,17 @@
+package org.linebender.android.rustview;
+
+import android.view.View;
+import com.google.android.material.snackbar.Snackbar;
+
+/**
+ * Shows a Material snackbar on behalf of Rust code. This is optional; apps
+ * that use it must depend on {@code com.google.android.material:material}
+ * themselves and use a Material theme.
+ */
+public final class RustSnackbar {
+    private RustSnackbar() {}
+
+    public static void show(View view, String text, int duration) {
+        Snackbar.make(view, text, duration).show();
+    }
+}
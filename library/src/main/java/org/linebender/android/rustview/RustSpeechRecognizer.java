@@ -0,0 +1,130 @@
+package org.linebender.android.rustview;
+
+import android.content.Context;
+import android.content.Intent;
+import android.os.Bundle;
+import android.speech.RecognitionListener;
+import android.speech.RecognizerIntent;
+import android.speech.SpeechRecognizer;
+import java.util.ArrayList;
+
+/**
+ * Forwards {@link SpeechRecognizer} callbacks to a Rust listener. The listener
+ * is identified by an opaque handle owned by the Rust side, which must call
+ * {@link #destroy} before freeing it.
+ */
+public final class RustSpeechRecognizer implements RecognitionListener {
+    private final SpeechRecognizer mRecognizer;
+    private long mListener;
+
+    public RustSpeechRecognizer(Context context, long listener) {
+        mRecognizer = SpeechRecognizer.createSpeechRecognizer(context);
+        mRecognizer.setRecognitionListener(this);
+        mListener = listener;
+    }
+
+    public static boolean isRecognitionAvailable(Context context) {
+        return SpeechRecognizer.isRecognitionAvailable(context);
+    }
+
+    public void startListening(String language, boolean partialResults) {
+        Intent intent = new Intent(RecognizerIntent.ACTION_RECOGNIZE_SPEECH);
+        intent.putExtra(
+                RecognizerIntent.EXTRA_LANGUAGE_MODEL, RecognizerIntent.LANGUAGE_MODEL_FREE_FORM);
+        intent.putExtra(RecognizerIntent.EXTRA_PARTIAL_RESULTS, partialResults);
+        if (language != null) {
+            intent.putExtra(RecognizerIntent.EXTRA_LANGUAGE, language);
+        }
+        mRecognizer.startListening(intent);
+    }
+
+    public void stopListening() {
+        mRecognizer.stopListening();
+    }
+
+    public void cancel() {
+        mRecognizer.cancel();
+    }
+
+    public void destroy() {
+        mRecognizer.destroy();
+        mListener = 0;
+    }
+
+    private static String[] getResults(Bundle bundle) {
+        ArrayList<String> results =
+                bundle != null
+                        ? bundle.getStringArrayList(SpeechRecognizer.RESULTS_RECOGNITION)
+                        : null;
+        return results != null ? results.toArray(new String[0]) : new String[0];
+    }
+
+    private static native void onReadyForSpeechNative(long listener);
+
+    private static native void onBeginningOfSpeechNative(long listener);
+
+    private static native void onRmsChangedNative(long listener, float rmsdB);
+
+    private static native void onEndOfSpeechNative(long listener);
+
+    private static native void onErrorNative(long listener, int error);
+
+    private static native void onResultsNative(long listener, String[] results);
+
+    private static native void onPartialResultsNative(long listener, String[] results);
+
+    @Override
+    public void onReadyForSpeech(Bundle params) {
+        if (mListener != 0) {
+            onReadyForSpeechNative(mListener);
+        }
+    }
+
+    @Override
+    public void onBeginningOfSpeech() {
+        if (mListener != 0) {
+            onBeginningOfSpeechNative(mListener);
+        }
+    }
+
+    @Override
+    public void onRmsChanged(float rmsdB) {
+        if (mListener != 0) {
+            onRmsChangedNative(mListener, rmsdB);
+        }
+    }
+
+    @Override
+    public void onBufferReceived(byte[] buffer) {}
+
+    @Override
+    public void onEndOfSpeech() {
+        if (mListener != 0) {
+            onEndOfSpeechNative(mListener);
+        }
+    }
+
+    @Override
+    public void onError(int error) {
+        if (mListener != 0) {
+            onErrorNative(mListener, error);
+        }
+    }
+
+    @Override
+    public void onResults(Bundle results) {
+        if (mListener != 0) {
+            onResultsNative(mListener, getResults(results));
+        }
+    }
+
+    @Override
+    public void onPartialResults(Bundle partialResults) {
+        if (mListener != 0) {
+            onPartialResultsNative(mListener, getResults(partialResults));
+        }
+    }
+
+    @Override
+    public void onEvent(int eventType, Bundle params) {}
+}
@@ -0,0 +1,99 @@
+package org.linebender.android.rustview;
+
+import android.content.Context;
+import android.content.res.TypedArray;
+import android.view.Gravity;
+import android.view.View;
+import android.view.ViewGroup;
+import android.widget.LinearLayout;
+import android.widget.PopupWindow;
+import android.widget.TextView;
+
+final class RustSuggestionsPopup implements PopupWindow.OnDismissListener {
+    private final RustView mAnchor;
+    private final int mId;
+    private final PopupWindow mWindow;
+
+    RustSuggestionsPopup(RustView anchor, int id, String[] suggestions, String deleteLabel) {
+        mAnchor = anchor;
+        mId = id;
+        Context context = anchor.getContext();
+        LinearLayout list = new LinearLayout(context);
+        list.setOrientation(LinearLayout.VERTICAL);
+        for (int i = 0; i < suggestions.length; i++) {
+            final int index = i;
+            addItem(
+                    list,
+                    newItem(context, suggestions[i]),
+                    new View.OnClickListener() {
+                        @Override
+                        public void onClick(View v) {
+                            mAnchor.onSuggestionPicked(mId, index);
+                            mWindow.dismiss();
+                        }
+                    });
+        }
+        if (deleteLabel != null) {
+            addItem(
+                    list,
+                    newItem(context, deleteLabel),
+                    new View.OnClickListener() {
+                        @Override
+                        public void onClick(View v) {
+                            mAnchor.onSuggestionDelete(mId);
+                            mWindow.dismiss();
+                        }
+                    });
+        }
+        mWindow = new PopupWindow(context, null, android.R.attr.popupWindowStyle);
+        mWindow.setContentView(list);
+        mWindow.setWidth(ViewGroup.LayoutParams.WRAP_CONTENT);
+        mWindow.setHeight(ViewGroup.LayoutParams.WRAP_CONTENT);
+        // Like the platform's suggestions popup, this leaves focus, and
+        // with it the keyboard, on the text being edited.
+        mWindow.setFocusable(false);
+        mWindow.setOutsideTouchable(true);
+        mWindow.setOnDismissListener(this);
+    }
+
+    private static TextView newItem(Context context, String text) {
+        TextView item = new TextView(context);
+        item.setText(text);
+        item.setTextAppearance(android.R.style.TextAppearance_Material_Body1);
+        float density = context.getResources().getDisplayMetrics().density;
+        int horizontal = (int) (16 * density);
+        int vertical = (int) (12 * density);
+        item.setPadding(horizontal, vertical, horizontal, vertical);
+        TypedArray a =
+                context.obtainStyledAttributes(
+                        new int[] {android.R.attr.selectableItemBackground});
+        item.setBackground(a.getDrawable(0));
+        a.recycle();
+        return item;
+    }
+
+    private static void addItem(LinearLayout list, TextView item, View.OnClickListener listener) {
+        item.setOnClickListener(listener);
+        list.addView(item);
+    }
+
+    void showAtAnchorOffset(int x, int y) {
+        int[] location = new int[2];
+        mAnchor.getLocationInWindow(location);
+        mWindow.showAtLocation(
+                mAnchor, Gravity.TOP | Gravity.START, location[0] + x, location[1] + y);
+    }
+
+    boolean isShowing() {
+        return mWindow.isShowing();
+    }
+
+    void dismiss() {
+        mWindow.dismiss();
+    }
+
+    @Override
+    public void onDismiss() {
+        mAnchor.onPopupDismissed(mId);
+    }
+}
@@ -0,0 +1,80 @@
+package org.linebender.android.rustview;
+
+import android.speech.tts.TextToSpeech;
+import android.speech.tts.UtteranceProgressListener;
+
+/**
+ * Wraps {@link TextToSpeech} so that initialization and utterance completion
+ * are reported to a {@link RustView}'s peer on the UI thread.
+ */
+public final class RustTextToSpeech {
+    private final RustView mView;
+    private final TextToSpeech mTts;
+
+    public RustTextToSpeech(RustView view) {
+        mView = view;
+        mTts =
+                new TextToSpeech(
+                        view.getContext(),
+                        new TextToSpeech.OnInitListener() {
+                            @Override
+                            public void onInit(final int status) {
+                                mView.post(
+                                        new Runnable() {
+                                            @Override
+                                            public void run() {
+                                                mView.onTextToSpeechInit(
+                                                        status == TextToSpeech.SUCCESS);
+                                            }
+                                        });
+                            }
+                        });
+        mTts.setOnUtteranceProgressListener(
+                new UtteranceProgressListener() {
+                    @Override
+                    public void onStart(String utteranceId) {}
+
+                    @Override
+                    public void onDone(String utteranceId) {
+                        postUtteranceDone(utteranceId, false);
+                    }
+
+                    @Override
+                    public void onError(String utteranceId) {
+                        postUtteranceDone(utteranceId, true);
+                    }
+
+                    @Override
+                    public void onStop(String utteranceId, boolean interrupted) {
+                        postUtteranceDone(utteranceId, true);
+                    }
+                });
+    }
+
+    private void postUtteranceDone(final String utteranceId, final boolean error) {
+        mView.post(
+                new Runnable() {
+                    @Override
+                    public void run() {
+                        mView.onUtteranceDone(utteranceId, error);
+                    }
+                });
+    }
+
+    public boolean speak(String text, boolean flush, String utteranceId) {
+        int queueMode = flush ? TextToSpeech.QUEUE_FLUSH : TextToSpeech.QUEUE_ADD;
+        return mTts.speak(text, queueMode, null, utteranceId) == TextToSpeech.SUCCESS;
+    }
+
+    public boolean isSpeaking() {
+        return mTts.isSpeaking();
+    }
+
+    public void stop() {
+        mTts.stop();
+    }
+
+    public void shutdown() {
+        mTts.shutdown();
+    }
+}
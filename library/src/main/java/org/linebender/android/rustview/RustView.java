@@ -1,26 +1,245 @@
 package org.linebender.android.rustview;
 
+import android.animation.Animator;
+import android.animation.AnimatorListenerAdapter;
+import android.app.Activity;
+import android.content.ActivityNotFoundException;
+import android.content.BroadcastReceiver;
 import android.content.Context;
+import android.content.ContextWrapper;
+import android.content.Intent;
+import android.content.IntentFilter;
+import android.content.res.Configuration;
+import android.content.res.TypedArray;
+import android.database.ContentObserver;
+import android.graphics.Bitmap;
 import android.graphics.Rect;
+import android.media.session.MediaController;
+import android.media.session.MediaSession;
+import android.hardware.display.DisplayManager;
+import android.hardware.input.InputManager;
+import android.net.ConnectivityManager;
+import android.net.Network;
+import android.net.NetworkCapabilities;
+import android.net.Uri;
+import android.os.Build;
 import android.os.Bundle;
+import android.os.Handler;
+import android.os.Looper;
+import android.os.PowerManager;
+import android.provider.Settings;
+import android.util.SparseArray;
 import android.view.Choreographer;
+import android.view.Display;
+import android.view.FrameMetrics;
+import android.view.InputDevice;
 import android.view.KeyEvent;
 import android.view.MotionEvent;
+import android.view.PixelCopy;
 import android.view.SurfaceHolder;
 import android.view.SurfaceView;
+import android.view.DisplayCutout;
+import android.view.View;
+import android.view.ViewPropertyAnimator;
+import android.view.ViewTreeObserver;
+import android.view.Window;
+import android.view.WindowInsets;
+import android.view.WindowManager;
 import android.view.accessibility.AccessibilityNodeInfo;
 import android.view.accessibility.AccessibilityNodeProvider;
 import android.view.inputmethod.EditorInfo;
 import android.view.inputmethod.InputConnection;
 import android.view.inputmethod.InputMethodManager;
+import java.util.ArrayList;
+import java.util.HashMap;
+import java.util.HashSet;
+import java.util.concurrent.atomic.AtomicBoolean;
 
 public abstract class RustView extends SurfaceView
         implements SurfaceHolder.Callback, Choreographer.FrameCallback {
     final long mViewPeer;
     final InputMethodManager mInputMethodManager;
+    // Filled in by the monitors' constructors, so declared before them.
+    final ArrayList<RustViewMonitor> mMonitors = new ArrayList<>();
+    private boolean mUnbufferedDispatch;
+    private boolean mHideUntilFirstFrame;
+    private boolean mFirstFrameRendered;
+    private boolean mSurfaceValid;
+    private boolean mRenderingActive;
+    private boolean mPauseRenderingWithoutWindowFocus;
+    private Window mFrameMetricsWindow;
+    private float mLastFontScale;
+    private int mLastFontWeightAdjustment;
+    private boolean mKeyboardMonitoringEnabled;
+    private boolean mHasHardwareKeyboard;
+    private boolean mImeVisible;
+    private int mImeHeight;
+    private boolean mInputDeviceMonitoringEnabled;
+    private float mLastRefreshRate;
+    private final HashSet<Integer> mPendingDocumentRequests = new HashSet<>();
+    private final HashSet<Integer> mPendingPermissionRequests = new HashSet<>();
+    private final RustViewScheduler mScheduler = new RustViewScheduler(this);
+    private final Rect mSafeContentRect = new Rect();
+    private final int[] mLocationInWindow = new int[2];
+    private final SparseArray<RustOverlaySurface> mOverlaySurfaces = new SparseArray<>();
+    private final Handler mOverlayHandler = new Handler(Looper.getMainLooper());
+    private final Runnable mUpdateOverlaySurfaces = this::updateOverlaySurfaces;
+    private final Handler mTaskHandler = new Handler(Looper.getMainLooper());
+    private final AtomicBoolean mTasksPosted = new AtomicBoolean();
+    private final Runnable mRunTasks = this::runTasks;
 
     protected abstract long newViewPeer(Context context);
 
+    /**
+     * The ID of this view's peer, for passing to native methods that a
+     * subclass declares and registers through {@code register_view_class},
+     * so they can reach the peer from Rust.
+     */
+    protected final long getViewPeer() {
+        return mViewPeer;
+    }
+
+    private native void runTasksNative(long peer);
+
+    private void runTasks() {
+        mTasksPosted.set(false);
+        runTasksNative(mViewPeer);
+    }
+
+    /**
+     * Schedule the peer's woken async tasks to run on the UI thread.
+     * Unlike the other methods, this may be called from any thread.
+     */
+    void wakeTasks() {
+        if (mTasksPosted.compareAndSet(false, true)) {
+            mTaskHandler.post(mRunTasks);
+        }
+    }
+
+    /** Receives the messages that the peer sends on a channel. */
+    public interface ChannelHandler {
+        /**
+         * Handle a message. The reply may be sent later, but only once, and
+         * on the UI thread.
+         */
+        void onMessage(byte[] message, ChannelReply reply);
+    }
+
+    /** A reply to a channel message, which is null if there was no handler. */
+    public interface ChannelReply {
+        void reply(byte[] message);
+    }
+
+    private final HashMap<String, ChannelHandler> mChannelHandlers = new HashMap<>();
+    private final SparseArray<ChannelReply> mPendingChannelReplies = new SparseArray<>();
+    private int mNextChannelReplyId;
+
+    private native void onChannelMessageNative(
+            long peer, String channel, byte[] message, int replyId);
+
+    private native void onChannelReplyNative(long peer, int replyId, byte[] message);
+
+    /** Set or, if {@code handler} is null, remove the handler for a channel. */
+    public void setChannelHandler(String channel, ChannelHandler handler) {
+        if (handler == null) {
+            mChannelHandlers.remove(channel);
+        } else {
+            mChannelHandlers.put(channel, handler);
+        }
+    }
+
+    /**
+     * Send a message to the peer's {@code on_channel_message} callback. If
+     * {@code reply} isn't null, it gets the peer's reply. Must be called on
+     * the UI thread.
+     */
+    public void sendChannelMessage(String channel, byte[] message, ChannelReply reply) {
+        int replyId = -1;
+        if (reply != null) {
+            replyId = mNextChannelReplyId;
+            mNextChannelReplyId = (mNextChannelReplyId + 1) & Integer.MAX_VALUE;
+            mPendingChannelReplies.put(replyId, reply);
+        }
+        onChannelMessageNative(mViewPeer, channel, message, replyId);
+    }
+
+    void deliverChannelMessage(String channel, byte[] message, final int replyId) {
+        ChannelReply reply = new ChannelReply() {
+            private boolean mReplied;
+
+            @Override
+            public void reply(byte[] replyMessage) {
+                if (mReplied) {
+                    throw new IllegalStateException("Channel message already replied to");
+                }
+                mReplied = true;
+                if (replyId >= 0) {
+                    onChannelReplyNative(mViewPeer, replyId, replyMessage);
+                }
+            }
+        };
+        ChannelHandler handler = mChannelHandlers.get(channel);
+        if (handler == null) {
+            reply.reply(null);
+        } else {
+            handler.onMessage(message, reply);
+        }
+    }
+
+    void deliverChannelReply(int replyId, byte[] message) {
+        ChannelReply reply = mPendingChannelReplies.get(replyId);
+        if (reply != null) {
+            mPendingChannelReplies.remove(replyId);
+            reply.reply(message);
+        }
+    }
+
+    /**
+     * Events that the peer emits through its {@code ViewEventEmitter}. Each
+     * method does nothing by default, so a listener only overrides the ones
+     * it needs. Events are delivered on the UI thread.
+     */
+    public interface ViewEventListener {
+        /** The height of the view's content changed, in pixels. */
+        default void onContentHeightChanged(int height) {}
+
+        /** The text selection changed, in UTF-16 offsets. */
+        default void onSelectionChanged(int start, int end) {}
+
+        /** The user asked for an action that the host handles. */
+        default void onActionRequested(String action) {}
+    }
+
+    private final ArrayList<ViewEventListener> mViewEventListeners = new ArrayList<>();
+
+    public void addViewEventListener(ViewEventListener listener) {
+        mViewEventListeners.add(listener);
+    }
+
+    public void removeViewEventListener(ViewEventListener listener) {
+        mViewEventListeners.remove(listener);
+    }
+
+    // Listeners may remove themselves while being notified, so these
+    // iterate over a copy.
+    void dispatchContentHeightChanged(int height) {
+        for (ViewEventListener listener : new ArrayList<>(mViewEventListeners)) {
+            listener.onContentHeightChanged(height);
+        }
+    }
+
+    void dispatchSelectionChanged(int start, int end) {
+        for (ViewEventListener listener : new ArrayList<>(mViewEventListeners)) {
+            listener.onSelectionChanged(start, end);
+        }
+    }
+
+    void dispatchActionRequested(String action) {
+        for (ViewEventListener listener : new ArrayList<>(mViewEventListeners)) {
+            listener.onActionRequested(action);
+        }
+    }
+
     public RustView(Context context) {
         super(context);
         mViewPeer = newViewPeer(context);
@@ -29,6 +248,35 @@ public abstract class RustView extends SurfaceView
                 (InputMethodManager) context.getSystemService(Context.INPUT_METHOD_SERVICE);
     }
 
+    // Called from Rust when the view is attached, with -1 for each attribute
+    // that the peer leaves as it is.
+    void applyViewConfig(
+            int focusable,
+            int focusableInTouchMode,
+            int defaultFocusHighlightEnabled,
+            int layerType,
+            int importantForAccessibility,
+            int keepScreenOn) {
+        if (focusable >= 0) {
+            setFocusable(focusable != 0);
+        }
+        if (focusableInTouchMode >= 0) {
+            setFocusableInTouchMode(focusableInTouchMode != 0);
+        }
+        if (defaultFocusHighlightEnabled >= 0) {
+            setDefaultFocusHighlightEnabled(defaultFocusHighlightEnabled != 0);
+        }
+        if (layerType >= 0) {
+            setLayerType(layerType, null);
+        }
+        if (importantForAccessibility >= 0) {
+            setImportantForAccessibility(importantForAccessibility);
+        }
+        if (keepScreenOn >= 0) {
+            setKeepScreenOn(keepScreenOn != 0);
+        }
+    }
+
     private native int[] onMeasureNative(long peer, int widthSpec, int heightSpec);
 
     @Override
@@ -48,6 +296,7 @@ public abstract class RustView extends SurfaceView
     protected void onLayout(boolean changed, int left, int top, int right, int bottom) {
         onLayoutNative(mViewPeer, changed, left, top, right, bottom);
         super.onLayout(changed, left, top, right, bottom);
+        updateSafeContentRect();
     }
 
     private native void onSizeChangedNative(long peer, int w, int h, int oldw, int oldh);
@@ -81,8 +330,25 @@ public abstract class RustView extends SurfaceView
 
     private native boolean onTouchEventNative(long peer, MotionEvent event);
 
+    /**
+     * When enabled, each touch gesture requests unbuffered dispatch, so that
+     * motion events are delivered as soon as they arrive rather than batched
+     * once per frame. This is meant for stylus drawing, where every sample
+     * matters; it increases CPU and battery usage, so leave it off otherwise.
+     */
+    public void setUnbufferedDispatchEnabled(boolean enabled) {
+        mUnbufferedDispatch = enabled;
+    }
+
+    public boolean isUnbufferedDispatchEnabled() {
+        return mUnbufferedDispatch;
+    }
+
     @Override
     public boolean onTouchEvent(MotionEvent event) {
+        if (mUnbufferedDispatch && event.getActionMasked() == MotionEvent.ACTION_DOWN) {
+            requestUnbufferedDispatch(event);
+        }
         return onTouchEventNative(mViewPeer, event) || super.onTouchEvent(event);
     }
 
@@ -115,6 +381,7 @@ public abstract class RustView extends SurfaceView
     public void onWindowFocusChanged(boolean hasWindowFocus) {
         super.onWindowFocusChanged(hasWindowFocus);
         onWindowFocusChangedNative(mViewPeer, hasWindowFocus);
+        updateRenderingActive();
     }
 
     private native void onAttachedToWindowNative(long peer);
@@ -123,6 +390,12 @@ public abstract class RustView extends SurfaceView
     protected void onAttachedToWindow() {
         super.onAttachedToWindow();
         onAttachedToWindowNative(mViewPeer);
+        for (RustViewMonitor monitor : mMonitors) {
+            monitor.update();
+        }
+        updateSchedulerActive();
+        updateRenderingActive();
+        scheduleOverlaySurfaceUpdate();
     }
 
     private native void onDetachedFromWindowNative(long peer);
@@ -130,6 +403,12 @@ public abstract class RustView extends SurfaceView
     @Override
     protected void onDetachedFromWindow() {
         super.onDetachedFromWindow();
+        mScheduler.setActive(false);
+        setRenderingActive(false);
+        for (RustViewMonitor monitor : mMonitors) {
+            monitor.stop();
+        }
+        scheduleOverlaySurfaceUpdate();
         onDetachedFromWindowNative(mViewPeer);
     }
 
@@ -139,13 +418,783 @@ public abstract class RustView extends SurfaceView
     protected void onWindowVisibilityChanged(int visibility) {
         super.onWindowVisibilityChanged(visibility);
         onWindowVisibilityChangedNative(mViewPeer, visibility);
+        updateSchedulerActive();
+        updateRenderingActive();
+    }
+
+    private native void invalidateViewStateNative(long peer);
+
+    @Override
+    protected void onVisibilityChanged(View changedView, int visibility) {
+        super.onVisibilityChanged(changedView, visibility);
+        invalidateViewStateNative(mViewPeer);
+        if (changedView == this) {
+            for (int i = 0; i < mOverlaySurfaces.size(); i++) {
+                mOverlaySurfaces.valueAt(i).setVisibility(visibility);
+            }
+        }
+    }
+
+    String getViewState(int[] out) {
+        // Must match the decoding in view_state.rs.
+        Configuration config = getResources().getConfiguration();
+        out[0] = getWidth();
+        out[1] = getHeight();
+        out[2] = getVisibility();
+        out[3] = getWindowVisibility();
+        out[4] =
+                (isFocused() ? 1 : 0)
+                        | (hasWindowFocus() ? 2 : 0)
+                        | (isAttachedToWindow() ? 4 : 0);
+        out[5] = Float.floatToRawIntBits(getResources().getDisplayMetrics().density);
+        out[6] = Float.floatToRawIntBits(config.fontScale);
+        return config.getLocales().get(0).toLanguageTag();
+    }
+
+    private native void onApplyWindowInsetsNative(long peer, WindowInsets insets);
+
+    @Override
+    public WindowInsets onApplyWindowInsets(WindowInsets insets) {
+        onApplyWindowInsetsNative(mViewPeer, insets);
+        updateSafeContentRect();
+        updateImeVisibility();
+        return super.onApplyWindowInsets(insets);
+    }
+
+    private native void onSafeContentRectChangedNative(
+            long peer, int left, int top, int right, int bottom);
+
+    /**
+     * Compute the part of the view, in its own coordinates, that isn't
+     * covered by system bars, the IME or the display cutout, and notify the
+     * peer if it changed.
+     */
+    private void updateSafeContentRect() {
+        WindowInsets insets = getRootWindowInsets();
+        if (insets == null) {
+            return;
+        }
+        int insetLeft, insetTop, insetRight, insetBottom;
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.R) {
+            android.graphics.Insets i =
+                    insets.getInsets(
+                            WindowInsets.Type.systemBars()
+                                    | WindowInsets.Type.ime()
+                                    | WindowInsets.Type.displayCutout());
+            insetLeft = i.left;
+            insetTop = i.top;
+            insetRight = i.right;
+            insetBottom = i.bottom;
+        } else {
+            // Before API level 30, the system window insets include the IME
+            // but not the cutout.
+            insetLeft = insets.getSystemWindowInsetLeft();
+            insetTop = insets.getSystemWindowInsetTop();
+            insetRight = insets.getSystemWindowInsetRight();
+            insetBottom = insets.getSystemWindowInsetBottom();
+            DisplayCutout cutout = insets.getDisplayCutout();
+            if (cutout != null) {
+                insetLeft = Math.max(insetLeft, cutout.getSafeInsetLeft());
+                insetTop = Math.max(insetTop, cutout.getSafeInsetTop());
+                insetRight = Math.max(insetRight, cutout.getSafeInsetRight());
+                insetBottom = Math.max(insetBottom, cutout.getSafeInsetBottom());
+            }
+        }
+        View root = getRootView();
+        getLocationInWindow(mLocationInWindow);
+        int x = mLocationInWindow[0];
+        int y = mLocationInWindow[1];
+        int left = Math.max(0, insetLeft - x);
+        int top = Math.max(0, insetTop - y);
+        int right = Math.max(left, Math.min(getWidth(), root.getWidth() - insetRight - x));
+        int bottom = Math.max(top, Math.min(getHeight(), root.getHeight() - insetBottom - y));
+        if (mSafeContentRect.left == left
+                && mSafeContentRect.top == top
+                && mSafeContentRect.right == right
+                && mSafeContentRect.bottom == bottom) {
+            return;
+        }
+        mSafeContentRect.set(left, top, right, bottom);
+        onSafeContentRectChangedNative(mViewPeer, left, top, right, bottom);
+    }
+
+    private native void onMultiWindowModeChangedNative(long peer, boolean isInMultiWindowMode);
+
+    /**
+     * Views don't receive multi-window mode changes directly; the host
+     * activity must forward its {@code onMultiWindowModeChanged} callback
+     * to this method.
+     */
+    public void onMultiWindowModeChanged(boolean isInMultiWindowMode) {
+        onMultiWindowModeChangedNative(mViewPeer, isInMultiWindowMode);
+    }
+
+    private native void onPictureInPictureModeChangedNative(
+            long peer, boolean isInPictureInPictureMode);
+
+    /**
+     * Views don't receive picture-in-picture mode changes directly; the host
+     * activity must forward its {@code onPictureInPictureModeChanged}
+     * callback to this method.
+     */
+    public void onPictureInPictureModeChanged(boolean isInPictureInPictureMode) {
+        onPictureInPictureModeChangedNative(mViewPeer, isInPictureInPictureMode);
+    }
+
+    private native void onFoldingFeaturesChangedNative(long peer, int[] features);
+
+    void onFoldingFeaturesChanged(int[] features) {
+        onFoldingFeaturesChangedNative(mViewPeer, features);
+    }
+
+    private native void onFrameMetricsAvailableNative(
+            long peer, FrameMetrics frameMetrics, int dropCountSinceLastInvocation);
+
+    private final Window.OnFrameMetricsAvailableListener mFrameMetricsListener =
+            new Window.OnFrameMetricsAvailableListener() {
+                @Override
+                public void onFrameMetricsAvailable(
+                        Window window, FrameMetrics frameMetrics, int dropCountSinceLastInvocation) {
+                    onFrameMetricsAvailableNative(
+                            mViewPeer, frameMetrics, dropCountSinceLastInvocation);
+                }
+            };
+
+    private final RustViewMonitor mFrameMetricsMonitor =
+            new RustViewMonitor(this) {
+                @Override
+                void register() {
+                    Activity activity = getActivity();
+                    if (activity != null) {
+                        mFrameMetricsWindow = activity.getWindow();
+                        mFrameMetricsWindow.addOnFrameMetricsAvailableListener(
+                                mFrameMetricsListener, new Handler(Looper.getMainLooper()));
+                    }
+                }
+
+                @Override
+                void unregister() {
+                    if (mFrameMetricsWindow != null) {
+                        mFrameMetricsWindow.removeOnFrameMetricsAvailableListener(
+                                mFrameMetricsListener);
+                        mFrameMetricsWindow = null;
+                    }
+                }
+            };
+
+    /**
+     * When enabled, the peer receives the frame metrics of the host activity's
+     * window while the view is attached. Note that these cover the window's
+     * own rendering, including the view's position and animations, but not
+     * frames that the peer renders directly to the view's surface.
+     */
+    public void setFrameMetricsEnabled(boolean enabled) {
+        mFrameMetricsMonitor.setEnabled(enabled);
+    }
+
+    private native void onThermalStatusChangedNative(long peer, int status);
+
+    private native void onPowerSaveModeChangedNative(long peer, boolean powerSaveMode);
+
+    // Created on first use, since the interface requires API level 29.
+    private PowerManager.OnThermalStatusChangedListener mThermalStatusListener;
+
+    private final BroadcastReceiver mPowerSaveModeReceiver =
+            new BroadcastReceiver() {
+                @Override
+                public void onReceive(Context context, Intent intent) {
+                    PowerManager pm = context.getSystemService(PowerManager.class);
+                    onPowerSaveModeChangedNative(mViewPeer, pm.isPowerSaveMode());
+                }
+            };
+
+    private final RustViewMonitor mPowerMonitor =
+            new RustViewMonitor(this) {
+                @Override
+                void register() {
+                    Context context = getContext();
+                    PowerManager pm = context.getSystemService(PowerManager.class);
+                    if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.Q) {
+                        if (mThermalStatusListener == null) {
+                            mThermalStatusListener =
+                                    new PowerManager.OnThermalStatusChangedListener() {
+                                        @Override
+                                        public void onThermalStatusChanged(int status) {
+                                            onThermalStatusChangedNative(mViewPeer, status);
+                                        }
+                                    };
+                        }
+                        pm.addThermalStatusListener(
+                                context.getMainExecutor(), mThermalStatusListener);
+                    }
+                    context.registerReceiver(
+                            mPowerSaveModeReceiver,
+                            new IntentFilter(PowerManager.ACTION_POWER_SAVE_MODE_CHANGED));
+                }
+
+                @Override
+                void unregister() {
+                    Context context = getContext();
+                    if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.Q) {
+                        context.getSystemService(PowerManager.class)
+                                .removeThermalStatusListener(mThermalStatusListener);
+                    }
+                    context.unregisterReceiver(mPowerSaveModeReceiver);
+                }
+            };
+
+    /**
+     * When enabled, the peer is notified of thermal status and power save mode
+     * changes while the view is attached, so it can reduce its rendering load.
+     * Thermal status requires API level 29.
+     */
+    public void setPowerMonitoringEnabled(boolean enabled) {
+        mPowerMonitor.setEnabled(enabled);
+    }
+
+    private native void onNetworkChangedNative(
+            long peer, boolean available, boolean validated, boolean metered);
+
+    private final ConnectivityManager.NetworkCallback mNetworkCallback =
+            new ConnectivityManager.NetworkCallback() {
+                @Override
+                public void onCapabilitiesChanged(
+                        Network network, NetworkCapabilities capabilities) {
+                    onNetworkChangedNative(
+                            mViewPeer,
+                            true,
+                            capabilities.hasCapability(
+                                    NetworkCapabilities.NET_CAPABILITY_VALIDATED),
+                            !capabilities.hasCapability(
+                                    NetworkCapabilities.NET_CAPABILITY_NOT_METERED));
+                }
+
+                @Override
+                public void onLost(Network network) {
+                    onNetworkChangedNative(mViewPeer, false, false, false);
+                }
+            };
+
+    private final RustViewMonitor mNetworkMonitor =
+            new RustViewMonitor(this) {
+                @Override
+                void register() {
+                    getContext()
+                            .getSystemService(ConnectivityManager.class)
+                            .registerDefaultNetworkCallback(
+                                    mNetworkCallback, new Handler(Looper.getMainLooper()));
+                }
+
+                @Override
+                void unregister() {
+                    getContext()
+                            .getSystemService(ConnectivityManager.class)
+                            .unregisterNetworkCallback(mNetworkCallback);
+                }
+            };
+
+    /**
+     * When enabled, the peer is notified of changes to the default network
+     * while the view is attached. The app must hold the
+     * {@code ACCESS_NETWORK_STATE} permission.
+     */
+    public void setNetworkMonitoringEnabled(boolean enabled) {
+        mNetworkMonitor.setEnabled(enabled);
+    }
+
+    private native void onDisplayPreferencesChangedNative(long peer);
+
+    private final ContentObserver mDisplayPreferencesObserver =
+            new ContentObserver(new Handler(Looper.getMainLooper())) {
+                @Override
+                public void onChange(boolean selfChange) {
+                    onDisplayPreferencesChangedNative(mViewPeer);
+                }
+            };
+
+    private final RustViewMonitor mDisplayPreferencesMonitor =
+            new RustViewMonitor(this) {
+                @Override
+                void register() {
+                    Configuration config = getResources().getConfiguration();
+                    mLastFontScale = config.fontScale;
+                    mLastFontWeightAdjustment = fontWeightAdjustment(config);
+                    getContext()
+                            .getContentResolver()
+                            .registerContentObserver(
+                                    Settings.Secure.getUriFor("high_text_contrast_enabled"),
+                                    false,
+                                    mDisplayPreferencesObserver);
+                    getContext()
+                            .getContentResolver()
+                            .registerContentObserver(
+                                    Settings.Global.getUriFor(
+                                            Settings.Global.ANIMATOR_DURATION_SCALE),
+                                    false,
+                                    mDisplayPreferencesObserver);
+                }
+
+                @Override
+                void unregister() {
+                    getContext()
+                            .getContentResolver()
+                            .unregisterContentObserver(mDisplayPreferencesObserver);
+                }
+            };
+
+    /**
+     * When enabled, the peer is notified while the view is attached when
+     * the user changes display settings that affect rendering: font scale,
+     * bold text, high contrast text, and animation removal.
+     */
+    public void setDisplayPreferencesMonitoringEnabled(boolean enabled) {
+        mDisplayPreferencesMonitor.setEnabled(enabled);
+    }
+
+    private static int fontWeightAdjustment(Configuration config) {
+        return Build.VERSION.SDK_INT >= Build.VERSION_CODES.S ? config.fontWeightAdjustment : 0;
+    }
+
+    @Override
+    protected void onConfigurationChanged(Configuration newConfig) {
+        super.onConfigurationChanged(newConfig);
+        invalidateViewStateNative(mViewPeer);
+        if (mDisplayPreferencesMonitor.isRegistered()
+                && (newConfig.fontScale != mLastFontScale
+                        || fontWeightAdjustment(newConfig) != mLastFontWeightAdjustment)) {
+            mLastFontScale = newConfig.fontScale;
+            mLastFontWeightAdjustment = fontWeightAdjustment(newConfig);
+            onDisplayPreferencesChangedNative(mViewPeer);
+        }
+        if (mInputDeviceMonitor.isRegistered()
+                && hasHardwareKeyboard(newConfig) != mHasHardwareKeyboard) {
+            mHasHardwareKeyboard = hasHardwareKeyboard(newConfig);
+            if (mKeyboardMonitoringEnabled) {
+                onHardwareKeyboardChangedNative(mViewPeer, mHasHardwareKeyboard);
+            }
+        }
+    }
+
+    private native void onHardwareKeyboardChangedNative(long peer, boolean present);
+
+    private native void onKeyboardLayoutChangedNative(long peer, int deviceId);
+
+    private native void onInputDeviceAddedNative(long peer, int deviceId);
+
+    private native void onInputDeviceRemovedNative(long peer, int deviceId);
+
+    private native void onInputDeviceChangedNative(long peer, int deviceId);
+
+    // Shared by keyboard and input device monitoring, and registered while
+    // either is enabled.
+    private final InputManager.InputDeviceListener mInputDeviceListener =
+            new InputManager.InputDeviceListener() {
+                @Override
+                public void onInputDeviceAdded(int deviceId) {
+                    if (mInputDeviceMonitoringEnabled) {
+                        onInputDeviceAddedNative(mViewPeer, deviceId);
+                    }
+                }
+
+                @Override
+                public void onInputDeviceRemoved(int deviceId) {
+                    if (mInputDeviceMonitoringEnabled) {
+                        onInputDeviceRemovedNative(mViewPeer, deviceId);
+                    }
+                }
+
+                @Override
+                public void onInputDeviceChanged(int deviceId) {
+                    if (mInputDeviceMonitoringEnabled) {
+                        onInputDeviceChangedNative(mViewPeer, deviceId);
+                    }
+                    if (mKeyboardMonitoringEnabled) {
+                        InputDevice device = InputDevice.getDevice(deviceId);
+                        if (device != null
+                                && !device.isVirtual()
+                                && device.getKeyboardType()
+                                        == InputDevice.KEYBOARD_TYPE_ALPHABETIC) {
+                            onKeyboardLayoutChangedNative(mViewPeer, deviceId);
+                        }
+                    }
+                }
+            };
+
+    private final RustViewMonitor mInputDeviceMonitor =
+            new RustViewMonitor(this) {
+                @Override
+                void register() {
+                    mHasHardwareKeyboard = hasHardwareKeyboard(getResources().getConfiguration());
+                    getContext()
+                            .getSystemService(InputManager.class)
+                            .registerInputDeviceListener(
+                                    mInputDeviceListener, new Handler(Looper.getMainLooper()));
+                }
+
+                @Override
+                void unregister() {
+                    getContext()
+                            .getSystemService(InputManager.class)
+                            .unregisterInputDeviceListener(mInputDeviceListener);
+                }
+            };
+
+    private void updateInputDeviceMonitor() {
+        mInputDeviceMonitor.setEnabled(mKeyboardMonitoringEnabled || mInputDeviceMonitoringEnabled);
+    }
+
+    /**
+     * When enabled, the peer is notified while the view is attached when a
+     * hardware keyboard is attached or detached, and when the layout of a
+     * hardware keyboard changes. Attaching or detaching a keyboard is a
+     * configuration change, so the activity must declare that it handles
+     * {@code keyboard|keyboardHidden} changes; otherwise it is recreated
+     * instead.
+     */
+    public void setKeyboardMonitoringEnabled(boolean enabled) {
+        mKeyboardMonitoringEnabled = enabled;
+        updateInputDeviceMonitor();
+    }
+
+    private static boolean hasHardwareKeyboard(Configuration config) {
+        return config.keyboard != Configuration.KEYBOARD_NOKEYS
+                && config.hardKeyboardHidden == Configuration.HARDKEYBOARDHIDDEN_NO;
+    }
+
+    public boolean hasHardwareKeyboard() {
+        return hasHardwareKeyboard(getResources().getConfiguration());
+    }
+
+    /**
+     * When enabled, the peer is notified while the view is attached when
+     * input devices such as mice, styluses and game controllers are added,
+     * removed or changed.
+     */
+    public void setInputDeviceMonitoringEnabled(boolean enabled) {
+        mInputDeviceMonitoringEnabled = enabled;
+        updateInputDeviceMonitor();
+    }
+
+    private native void onImeVisibilityChangedNative(long peer, boolean visible, int height);
+
+    private final ViewTreeObserver.OnGlobalLayoutListener mImeLayoutListener =
+            this::updateImeVisibility;
+
+    private final RustViewMonitor mImeVisibilityMonitor =
+            new RustViewMonitor(this) {
+                @Override
+                void register() {
+                    // Insets aren't dispatched to this view if a parent consumes
+                    // them, and the window is panned rather than resized in some
+                    // soft input modes, but either way the window is laid out again.
+                    getViewTreeObserver().addOnGlobalLayoutListener(mImeLayoutListener);
+                    mImeHeight = getImeHeight();
+                    mImeVisible = mImeHeight > 0;
+                }
+
+                @Override
+                void unregister() {
+                    getViewTreeObserver().removeOnGlobalLayoutListener(mImeLayoutListener);
+                }
+            };
+
+    /**
+     * When enabled, the peer is notified while the view is attached when the
+     * IME is shown or hidden, or its height changes.
+     */
+    public void setImeVisibilityMonitoringEnabled(boolean enabled) {
+        mImeVisibilityMonitor.setEnabled(enabled);
+    }
+
+    private void updateImeVisibility() {
+        if (!mImeVisibilityMonitor.isRegistered()) {
+            return;
+        }
+        int height = getImeHeight();
+        boolean visible = height > 0;
+        if (visible == mImeVisible && height == mImeHeight) {
+            return;
+        }
+        mImeVisible = visible;
+        mImeHeight = height;
+        onImeVisibilityChangedNative(mViewPeer, visible, height);
+    }
+
+    /**
+     * The height of the part of the window covered by the IME, in pixels, or 0 if the IME isn't
+     * visible. Before API level 30, this is estimated from the part of the window hidden from
+     * the user, which counts only if it's more than a sixth of the window, to leave out the
+     * navigation bar.
+     */
+    public int getImeHeight() {
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.R) {
+            WindowInsets insets = getRootWindowInsets();
+            if (insets == null || !insets.isVisible(WindowInsets.Type.ime())) {
+                return 0;
+            }
+            return insets.getInsets(WindowInsets.Type.ime()).bottom;
+        }
+        if (!isAttachedToWindow()) {
+            return 0;
+        }
+        View root = getRootView();
+        Rect visibleFrame = new Rect();
+        root.getWindowVisibleDisplayFrame(visibleFrame);
+        int[] location = new int[2];
+        root.getLocationOnScreen(location);
+        int covered = Math.max(0, location[1] + root.getHeight() - visibleFrame.bottom);
+        return covered > root.getHeight() / 6 ? covered : 0;
+    }
+
+    public boolean isImeVisible() {
+        return getImeHeight() > 0;
+    }
+
+    private native void onRefreshRateChangedNative(long peer, float refreshRate);
+
+    private final DisplayManager.DisplayListener mDisplayListener =
+            new DisplayManager.DisplayListener() {
+                @Override
+                public void onDisplayAdded(int displayId) {}
+
+                @Override
+                public void onDisplayRemoved(int displayId) {}
+
+                @Override
+                public void onDisplayChanged(int displayId) {
+                    Display display = getDisplay();
+                    if (display == null || display.getDisplayId() != displayId) {
+                        return;
+                    }
+                    float refreshRate = display.getRefreshRate();
+                    if (refreshRate != mLastRefreshRate) {
+                        mLastRefreshRate = refreshRate;
+                        onRefreshRateChangedNative(mViewPeer, refreshRate);
+                    }
+                }
+            };
+
+    private final RustViewMonitor mRefreshRateMonitor =
+            new RustViewMonitor(this) {
+                @Override
+                void register() {
+                    Display display = getDisplay();
+                    mLastRefreshRate = display != null ? display.getRefreshRate() : 0;
+                    getContext()
+                            .getSystemService(DisplayManager.class)
+                            .registerDisplayListener(
+                                    mDisplayListener, new Handler(Looper.getMainLooper()));
+                }
+
+                @Override
+                void unregister() {
+                    getContext()
+                            .getSystemService(DisplayManager.class)
+                            .unregisterDisplayListener(mDisplayListener);
+                }
+            };
+
+    /**
+     * When enabled, the peer is notified while the view is attached when the
+     * refresh rate of the view's display changes, such as when a panel with
+     * a variable refresh rate switches between 60 and 120 Hz.
+     */
+    public void setRefreshRateMonitoringEnabled(boolean enabled) {
+        mRefreshRateMonitor.setEnabled(enabled);
+    }
+
+    private native void onDocumentResultNative(long peer, int requestCode, Uri uri);
+
+    boolean startDocumentRequest(Intent intent, int requestCode) {
+        Activity activity = getActivity();
+        if (activity == null) {
+            return false;
+        }
+        try {
+            activity.startActivityForResult(intent, requestCode);
+        } catch (ActivityNotFoundException e) {
+            return false;
+        }
+        mPendingDocumentRequests.add(requestCode);
+        return true;
+    }
+
+    /**
+     * Views don't receive activity results directly; the host activity must
+     * forward its {@code onActivityResult} callback to this method. Returns
+     * true if the request was started by this view and has been handled.
+     */
+    public boolean onActivityResult(int requestCode, int resultCode, Intent data) {
+        if (!mPendingDocumentRequests.remove(requestCode)) {
+            return false;
+        }
+        Uri uri = resultCode == Activity.RESULT_OK && data != null ? data.getData() : null;
+        onDocumentResultNative(mViewPeer, requestCode, uri);
+        return true;
+    }
+
+    private native void onRequestPermissionsResultNative(
+            long peer, int requestCode, String[] permissions, int[] grantResults);
+
+    boolean requestPermissions(String[] permissions, int requestCode) {
+        Activity activity = getActivity();
+        if (activity == null) {
+            return false;
+        }
+        mPendingPermissionRequests.add(requestCode);
+        activity.requestPermissions(permissions, requestCode);
+        return true;
+    }
+
+    boolean shouldShowRequestPermissionRationale(String permission) {
+        Activity activity = getActivity();
+        return activity != null && activity.shouldShowRequestPermissionRationale(permission);
+    }
+
+    /**
+     * Views don't receive permission results directly; the host activity must
+     * forward its {@code onRequestPermissionsResult} callback to this method.
+     * Returns true if the request was made by this view and has been handled.
+     */
+    public boolean onRequestPermissionsResult(
+            int requestCode, String[] permissions, int[] grantResults) {
+        if (!mPendingPermissionRequests.remove(requestCode)) {
+            return false;
+        }
+        onRequestPermissionsResultNative(mViewPeer, requestCode, permissions, grantResults);
+        return true;
+    }
+
+    private native void onTextToSpeechInitNative(long peer, boolean success);
+
+    void onTextToSpeechInit(boolean success) {
+        onTextToSpeechInitNative(mViewPeer, success);
+    }
+
+    private native void onUtteranceDoneNative(long peer, String utteranceId, boolean error);
+
+    void onUtteranceDone(String utteranceId, boolean error) {
+        onUtteranceDoneNative(mViewPeer, utteranceId, error);
+    }
+
+    private native void onPopupDismissedNative(long peer, int popupId);
+
+    void onPopupDismissed(int popupId) {
+        onPopupDismissedNative(mViewPeer, popupId);
+    }
+
+    private native void onSuggestionPickedNative(long peer, int popupId, int index);
+
+    void onSuggestionPicked(int popupId, int index) {
+        onSuggestionPickedNative(mViewPeer, popupId, index);
+    }
+
+    private native void onSuggestionDeleteNative(long peer, int popupId);
+
+    void onSuggestionDelete(int popupId) {
+        onSuggestionDeleteNative(mViewPeer, popupId);
+    }
+
+    private native void onSaveStateNative(long peer, Bundle outState);
+
+    /** Save the peer's state into {@code outState}, for example from a fragment. */
+    public void saveState(Bundle outState) {
+        onSaveStateNative(mViewPeer, outState);
+    }
+
+    private native void onRestoreStateNative(long peer, Bundle state);
+
+    /** Restore state previously saved with {@link #saveState}. */
+    public void restoreState(Bundle state) {
+        onRestoreStateNative(mViewPeer, state);
+    }
+
+    /**
+     * Include this view's state in the state saved by {@link RustProcessState}, under {@code key},
+     * which must be unique among the app's views and stable across launches. If state for
+     * {@code key} was already restored, it's restored into this view right away, so call this
+     * before the view is attached.
+     */
+    public void setStateKey(String key) {
+        RustProcessState.registerView(key, this);
+    }
+
+    private native void onHostResumeNative(long peer);
+
+    /** Notify the peer that the hosting activity or fragment has resumed. */
+    public void onHostResume() {
+        onHostResumeNative(mViewPeer);
+    }
+
+    private native void onHostPauseNative(long peer);
+
+    /** Notify the peer that the hosting activity or fragment has paused. */
+    public void onHostPause() {
+        onHostPauseNative(mViewPeer);
+    }
+
+    private Activity getActivity() {
+        Context context = getContext();
+        while (context instanceof ContextWrapper) {
+            if (context instanceof Activity) {
+                return (Activity) context;
+            }
+            context = ((ContextWrapper) context).getBaseContext();
+        }
+        return null;
+    }
+
+    boolean setWindowSecure(boolean secure) {
+        Activity activity = getActivity();
+        if (activity == null) {
+            return false;
+        }
+        if (secure) {
+            activity.getWindow().addFlags(WindowManager.LayoutParams.FLAG_SECURE);
+        } else {
+            activity.getWindow().clearFlags(WindowManager.LayoutParams.FLAG_SECURE);
+        }
+        return true;
+    }
+
+    boolean setWindowColorMode(int mode) {
+        Activity activity = getActivity();
+        if (activity == null) {
+            return false;
+        }
+        activity.getWindow().setColorMode(mode);
+        return true;
+    }
+
+    boolean setMediaSessionToken(MediaSession.Token token) {
+        Activity activity = getActivity();
+        if (activity == null) {
+            return false;
+        }
+        activity.setMediaController(
+                token != null ? new MediaController(activity, token) : null);
+        return true;
+    }
+
+    public boolean isInMultiWindowMode() {
+        Activity activity = getActivity();
+        return activity != null && activity.isInMultiWindowMode();
+    }
+
+    public boolean isInPictureInPictureMode() {
+        Activity activity = getActivity();
+        return activity != null && activity.isInPictureInPictureMode();
     }
 
     private native void surfaceCreatedNative(long peer, SurfaceHolder holder);
 
     @Override
     public void surfaceCreated(SurfaceHolder holder) {
+        mFirstFrameRendered = false;
+        updateFirstFrameCover();
         surfaceCreatedNative(mViewPeer, holder);
+        mSurfaceValid = true;
+        updateRenderingActive();
     }
 
     private native void surfaceChangedNative(
@@ -160,9 +1209,145 @@ public abstract class RustView extends SurfaceView
 
     @Override
     public void surfaceDestroyed(SurfaceHolder holder) {
+        mSurfaceValid = false;
+        updateRenderingActive();
         surfaceDestroyedNative(mViewPeer, holder);
     }
 
+    /**
+     * Adds an extra surface layered over this view, identified by a
+     * peer-chosen ID. See {@link RustOverlaySurface} for how it's placed.
+     * Does nothing if an overlay with that ID already exists.
+     */
+    public void addOverlaySurface(int id, boolean aboveWindow) {
+        if (mOverlaySurfaces.get(id) != null) {
+            return;
+        }
+        RustOverlaySurface overlay = new RustOverlaySurface(this, id, aboveWindow);
+        overlay.setVisibility(getVisibility());
+        mOverlaySurfaces.put(id, overlay);
+        scheduleOverlaySurfaceUpdate();
+    }
+
+    public void removeOverlaySurface(int id) {
+        RustOverlaySurface overlay = mOverlaySurfaces.get(id);
+        if (overlay != null) {
+            mOverlaySurfaces.remove(id);
+            overlay.detach();
+        }
+    }
+
+    public boolean hasOverlaySurface(int id) {
+        return mOverlaySurfaces.get(id) != null;
+    }
+
+    private void scheduleOverlaySurfaceUpdate() {
+        // The parent is still dispatching attach or detach to its children,
+        // so it can't add or remove one yet.
+        mOverlayHandler.removeCallbacks(mUpdateOverlaySurfaces);
+        mOverlayHandler.post(mUpdateOverlaySurfaces);
+    }
+
+    private void updateOverlaySurfaces() {
+        boolean attached = isAttachedToWindow();
+        for (int i = 0; i < mOverlaySurfaces.size(); i++) {
+            RustOverlaySurface overlay = mOverlaySurfaces.valueAt(i);
+            if (attached) {
+                overlay.attach();
+            } else {
+                overlay.detach();
+            }
+        }
+    }
+
+    private native void onOverlaySurfaceCreatedNative(long peer, int id, SurfaceHolder holder);
+
+    void onOverlaySurfaceCreated(int id, SurfaceHolder holder) {
+        onOverlaySurfaceCreatedNative(mViewPeer, id, holder);
+    }
+
+    private native void onOverlaySurfaceChangedNative(
+            long peer, int id, SurfaceHolder holder, int format, int width, int height);
+
+    void onOverlaySurfaceChanged(int id, SurfaceHolder holder, int format, int width, int height) {
+        onOverlaySurfaceChangedNative(mViewPeer, id, holder, format, width, height);
+    }
+
+    private native void onOverlaySurfaceDestroyedNative(long peer, int id, SurfaceHolder holder);
+
+    void onOverlaySurfaceDestroyed(int id, SurfaceHolder holder) {
+        onOverlaySurfaceDestroyedNative(mViewPeer, id, holder);
+    }
+
+    private native void onRenderingActiveChangedNative(long peer, boolean active);
+
+    /**
+     * Rendering is active while the view is attached to a visible window and
+     * has a valid surface. When this is enabled, it also requires the window
+     * to have focus; note that in multi-window mode, windows without focus
+     * are still visible.
+     */
+    public void setPauseRenderingWithoutWindowFocus(boolean pause) {
+        mPauseRenderingWithoutWindowFocus = pause;
+        updateRenderingActive();
+    }
+
+    public boolean isRenderingActive() {
+        return mRenderingActive;
+    }
+
+    private void updateRenderingActive() {
+        setRenderingActive(
+                isAttachedToWindow()
+                        && getWindowVisibility() == VISIBLE
+                        && mSurfaceValid
+                        && (hasWindowFocus() || !mPauseRenderingWithoutWindowFocus));
+    }
+
+    private void setRenderingActive(boolean active) {
+        if (active == mRenderingActive) {
+            return;
+        }
+        mRenderingActive = active;
+        if (!active) {
+            removeFrameCallback();
+        }
+        onRenderingActiveChangedNative(mViewPeer, active);
+    }
+
+    /**
+     * A new SurfaceView shows black until its surface receives a frame. When
+     * this is enabled, the view is covered with the theme's background color
+     * from the time the surface is created until the peer reports that it has
+     * rendered its first frame, avoiding the black flash.
+     */
+    public void setHideUntilFirstFrame(boolean hide) {
+        mHideUntilFirstFrame = hide;
+        updateFirstFrameCover();
+    }
+
+    public boolean isHideUntilFirstFrame() {
+        return mHideUntilFirstFrame;
+    }
+
+    void onFirstFrameRendered() {
+        if (!mFirstFrameRendered) {
+            mFirstFrameRendered = true;
+            updateFirstFrameCover();
+        }
+    }
+
+    private void updateFirstFrameCover() {
+        if (mHideUntilFirstFrame && !mFirstFrameRendered) {
+            TypedArray a =
+                    getContext().obtainStyledAttributes(new int[] {android.R.attr.colorBackground});
+            setBackgroundColor(a.getColor(0, 0xff000000));
+            a.recycle();
+        } else {
+            setBackground(null);
+        }
+    }
+
     void postFrameCallback() {
         Choreographer c = Choreographer.getInstance();
         c.removeFrameCallback(this);
@@ -198,6 +1383,63 @@ public abstract class RustView extends SurfaceView
         return removeCallbacks(mDelayedCallback);
     }
 
+    private native void onTimerNative(long peer, int timerId);
+
+    void onTimer(int timerId) {
+        onTimerNative(mViewPeer, timerId);
+    }
+
+    RustViewScheduler getScheduler() {
+        return mScheduler;
+    }
+
+    private void updateSchedulerActive() {
+        mScheduler.setActive(isAttachedToWindow() && getWindowVisibility() == VISIBLE);
+    }
+
+    private native void onAnimationCancelNative(long peer, int animationId);
+
+    private native void onAnimationEndNative(long peer, int animationId);
+
+    ViewPropertyAnimator animate(final int animationId) {
+        return animate()
+                .setListener(
+                        new AnimatorListenerAdapter() {
+                            @Override
+                            public void onAnimationCancel(Animator animation) {
+                                onAnimationCancelNative(mViewPeer, animationId);
+                            }
+
+                            @Override
+                            public void onAnimationEnd(Animator animation) {
+                                onAnimationEndNative(mViewPeer, animationId);
+                            }
+                        });
+    }
+
+    private native void onPixelCopyFinishedNative(
+            long peer, int requestId, int result, Bitmap bitmap);
+
+    boolean requestPixelCopy(final int requestId) {
+        if (getWidth() == 0 || getHeight() == 0 || !getHolder().getSurface().isValid()) {
+            return false;
+        }
+        final Bitmap bitmap =
+                Bitmap.createBitmap(getWidth(), getHeight(), Bitmap.Config.ARGB_8888);
+        PixelCopy.request(
+                this,
+                bitmap,
+                new PixelCopy.OnPixelCopyFinishedListener() {
+                    @Override
+                    public void onPixelCopyFinished(int result) {
+                        onPixelCopyFinishedNative(mViewPeer, requestId, result, bitmap);
+                        bitmap.recycle();
+                    }
+                },
+                getHandler());
+        return true;
+    }
+
     private native boolean hasAccessibilityNodeProviderNative(long peer);
 
     private native AccessibilityNodeInfo createAccessibilityNodeInfoNative(
@@ -239,9 +1481,12 @@ public abstract class RustView extends SurfaceView
         if (!onCreateInputConnectionNative(mViewPeer, outAttrs)) {
             return null;
         }
+        RustEmojiCompat.updateEditorInfo(outAttrs);
         return new RustInputConnection(this);
     }
 
+    native String getExtractedTextNative(long peer, int[] outOffsets);
+
     native String getTextBeforeCursorNative(long peer, int n);
 
     native String getTextAfterCursorNative(long peer, int n);
@@ -0,0 +1,42 @@
+package org.linebender.android.rustview;
+
+import android.content.Context;
+
+/**
+ * Creates Rust views by the name under which their peers were registered with {@code
+ * register_view_factory}, for hosts that construct views from a factory function rather than
+ * from a subclass. For example, in Jetpack Compose:
+ *
+ * <pre>
+ * AndroidView(factory = { context -> RustViewFactory.create(context, "editor") })
+ * </pre>
+ */
+public final class RustViewFactory {
+    private static final ThreadLocal<String> sPendingName = new ThreadLocal<>();
+
+    private RustViewFactory() {}
+
+    public static RustView create(Context context, String name) {
+        sPendingName.set(name);
+        try {
+            return new FactoryView(context);
+        } finally {
+            sPendingName.remove();
+        }
+    }
+
+    static final class FactoryView extends RustView {
+        FactoryView(Context context) {
+            super(context);
+        }
+
+        private native long newFactoryViewPeer(Context context, String name);
+
+        @Override
+        protected long newViewPeer(Context context) {
+            // This runs from the RustView constructor, before any field of this class is
+            // initialized, so the name is passed through a thread-local.
+            return newFactoryViewPeer(context, sPendingName.get());
+        }
+    }
+}
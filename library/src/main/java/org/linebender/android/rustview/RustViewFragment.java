@@ -0,0 +1,88 @@
+package org.linebender.android.rustview;
+
+import android.os.Bundle;
+import android.view.LayoutInflater;
+import android.view.View;
+import android.view.ViewGroup;
+import androidx.fragment.app.Fragment;
+
+/**
+ * A fragment that hosts a Rust view created with {@link RustViewFactory}, forwarding the
+ * fragment's lifecycle and saved state to the view's peer. The peer's state survives both
+ * configuration changes and the view being destroyed while the fragment is on the back stack.
+ */
+public class RustViewFragment extends Fragment {
+    private static final String ARG_VIEW_NAME = "org.linebender.android.rustview.VIEW_NAME";
+    private static final String STATE_VIEW = "org.linebender.android.rustview.VIEW_STATE";
+
+    private RustView mView;
+    private Bundle mViewState;
+
+    /** Create a fragment hosting the view registered under {@code viewName}. */
+    public static RustViewFragment newInstance(String viewName) {
+        RustViewFragment fragment = new RustViewFragment();
+        Bundle args = new Bundle();
+        args.putString(ARG_VIEW_NAME, viewName);
+        fragment.setArguments(args);
+        return fragment;
+    }
+
+    /** The hosted view, or null if the fragment's view hasn't been created. */
+    public RustView getRustView() {
+        return mView;
+    }
+
+    @Override
+    public void onCreate(Bundle savedInstanceState) {
+        super.onCreate(savedInstanceState);
+        if (savedInstanceState != null) {
+            mViewState = savedInstanceState.getBundle(STATE_VIEW);
+        }
+    }
+
+    @Override
+    public View onCreateView(
+            LayoutInflater inflater, ViewGroup container, Bundle savedInstanceState) {
+        String name = requireArguments().getString(ARG_VIEW_NAME);
+        mView = RustViewFactory.create(requireContext(), name);
+        if (mViewState != null) {
+            mView.restoreState(mViewState);
+            mViewState = null;
+        }
+        return mView;
+    }
+
+    @Override
+    public void onResume() {
+        super.onResume();
+        mView.onHostResume();
+    }
+
+    @Override
+    public void onPause() {
+        mView.onHostPause();
+        super.onPause();
+    }
+
+    @Override
+    public void onSaveInstanceState(Bundle outState) {
+        super.onSaveInstanceState(outState);
+        if (mView != null) {
+            Bundle state = new Bundle();
+            mView.saveState(state);
+            outState.putBundle(STATE_VIEW, state);
+        } else if (mViewState != null) {
+            outState.putBundle(STATE_VIEW, mViewState);
+        }
+    }
+
+    @Override
+    public void onDestroyView() {
+        // Keep the state in case the fragment is on the back stack and its view is created
+        // again later.
+        mViewState = new Bundle();
+        mView.saveState(mViewState);
+        mView = null;
+        super.onDestroyView();
+    }
+}
@@ -0,0 +1,56 @@
+package org.linebender.android.rustview;
+
+import android.content.Context;
+import android.view.ViewGroup;
+
+/**
+ * A container whose platform child views, such as a WebView or a map, are measured and laid
+ * out by a Rust peer.
+ */
+public abstract class RustViewGroup extends ViewGroup {
+    final long mViewGroupPeer;
+
+    protected abstract long newViewGroupPeer(Context context);
+
+    public RustViewGroup(Context context) {
+        super(context);
+        mViewGroupPeer = newViewGroupPeer(context);
+    }
+
+    private native int[] onMeasureNative(long peer, int widthSpec, int heightSpec);
+
+    @Override
+    protected void onMeasure(int widthSpec, int heightSpec) {
+        int[] result = onMeasureNative(mViewGroupPeer, widthSpec, heightSpec);
+        if (result != null) {
+            setMeasuredDimension(result[0], result[1]);
+        } else {
+            measureChildren(widthSpec, heightSpec);
+            super.onMeasure(widthSpec, heightSpec);
+        }
+    }
+
+    private native void onLayoutNative(
+            long peer, boolean changed, int left, int top, int right, int bottom);
+
+    @Override
+    protected void onLayout(boolean changed, int left, int top, int right, int bottom) {
+        onLayoutNative(mViewGroupPeer, changed, left, top, right, bottom);
+    }
+
+    private native void onAttachedToWindowNative(long peer);
+
+    @Override
+    protected void onAttachedToWindow() {
+        super.onAttachedToWindow();
+        onAttachedToWindowNative(mViewGroupPeer);
+    }
+
+    private native void onDetachedFromWindowNative(long peer);
+
+    @Override
+    protected void onDetachedFromWindow() {
+        super.onDetachedFromWindow();
+        onDetachedFromWindowNative(mViewGroupPeer);
+    }
+}
@@ -0,0 +1,58 @@
+package org.linebender.android.rustview;
+
+/**
+ * A system listener that a {@link RustView} keeps registered while
+ * monitoring is enabled and the view is attached to a window, so the peer
+ * isn't notified of changes it can't act on and the listener doesn't leak
+ * the view. Subclasses only register and unregister the listener; this
+ * class tracks whether it's registered, and the view updates every monitor
+ * when it's attached or detached.
+ */
+abstract class RustViewMonitor {
+    private final RustView mView;
+    private boolean mEnabled;
+    private boolean mRegistered;
+
+    RustViewMonitor(RustView view) {
+        mView = view;
+        view.mMonitors.add(this);
+    }
+
+    /** Register the listener. Only called while the view is attached. */
+    abstract void register();
+
+    abstract void unregister();
+
+    final void setEnabled(boolean enabled) {
+        mEnabled = enabled;
+        update();
+    }
+
+    final boolean isRegistered() {
+        return mRegistered;
+    }
+
+    /**
+     * Register or unregister the listener to match whether monitoring is
+     * enabled and the view is attached.
+     */
+    final void update() {
+        if (!mEnabled || !mView.isAttachedToWindow()) {
+            stop();
+        } else if (!mRegistered) {
+            register();
+            mRegistered = true;
+        }
+    }
+
+    /**
+     * Unregister the listener if it's registered. Called when the view is
+     * detached, while it still reports being attached.
+     */
+    final void stop() {
+        if (mRegistered) {
+            unregister();
+            mRegistered = false;
+        }
+    }
+}
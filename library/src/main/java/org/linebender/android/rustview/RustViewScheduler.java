@@ -0,0 +1,105 @@
+package org.linebender.android.rustview;
+
+import android.os.SystemClock;
+import android.util.SparseArray;
+
+/**
+ * One-shot and repeating timers for a {@link RustView}, delivered to the
+ * peer's {@code on_timer} callback. Timers only run while the view is
+ * attached to a visible window; otherwise they're paused, keeping the time
+ * that was left, and resumed when the view becomes visible again.
+ */
+final class RustViewScheduler {
+    private final RustView mView;
+    private final SparseArray<Timer> mTimers = new SparseArray<>();
+    private boolean mActive;
+
+    private final class Timer implements Runnable {
+        final int mId;
+        final long mIntervalMillis;
+        long mDueUptimeMillis;
+        long mRemainingMillis;
+
+        Timer(int id, long delayMillis, long intervalMillis) {
+            mId = id;
+            mIntervalMillis = intervalMillis;
+            mRemainingMillis = delayMillis;
+        }
+
+        void post() {
+            mDueUptimeMillis = SystemClock.uptimeMillis() + mRemainingMillis;
+            mView.postDelayed(this, mRemainingMillis);
+        }
+
+        void pause() {
+            mView.removeCallbacks(this);
+            mRemainingMillis = Math.max(0, mDueUptimeMillis - SystemClock.uptimeMillis());
+        }
+
+        @Override
+        public void run() {
+            if (mIntervalMillis > 0) {
+                mRemainingMillis = mIntervalMillis;
+                post();
+            } else {
+                mTimers.remove(mId);
+            }
+            mView.onTimer(mId);
+        }
+    }
+
+    RustViewScheduler(RustView view) {
+        mView = view;
+    }
+
+    /**
+     * Schedule timer {@code id} to fire after {@code delayMillis}, then every
+     * {@code intervalMillis} if that's positive. Replaces any existing timer
+     * with the same ID.
+     */
+    void schedule(int id, long delayMillis, long intervalMillis) {
+        cancel(id);
+        Timer timer = new Timer(id, Math.max(0, delayMillis), intervalMillis);
+        mTimers.put(id, timer);
+        if (mActive) {
+            timer.post();
+        }
+    }
+
+    void cancel(int id) {
+        Timer timer = mTimers.get(id);
+        if (timer != null) {
+            mView.removeCallbacks(timer);
+            mTimers.remove(id);
+        }
+    }
+
+    void cancelAll() {
+        for (int i = 0; i < mTimers.size(); i++) {
+            mView.removeCallbacks(mTimers.valueAt(i));
+        }
+        mTimers.clear();
+    }
+
+    boolean isScheduled(int id) {
+        return mTimers.get(id) != null;
+    }
+
+    boolean isActive() {
+        return mActive;
+    }
+
+    void setActive(boolean active) {
+        if (active == mActive) {
+            return;
+        }
+        mActive = active;
+        for (int i = 0; i < mTimers.size(); i++) {
+            if (active) {
+                mTimers.valueAt(i).post();
+            } else {
+                mTimers.valueAt(i).pause();
+            }
+        }
+    }
+}
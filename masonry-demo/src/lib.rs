@@ -109,6 +109,7 @@ pub unsafe extern "system" fn JNI_OnLoad(vm: *mut RawJavaVM, _: *mut c_void) ->
         &mut env,
         "org/linebender/android/masonrydemo/DemoView",
         new_view_peer,
+        Vec::new(),
     );
     JNI_VERSION_1_6
 }
@@ -81,8 +81,9 @@ fn show_soft_input<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) {
 
 fn hide_soft_input<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) {
     let imm = view.input_method_manager(env);
-    let window_token = view.window_token(env);
-    imm.hide_soft_input_from_window(env, &window_token, 0);
+    if let Some(window_token) = view.window_token(env) {
+        imm.hide_soft_input_from_window(env, &window_token, 0);
+    }
 }
 
 pub struct MasonryState {
@@ -3,13 +3,695 @@ use jni::{
     objects::JObject,
     sys::{jboolean, jint, jlong},
 };
+use ndk::event::MotionAction;
 
-use crate::{bundle::*, callback_ctx::*, util::*, view::*};
+#[cfg(feature = "trace")]
+use crate::trace::*;
+use crate::{
+    bundle::*, callback_ctx::*, events::*, jni_cache::*, native_methods::*, util::*, view::*,
+};
+
+pub const ACCESSIBILITY_SERVICE: &str = "accessibility";
+
+pub const ACCESSIBILITY_EVENT_TYPE_VIEW_TEXT_CHANGED: u32 = 0x00000010;
+pub const ACCESSIBILITY_EVENT_TYPE_VIEW_HOVER_ENTER: u32 = 0x00000080;
+pub const ACCESSIBILITY_EVENT_TYPE_VIEW_HOVER_EXIT: u32 = 0x00000100;
+pub const ACCESSIBILITY_EVENT_TYPE_WINDOW_CONTENT_CHANGED: u32 = 0x00000800;
+pub const ACCESSIBILITY_EVENT_TYPE_VIEW_SCROLLED: u32 = 0x00001000;
+pub const ACCESSIBILITY_EVENT_TYPE_VIEW_TEXT_SELECTION_CHANGED: u32 = 0x00002000;
+pub const ACCESSIBILITY_EVENT_TYPE_ANNOUNCEMENT: u32 = 0x00004000;
+pub const ACCESSIBILITY_EVENT_TYPE_VIEW_TEXT_TRAVERSED_AT_MOVEMENT_GRANULARITY: u32 = 0x00020000;
+
+pub const CONTENT_CHANGE_TYPE_UNDEFINED: u32 = 0x00000000;
+pub const CONTENT_CHANGE_TYPE_SUBTREE: u32 = 0x00000001;
+pub const CONTENT_CHANGE_TYPE_TEXT: u32 = 0x00000002;
+pub const CONTENT_CHANGE_TYPE_CONTENT_DESCRIPTION: u32 = 0x00000004;
+
+pub const ACCESSIBILITY_LIVE_REGION_NONE: jint = 0;
+pub const ACCESSIBILITY_LIVE_REGION_POLITE: jint = 1;
+pub const ACCESSIBILITY_LIVE_REGION_ASSERTIVE: jint = 2;
+
+pub const ACTION_NEXT_AT_MOVEMENT_GRANULARITY: jint = 0x00000100;
+pub const ACTION_PREVIOUS_AT_MOVEMENT_GRANULARITY: jint = 0x00000200;
+pub const ACTION_SET_SELECTION: jint = 0x00020000;
+
+pub const ACTION_ARGUMENT_MOVEMENT_GRANULARITY_INT: &str =
+    "ACTION_ARGUMENT_MOVEMENT_GRANULARITY_INT";
+pub const ACTION_ARGUMENT_EXTEND_SELECTION_BOOLEAN: &str =
+    "ACTION_ARGUMENT_EXTEND_SELECTION_BOOLEAN";
+pub const ACTION_ARGUMENT_SELECTION_START_INT: &str = "ACTION_ARGUMENT_SELECTION_START_INT";
+pub const ACTION_ARGUMENT_SELECTION_END_INT: &str = "ACTION_ARGUMENT_SELECTION_END_INT";
+
+pub const MOVEMENT_GRANULARITY_CHARACTER: jint = 0x00000001;
+pub const MOVEMENT_GRANULARITY_WORD: jint = 0x00000002;
+pub const MOVEMENT_GRANULARITY_LINE: jint = 0x00000004;
+pub const MOVEMENT_GRANULARITY_PARAGRAPH: jint = 0x00000008;
+pub const MOVEMENT_GRANULARITY_PAGE: jint = 0x00000010;
+
+/// The virtual view ID that refers to the host view itself.
+pub const HOST_VIEW_ID: jint = -1;
 
 #[derive(Default)]
 #[repr(transparent)]
 pub struct AccessibilityNodeInfo<'local>(pub JObject<'local>);
 
+impl<'local> AccessibilityNodeInfo<'local> {
+    pub fn add_action(&self, env: &mut JNIEnv<'local>, action: jint) {
+        static ADD_ACTION: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityNodeInfo",
+            "addAction",
+            "(I)V",
+        );
+        ADD_ACTION.call(env, &self.0, &[action.into()]).v().unwrap();
+    }
+
+    /// Set the granularities, as a bitmask of `MOVEMENT_GRANULARITY_*`
+    /// values, at which the text of this node can be traversed.
+    pub fn set_movement_granularities(&self, env: &mut JNIEnv<'local>, granularities: jint) {
+        static SET_MOVEMENT_GRANULARITIES: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityNodeInfo",
+            "setMovementGranularities",
+            "(I)V",
+        );
+        SET_MOVEMENT_GRANULARITIES
+            .call(env, &self.0, &[granularities.into()])
+            .v()
+            .unwrap();
+    }
+
+    /// Set the selection in UTF-16 code units.
+    pub fn set_text_selection(&self, env: &mut JNIEnv<'local>, start: jint, end: jint) {
+        static SET_TEXT_SELECTION: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityNodeInfo",
+            "setTextSelection",
+            "(II)V",
+        );
+        SET_TEXT_SELECTION
+            .call(env, &self.0, &[start.into(), end.into()])
+            .v()
+            .unwrap();
+    }
+
+    /// Mark the node as a live region, so that accessibility services
+    /// announce changes to it without it having focus.
+    pub fn set_live_region(&self, env: &mut JNIEnv<'local>, mode: jint) {
+        static SET_LIVE_REGION: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityNodeInfo",
+            "setLiveRegion",
+            "(I)V",
+        );
+        SET_LIVE_REGION
+            .call(env, &self.0, &[mode.into()])
+            .v()
+            .unwrap();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovementGranularity {
+    Character,
+    Word,
+    Line,
+    Paragraph,
+    Page,
+}
+
+impl MovementGranularity {
+    pub fn from_java(granularity: jint) -> Option<Self> {
+        match granularity {
+            MOVEMENT_GRANULARITY_CHARACTER => Some(Self::Character),
+            MOVEMENT_GRANULARITY_WORD => Some(Self::Word),
+            MOVEMENT_GRANULARITY_LINE => Some(Self::Line),
+            MOVEMENT_GRANULARITY_PARAGRAPH => Some(Self::Paragraph),
+            MOVEMENT_GRANULARITY_PAGE => Some(Self::Page),
+            _ => None,
+        }
+    }
+
+    pub fn to_java(self) -> jint {
+        match self {
+            Self::Character => MOVEMENT_GRANULARITY_CHARACTER,
+            Self::Word => MOVEMENT_GRANULARITY_WORD,
+            Self::Line => MOVEMENT_GRANULARITY_LINE,
+            Self::Paragraph => MOVEMENT_GRANULARITY_PARAGRAPH,
+            Self::Page => MOVEMENT_GRANULARITY_PAGE,
+        }
+    }
+}
+
+/// A text navigation request from an accessibility service, such as
+/// a screen reader, braille display, or switch access. Editors that
+/// keep their text in a `CompositionState` can apply it with
+/// `CompositionState::apply_navigation`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextNavigationAction {
+    /// Move the caret forward by one unit of the given granularity,
+    /// optionally extending the selection.
+    Next {
+        granularity: MovementGranularity,
+        extend_selection: bool,
+    },
+    /// Move the caret backward by one unit of the given granularity,
+    /// optionally extending the selection.
+    Previous {
+        granularity: MovementGranularity,
+        extend_selection: bool,
+    },
+    /// Select the given range, in UTF-16 code units. If both are
+    /// equal, this places the caret.
+    SetSelection { start: jint, end: jint },
+}
+
+impl TextNavigationAction {
+    /// Parse the arguments of [`AccessibilityNodeProvider::perform_action`].
+    /// Returns `None` if the action isn't a text navigation action or
+    /// its arguments are missing or invalid.
+    pub fn from_java<'local>(
+        env: &mut JNIEnv<'local>,
+        action: jint,
+        arguments: &Bundle<'local>,
+    ) -> Option<Self> {
+        match action {
+            ACTION_NEXT_AT_MOVEMENT_GRANULARITY | ACTION_PREVIOUS_AT_MOVEMENT_GRANULARITY => {
+                let granularity = MovementGranularity::from_java(arguments.get_int(
+                    env,
+                    ACTION_ARGUMENT_MOVEMENT_GRANULARITY_INT,
+                    0,
+                ))?;
+                let extend_selection =
+                    arguments.get_boolean(env, ACTION_ARGUMENT_EXTEND_SELECTION_BOOLEAN, false);
+                Some(if action == ACTION_NEXT_AT_MOVEMENT_GRANULARITY {
+                    Self::Next {
+                        granularity,
+                        extend_selection,
+                    }
+                } else {
+                    Self::Previous {
+                        granularity,
+                        extend_selection,
+                    }
+                })
+            }
+            ACTION_SET_SELECTION => {
+                // As in `TextView`, a missing argument means the selection
+                // is cleared, which we represent as a negative range.
+                let start = arguments.get_int(env, ACTION_ARGUMENT_SELECTION_START_INT, -1);
+                let end = arguments.get_int(env, ACTION_ARGUMENT_SELECTION_END_INT, -1);
+                Some(Self::SetSelection { start, end })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[repr(transparent)]
+pub struct AccessibilityEvent<'local>(pub JObject<'local>);
+
+impl<'local> AccessibilityEvent<'local> {
+    pub fn obtain(env: &mut JNIEnv<'local>, event_type: u32) -> Self {
+        static OBTAIN: CachedStaticMethod = CachedStaticMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "obtain",
+            "(I)Landroid/view/accessibility/AccessibilityEvent;",
+        );
+        Self(
+            OBTAIN
+                .call(env, &[(event_type as jint).into()])
+                .l()
+                .unwrap(),
+        )
+    }
+
+    /// Obtain an event of the given type whose source is `view`, or
+    /// the virtual descendant of `view` with the given ID.
+    /// The class and package names are populated as the framework does
+    /// for events originating from a view.
+    pub fn obtain_for_source(
+        env: &mut JNIEnv<'local>,
+        event_type: u32,
+        view: &View<'local>,
+        virtual_view_id: jint,
+    ) -> Self {
+        let event = Self::obtain(env, event_type);
+        event.set_source(env, view, virtual_view_id);
+        event.set_class_name(env, "android.view.View");
+        let package_name = view.context(env).package_name(env);
+        event.set_package_name(env, &package_name);
+        event
+    }
+
+    /// Make an event reporting that text was replaced in an editable
+    /// node. `before_text` is the full text before the change,
+    /// and `text` is the full text after the change. Indices are
+    /// in UTF-16 code units, as on the Java side.
+    #[allow(clippy::too_many_arguments)]
+    pub fn text_changed(
+        env: &mut JNIEnv<'local>,
+        view: &View<'local>,
+        virtual_view_id: jint,
+        before_text: &str,
+        text: &str,
+        from_index: jint,
+        removed_count: jint,
+        added_count: jint,
+    ) -> Self {
+        static SET_BEFORE_TEXT: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setBeforeText",
+            "(Ljava/lang/CharSequence;)V",
+        );
+        static SET_REMOVED_COUNT: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setRemovedCount",
+            "(I)V",
+        );
+        static SET_ADDED_COUNT: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setAddedCount",
+            "(I)V",
+        );
+        let event = Self::obtain_for_source(
+            env,
+            ACCESSIBILITY_EVENT_TYPE_VIEW_TEXT_CHANGED,
+            view,
+            virtual_view_id,
+        );
+        let before_text = new_java_string(env, before_text);
+        SET_BEFORE_TEXT
+            .call(env, &event.0, &[(&before_text).into()])
+            .v()
+            .unwrap();
+        event.add_text(env, text);
+        event.set_from_index(env, from_index);
+        SET_REMOVED_COUNT
+            .call(env, &event.0, &[removed_count.into()])
+            .v()
+            .unwrap();
+        SET_ADDED_COUNT
+            .call(env, &event.0, &[added_count.into()])
+            .v()
+            .unwrap();
+        event
+    }
+
+    /// Make an event reporting that the selection or caret moved
+    /// within `text`. Indices are in UTF-16 code units.
+    pub fn text_selection_changed(
+        env: &mut JNIEnv<'local>,
+        view: &View<'local>,
+        virtual_view_id: jint,
+        text: &str,
+        sel_start: jint,
+        sel_end: jint,
+        text_len: jint,
+    ) -> Self {
+        let event = Self::obtain_for_source(
+            env,
+            ACCESSIBILITY_EVENT_TYPE_VIEW_TEXT_SELECTION_CHANGED,
+            view,
+            virtual_view_id,
+        );
+        event.add_text(env, text);
+        event.set_from_index(env, sel_start);
+        event.set_to_index(env, sel_end);
+        event.set_item_count(env, text_len);
+        event
+    }
+
+    /// Make an event reporting that the caret moved in response to
+    /// [`TextNavigationAction::Next`] or [`TextNavigationAction::Previous`].
+    /// `from_index` and `to_index` delimit the text that was traversed,
+    /// in UTF-16 code units. Braille displays and screen readers use this
+    /// to present the traversed unit.
+    #[allow(clippy::too_many_arguments)]
+    pub fn text_traversed_at_movement_granularity(
+        env: &mut JNIEnv<'local>,
+        view: &View<'local>,
+        virtual_view_id: jint,
+        text: &str,
+        action: jint,
+        granularity: MovementGranularity,
+        from_index: jint,
+        to_index: jint,
+    ) -> Self {
+        static SET_ACTION: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setAction",
+            "(I)V",
+        );
+        static SET_MOVEMENT_GRANULARITY: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setMovementGranularity",
+            "(I)V",
+        );
+        let event = Self::obtain_for_source(
+            env,
+            ACCESSIBILITY_EVENT_TYPE_VIEW_TEXT_TRAVERSED_AT_MOVEMENT_GRANULARITY,
+            view,
+            virtual_view_id,
+        );
+        event.add_text(env, text);
+        event.set_from_index(env, from_index);
+        event.set_to_index(env, to_index);
+        SET_ACTION
+            .call(env, &event.0, &[action.into()])
+            .v()
+            .unwrap();
+        SET_MOVEMENT_GRANULARITY
+            .call(env, &event.0, &[granularity.to_java().into()])
+            .v()
+            .unwrap();
+        event
+    }
+
+    /// Make an announcement event for the given text.
+    pub fn announcement(env: &mut JNIEnv<'local>, view: &View<'local>, text: &str) -> Self {
+        let event = Self::obtain_for_source(
+            env,
+            ACCESSIBILITY_EVENT_TYPE_ANNOUNCEMENT,
+            view,
+            HOST_VIEW_ID,
+        );
+        event.add_text(env, text);
+        event
+    }
+
+    /// Make an event reporting that a node scrolled to the given
+    /// position, in pixels.
+    pub fn view_scrolled(
+        env: &mut JNIEnv<'local>,
+        view: &View<'local>,
+        virtual_view_id: jint,
+        scroll_x: jint,
+        scroll_y: jint,
+        max_scroll_x: jint,
+        max_scroll_y: jint,
+    ) -> Self {
+        static SET_SCROLL_X: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setScrollX",
+            "(I)V",
+        );
+        static SET_SCROLL_Y: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setScrollY",
+            "(I)V",
+        );
+        static SET_MAX_SCROLL_X: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setMaxScrollX",
+            "(I)V",
+        );
+        static SET_MAX_SCROLL_Y: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setMaxScrollY",
+            "(I)V",
+        );
+        let event = Self::obtain_for_source(
+            env,
+            ACCESSIBILITY_EVENT_TYPE_VIEW_SCROLLED,
+            view,
+            virtual_view_id,
+        );
+        for (method, value) in [
+            (&SET_SCROLL_X, scroll_x),
+            (&SET_SCROLL_Y, scroll_y),
+            (&SET_MAX_SCROLL_X, max_scroll_x),
+            (&SET_MAX_SCROLL_Y, max_scroll_y),
+        ] {
+            method.call(env, &event.0, &[value.into()]).v().unwrap();
+        }
+        event
+    }
+
+    /// Make an event reporting that the content of a node changed,
+    /// which is how changes to live regions are announced.
+    pub fn window_content_changed(
+        env: &mut JNIEnv<'local>,
+        view: &View<'local>,
+        virtual_view_id: jint,
+        content_change_types: u32,
+    ) -> Self {
+        static SET_CONTENT_CHANGE_TYPES: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setContentChangeTypes",
+            "(I)V",
+        );
+        let event = Self::obtain_for_source(
+            env,
+            ACCESSIBILITY_EVENT_TYPE_WINDOW_CONTENT_CHANGED,
+            view,
+            virtual_view_id,
+        );
+        SET_CONTENT_CHANGE_TYPES
+            .call(env, &event.0, &[(content_change_types as jint).into()])
+            .v()
+            .unwrap();
+        event
+    }
+
+    pub fn set_source(&self, env: &mut JNIEnv<'local>, view: &View<'local>, virtual_view_id: jint) {
+        static SET_SOURCE: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setSource",
+            "(Landroid/view/View;I)V",
+        );
+        SET_SOURCE
+            .call(env, &self.0, &[(&view.0).into(), virtual_view_id.into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn set_from_index(&self, env: &mut JNIEnv<'local>, index: jint) {
+        static SET_FROM_INDEX: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setFromIndex",
+            "(I)V",
+        );
+        SET_FROM_INDEX
+            .call(env, &self.0, &[index.into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn set_to_index(&self, env: &mut JNIEnv<'local>, index: jint) {
+        static SET_TO_INDEX: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setToIndex",
+            "(I)V",
+        );
+        SET_TO_INDEX
+            .call(env, &self.0, &[index.into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn set_item_count(&self, env: &mut JNIEnv<'local>, count: jint) {
+        static SET_ITEM_COUNT: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setItemCount",
+            "(I)V",
+        );
+        SET_ITEM_COUNT
+            .call(env, &self.0, &[count.into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn set_class_name(&self, env: &mut JNIEnv<'local>, class_name: &str) {
+        static SET_CLASS_NAME: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setClassName",
+            "(Ljava/lang/CharSequence;)V",
+        );
+        let class_name = new_java_string(env, class_name);
+        SET_CLASS_NAME
+            .call(env, &self.0, &[(&class_name).into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn set_package_name(&self, env: &mut JNIEnv<'local>, package_name: &JObject<'local>) {
+        static SET_PACKAGE_NAME: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "setPackageName",
+            "(Ljava/lang/CharSequence;)V",
+        );
+        SET_PACKAGE_NAME
+            .call(env, &self.0, &[package_name.into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn add_text(&self, env: &mut JNIEnv<'local>, text: &str) {
+        static GET_TEXT: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityEvent",
+            "getText",
+            "()Ljava/util/List;",
+        );
+        static ADD: CachedMethod =
+            CachedMethod::new("java/util/List", "add", "(Ljava/lang/Object;)Z");
+        let list = GET_TEXT.call(env, &self.0, &[]).l().unwrap();
+        let text = new_java_string(env, text);
+        ADD.call(env, &list, &[(&text).into()]).z().unwrap();
+    }
+}
+
+#[repr(transparent)]
+pub struct AccessibilityManager<'local>(pub JObject<'local>);
+
+impl<'local> AccessibilityManager<'local> {
+    pub fn is_enabled(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_ENABLED: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityManager",
+            "isEnabled",
+            "()Z",
+        );
+        IS_ENABLED.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    pub fn is_touch_exploration_enabled(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_TOUCH_EXPLORATION_ENABLED: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityManager",
+            "isTouchExplorationEnabled",
+            "()Z",
+        );
+        IS_TOUCH_EXPLORATION_ENABLED
+            .call(env, &self.0, &[])
+            .z()
+            .unwrap()
+    }
+
+    /// Send an event to the enabled accessibility services.
+    ///
+    /// The framework throws if accessibility is disabled, so callers should
+    /// check [`AccessibilityManager::is_enabled`] first.
+    pub fn send_accessibility_event(
+        &self,
+        env: &mut JNIEnv<'local>,
+        event: &AccessibilityEvent<'local>,
+    ) {
+        static SEND_ACCESSIBILITY_EVENT: CachedMethod = CachedMethod::new(
+            "android/view/accessibility/AccessibilityManager",
+            "sendAccessibilityEvent",
+            "(Landroid/view/accessibility/AccessibilityEvent;)V",
+        );
+        SEND_ACCESSIBILITY_EVENT
+            .call(env, &self.0, &[(&event.0).into()])
+            .v()
+            .unwrap();
+    }
+
+    /// Ask the screen reader, if any, to speak `text`, attributing the
+    /// announcement to `view`. Does nothing if accessibility is disabled.
+    pub fn announce(&self, env: &mut JNIEnv<'local>, view: &View<'local>, text: &str) {
+        if !self.is_enabled(env) {
+            return;
+        }
+        let event = AccessibilityEvent::announcement(env, view, text);
+        self.send_accessibility_event(env, &event);
+    }
+}
+
+/// Tracks which virtual view is under the pointer during touch exploration,
+/// sending hover enter and exit events for virtual views as the framework
+/// does for real views. This is the equivalent of the hover handling in
+/// AndroidX `ExploreByTouchHelper`.
+#[derive(Debug)]
+pub struct AccessibilityHoverHelper {
+    hovered_virtual_view_id: Option<jint>,
+}
+
+impl Default for AccessibilityHoverHelper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccessibilityHoverHelper {
+    pub fn new() -> Self {
+        Self {
+            hovered_virtual_view_id: None,
+        }
+    }
+
+    /// The virtual view currently under the pointer, if any.
+    pub fn hovered_virtual_view_id(&self) -> Option<jint> {
+        self.hovered_virtual_view_id
+    }
+
+    /// Handle a hover event from [`ViewPeer::on_hover_event`].
+    ///
+    /// `hit_test` maps a point in view coordinates to the ID of the
+    /// virtual view at that point, or `None` if there is none. Returns
+    /// `true` if the event was consumed by touch exploration; otherwise
+    /// the peer should handle the event normally. Touch exploration
+    /// events are only consumed while a screen reader with touch
+    /// exploration is active.
+    pub fn on_hover_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+        hit_test: impl FnOnce(f32, f32) -> Option<jint>,
+    ) -> bool {
+        let manager = ctx.view.accessibility_manager(&mut ctx.env);
+        if !manager.is_enabled(&mut ctx.env) || !manager.is_touch_exploration_enabled(&mut ctx.env)
+        {
+            return false;
+        }
+        match event.action_masked(&mut ctx.env) {
+            MotionAction::HoverEnter | MotionAction::HoverMove => {
+                let x = event.x(&mut ctx.env);
+                let y = event.y(&mut ctx.env);
+                let id = hit_test(x, y);
+                self.update_hovered_virtual_view(ctx, id);
+                id.is_some()
+            }
+            MotionAction::HoverExit if self.hovered_virtual_view_id.is_some() => {
+                self.update_hovered_virtual_view(ctx, None);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget the hovered virtual view, sending a hover exit event for it.
+    /// Call this when the virtual view hierarchy is replaced or the view
+    /// loses its window.
+    pub fn reset(&mut self, ctx: &mut CallbackCtx) {
+        self.update_hovered_virtual_view(ctx, None);
+    }
+
+    fn update_hovered_virtual_view(&mut self, ctx: &mut CallbackCtx, id: Option<jint>) {
+        if self.hovered_virtual_view_id == id {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.hovered_virtual_view_id, id);
+        // Sending the events can call back into the node provider,
+        // so it must happen after the peer is released.
+        ctx.push_dynamic_deferred_callback(move |env, view| {
+            if let Some(id) = id {
+                let event = AccessibilityEvent::obtain_for_source(
+                    env,
+                    ACCESSIBILITY_EVENT_TYPE_VIEW_HOVER_ENTER,
+                    view,
+                    id,
+                );
+                view.request_send_accessibility_event(env, &event);
+            }
+            if let Some(previous) = previous {
+                let event = AccessibilityEvent::obtain_for_source(
+                    env,
+                    ACCESSIBILITY_EVENT_TYPE_VIEW_HOVER_EXIT,
+                    view,
+                    previous,
+                );
+                view.request_send_accessibility_event(env, &event);
+            }
+        });
+    }
+}
+
 #[allow(unused_variables)]
 pub trait AccessibilityNodeProvider {
     fn create_accessibility_node_info<'local>(
@@ -50,51 +732,85 @@ where
     })
 }
 
-pub(crate) extern "system" fn has_accessibility_node_provider<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) -> jboolean {
-    as_jboolean(with_accessibility_node_provider(
-        env,
-        view,
-        peer,
-        |_ctx, _anp| true,
-    ))
-}
+native_methods! {
+    fn accessibility_methods;
 
-pub(crate) extern "system" fn create_accessibility_node_info<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    virtual_view_id: jint,
-) -> AccessibilityNodeInfo<'local> {
-    with_accessibility_node_provider(env, view, peer, |ctx, anp| {
-        anp.create_accessibility_node_info(ctx, virtual_view_id)
-    })
-}
+    "hasAccessibilityNodeProviderNative" => fn has_accessibility_node_provider<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) -> jboolean {
+        as_jboolean(with_accessibility_node_provider(
+            env,
+            view,
+            peer,
+            |_ctx, _anp| true,
+        ))
+    }
 
-pub(crate) extern "system" fn accessibility_find_focus<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    focus_type: jint,
-) -> AccessibilityNodeInfo<'local> {
-    with_accessibility_node_provider(env, view, peer, |ctx, anp| anp.find_focus(ctx, focus_type))
+    "createAccessibilityNodeInfoNative" => fn create_accessibility_node_info<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        virtual_view_id: jint,
+    ) -> AccessibilityNodeInfo<'local> {
+        with_accessibility_node_provider(env, view, peer, |ctx, anp| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::ACCESSIBILITY,
+                format_args!("create_accessibility_node_info: {virtual_view_id}"),
+            );
+            anp.create_accessibility_node_info(ctx, virtual_view_id)
+        })
+    }
+
+    "accessibilityFindFocusNative" => fn accessibility_find_focus<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        focus_type: jint,
+    ) -> AccessibilityNodeInfo<'local> {
+        with_accessibility_node_provider(env, view, peer, |ctx, anp| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::ACCESSIBILITY,
+                format_args!("find_focus: {focus_type}"),
+            );
+            anp.find_focus(ctx, focus_type)
+        })
+    }
+
+    "performAccessibilityActionNative" => fn perform_accessibility_action<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        virtual_view_id: jint,
+        action: jint,
+        arguments: Bundle<'local>,
+    ) -> jboolean {
+        as_jboolean(with_accessibility_node_provider(
+            env,
+            view,
+            peer,
+            |ctx, anp| {
+                #[cfg(feature = "trace")]
+                log(
+                    ctx,
+                    TraceCategories::ACCESSIBILITY,
+                    format_args!("perform_action: {action} on {virtual_view_id}"),
+                );
+                anp.perform_action(ctx, virtual_view_id, action, &arguments)
+            },
+        ))
+    }
 }
 
-pub(crate) extern "system" fn perform_accessibility_action<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    virtual_view_id: jint,
-    action: jint,
-    arguments: Bundle<'local>,
-) -> jboolean {
-    as_jboolean(with_accessibility_node_provider(
-        env,
-        view,
-        peer,
-        |ctx, anp| anp.perform_action(ctx, virtual_view_id, action, &arguments),
-    ))
+pub(crate) fn register_natives(env: &mut JNIEnv) {
+    env.register_native_methods(
+        "org/linebender/android/rustview/RustView",
+        &accessibility_methods(),
+    )
+    .unwrap();
 }
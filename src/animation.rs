@@ -0,0 +1,70 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jlong},
+};
+
+use crate::jni_cache::*;
+
+/// A binding to `android.view.ViewPropertyAnimator`, obtained from
+/// [`crate::View::animate`]. The setters target the end value of each
+/// property; the animation starts on the next frame, or immediately
+/// when [`ViewPropertyAnimator::start`] is called.
+#[repr(transparent)]
+pub struct ViewPropertyAnimator<'local>(pub JObject<'local>);
+
+macro_rules! property {
+    ($name:ident, $java_name:literal) => {
+        pub fn $name(&self, env: &mut JNIEnv<'local>, value: jfloat) -> &Self {
+            static METHOD: CachedMethod = CachedMethod::new(
+                "android/view/ViewPropertyAnimator",
+                $java_name,
+                "(F)Landroid/view/ViewPropertyAnimator;",
+            );
+            METHOD.call(env, &self.0, &[value.into()]);
+            self
+        }
+    };
+}
+
+impl<'local> ViewPropertyAnimator<'local> {
+    property!(alpha, "alpha");
+    property!(translation_x, "translationX");
+    property!(translation_y, "translationY");
+    property!(translation_z, "translationZ");
+    property!(scale_x, "scaleX");
+    property!(scale_y, "scaleY");
+    property!(rotation, "rotation");
+
+    pub fn set_duration(&self, env: &mut JNIEnv<'local>, duration_millis: jlong) -> &Self {
+        static SET_DURATION: CachedMethod = CachedMethod::new(
+            "android/view/ViewPropertyAnimator",
+            "setDuration",
+            "(J)Landroid/view/ViewPropertyAnimator;",
+        );
+        SET_DURATION.call(env, &self.0, &[duration_millis.into()]);
+        self
+    }
+
+    pub fn set_start_delay(&self, env: &mut JNIEnv<'local>, delay_millis: jlong) -> &Self {
+        static SET_START_DELAY: CachedMethod = CachedMethod::new(
+            "android/view/ViewPropertyAnimator",
+            "setStartDelay",
+            "(J)Landroid/view/ViewPropertyAnimator;",
+        );
+        SET_START_DELAY.call(env, &self.0, &[delay_millis.into()]);
+        self
+    }
+
+    pub fn start(&self, env: &mut JNIEnv<'local>) {
+        static START: CachedMethod =
+            CachedMethod::new("android/view/ViewPropertyAnimator", "start", "()V");
+        START.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    pub fn cancel(&self, env: &mut JNIEnv<'local>) {
+        static CANCEL: CachedMethod =
+            CachedMethod::new("android/view/ViewPropertyAnimator", "cancel", "()V");
+        CANCEL.call(env, &self.0, &[]).v().unwrap()
+    }
+}
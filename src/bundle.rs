@@ -1,4 +1,138 @@
-use jni::objects::JObject;
+use jni::{
+    JNIEnv,
+    objects::{JByteArray, JObject, JString},
+    sys::jint,
+};
+
+use crate::jni_cache::*;
 
 #[repr(transparent)]
 pub struct Bundle<'local>(pub JObject<'local>);
+
+impl<'local> Bundle<'local> {
+    pub fn get_int(&self, env: &mut JNIEnv<'local>, key: &str, default_value: jint) -> jint {
+        static GET_INT: CachedMethod =
+            CachedMethod::new("android/os/Bundle", "getInt", "(Ljava/lang/String;I)I");
+        if self.0.as_raw().is_null() {
+            return default_value;
+        }
+        let key = new_java_string(env, key);
+        GET_INT
+            .call(env, &self.0, &[(&key).into(), default_value.into()])
+            .i()
+            .unwrap()
+    }
+
+    pub fn get_boolean(&self, env: &mut JNIEnv<'local>, key: &str, default_value: bool) -> bool {
+        static GET_BOOLEAN: CachedMethod =
+            CachedMethod::new("android/os/Bundle", "getBoolean", "(Ljava/lang/String;Z)Z");
+        if self.0.as_raw().is_null() {
+            return default_value;
+        }
+        let key = new_java_string(env, key);
+        GET_BOOLEAN
+            .call(env, &self.0, &[(&key).into(), default_value.into()])
+            .z()
+            .unwrap()
+    }
+
+    pub fn contains_key(&self, env: &mut JNIEnv<'local>, key: &str) -> bool {
+        static CONTAINS_KEY: CachedMethod =
+            CachedMethod::new("android/os/Bundle", "containsKey", "(Ljava/lang/String;)Z");
+        if self.0.as_raw().is_null() {
+            return false;
+        }
+        let key = new_java_string(env, key);
+        CONTAINS_KEY
+            .call(env, &self.0, &[(&key).into()])
+            .z()
+            .unwrap()
+    }
+
+    pub fn put_int(&self, env: &mut JNIEnv<'local>, key: &str, value: jint) {
+        static PUT_INT: CachedMethod =
+            CachedMethod::new("android/os/Bundle", "putInt", "(Ljava/lang/String;I)V");
+        let key = new_java_string(env, key);
+        PUT_INT
+            .call(env, &self.0, &[(&key).into(), value.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn put_boolean(&self, env: &mut JNIEnv<'local>, key: &str, value: bool) {
+        static PUT_BOOLEAN: CachedMethod =
+            CachedMethod::new("android/os/Bundle", "putBoolean", "(Ljava/lang/String;Z)V");
+        let key = new_java_string(env, key);
+        PUT_BOOLEAN
+            .call(env, &self.0, &[(&key).into(), value.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn get_string(&self, env: &mut JNIEnv<'local>, key: &str) -> Option<String> {
+        static GET_STRING: CachedMethod = CachedMethod::new(
+            "android/os/Bundle",
+            "getString",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+        );
+        if self.0.as_raw().is_null() {
+            return None;
+        }
+        let key = new_java_string(env, key);
+        let value = GET_STRING.call(env, &self.0, &[(&key).into()]).l().unwrap();
+        if value.is_null() {
+            return None;
+        }
+        let value = JString::from(value);
+        Some(env.get_string(&value).unwrap().into())
+    }
+
+    pub fn put_string(&self, env: &mut JNIEnv<'local>, key: &str, value: &str) {
+        static PUT_STRING: CachedMethod = CachedMethod::new(
+            "android/os/Bundle",
+            "putString",
+            "(Ljava/lang/String;Ljava/lang/String;)V",
+        );
+        let key = new_java_string(env, key);
+        let value = new_java_string(env, value);
+        PUT_STRING
+            .call(env, &self.0, &[(&key).into(), (&value).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Get a byte array, such as state serialized by the peer.
+    pub fn get_byte_array(&self, env: &mut JNIEnv<'local>, key: &str) -> Option<Vec<u8>> {
+        static GET_BYTE_ARRAY: CachedMethod = CachedMethod::new(
+            "android/os/Bundle",
+            "getByteArray",
+            "(Ljava/lang/String;)[B",
+        );
+        if self.0.as_raw().is_null() {
+            return None;
+        }
+        let key = new_java_string(env, key);
+        let value = GET_BYTE_ARRAY
+            .call(env, &self.0, &[(&key).into()])
+            .l()
+            .unwrap();
+        if value.is_null() {
+            return None;
+        }
+        Some(env.convert_byte_array(JByteArray::from(value)).unwrap())
+    }
+
+    pub fn put_byte_array(&self, env: &mut JNIEnv<'local>, key: &str, value: &[u8]) {
+        static PUT_BYTE_ARRAY: CachedMethod = CachedMethod::new(
+            "android/os/Bundle",
+            "putByteArray",
+            "(Ljava/lang/String;[B)V",
+        );
+        let key = new_java_string(env, key);
+        let value = env.byte_array_from_slice(value).unwrap();
+        PUT_BYTE_ARRAY
+            .call(env, &self.0, &[(&key).into(), (&value).into()])
+            .v()
+            .unwrap()
+    }
+}
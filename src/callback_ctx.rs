@@ -1,7 +1,11 @@
-use jni::JNIEnv;
+use jni::{JNIEnv, sys::jlong};
 use smallvec::SmallVec;
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
 
-use crate::view::View;
+use crate::view::{View, ViewPeer};
 
 enum DeferredCallback<'local> {
     Static(fn(&mut JNIEnv<'local>, &View<'local>)),
@@ -11,18 +15,26 @@ enum DeferredCallback<'local> {
 pub struct CallbackCtx<'local> {
     pub env: JNIEnv<'local>,
     pub view: View<'local>,
+    peer: WeakPeerHandle,
     deferred_callbacks: SmallVec<[DeferredCallback<'local>; 4]>,
 }
 
 impl<'local> CallbackCtx<'local> {
-    pub(crate) fn new(env: JNIEnv<'local>, view: View<'local>) -> Self {
+    pub(crate) fn new(env: JNIEnv<'local>, view: View<'local>, peer: WeakPeerHandle) -> Self {
         Self {
             env,
             view,
+            peer,
             deferred_callbacks: SmallVec::new(),
         }
     }
 
+    /// Return a handle to the peer receiving this callback, for tasks
+    /// that run later and may outlive the peer.
+    pub fn peer_handle(&self) -> WeakPeerHandle {
+        self.peer.clone()
+    }
+
     pub fn push_static_deferred_callback(
         &mut self,
         callback: fn(&mut JNIEnv<'local>, &View<'local>),
@@ -50,3 +62,50 @@ impl CallbackCtx<'_> {
         }
     }
 }
+
+/// A weak reference to a view peer, for delayed or posted tasks that
+/// need to call back into the peer. The peer is dropped when its view
+/// is detached from the window; after that, [`WeakPeerHandle::with`]
+/// does nothing, so a task that fires late is harmless.
+#[derive(Clone)]
+pub struct WeakPeerHandle {
+    id: jlong,
+    peer: Weak<RefCell<Box<dyn ViewPeer>>>,
+}
+
+impl WeakPeerHandle {
+    pub(crate) fn new(id: jlong, peer: &Rc<RefCell<Box<dyn ViewPeer>>>) -> Self {
+        Self {
+            id,
+            peer: Rc::downgrade(peer),
+        }
+    }
+
+    /// The ID returned by [`crate::register_view_peer`] for the peer.
+    pub fn id(&self) -> jlong {
+        self.id
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.peer.strong_count() != 0
+    }
+
+    /// Call `f` with the peer, if it still exists, and return its result.
+    /// Returns `None` without calling `f` if the peer has been dropped,
+    /// or if it is already borrowed because this is called from within
+    /// one of the peer's own callbacks.
+    pub fn with<'local, T>(
+        &self,
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        f: impl FnOnce(&mut CallbackCtx<'local>, &mut dyn ViewPeer) -> T,
+    ) -> Option<T> {
+        let peer = self.peer.upgrade()?;
+        let mut borrowed = peer.try_borrow_mut().ok()?;
+        let mut ctx = CallbackCtx::new(env, view, self.clone());
+        let result = f(&mut ctx, &mut **borrowed);
+        drop(borrowed);
+        ctx.finish();
+        Some(result)
+    }
+}
@@ -0,0 +1,130 @@
+use jni::{
+    JNIEnv,
+    objects::{JIntArray, JObjectArray, JString},
+    sys::jint,
+};
+
+use crate::{jni_cache::*, util::*, view::*};
+
+pub const LENS_FACING_FRONT: jint = 0;
+pub const LENS_FACING_BACK: jint = 1;
+pub const LENS_FACING_EXTERNAL: jint = 2;
+
+/// A Camera2 preview streamed directly into the view's surface.
+///
+/// The intended flow is to call [`CameraPreview::configure_size`] from
+/// [`ViewPeer::surface_created`], which resizes the surface to a size
+/// the camera supports, then call [`CameraPreview::start`] from
+/// [`ViewPeer::surface_changed`] once the surface has that size, and
+/// [`CameraPreview::stop`] from [`ViewPeer::surface_destroyed`].
+/// While the preview is running, the peer must not render to the surface.
+///
+/// The app must hold the `CAMERA` permission. This holds a global
+/// reference, so it can be kept in a peer across callbacks.
+pub struct CameraPreview(TrackedGlobalRef);
+
+impl CameraPreview {
+    pub fn new<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) -> Self {
+        let preview = new_java_object(
+            env,
+            "org/linebender/android/rustview/RustCameraPreview",
+            "(Lorg/linebender/android/rustview/RustView;)V",
+            &[(&view.0).into()],
+        );
+        Self(TrackedGlobalRef::new(env, preview, "CameraPreview"))
+    }
+
+    pub fn camera_ids(&self, env: &mut JNIEnv) -> Vec<String> {
+        static GET_CAMERA_IDS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustCameraPreview",
+            "getCameraIds",
+            "()[Ljava/lang/String;",
+        );
+        let ids: JObjectArray = GET_CAMERA_IDS
+            .call(env, self.0.as_obj(), &[])
+            .l()
+            .unwrap()
+            .into();
+        let len = env.get_array_length(&ids).unwrap();
+        (0..len)
+            .map(|i| {
+                let id: JString = env.get_object_array_element(&ids, i).unwrap().into();
+                env.get_string(&id).unwrap().into()
+            })
+            .collect()
+    }
+
+    /// The ID of the first camera facing the given direction, one of the
+    /// `LENS_FACING_*` constants.
+    pub fn find_camera(&self, env: &mut JNIEnv, lens_facing: jint) -> Option<String> {
+        static FIND_CAMERA: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustCameraPreview",
+            "findCamera",
+            "(I)Ljava/lang/String;",
+        );
+        let id = FIND_CAMERA
+            .call(env, self.0.as_obj(), &[lens_facing.into()])
+            .l()
+            .unwrap();
+        if id.as_raw().is_null() {
+            return None;
+        }
+        Some(env.get_string(&id.into()).unwrap().into())
+    }
+
+    /// Resize the surface to the largest preview size the camera supports
+    /// that fits within the given bounds, and return that size.
+    pub fn configure_size(
+        &self,
+        env: &mut JNIEnv,
+        camera_id: &str,
+        max_width: jint,
+        max_height: jint,
+    ) -> Option<(jint, jint)> {
+        static CONFIGURE_SIZE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustCameraPreview",
+            "configureSize",
+            "(Ljava/lang/String;II)[I",
+        );
+        let camera_id = new_java_string(env, camera_id);
+        let size: JIntArray = CONFIGURE_SIZE
+            .call(
+                env,
+                self.0.as_obj(),
+                &[(&camera_id).into(), max_width.into(), max_height.into()],
+            )
+            .l()
+            .unwrap()
+            .into();
+        if size.as_raw().is_null() {
+            return None;
+        }
+        let mut buf = [0; 2];
+        env.get_int_array_region(&size, 0, &mut buf).unwrap();
+        Some((buf[0], buf[1]))
+    }
+
+    /// Open the camera and start streaming to the view's current surface.
+    /// Returns `false` if there is no surface or the camera can't be opened.
+    pub fn start(&self, env: &mut JNIEnv, camera_id: &str) -> bool {
+        static START: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustCameraPreview",
+            "start",
+            "(Ljava/lang/String;)Z",
+        );
+        let camera_id = new_java_string(env, camera_id);
+        START
+            .call(env, self.0.as_obj(), &[(&camera_id).into()])
+            .z()
+            .unwrap()
+    }
+
+    pub fn stop(&self, env: &mut JNIEnv) {
+        static STOP: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustCameraPreview",
+            "stop",
+            "()V",
+        );
+        STOP.call(env, self.0.as_obj(), &[]).v().unwrap()
+    }
+}
@@ -0,0 +1,451 @@
+//! A C interface, enabled by the `capi` feature, for implementing view
+//! peers in languages other than Rust, such as C++ or Zig. The crate still
+//! handles the Java side: registering the native methods, dispatching
+//! callbacks, deferring work to the end of a callback and checking the
+//! thread. The declarations for C are in `include/android_view.h`.
+//!
+//! A peer is a table of function pointers and a user data pointer, passed
+//! to [`android_view_register_view_peer`] from the app's `newViewPeer`
+//! implementation. Each callback gets the user data and an opaque context,
+//! from which the `JNIEnv` and the view can be retrieved to make JNI calls
+//! directly. Java objects passed to callbacks are local references that
+//! are only valid during the callback.
+//!
+//! The table starts with its own size, so that fields can be added at the
+//! end without breaking callers built against an older header; fields
+//! past the size the caller passed are treated as null.
+
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jint, jlong, jobject},
+};
+use ndk::event::Keycode;
+use std::{
+    ffi::{CStr, c_char, c_void},
+    mem::MaybeUninit,
+    ptr,
+};
+
+use crate::{callback_ctx::*, context::*, events::*, geometry::*, layout::*, surface::*, view::*};
+
+/// The context of a callback, which is only valid during that callback.
+#[repr(C)]
+pub struct AndroidViewCallbackCtx {
+    _private: [u8; 0],
+}
+
+type Ctx = *mut AndroidViewCallbackCtx;
+
+/// The callbacks of a view peer implemented through the C interface. Any
+/// callback may be null, in which case the default behavior applies.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct AndroidViewPeerCallbacks {
+    /// `sizeof(AndroidViewPeerCallbacks)` as the caller was compiled.
+    pub size: usize,
+    pub user_data: *mut c_void,
+    /// Called when the peer is dropped, after the view is detached.
+    pub destroy: Option<unsafe extern "C" fn(user_data: *mut c_void)>,
+    /// Return true and store the measured size to override the default
+    /// measurement.
+    pub on_measure: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ctx: Ctx,
+            width_spec: jint,
+            height_spec: jint,
+            out_width: *mut jint,
+            out_height: *mut jint,
+        ) -> bool,
+    >,
+    pub on_layout: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ctx: Ctx,
+            changed: bool,
+            left: jint,
+            top: jint,
+            right: jint,
+            bottom: jint,
+        ),
+    >,
+    pub on_size_changed: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ctx: Ctx,
+            w: jint,
+            h: jint,
+            oldw: jint,
+            oldh: jint,
+        ),
+    >,
+    pub on_key_down: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ctx: Ctx,
+            key_code: jint,
+            event: jobject,
+        ) -> bool,
+    >,
+    pub on_key_up: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ctx: Ctx,
+            key_code: jint,
+            event: jobject,
+        ) -> bool,
+    >,
+    pub on_touch_event:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx, event: jobject) -> bool>,
+    pub on_generic_motion_event:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx, event: jobject) -> bool>,
+    pub on_hover_event:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx, event: jobject) -> bool>,
+    /// `previously_focused_rect` is null if there's none.
+    pub on_focus_changed: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ctx: Ctx,
+            gain_focus: bool,
+            direction: jint,
+            previously_focused_rect: jobject,
+        ),
+    >,
+    pub on_window_focus_changed:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx, has_window_focus: bool)>,
+    pub on_attached_to_window: Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx)>,
+    pub on_detached_from_window: Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx)>,
+    pub surface_created:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx, holder: jobject)>,
+    pub surface_changed: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ctx: Ctx,
+            holder: jobject,
+            format: jint,
+            width: jint,
+            height: jint,
+        ),
+    >,
+    pub surface_destroyed:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx, holder: jobject)>,
+    pub do_frame:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx, frame_time_nanos: jlong)>,
+    pub delayed_callback: Option<unsafe extern "C" fn(user_data: *mut c_void, ctx: Ctx)>,
+}
+
+struct CViewPeer(AndroidViewPeerCallbacks);
+
+fn ctx_ptr(ctx: &mut CallbackCtx) -> Ctx {
+    ctx as *mut CallbackCtx as Ctx
+}
+
+impl Drop for CViewPeer {
+    fn drop(&mut self) {
+        if let Some(destroy) = self.0.destroy {
+            // SAFETY: The caller of `android_view_register_view_peer`
+            // promised that the callbacks are safe to call with this data.
+            unsafe { destroy(self.0.user_data) };
+        }
+    }
+}
+
+// SAFETY, for every callback below: the caller of
+// `android_view_register_view_peer` promised that the callbacks are safe
+// to call with the user data, and the context and Java objects passed
+// are valid for the duration of the call.
+impl ViewPeer for CViewPeer {
+    fn on_measure(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        width_spec: MeasureSpec,
+        height_spec: MeasureSpec,
+    ) -> Option<(jint, jint)> {
+        let f = self.0.on_measure?;
+        let (mut width, mut height) = (0, 0);
+        unsafe {
+            f(
+                self.0.user_data,
+                ctx_ptr(ctx),
+                width_spec.0,
+                height_spec.0,
+                &mut width,
+                &mut height,
+            )
+        }
+        .then_some((width, height))
+    }
+
+    fn on_layout(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        changed: bool,
+        left: jint,
+        top: jint,
+        right: jint,
+        bottom: jint,
+    ) {
+        if let Some(f) = self.0.on_layout {
+            unsafe {
+                f(
+                    self.0.user_data,
+                    ctx_ptr(ctx),
+                    changed,
+                    left,
+                    top,
+                    right,
+                    bottom,
+                )
+            }
+        }
+    }
+
+    fn on_size_changed(&mut self, ctx: &mut CallbackCtx, w: jint, h: jint, oldw: jint, oldh: jint) {
+        if let Some(f) = self.0.on_size_changed {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx), w, h, oldw, oldh) }
+        }
+    }
+
+    fn on_key_down<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        let Some(f) = self.0.on_key_down else {
+            return false;
+        };
+        let event = event.0.as_raw();
+        unsafe { f(self.0.user_data, ctx_ptr(ctx), key_code.into(), event) }
+    }
+
+    fn on_key_up<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        let Some(f) = self.0.on_key_up else {
+            return false;
+        };
+        let event = event.0.as_raw();
+        unsafe { f(self.0.user_data, ctx_ptr(ctx), key_code.into(), event) }
+    }
+
+    fn on_touch_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        let Some(f) = self.0.on_touch_event else {
+            return false;
+        };
+        unsafe { f(self.0.user_data, ctx_ptr(ctx), event.0.as_raw()) }
+    }
+
+    fn on_generic_motion_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        let Some(f) = self.0.on_generic_motion_event else {
+            return false;
+        };
+        unsafe { f(self.0.user_data, ctx_ptr(ctx), event.0.as_raw()) }
+    }
+
+    fn on_hover_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        let Some(f) = self.0.on_hover_event else {
+            return false;
+        };
+        unsafe { f(self.0.user_data, ctx_ptr(ctx), event.0.as_raw()) }
+    }
+
+    fn on_focus_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        gain_focus: bool,
+        direction: jint,
+        previously_focused_rect: Option<&Rect<'local>>,
+    ) {
+        if let Some(f) = self.0.on_focus_changed {
+            let rect = previously_focused_rect.map_or(ptr::null_mut(), |rect| rect.0.as_raw());
+            unsafe { f(self.0.user_data, ctx_ptr(ctx), gain_focus, direction, rect) }
+        }
+    }
+
+    fn on_window_focus_changed(&mut self, ctx: &mut CallbackCtx, has_window_focus: bool) {
+        if let Some(f) = self.0.on_window_focus_changed {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx), has_window_focus) }
+        }
+    }
+
+    fn on_attached_to_window(&mut self, ctx: &mut CallbackCtx) {
+        if let Some(f) = self.0.on_attached_to_window {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx)) }
+        }
+    }
+
+    fn on_detached_from_window(&mut self, ctx: &mut CallbackCtx) {
+        if let Some(f) = self.0.on_detached_from_window {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx)) }
+        }
+    }
+
+    fn surface_created<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+    ) {
+        if let Some(f) = self.0.surface_created {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx), holder.0.as_raw()) }
+        }
+    }
+
+    fn surface_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+        format: jint,
+        width: jint,
+        height: jint,
+    ) {
+        if let Some(f) = self.0.surface_changed {
+            let holder = holder.0.as_raw();
+            unsafe {
+                f(
+                    self.0.user_data,
+                    ctx_ptr(ctx),
+                    holder,
+                    format,
+                    width,
+                    height,
+                )
+            }
+        }
+    }
+
+    fn surface_destroyed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+    ) {
+        if let Some(f) = self.0.surface_destroyed {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx), holder.0.as_raw()) }
+        }
+    }
+
+    fn do_frame(&mut self, ctx: &mut CallbackCtx, frame_time_nanos: jlong) {
+        if let Some(f) = self.0.do_frame {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx), frame_time_nanos) }
+        }
+    }
+
+    fn delayed_callback(&mut self, ctx: &mut CallbackCtx) {
+        if let Some(f) = self.0.delayed_callback {
+            unsafe { f(self.0.user_data, ctx_ptr(ctx)) }
+        }
+    }
+}
+
+/// Register a peer implemented by the given callbacks, returning its ID
+/// for `newViewPeer` to return. The table is copied; the user data is
+/// owned by the peer from then on, and passed to `destroy` when the peer
+/// is dropped.
+///
+/// # Safety
+///
+/// `callbacks` must point to a table whose `size` field is at most the
+/// size of the table. Each callback must be safe to call on the UI thread
+/// with the user data until `destroy` is called.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn android_view_register_view_peer(
+    callbacks: *const AndroidViewPeerCallbacks,
+) -> jlong {
+    let mut table = MaybeUninit::<AndroidViewPeerCallbacks>::zeroed();
+    // SAFETY: The caller promised that `size` bytes are readable, and we
+    // copy no more than the table we know. Null function pointers and
+    // user data are valid values for the fields that aren't copied.
+    let table = unsafe {
+        let len = (*callbacks).size.min(size_of::<AndroidViewPeerCallbacks>());
+        ptr::copy_nonoverlapping(callbacks as *const u8, table.as_mut_ptr() as *mut u8, len);
+        table.assume_init()
+    };
+    register_view_peer(CViewPeer(table))
+}
+
+/// Register the native methods of the `RustView` subclass with the given
+/// name, such as `com/example/MyView`, with `new_peer` implementing its
+/// `newViewPeer` method. Typically called from `JNI_OnLoad`.
+///
+/// # Safety
+///
+/// `env` must be a valid `JNIEnv` for the current thread, `class_name`
+/// a valid NUL-terminated string, and `new_peer` a function with the
+/// JNI calling convention.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn android_view_register_view_class(
+    env: *mut jni::sys::JNIEnv,
+    class_name: *const c_char,
+    new_peer: unsafe extern "system" fn(
+        env: *mut jni::sys::JNIEnv,
+        view: jobject,
+        context: jobject,
+    ) -> jlong,
+) {
+    // SAFETY: The caller promised that `env` is valid.
+    let mut env = unsafe { JNIEnv::from_raw(env) }.unwrap();
+    // SAFETY: The caller promised that `class_name` is a valid string.
+    let class_name = unsafe { CStr::from_ptr(class_name) }.to_str().unwrap();
+    // SAFETY: `JNIEnv`, `View` and `Context` are transparent wrappers
+    // around the raw pointers, so the signatures are ABI-compatible.
+    let new_peer = unsafe {
+        std::mem::transmute::<
+            unsafe extern "system" fn(*mut jni::sys::JNIEnv, jobject, jobject) -> jlong,
+            for<'a> extern "system" fn(JNIEnv<'a>, View<'a>, Context<'a>) -> jlong,
+        >(new_peer)
+    };
+    register_view_class(&mut env, class_name, new_peer, Vec::new());
+}
+
+/// The `JNIEnv` of the callback, for making JNI calls.
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the current callback.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn android_view_ctx_env(ctx: Ctx) -> *mut jni::sys::JNIEnv {
+    // SAFETY: The caller promised that `ctx` came from `ctx_ptr`.
+    let ctx = unsafe { &*(ctx as *const CallbackCtx) };
+    ctx.env.get_raw()
+}
+
+/// The `RustView` receiving the callback, as a local reference.
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the current callback.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn android_view_ctx_view(ctx: Ctx) -> jobject {
+    // SAFETY: The caller promised that `ctx` came from `ctx_ptr`.
+    let ctx = unsafe { &*(ctx as *const CallbackCtx) };
+    let view: &JObject = &ctx.view.0;
+    view.as_raw()
+}
+
+/// The ID of the peer receiving the callback.
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the current callback.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn android_view_ctx_peer_id(ctx: Ctx) -> jlong {
+    // SAFETY: The caller promised that `ctx` came from `ctx_ptr`.
+    let ctx = unsafe { &*(ctx as *const CallbackCtx) };
+    ctx.peer_handle().id()
+}
@@ -0,0 +1,191 @@
+//! Named message channels between the app's Java or Kotlin code and the
+//! view's peer, like Flutter's platform channels, so that hybrid apps can
+//! talk to the peer without writing JNI on either side.
+//!
+//! On the Java side, `RustView.sendChannelMessage` sends a message to
+//! [`ViewPeer::on_channel_message`], and `RustView.setChannelHandler`
+//! receives the messages sent with [`CallbackCtx::send_channel_message`].
+//! Messages are byte arrays; `RustJsonChannel` wraps a channel whose
+//! messages are JSON objects encoded as UTF-8, which the peer can parse
+//! with any JSON crate. Either side may reply to a message once, with a
+//! message or with null, which is also the reply when no handler is set.
+//!
+//! Messages and replies are delivered on the UI thread, after the current
+//! callback returns, so a handler that replies or sends a message right
+//! away never reenters the peer.
+
+use jni::{
+    JNIEnv,
+    objects::{JByteArray, JObject, JString},
+    sys::{jint, jlong},
+};
+use std::{cell::RefCell, collections::BTreeMap};
+
+use crate::{callback_ctx::*, jni_cache::*, native_methods::*, view::*};
+
+type ReplyCallback =
+    Box<dyn for<'local> FnOnce(&mut CallbackCtx<'local>, &mut dyn ViewPeer, Option<&[u8]>)>;
+
+#[derive(Default)]
+struct PendingReplies {
+    next_id: jint,
+    callbacks: BTreeMap<(jlong, jint), ReplyCallback>,
+}
+
+thread_local! {
+    static PENDING_REPLIES: RefCell<PendingReplies> = RefCell::default();
+}
+
+/// The reply to a message from Java, which must be sent exactly once, now
+/// or from a later callback.
+#[must_use = "the sender waits for a reply"]
+#[derive(Debug)]
+pub struct ChannelReply {
+    id: jint,
+}
+
+impl ChannelReply {
+    /// Send the reply, or null if `message` is `None`.
+    pub fn send(self, ctx: &mut CallbackCtx, message: Option<&[u8]>) {
+        static DELIVER_CHANNEL_REPLY: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "deliverChannelReply",
+            "(I[B)V",
+        );
+        // The sender didn't ask for a reply.
+        if self.id < 0 {
+            return;
+        }
+        let id = self.id;
+        let message = message.map(<[u8]>::to_vec);
+        ctx.push_dynamic_deferred_callback(move |env, view| {
+            let message = byte_array_or_null(env, message.as_deref());
+            DELIVER_CHANNEL_REPLY
+                .call(env, &view.0, &[id.into(), (&message).into()])
+                .v()
+                .unwrap();
+        });
+    }
+}
+
+fn byte_array_or_null<'local>(env: &mut JNIEnv<'local>, bytes: Option<&[u8]>) -> JObject<'local> {
+    match bytes {
+        Some(bytes) => env.byte_array_from_slice(bytes).unwrap().into(),
+        None => JObject::null(),
+    }
+}
+
+fn bytes_or_none(env: &mut JNIEnv, array: &JByteArray) -> Option<Vec<u8>> {
+    (!array.as_raw().is_null()).then(|| env.convert_byte_array(array).unwrap())
+}
+
+impl CallbackCtx<'_> {
+    /// Send a message to the handler that the Java side set for `channel`,
+    /// without waiting for a reply.
+    pub fn send_channel_message(&mut self, channel: &str, message: &[u8]) {
+        self.send_channel_message_with_id(channel, message, -1);
+    }
+
+    /// Send a message to the handler that the Java side set for `channel`,
+    /// and call `on_reply` with the peer and the reply, which is `None` if
+    /// the handler replied with null or there's no handler. `on_reply` is
+    /// dropped without being called if the view is detached first.
+    pub fn send_channel_message_with_reply(
+        &mut self,
+        channel: &str,
+        message: &[u8],
+        on_reply: impl for<'local> FnOnce(&mut CallbackCtx<'local>, &mut dyn ViewPeer, Option<&[u8]>)
+        + 'static,
+    ) {
+        let peer = self.peer_handle().id();
+        let id = PENDING_REPLIES.with_borrow_mut(|pending| {
+            let id = pending.next_id;
+            pending.next_id = pending.next_id.wrapping_add(1) & jint::MAX;
+            pending.callbacks.insert((peer, id), Box::new(on_reply));
+            id
+        });
+        self.send_channel_message_with_id(channel, message, id);
+    }
+
+    fn send_channel_message_with_id(&mut self, channel: &str, message: &[u8], reply_id: jint) {
+        static DELIVER_CHANNEL_MESSAGE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "deliverChannelMessage",
+            "(Ljava/lang/String;[BI)V",
+        );
+        let channel = channel.to_string();
+        let message = message.to_vec();
+        self.push_dynamic_deferred_callback(move |env, view| {
+            let channel = new_java_string(env, &channel);
+            let message = env.byte_array_from_slice(&message).unwrap();
+            DELIVER_CHANNEL_MESSAGE
+                .call(
+                    env,
+                    &view.0,
+                    &[(&channel).into(), (&message).into(), reply_id.into()],
+                )
+                .v()
+                .unwrap();
+        });
+    }
+}
+
+pub(crate) fn drop_replies(peer: jlong) {
+    let callbacks = PENDING_REPLIES.with_borrow_mut(|pending| {
+        let ids = pending
+            .callbacks
+            .range((peer, 0)..=(peer, jint::MAX))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        ids.into_iter()
+            .filter_map(|key| pending.callbacks.remove(&key))
+            .collect::<Vec<_>>()
+    });
+    // Dropped outside the borrow, since a callback may own anything.
+    drop(callbacks);
+}
+
+native_methods! {
+    fn channel_methods;
+
+    "onChannelMessageNative" => fn on_channel_message<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        channel: JString<'local>,
+        message: JByteArray<'local>,
+        reply_id: jint,
+    ) {
+        let channel: String = env.get_string(&channel).unwrap().into();
+        let message = bytes_or_none(&mut env, &message).unwrap_or_default();
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_channel_message(ctx, &channel, &message, ChannelReply { id: reply_id });
+        })
+    }
+
+    "onChannelReplyNative" => fn on_channel_reply<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        reply_id: jint,
+        message: JByteArray<'local>,
+    ) {
+        let message = bytes_or_none(&mut env, &message);
+        let Some(callback) =
+            PENDING_REPLIES.with_borrow_mut(|pending| pending.callbacks.remove(&(peer, reply_id)))
+        else {
+            return;
+        };
+        with_peer(env, view, peer, |ctx, peer| {
+            callback(ctx, peer, message.as_deref());
+        })
+    }
+}
+
+pub(crate) fn register_natives(env: &mut JNIEnv) {
+    env.register_native_methods(
+        "org/linebender/android/rustview/RustView",
+        &channel_methods(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,57 @@
+use jni::sys::jint;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::surface::*;
+
+pub const WINDOW_COLOR_MODE_DEFAULT: jint = 0;
+pub const WINDOW_COLOR_MODE_WIDE_COLOR_GAMUT: jint = 1;
+pub const WINDOW_COLOR_MODE_HDR: jint = 2;
+
+/// The color spaces a renderer is likely to target, identified by their
+/// `android.graphics.ColorSpace.Named` ordinal.
+///
+/// Choosing a wide-gamut color space takes three steps: set the window's
+/// color mode with [`crate::View::set_window_color_mode`], set the
+/// surface's format with [`SurfaceHolder::set_format`], and have the
+/// renderer tag its swapchain with the matching color space, such as
+/// `VK_COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT` or
+/// `EGL_GL_COLORSPACE_DISPLAY_P3_EXT`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum ColorSpace {
+    Srgb = 0,
+    /// sRGB primaries with values outside [0, 1], for half-float content.
+    ExtendedSrgb = 2,
+    /// Linear sRGB primaries with values outside [0, 1], also known as
+    /// scRGB, for half-float content.
+    LinearExtendedSrgb = 3,
+    Bt2020 = 5,
+    DisplayP3 = 7,
+}
+
+impl ColorSpace {
+    /// Whether content in this color space needs the window to be in
+    /// [`WINDOW_COLOR_MODE_WIDE_COLOR_GAMUT`] to be shown as intended.
+    pub fn is_wide_gamut(self) -> bool {
+        self != Self::Srgb
+    }
+
+    /// The surface pixel format suited to this color space. The extended
+    /// ranges need half-float components; the others fit in 8 bits.
+    pub fn pixel_format(self) -> jint {
+        match self {
+            Self::ExtendedSrgb | Self::LinearExtendedSrgb => PIXEL_FORMAT_RGBA_F16,
+            Self::Bt2020 => PIXEL_FORMAT_RGBA_1010102,
+            Self::Srgb | Self::DisplayP3 => PIXEL_FORMAT_RGBA_8888,
+        }
+    }
+
+    /// The window color mode to request for this color space.
+    pub fn window_color_mode(self) -> jint {
+        if self.is_wide_gamut() {
+            WINDOW_COLOR_MODE_WIDE_COLOR_GAMUT
+        } else {
+            WINDOW_COLOR_MODE_DEFAULT
+        }
+    }
+}
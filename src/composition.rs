@@ -0,0 +1,524 @@
+//! A reference model of the editing operations that a keyboard performs
+//! through `InputConnection`, for editors that don't already have their
+//! own composition support.
+//!
+//! [`CompositionState`] follows the behavior of the platform's
+//! `BaseInputConnection`, which keyboards are tested against. Offsets
+//! that keyboards pass in are in UTF-16 code units; the state itself
+//! uses byte offsets, and always keeps them on character boundaries.
+
+use jni::sys::jint;
+use std::ops::Range;
+
+#[cfg(feature = "accessibility")]
+use crate::accessibility::{MovementGranularity, TextNavigationAction};
+use crate::{grapheme::*, util::*};
+#[cfg(feature = "accessibility")]
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The text being edited, with its selection and composing region.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompositionState {
+    text: String,
+    selection: Range<usize>,
+    composing: Option<Range<usize>>,
+}
+
+impl CompositionState {
+    /// Create a state with no composing region. `selection` is a byte
+    /// range, which must lie on character boundaries.
+    pub fn new(text: impl Into<String>, selection: Range<usize>) -> Self {
+        let text = text.into();
+        assert!(text.is_char_boundary(selection.start) && text.is_char_boundary(selection.end));
+        Self {
+            text,
+            selection,
+            composing: None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The selection as a byte range. When it's empty, this is the cursor.
+    pub fn selection(&self) -> Range<usize> {
+        self.selection.clone()
+    }
+
+    /// The composing region as a byte range, if any.
+    pub fn composing_region(&self) -> Option<Range<usize>> {
+        self.composing.clone()
+    }
+
+    /// The selection in UTF-16 code units, as reported to the keyboard
+    /// through [`crate::InputMethodManager::update_selection`].
+    pub fn utf16_selection(&self) -> (jint, jint) {
+        (
+            self.utf16_offset(self.selection.start),
+            self.utf16_offset(self.selection.end),
+        )
+    }
+
+    /// The composing region in UTF-16 code units, or `(-1, -1)` if there
+    /// is none, as reported to the keyboard.
+    pub fn utf16_composing_region(&self) -> (jint, jint) {
+        match &self.composing {
+            Some(range) => (self.utf16_offset(range.start), self.utf16_offset(range.end)),
+            None => (-1, -1),
+        }
+    }
+
+    fn utf16_offset(&self, byte_offset: usize) -> jint {
+        byte_to_utf16_offset(&self.text, byte_offset) as jint
+    }
+
+    fn byte_offset(&self, utf16_offset: jint) -> usize {
+        utf16_to_byte_offset(&self.text, utf16_offset.max(0) as usize)
+    }
+
+    /// Replace the composing region, or the selection if there is none,
+    /// with `text`, and place the cursor according to
+    /// `new_cursor_position`: if positive, relative to the end of the new
+    /// text minus one, otherwise relative to its start. So 1 puts the
+    /// cursor after the new text and 0 puts it before.
+    fn replace(&mut self, text: &str, new_cursor_position: jint) -> Range<usize> {
+        let range = self
+            .composing
+            .take()
+            .unwrap_or_else(|| self.selection.clone());
+        self.text.replace_range(range.clone(), text);
+        let inserted = range.start..(range.start + text.len());
+        let cursor = if new_cursor_position > 0 {
+            self.utf16_offset(inserted.end) as i64 + new_cursor_position as i64 - 1
+        } else {
+            self.utf16_offset(inserted.start) as i64 + new_cursor_position as i64
+        };
+        let len = self.utf16_offset(self.text.len()) as i64;
+        let cursor = self.byte_offset(cursor.clamp(0, len) as jint);
+        self.selection = cursor..cursor;
+        inserted
+    }
+
+    /// `InputConnection.setComposingText`. Empty text removes the
+    /// composing region's contents and ends composition.
+    pub fn set_composing_text(&mut self, text: &str, new_cursor_position: jint) {
+        let inserted = self.replace(text, new_cursor_position);
+        if !inserted.is_empty() {
+            self.composing = Some(inserted);
+        }
+    }
+
+    /// `InputConnection.commitText`. Unlike [`Self::set_composing_text`]
+    /// followed by [`Self::finish_composing_text`], this leaves no
+    /// composing region even momentarily.
+    pub fn commit_text(&mut self, text: &str, new_cursor_position: jint) {
+        self.replace(text, new_cursor_position);
+    }
+
+    /// `InputConnection.setComposingRegion`. The offsets may be in either
+    /// order and are clamped to the text; an empty region ends
+    /// composition.
+    pub fn set_composing_region(&mut self, start: jint, end: jint) {
+        let start = self.byte_offset(start);
+        let end = self.byte_offset(end);
+        let (start, end) = (start.min(end), start.max(end));
+        self.composing = (start != end).then_some(start..end);
+    }
+
+    /// `InputConnection.finishComposingText`. The text and selection are
+    /// left unchanged.
+    pub fn finish_composing_text(&mut self) {
+        self.composing = None;
+    }
+
+    /// `InputConnection.setSelection`. Returns false, leaving the
+    /// selection unchanged, if either offset is out of range. The
+    /// composing region is kept.
+    pub fn set_selection(&mut self, start: jint, end: jint) -> bool {
+        let len = self.utf16_offset(self.text.len());
+        if start < 0 || end < 0 || start > len || end > len {
+            return false;
+        }
+        self.selection = self.byte_offset(start)..self.byte_offset(end);
+        true
+    }
+
+    /// `InputConnection.deleteSurroundingText`, using
+    /// [`surrounding_text_deletion`]. The composing region is adjusted to
+    /// exclude the deleted text.
+    pub fn delete_surrounding_text(&mut self, before_length: jint, after_length: jint) {
+        let deletion = surrounding_text_deletion(
+            &self.text,
+            self.selection.clone(),
+            before_length,
+            after_length,
+        );
+        self.apply_deletion(deletion);
+    }
+
+    /// `InputConnection.deleteSurroundingTextInCodePoints`.
+    pub fn delete_surrounding_text_in_code_points(
+        &mut self,
+        before_length: jint,
+        after_length: jint,
+    ) {
+        let deletion = surrounding_text_deletion_in_code_points(
+            &self.text,
+            self.selection.clone(),
+            before_length,
+            after_length,
+        );
+        self.apply_deletion(deletion);
+    }
+
+    /// Apply a text navigation action from an accessibility service,
+    /// moving by characters, words or paragraphs the way `TextView`
+    /// does. For [`TextNavigationAction::Next`] and
+    /// [`TextNavigationAction::Previous`], this returns the range that
+    /// was traversed, in UTF-16 code units, to report with
+    /// [`crate::AccessibilityEvent::text_traversed_at_movement_granularity`];
+    /// for [`TextNavigationAction::SetSelection`], the new selection.
+    ///
+    /// Returns `None`, leaving the selection unchanged, if there's no
+    /// unit to move over in that direction, the selection is out of
+    /// range, or the granularity is line or page, which depend on the
+    /// editor's layout.
+    #[cfg(feature = "accessibility")]
+    pub fn apply_navigation(&mut self, action: TextNavigationAction) -> Option<(jint, jint)> {
+        let (granularity, extend_selection, forward) = match action {
+            TextNavigationAction::Next {
+                granularity,
+                extend_selection,
+            } => (granularity, extend_selection, true),
+            TextNavigationAction::Previous {
+                granularity,
+                extend_selection,
+            } => (granularity, extend_selection, false),
+            TextNavigationAction::SetSelection { start, end } => {
+                return self
+                    .set_selection(start, end)
+                    .then(|| self.utf16_selection());
+            }
+        };
+        let units: Vec<Range<usize>> = match granularity {
+            MovementGranularity::Character => self
+                .text
+                .grapheme_indices(true)
+                .map(|(i, g)| i..(i + g.len()))
+                .collect(),
+            MovementGranularity::Word => self
+                .text
+                .split_word_bound_indices()
+                .filter(|(_, w)| w.chars().any(char::is_alphanumeric))
+                .map(|(i, w)| i..(i + w.len()))
+                .collect(),
+            MovementGranularity::Paragraph => {
+                let mut start = 0;
+                let mut units = Vec::new();
+                for line in self.text.split('\n') {
+                    if !line.is_empty() {
+                        units.push(start..(start + line.len()));
+                    }
+                    start += line.len() + 1;
+                }
+                units
+            }
+            MovementGranularity::Line | MovementGranularity::Page => return None,
+        };
+        // Like `TextView`, move from the end of the selection, which is
+        // where the caret is after a previous move.
+        let cursor = self.selection.end;
+        let segment = if forward {
+            let unit = units.into_iter().find(|unit| unit.end > cursor)?;
+            unit.start.max(cursor)..unit.end
+        } else {
+            let unit = units.into_iter().rev().find(|unit| unit.start < cursor)?;
+            unit.start..unit.end.min(cursor)
+        };
+        let caret = if forward { segment.end } else { segment.start };
+        self.selection = if extend_selection {
+            self.selection.start..caret
+        } else {
+            caret..caret
+        };
+        Some((
+            self.utf16_offset(segment.start),
+            self.utf16_offset(segment.end),
+        ))
+    }
+
+    fn apply_deletion(&mut self, deletion: SurroundingTextDeletion) {
+        let adjust = |offset: usize| {
+            let mut offset = offset;
+            for range in [&deletion.after, &deletion.before] {
+                if offset > range.start {
+                    offset -= offset.min(range.end) - range.start;
+                }
+            }
+            offset
+        };
+        self.composing = self
+            .composing
+            .take()
+            .map(|range| adjust(range.start)..adjust(range.end))
+            .filter(|range| !range.is_empty());
+        self.selection = deletion.apply(&mut self.text, self.selection.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(text: &str, cursor: usize) -> CompositionState {
+        CompositionState::new(text, cursor..cursor)
+    }
+
+    fn check(state: &CompositionState, text: &str, cursor: usize, composing: Option<Range<usize>>) {
+        assert_eq!(state.text(), text);
+        assert_eq!(state.selection(), cursor..cursor);
+        assert_eq!(state.composing_region(), composing);
+    }
+
+    // The call sequences below follow what each keyboard sends through
+    // `InputConnection` for the same user actions.
+
+    #[test]
+    fn gboard_typing_with_autocorrect() {
+        let mut s = state("", 0);
+        s.set_composing_text("t", 1);
+        s.set_composing_text("te", 1);
+        s.set_composing_text("teh", 1);
+        check(&s, "teh", 3, Some(0..3));
+        // Space accepts the correction.
+        s.commit_text("the", 1);
+        s.commit_text(" ", 1);
+        check(&s, "the ", 4, None);
+    }
+
+    #[test]
+    fn gboard_backspace_resumes_composition() {
+        let mut s = state("the ", 4);
+        s.delete_surrounding_text(1, 0);
+        check(&s, "the", 3, None);
+        s.set_composing_region(0, 3);
+        check(&s, "the", 3, Some(0..3));
+        s.set_composing_text("th", 1);
+        check(&s, "th", 2, Some(0..2));
+        s.finish_composing_text();
+        check(&s, "th", 2, None);
+    }
+
+    #[test]
+    fn samsung_recompose_word_with_reversed_region() {
+        let mut s = state("hello world", 11);
+        assert!(s.set_selection(8, 8));
+        s.set_composing_region(11, 6);
+        check(&s, "hello world", 8, Some(6..11));
+        s.set_composing_text("word", 1);
+        check(&s, "hello word", 10, Some(6..10));
+        s.finish_composing_text();
+        s.commit_text(" ", 1);
+        check(&s, "hello word ", 11, None);
+    }
+
+    #[test]
+    fn samsung_clears_composition_with_empty_text() {
+        let mut s = state("hello ", 6);
+        s.set_composing_text("wor", 1);
+        check(&s, "hello wor", 9, Some(6..9));
+        s.set_composing_text("", 1);
+        check(&s, "hello ", 6, None);
+    }
+
+    #[test]
+    fn swiftkey_punctuation_swaps_space() {
+        let mut s = state("", 0);
+        s.set_composing_text("hello", 1);
+        s.commit_text("hello", 1);
+        s.commit_text(" ", 1);
+        check(&s, "hello ", 6, None);
+        s.delete_surrounding_text(1, 0);
+        s.commit_text(", ", 1);
+        check(&s, "hello, ", 7, None);
+    }
+
+    #[test]
+    fn swiftkey_paired_brackets() {
+        let mut s = state("f", 1);
+        // The cursor goes between the brackets.
+        s.commit_text("()", 0);
+        check(&s, "f()", 1, None);
+        assert!(s.set_selection(2, 2));
+        s.commit_text("x", 1);
+        check(&s, "f(x)", 3, None);
+    }
+
+    #[test]
+    fn new_cursor_position() {
+        let commit = |position| {
+            let mut s = state("abcd", 2);
+            s.commit_text("XY", position);
+            s.selection().start
+        };
+        // After the new text, and further along from there.
+        assert_eq!(commit(1), 4);
+        assert_eq!(commit(2), 5);
+        // Before the new text, and further back from there.
+        assert_eq!(commit(0), 2);
+        assert_eq!(commit(-1), 1);
+        // Clamped to the text.
+        assert_eq!(commit(100), 6);
+        assert_eq!(commit(-100), 0);
+        assert_eq!(commit(jint::MAX), 6);
+        assert_eq!(commit(jint::MIN), 0);
+    }
+
+    #[test]
+    fn new_cursor_position_while_composing() {
+        let mut s = state("ab", 2);
+        s.set_composing_text("cd", 0);
+        check(&s, "abcd", 2, Some(2..4));
+        s.set_composing_text("cde", -2);
+        check(&s, "abcde", 0, Some(2..5));
+        s.set_composing_text("c", 10);
+        check(&s, "abc", 3, Some(2..3));
+    }
+
+    #[test]
+    fn deletion_overlapping_composing_region() {
+        let mut s = state("hello world", 8);
+        s.set_composing_region(6, 11);
+        s.delete_surrounding_text(3, 1);
+        check(&s, "hellold", 5, Some(5..7));
+    }
+
+    #[test]
+    fn deletion_covering_composing_region() {
+        let mut s = state("hello world", 11);
+        s.set_composing_region(6, 11);
+        s.delete_surrounding_text(6, 0);
+        check(&s, "hello", 5, None);
+    }
+
+    #[test]
+    fn deletion_after_cursor_shifts_nothing_before() {
+        let mut s = state("ab cd", 2);
+        s.set_composing_region(0, 2);
+        s.delete_surrounding_text(0, 3);
+        check(&s, "ab", 2, Some(0..2));
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        let mut s = state("", 0);
+        s.commit_text("a😀", 1);
+        check(&s, "a😀", 5, None);
+        assert_eq!(s.utf16_selection(), (3, 3));
+        s.commit_text("b", 1);
+        s.set_composing_region(1, 3);
+        assert_eq!(s.composing_region(), Some(1..5));
+        assert_eq!(s.utf16_composing_region(), (1, 3));
+        // An offset inside the pair moves to its start.
+        assert!(s.set_selection(2, 2));
+        assert_eq!(s.selection(), 1..1);
+        // A deletion that counts one unit of the pair deletes both.
+        assert!(s.set_selection(3, 3));
+        s.delete_surrounding_text(1, 0);
+        check(&s, "ab", 1, None);
+    }
+
+    #[test]
+    fn surrogate_pairs_cursor_position() {
+        let mut s = state("ab", 1);
+        s.commit_text("😀😀", -1);
+        assert_eq!(s.selection(), 0..0);
+        let mut s = state("ab", 1);
+        s.commit_text("😀😀", 2);
+        // One code unit past the new text, which is after the `b`.
+        assert_eq!(s.utf16_selection(), (6, 6));
+        s.delete_surrounding_text_in_code_points(2, 0);
+        check(&s, "a😀", 5, None);
+    }
+
+    #[cfg(feature = "accessibility")]
+    fn next(granularity: MovementGranularity, extend_selection: bool) -> TextNavigationAction {
+        TextNavigationAction::Next {
+            granularity,
+            extend_selection,
+        }
+    }
+
+    #[cfg(feature = "accessibility")]
+    fn previous(granularity: MovementGranularity, extend_selection: bool) -> TextNavigationAction {
+        TextNavigationAction::Previous {
+            granularity,
+            extend_selection,
+        }
+    }
+
+    #[cfg(feature = "accessibility")]
+    #[test]
+    fn navigation_by_character() {
+        let mut s = state("a😀b", 0);
+        let forward = next(MovementGranularity::Character, false);
+        assert_eq!(s.apply_navigation(forward), Some((0, 1)));
+        assert_eq!(s.apply_navigation(forward), Some((1, 3)));
+        assert_eq!(s.selection(), 5..5);
+        assert_eq!(s.apply_navigation(forward), Some((3, 4)));
+        // Nothing left to traverse.
+        assert_eq!(s.apply_navigation(forward), None);
+        assert_eq!(s.selection(), 6..6);
+        let backward = previous(MovementGranularity::Character, false);
+        assert_eq!(s.apply_navigation(backward), Some((3, 4)));
+        assert_eq!(s.selection(), 5..5);
+    }
+
+    #[cfg(feature = "accessibility")]
+    #[test]
+    fn navigation_by_word_skips_spaces_and_punctuation() {
+        let mut s = state("Hello, big world.", 0);
+        let forward = next(MovementGranularity::Word, false);
+        assert_eq!(s.apply_navigation(forward), Some((0, 5)));
+        assert_eq!(s.apply_navigation(forward), Some((7, 10)));
+        assert_eq!(s.apply_navigation(forward), Some((11, 16)));
+        assert_eq!(s.apply_navigation(forward), None);
+        let backward = previous(MovementGranularity::Word, false);
+        assert_eq!(s.apply_navigation(backward), Some((11, 16)));
+        assert_eq!(s.selection(), 11..11);
+        // From inside a word, only the part before the caret is traversed.
+        let mut s = state("Hello world", 8);
+        assert_eq!(s.apply_navigation(backward), Some((6, 8)));
+        assert_eq!(s.selection(), 6..6);
+    }
+
+    #[cfg(feature = "accessibility")]
+    #[test]
+    fn navigation_by_paragraph_extending_selection() {
+        let mut s = state("one\n\ntwo\nthree", 0);
+        let forward = next(MovementGranularity::Paragraph, true);
+        assert_eq!(s.apply_navigation(forward), Some((0, 3)));
+        assert_eq!(s.apply_navigation(forward), Some((5, 8)));
+        assert_eq!(s.selection(), 0..8);
+        let backward = previous(MovementGranularity::Paragraph, true);
+        assert_eq!(s.apply_navigation(backward), Some((5, 8)));
+        assert_eq!(s.selection(), 0..5);
+    }
+
+    #[cfg(feature = "accessibility")]
+    #[test]
+    fn navigation_set_selection_and_layout_granularities() {
+        let mut s = state("abc", 0);
+        let action = TextNavigationAction::SetSelection { start: 1, end: 3 };
+        assert_eq!(s.apply_navigation(action), Some((1, 3)));
+        assert_eq!(s.selection(), 1..3);
+        let action = TextNavigationAction::SetSelection { start: -1, end: -1 };
+        assert_eq!(s.apply_navigation(action), None);
+        assert_eq!(
+            s.apply_navigation(next(MovementGranularity::Line, false)),
+            None
+        );
+        assert_eq!(s.selection(), 1..3);
+    }
+}
@@ -0,0 +1,63 @@
+use jni::{JNIEnv, objects::JObject, sys::jint};
+
+use crate::jni_cache::*;
+
+pub const CONNECTIVITY_SERVICE: &str = "connectivity";
+
+const NET_CAPABILITY_NOT_METERED: jint = 11;
+const NET_CAPABILITY_VALIDATED: jint = 16;
+
+/// The state of the default network.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NetworkStatus {
+    /// Whether there is a default network at all.
+    pub available: bool,
+    /// Whether the network has been found to provide working internet access.
+    pub validated: bool,
+    /// Whether the user may be charged for data on the network.
+    pub metered: bool,
+}
+
+/// A binding to `android.net.ConnectivityManager`. The app must hold the
+/// `ACCESS_NETWORK_STATE` permission.
+#[repr(transparent)]
+pub struct ConnectivityManager<'local>(pub JObject<'local>);
+
+impl<'local> ConnectivityManager<'local> {
+    pub fn network_status(&self, env: &mut JNIEnv<'local>) -> NetworkStatus {
+        static GET_ACTIVE_NETWORK: CachedMethod = CachedMethod::new(
+            "android/net/ConnectivityManager",
+            "getActiveNetwork",
+            "()Landroid/net/Network;",
+        );
+        static GET_NETWORK_CAPABILITIES: CachedMethod = CachedMethod::new(
+            "android/net/ConnectivityManager",
+            "getNetworkCapabilities",
+            "(Landroid/net/Network;)Landroid/net/NetworkCapabilities;",
+        );
+        static HAS_CAPABILITY: CachedMethod =
+            CachedMethod::new("android/net/NetworkCapabilities", "hasCapability", "(I)Z");
+        let network = GET_ACTIVE_NETWORK.call(env, &self.0, &[]).l().unwrap();
+        if network.as_raw().is_null() {
+            return NetworkStatus::default();
+        }
+        let capabilities = GET_NETWORK_CAPABILITIES
+            .call(env, &self.0, &[(&network).into()])
+            .l()
+            .unwrap();
+        if capabilities.as_raw().is_null() {
+            return NetworkStatus::default();
+        }
+        NetworkStatus {
+            available: true,
+            validated: HAS_CAPABILITY
+                .call(env, &capabilities, &[NET_CAPABILITY_VALIDATED.into()])
+                .z()
+                .unwrap(),
+            metered: !HAS_CAPABILITY
+                .call(env, &capabilities, &[NET_CAPABILITY_NOT_METERED.into()])
+                .z()
+                .unwrap(),
+        }
+    }
+}
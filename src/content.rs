@@ -0,0 +1,197 @@
+use jni::{
+    JNIEnv,
+    objects::{JByteArray, JObject},
+    sys::jint,
+};
+use std::io;
+
+use crate::{intent::*, jni_cache::*};
+
+const BUFFER_SIZE: jint = 8192;
+
+/// Clear the pending Java exception and turn it into an I/O error.
+pub(crate) fn take_exception(env: &mut JNIEnv) -> io::Error {
+    let message = env
+        .exception_occurred()
+        .ok()
+        .filter(|e| !e.as_raw().is_null())
+        .and_then(|e| {
+            let _ = env.exception_clear();
+            let message = env
+                .call_method(&e, "toString", "()Ljava/lang/String;", &[])
+                .ok()?
+                .l()
+                .ok()?;
+            env.get_string(&message.into()).ok().map(String::from)
+        });
+    let _ = env.exception_clear();
+    io::Error::other(message.unwrap_or_else(|| "Java exception".into()))
+}
+
+#[repr(transparent)]
+pub struct ContentResolver<'local>(pub JObject<'local>);
+
+impl<'local> ContentResolver<'local> {
+    /// The MIME type of the content at `uri`, if known.
+    pub fn get_type(&self, env: &mut JNIEnv<'local>, uri: &Uri<'local>) -> Option<String> {
+        static GET_TYPE: CachedMethod = CachedMethod::new(
+            "android/content/ContentResolver",
+            "getType",
+            "(Landroid/net/Uri;)Ljava/lang/String;",
+        );
+        let mime_type = GET_TYPE.call(env, &self.0, &[(&uri.0).into()]).l().unwrap();
+        if mime_type.as_raw().is_null() {
+            return None;
+        }
+        Some(env.get_string(&mime_type.into()).unwrap().into())
+    }
+
+    /// Open the content at `uri` for reading. Fails if the content
+    /// doesn't exist or the app doesn't have permission to read it.
+    pub fn open_input_stream<'a>(
+        &self,
+        env: &'a mut JNIEnv<'local>,
+        uri: &Uri<'local>,
+    ) -> io::Result<JavaInputStream<'a, 'local>> {
+        static OPEN_INPUT_STREAM: CachedMethod = CachedMethod::new(
+            "android/content/ContentResolver",
+            "openInputStream",
+            "(Landroid/net/Uri;)Ljava/io/InputStream;",
+        );
+        let stream = OPEN_INPUT_STREAM
+            .try_call(env, &self.0, &[(&uri.0).into()])
+            .and_then(|value| value.l())
+            .map_err(|_| take_exception(env))?;
+        if stream.as_raw().is_null() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let buffer = env.new_byte_array(BUFFER_SIZE).unwrap();
+        Ok(JavaInputStream {
+            env,
+            stream,
+            buffer,
+        })
+    }
+
+    /// Open the content at `uri` for writing. `mode` is `"w"` to write,
+    /// `"wt"` to truncate and write, or `"wa"` to append.
+    pub fn open_output_stream<'a>(
+        &self,
+        env: &'a mut JNIEnv<'local>,
+        uri: &Uri<'local>,
+        mode: &str,
+    ) -> io::Result<JavaOutputStream<'a, 'local>> {
+        static OPEN_OUTPUT_STREAM: CachedMethod = CachedMethod::new(
+            "android/content/ContentResolver",
+            "openOutputStream",
+            "(Landroid/net/Uri;Ljava/lang/String;)Ljava/io/OutputStream;",
+        );
+        let mode = new_java_string(env, mode);
+        let stream = OPEN_OUTPUT_STREAM
+            .try_call(env, &self.0, &[(&uri.0).into(), (&mode).into()])
+            .and_then(|value| value.l())
+            .map_err(|_| take_exception(env))?;
+        if stream.as_raw().is_null() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let buffer = env.new_byte_array(BUFFER_SIZE).unwrap();
+        Ok(JavaOutputStream {
+            env,
+            stream,
+            buffer,
+        })
+    }
+}
+
+/// A `java.io.InputStream` adapted to [`io::Read`]. The stream is closed
+/// when this is dropped.
+pub struct JavaInputStream<'a, 'local> {
+    env: &'a mut JNIEnv<'local>,
+    stream: JObject<'local>,
+    buffer: JByteArray<'local>,
+}
+
+impl io::Read for JavaInputStream<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        static READ: CachedMethod = CachedMethod::new("java/io/InputStream", "read", "([BII)I");
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = buf.len().min(BUFFER_SIZE as usize) as jint;
+        let count = READ
+            .try_call(
+                self.env,
+                &self.stream,
+                &[(&self.buffer).into(), 0.into(), len.into()],
+            )
+            .and_then(|value| value.i())
+            .map_err(|_| take_exception(self.env))?;
+        // -1 means end of stream.
+        if count <= 0 {
+            return Ok(0);
+        }
+        let count = count as usize;
+        let dest = &mut buf[..count];
+        // SAFETY: `i8` and `u8` have the same layout.
+        let dest = unsafe { &mut *(dest as *mut [u8] as *mut [i8]) };
+        self.env
+            .get_byte_array_region(&self.buffer, 0, dest)
+            .unwrap();
+        Ok(count)
+    }
+}
+
+impl Drop for JavaInputStream<'_, '_> {
+    fn drop(&mut self) {
+        static CLOSE: CachedMethod = CachedMethod::new("java/io/InputStream", "close", "()V");
+        if CLOSE.try_call(self.env, &self.stream, &[]).is_err() {
+            let _ = self.env.exception_clear();
+        }
+    }
+}
+
+/// A `java.io.OutputStream` adapted to [`io::Write`]. The stream is closed
+/// when this is dropped; call [`io::Write::flush`] first to detect errors.
+pub struct JavaOutputStream<'a, 'local> {
+    env: &'a mut JNIEnv<'local>,
+    stream: JObject<'local>,
+    buffer: JByteArray<'local>,
+}
+
+impl io::Write for JavaOutputStream<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        static WRITE: CachedMethod = CachedMethod::new("java/io/OutputStream", "write", "([BII)V");
+        let len = buf.len().min(BUFFER_SIZE as usize);
+        let src = &buf[..len];
+        // SAFETY: `i8` and `u8` have the same layout.
+        let src = unsafe { &*(src as *const [u8] as *const [i8]) };
+        self.env
+            .set_byte_array_region(&self.buffer, 0, src)
+            .unwrap();
+        WRITE
+            .try_call(
+                self.env,
+                &self.stream,
+                &[(&self.buffer).into(), 0.into(), (len as jint).into()],
+            )
+            .map_err(|_| take_exception(self.env))?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        static FLUSH: CachedMethod = CachedMethod::new("java/io/OutputStream", "flush", "()V");
+        FLUSH
+            .try_call(self.env, &self.stream, &[])
+            .map_err(|_| take_exception(self.env))?;
+        Ok(())
+    }
+}
+
+impl Drop for JavaOutputStream<'_, '_> {
+    fn drop(&mut self) {
+        static CLOSE: CachedMethod = CachedMethod::new("java/io/OutputStream", "close", "()V");
+        if CLOSE.try_call(self.env, &self.stream, &[]).is_err() {
+            let _ = self.env.exception_clear();
+        }
+    }
+}
@@ -1,21 +1,113 @@
-use jni::{JNIEnv, objects::JObject, sys::jfloat};
+use jni::{
+    JNIEnv,
+    objects::{JObject, JString},
+    sys::{jfloat, jint},
+};
+
+use crate::{content::*, intent::*, jni_cache::*};
+
+pub const WINDOW_SERVICE: &str = "window";
+
+pub const PERMISSION_GRANTED: jint = 0;
+pub const PERMISSION_DENIED: jint = -1;
+
+pub const TOAST_LENGTH_SHORT: jint = 0;
+pub const TOAST_LENGTH_LONG: jint = 1;
 
 #[repr(transparent)]
 pub struct Context<'local>(pub JObject<'local>);
 
 impl<'local> Context<'local> {
     pub fn resources(&self, env: &mut JNIEnv<'local>) -> Resources<'local> {
-        Resources(
-            env.call_method(
-                &self.0,
-                "getResources",
-                "()Landroid/content/res/Resources;",
-                &[],
-            )
+        static GET_RESOURCES: CachedMethod = CachedMethod::new(
+            "android/content/Context",
+            "getResources",
+            "()Landroid/content/res/Resources;",
+        );
+        Resources(GET_RESOURCES.call(env, &self.0, &[]).l().unwrap())
+    }
+
+    pub fn system_service(&self, env: &mut JNIEnv<'local>, name: &str) -> JObject<'local> {
+        static GET_SYSTEM_SERVICE: CachedMethod = CachedMethod::new(
+            "android/content/Context",
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+        );
+        let name = new_java_string(env, name);
+        GET_SYSTEM_SERVICE
+            .call(env, &self.0, &[(&name).into()])
+            .l()
             .unwrap()
+    }
+
+    pub fn package_name(&self, env: &mut JNIEnv<'local>) -> JString<'local> {
+        static GET_PACKAGE_NAME: CachedMethod = CachedMethod::new(
+            "android/content/Context",
+            "getPackageName",
+            "()Ljava/lang/String;",
+        );
+        GET_PACKAGE_NAME.call(env, &self.0, &[]).l().unwrap().into()
+    }
+
+    /// Show a brief message over the app. `duration` is
+    /// [`TOAST_LENGTH_SHORT`] or [`TOAST_LENGTH_LONG`]. This must be called
+    /// on the UI thread.
+    pub fn show_toast(&self, env: &mut JNIEnv<'local>, text: &str, duration: jint) {
+        static MAKE_TEXT: CachedStaticMethod = CachedStaticMethod::new(
+            "android/widget/Toast",
+            "makeText",
+            "(Landroid/content/Context;Ljava/lang/CharSequence;I)Landroid/widget/Toast;",
+        );
+        static SHOW: CachedMethod = CachedMethod::new("android/widget/Toast", "show", "()V");
+        let text = new_java_string(env, text);
+        let toast = MAKE_TEXT
+            .call(env, &[(&self.0).into(), (&text).into(), duration.into()])
             .l()
-            .unwrap(),
-        )
+            .unwrap();
+        SHOW.call(env, &toast, &[]).v().unwrap()
+    }
+
+    /// Start the activity described by `intent`. Returns `false` if no
+    /// activity can handle it. If this context isn't an activity,
+    /// the intent must have [`FLAG_ACTIVITY_NEW_TASK`].
+    pub fn start_activity(&self, env: &mut JNIEnv<'local>, intent: &Intent<'local>) -> bool {
+        static START_ACTIVITY: CachedMethod = CachedMethod::new(
+            "android/content/Context",
+            "startActivity",
+            "(Landroid/content/Intent;)V",
+        );
+        let result = START_ACTIVITY.try_call(env, &self.0, &[(&intent.0).into()]);
+        if result.is_err() {
+            // ActivityNotFoundException
+            let _ = env.exception_clear();
+            return false;
+        }
+        true
+    }
+
+    pub fn content_resolver(&self, env: &mut JNIEnv<'local>) -> ContentResolver<'local> {
+        static GET_CONTENT_RESOLVER: CachedMethod = CachedMethod::new(
+            "android/content/Context",
+            "getContentResolver",
+            "()Landroid/content/ContentResolver;",
+        );
+        ContentResolver(GET_CONTENT_RESOLVER.call(env, &self.0, &[]).l().unwrap())
+    }
+
+    /// Whether the app has been granted `permission`, such as
+    /// `"android.permission.RECORD_AUDIO"`.
+    pub fn has_permission(&self, env: &mut JNIEnv<'local>, permission: &str) -> bool {
+        static CHECK_SELF_PERMISSION: CachedMethod = CachedMethod::new(
+            "android/content/Context",
+            "checkSelfPermission",
+            "(Ljava/lang/String;)I",
+        );
+        let permission = new_java_string(env, permission);
+        CHECK_SELF_PERMISSION
+            .call(env, &self.0, &[(&permission).into()])
+            .i()
+            .unwrap()
+            == PERMISSION_GRANTED
     }
 
     // TODO: more methods?
@@ -26,17 +118,12 @@ pub struct Resources<'local>(pub JObject<'local>);
 
 impl<'local> Resources<'local> {
     pub fn display_metrics(&self, env: &mut JNIEnv<'local>) -> DisplayMetrics<'local> {
-        DisplayMetrics(
-            env.call_method(
-                &self.0,
-                "getDisplayMetrics",
-                "()Landroid/util/DisplayMetrics;",
-                &[],
-            )
-            .unwrap()
-            .l()
-            .unwrap(),
-        )
+        static GET_DISPLAY_METRICS: CachedMethod = CachedMethod::new(
+            "android/content/res/Resources",
+            "getDisplayMetrics",
+            "()Landroid/util/DisplayMetrics;",
+        );
+        DisplayMetrics(GET_DISPLAY_METRICS.call(env, &self.0, &[]).l().unwrap())
     }
 }
 
@@ -45,6 +132,8 @@ pub struct DisplayMetrics<'local>(pub JObject<'local>);
 
 impl<'local> DisplayMetrics<'local> {
     pub fn density(&self, env: &mut JNIEnv<'local>) -> jfloat {
-        env.get_field(&self.0, "density", "F").unwrap().f().unwrap()
+        static DENSITY: CachedField =
+            CachedField::new("android/util/DisplayMetrics", "density", "F");
+        DENSITY.get(env, &self.0).f().unwrap()
     }
 }
@@ -0,0 +1,191 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jint},
+};
+
+use crate::jni_cache::*;
+
+/// Where a view is in its window and on the screen, for converting
+/// between view, window and screen coordinates, as needed to position
+/// popups, report cursor anchor info or set accessibility bounds.
+/// Obtain it with [`crate::View::coordinate_space`].
+///
+/// The origins come from `getLocationInWindow` and `getLocationOnScreen`,
+/// so they account for the layout position of the view and its ancestors,
+/// the scroll offsets of any scrolling ancestors, and translations set by
+/// animations, but not for scaling or rotation. The snapshot is only
+/// valid until the view or one of its ancestors moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoordinateSpace {
+    pub origin_in_window: (jint, jint),
+    pub origin_on_screen: (jint, jint),
+}
+
+impl CoordinateSpace {
+    pub fn view_to_window(&self, (x, y): (jfloat, jfloat)) -> (jfloat, jfloat) {
+        let (ox, oy) = self.origin_in_window;
+        (x + ox as jfloat, y + oy as jfloat)
+    }
+
+    pub fn window_to_view(&self, (x, y): (jfloat, jfloat)) -> (jfloat, jfloat) {
+        let (ox, oy) = self.origin_in_window;
+        (x - ox as jfloat, y - oy as jfloat)
+    }
+
+    pub fn view_to_screen(&self, (x, y): (jfloat, jfloat)) -> (jfloat, jfloat) {
+        let (ox, oy) = self.origin_on_screen;
+        (x + ox as jfloat, y + oy as jfloat)
+    }
+
+    pub fn screen_to_view(&self, (x, y): (jfloat, jfloat)) -> (jfloat, jfloat) {
+        let (ox, oy) = self.origin_on_screen;
+        (x - ox as jfloat, y - oy as jfloat)
+    }
+
+    pub fn window_to_screen(&self, point: (jfloat, jfloat)) -> (jfloat, jfloat) {
+        self.view_to_screen(self.window_to_view(point))
+    }
+
+    pub fn screen_to_window(&self, point: (jfloat, jfloat)) -> (jfloat, jfloat) {
+        self.view_to_window(self.screen_to_view(point))
+    }
+}
+
+/// A 2D affine transform, as held by `android.graphics.Matrix` without
+/// the perspective terms. Points are mapped as
+/// `(scale_x * x + skew_x * y + translate_x, skew_y * x + scale_y * y + translate_y)`.
+///
+/// Touch events delivered to a view are already in its local coordinates,
+/// with the inverse of its own matrix applied. Use these transforms when
+/// coordinates come from elsewhere, such as the window coordinates of a
+/// popup or a drag that started in another view, or to map an event into
+/// content that the peer itself scales or rotates with
+/// [`crate::MotionEvent::transform`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AffineTransform {
+    pub scale_x: jfloat,
+    pub skew_x: jfloat,
+    pub translate_x: jfloat,
+    pub skew_y: jfloat,
+    pub scale_y: jfloat,
+    pub translate_y: jfloat,
+}
+
+impl Default for AffineTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl AffineTransform {
+    pub const IDENTITY: Self = Self {
+        scale_x: 1.0,
+        skew_x: 0.0,
+        translate_x: 0.0,
+        skew_y: 0.0,
+        scale_y: 1.0,
+        translate_y: 0.0,
+    };
+
+    pub fn translate(x: jfloat, y: jfloat) -> Self {
+        Self {
+            translate_x: x,
+            translate_y: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    pub fn map_point(&self, (x, y): (jfloat, jfloat)) -> (jfloat, jfloat) {
+        (
+            self.scale_x * x + self.skew_x * y + self.translate_x,
+            self.skew_y * x + self.scale_y * y + self.translate_y,
+        )
+    }
+
+    /// The transform that applies `self` and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            scale_x: next.scale_x * self.scale_x + next.skew_x * self.skew_y,
+            skew_x: next.scale_x * self.skew_x + next.skew_x * self.scale_y,
+            translate_x: next.scale_x * self.translate_x
+                + next.skew_x * self.translate_y
+                + next.translate_x,
+            skew_y: next.skew_y * self.scale_x + next.scale_y * self.skew_y,
+            scale_y: next.skew_y * self.skew_x + next.scale_y * self.scale_y,
+            translate_y: next.skew_y * self.translate_x
+                + next.scale_y * self.translate_y
+                + next.translate_y,
+        }
+    }
+
+    /// The inverse transform, or `None` if this one isn't invertible,
+    /// such as when a view is scaled to zero.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.scale_x * self.scale_y - self.skew_x * self.skew_y;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let scale_x = self.scale_y * inv_det;
+        let skew_x = -self.skew_x * inv_det;
+        let skew_y = -self.skew_y * inv_det;
+        let scale_y = self.scale_x * inv_det;
+        Some(Self {
+            scale_x,
+            skew_x,
+            translate_x: -(scale_x * self.translate_x + skew_x * self.translate_y),
+            skew_y,
+            scale_y,
+            translate_y: -(skew_y * self.translate_x + scale_y * self.translate_y),
+        })
+    }
+
+    pub(crate) fn from_java<'local>(env: &mut JNIEnv<'local>, matrix: &JObject<'local>) -> Self {
+        static GET_VALUES: CachedMethod =
+            CachedMethod::new("android/graphics/Matrix", "getValues", "([F)V");
+        let array = env.new_float_array(9).unwrap();
+        GET_VALUES
+            .call(env, matrix, &[(&array).into()])
+            .v()
+            .unwrap();
+        let mut values = [0.0; 9];
+        env.get_float_array_region(&array, 0, &mut values).unwrap();
+        Self {
+            scale_x: values[0],
+            skew_x: values[1],
+            translate_x: values[2],
+            skew_y: values[3],
+            scale_y: values[4],
+            translate_y: values[5],
+        }
+    }
+
+    pub(crate) fn to_java<'local>(self, env: &mut JNIEnv<'local>) -> JObject<'local> {
+        static SET_VALUES: CachedMethod =
+            CachedMethod::new("android/graphics/Matrix", "setValues", "([F)V");
+        let values = [
+            self.scale_x,
+            self.skew_x,
+            self.translate_x,
+            self.skew_y,
+            self.scale_y,
+            self.translate_y,
+            0.0,
+            0.0,
+            1.0,
+        ];
+        let array = env.new_float_array(9).unwrap();
+        env.set_float_array_region(&array, 0, &values).unwrap();
+        let matrix = new_java_object(env, "android/graphics/Matrix", "()V", &[]);
+        SET_VALUES
+            .call(env, &matrix, &[(&array).into()])
+            .v()
+            .unwrap();
+        matrix
+    }
+}
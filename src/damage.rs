@@ -0,0 +1,126 @@
+use jni::{JNIEnv, objects::JObject};
+use smallvec::SmallVec;
+
+use crate::{geometry::*, jni_cache::*};
+
+// Beyond this, tracking separate rectangles costs more than redrawing
+// the area between them.
+const MAX_RECTS: usize = 8;
+
+/// Accumulates the parts of a view that need to be redrawn between
+/// frames, so a renderer can redraw and present only those parts. Text
+/// editors, for example, usually only need to redraw the line with the
+/// caret.
+///
+/// For software rendering, pass the region's bounds to
+/// [`crate::SurfaceHolder::lock_canvas`]. For GPU rendering, pass the
+/// rectangles to the graphics API as damage hints, such as with
+/// [`DirtyRegion::to_egl_rects`], so the compositor only updates what
+/// changed.
+///
+/// Overlapping rectangles are merged, and once there are too many
+/// separate rectangles, they're collapsed into their bounds. Coordinates
+/// are in the view's pixels.
+#[derive(Clone, Debug, Default)]
+pub struct DirtyRegion {
+    rects: SmallVec<[IntRect; MAX_RECTS]>,
+}
+
+impl DirtyRegion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `rect` as needing to be redrawn.
+    pub fn add(&mut self, rect: IntRect) {
+        if rect.is_empty() || self.rects.iter().any(|r| r.contains_rect(&rect)) {
+            return;
+        }
+        let mut rect = rect;
+        // Merging can make the result overlap rectangles that the
+        // original didn't, so repeat until nothing else overlaps.
+        while let Some(i) = self.rects.iter().position(|r| r.intersects(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(i));
+        }
+        self.rects.push(rect);
+        if self.rects.len() > MAX_RECTS {
+            let bounds = self.bounds().unwrap();
+            self.rects.clear();
+            self.rects.push(bounds);
+        }
+    }
+
+    /// Mark the whole view, of the given size, as needing to be redrawn,
+    /// for example after a resize or when the surface is recreated.
+    pub fn add_all(&mut self, width: i32, height: i32) {
+        self.rects.clear();
+        self.add(IntRect {
+            left: 0,
+            top: 0,
+            right: width,
+            bottom: height,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// The non-overlapping rectangles that make up the region.
+    pub fn rects(&self) -> &[IntRect] {
+        &self.rects
+    }
+
+    /// The smallest rectangle containing the whole region, or `None` if
+    /// it's empty.
+    pub fn bounds(&self) -> Option<IntRect> {
+        self.rects.iter().copied().reduce(|a, b| a.union(&b))
+    }
+
+    /// Restrict the region to `clip`, such as the view's bounds or its
+    /// visible part.
+    pub fn clip(&mut self, clip: &IntRect) {
+        self.rects = self
+            .rects
+            .iter()
+            .filter_map(|r| r.intersect(clip))
+            .collect();
+    }
+
+    /// Return the region's rectangles and leave it empty, typically at the
+    /// start of rendering a frame.
+    pub fn take(&mut self) -> SmallVec<[IntRect; MAX_RECTS]> {
+        std::mem::take(&mut self.rects)
+    }
+
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+
+    /// The region as a flat list of `x, y, width, height` rectangles with
+    /// the origin at the bottom left of a surface of the given height, as
+    /// expected by `eglSetDamageRegionKHR` and `eglSwapBuffersWithDamageKHR`.
+    pub fn to_egl_rects(&self, surface_height: i32) -> Vec<i32> {
+        self.rects
+            .iter()
+            .flat_map(|r| [r.left, surface_height - r.bottom, r.width(), r.height()])
+            .collect()
+    }
+
+    /// Convert the region to an `android.graphics.Region`, such as for
+    /// [`crate::SurfaceControlTransaction::set_damage_region`].
+    pub fn to_java<'local>(&self, env: &mut JNIEnv<'local>) -> JObject<'local> {
+        static UNION: CachedMethod = CachedMethod::new(
+            "android/graphics/Region",
+            "union",
+            "(Landroid/graphics/Rect;)Z",
+        );
+        let region = new_java_object(env, "android/graphics/Region", "()V", &[]);
+        for rect in &self.rects {
+            let rect = Rect::new(env, *rect);
+            UNION.call(env, &region, &[(&rect.0).into()]).z().unwrap();
+            env.delete_local_ref(rect.0).unwrap();
+        }
+        region
+    }
+}
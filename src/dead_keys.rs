@@ -0,0 +1,125 @@
+use jni::JNIEnv;
+use ndk::event::{KeyAction, Keycode, MetaState};
+use smallvec::SmallVec;
+
+use crate::{events::KeyEvent, key_character_map::*};
+
+/// What a key event produced, as determined by [`DeadKeyComposer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeadKeyOutput {
+    /// Text to insert.
+    Text(String),
+    /// The event was a dead key, or cancelled pending accents, and
+    /// shouldn't be handled further.
+    Consumed,
+    /// The event doesn't produce text; handle it as usual.
+    Ignored,
+}
+
+/// Translates key events from physical keyboards to text according to
+/// the user's keyboard layout, composing dead keys with the following
+/// character as `TextKeyListener` does.
+///
+/// Dead keys accumulate, so a layout's accent keys can be stacked, and
+/// each accent is applied to the following character in the order
+/// typed. Accents that don't combine are inserted in their spacing form
+/// before the character. Pressing the same dead key twice, or a dead key
+/// followed by space, inserts the spacing accent, and backspace discards
+/// the pending accents.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeadKeyComposer {
+    pending_accents: SmallVec<[u32; 2]>,
+}
+
+fn spacing_accent(env: &mut JNIEnv, accent: u32) -> Option<char> {
+    KeyCharacterMap::dead_char(env, accent, ' ').or_else(|| char::from_u32(accent))
+}
+
+impl DeadKeyComposer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether there are dead-key accents waiting for the next character.
+    /// Editors may want to show the accent as composing text meanwhile.
+    pub fn is_composing(&self) -> bool {
+        !self.pending_accents.is_empty()
+    }
+
+    /// The pending combining accents, in the order typed.
+    pub fn pending_accents(&self) -> impl Iterator<Item = char> + '_ {
+        self.pending_accents
+            .iter()
+            .filter_map(|&c| char::from_u32(c))
+    }
+
+    /// Discard pending accents, e.g. when focus or the selection changes.
+    pub fn reset(&mut self) {
+        self.pending_accents.clear();
+    }
+
+    /// Process a key event. `meta_state` is usually the event's own, but
+    /// may include latched modifiers from a [`crate::MetaKeyTracker`].
+    pub fn process<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        event: &KeyEvent<'local>,
+        meta_state: MetaState,
+    ) -> DeadKeyOutput {
+        if event.action(env) != KeyAction::Down {
+            return DeadKeyOutput::Ignored;
+        }
+        let key_code = event.key_code(env);
+        if key_code == Keycode::Del && self.is_composing() {
+            self.reset();
+            return DeadKeyOutput::Consumed;
+        }
+        let map = event.key_character_map(env);
+        let c = map.get(env, key_code, meta_state);
+        self.compose(env, c)
+    }
+
+    /// Process a value returned by [`KeyCharacterMap::get`].
+    pub fn compose(&mut self, env: &mut JNIEnv, c: u32) -> DeadKeyOutput {
+        if c == 0 {
+            return DeadKeyOutput::Ignored;
+        }
+        if c & KEY_CHARACTER_MAP_COMBINING_ACCENT != 0 {
+            let accent = c & KEY_CHARACTER_MAP_COMBINING_ACCENT_MASK;
+            if self.pending_accents.last() == Some(&accent) {
+                self.pending_accents.pop();
+                let accent = spacing_accent(env, accent);
+                return self.finish(env, accent);
+            }
+            self.pending_accents.push(accent);
+            return DeadKeyOutput::Consumed;
+        }
+        let Some(c) = char::from_u32(c) else {
+            return DeadKeyOutput::Ignored;
+        };
+        if c == ' '
+            && let Some(accent) = self.pending_accents.pop()
+        {
+            let accent = spacing_accent(env, accent);
+            return self.finish(env, accent);
+        }
+        self.finish(env, Some(c))
+    }
+
+    fn finish(&mut self, env: &mut JNIEnv, c: Option<char>) -> DeadKeyOutput {
+        let mut c = c;
+        let mut text = String::new();
+        for accent in self.pending_accents.drain(..) {
+            match c.and_then(|base| KeyCharacterMap::dead_char(env, accent, base)) {
+                Some(combined) => c = Some(combined),
+                None => text.extend(spacing_accent(env, accent)),
+            }
+        }
+        text.extend(c);
+        if text.is_empty() {
+            DeadKeyOutput::Consumed
+        } else {
+            DeadKeyOutput::Text(text)
+        }
+    }
+}
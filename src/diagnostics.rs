@@ -0,0 +1,127 @@
+//! Reporting of failed calls into Java.
+//!
+//! The bindings in this crate treat a missing method or an unexpected
+//! Java exception as a bug, and panic, which aborts the process. The
+//! panic message alone often isn't enough to find the cause in a
+//! production crash report, so each such failure is first passed, with
+//! the Java member involved and the exception's description, to the
+//! handler installed with [`set_diagnostic_handler`], which can record
+//! it through the app's own telemetry.
+//!
+//! Where a wrapper already has a way to say that a feature is
+//! unavailable, such as returning `None` before the API level that added
+//! it, a failure is reported and then treated the same way, so the app
+//! keeps running. Other failures still panic once the handler returns.
+
+use jni::{JNIEnv, errors::Error, objects::JString};
+use std::sync::RwLock;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A class, method or field wasn't found, usually because it was
+    /// added in a newer API level than the device has.
+    LookupFailed,
+    /// A Java method threw an exception that the binding didn't expect.
+    JavaException,
+    /// Any other JNI failure.
+    Other,
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// The Java member involved, such as `android/view/View.getX`.
+    pub member: String,
+    /// The error, including the exception's class and message if there
+    /// was one.
+    pub message: String,
+}
+
+type Handler = Box<dyn Fn(&Diagnostic) + Send + Sync>;
+
+static HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+
+/// Install a handler for failed calls into Java, replacing any previous
+/// one. The handler is called on the thread where the failure happened,
+/// possibly just before the crate panics, so it should record the
+/// diagnostic synchronously, such as by writing it to a file or logging it.
+/// Whether the crate then panics depends on the call, not on the
+/// handler; see the [module documentation](self).
+pub fn set_diagnostic_handler(handler: impl Fn(&Diagnostic) + Send + Sync + 'static) {
+    *HANDLER.write().unwrap() = Some(Box::new(handler));
+}
+
+pub fn clear_diagnostic_handler() {
+    *HANDLER.write().unwrap() = None;
+}
+
+fn describe_pending_exception(env: &mut JNIEnv) -> Option<String> {
+    if !env.exception_check().unwrap_or(false) {
+        return None;
+    }
+    let exception = env.exception_occurred().ok()?;
+    env.exception_clear().ok()?;
+    let description: JString = env
+        .call_method(&exception, "toString", "()Ljava/lang/String;", &[])
+        .ok()?
+        .l()
+        .ok()?
+        .into();
+    let description = env.get_string(&description).ok()?.into();
+    Some(description)
+}
+
+/// Pass a failed JNI call on `member` to the diagnostic handler,
+/// clearing any pending exception.
+fn report(env: &mut JNIEnv, error: Error, member: impl FnOnce() -> String) -> Diagnostic {
+    let kind = match &error {
+        Error::MethodNotFound { .. } | Error::FieldNotFound { .. } => DiagnosticKind::LookupFailed,
+        Error::JavaException => DiagnosticKind::JavaException,
+        _ => DiagnosticKind::Other,
+    };
+    let message = match describe_pending_exception(env) {
+        Some(exception) => format!("{error}: {exception}"),
+        None => error.to_string(),
+    };
+    let diagnostic = Diagnostic {
+        kind,
+        member: member(),
+        message,
+    };
+    if let Some(handler) = &*HANDLER.read().unwrap() {
+        handler(&diagnostic);
+    }
+    diagnostic
+}
+
+/// Unwrap the result of a JNI call on `member`, reporting the failure
+/// to the diagnostic handler before panicking.
+pub(crate) fn unwrap_or_report<T>(
+    env: &mut JNIEnv,
+    result: jni::errors::Result<T>,
+    member: impl FnOnce() -> String,
+) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => {
+            let diagnostic = report(env, error, member);
+            panic!("{}: {}", diagnostic.member, diagnostic.message);
+        }
+    }
+}
+
+/// Like [`unwrap_or_report`], but returns `None` after reporting
+/// the failure, for calls whose wrapper can do without the result.
+pub(crate) fn ok_or_report<T>(
+    env: &mut JNIEnv,
+    result: jni::errors::Result<T>,
+    member: impl FnOnce() -> String,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(error) => {
+            report(env, error, member);
+            None
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use jni::{
+    JNIEnv,
+    objects::{JIntArray, JObject, JObjectArray},
+    sys::{jfloat, jint, jlong},
+};
+
+use crate::{color_space::*, jni_cache::*, util::*};
+
+pub const HDR_TYPE_DOLBY_VISION: jint = 1;
+pub const HDR_TYPE_HDR10: jint = 2;
+pub const HDR_TYPE_HLG: jint = 3;
+pub const HDR_TYPE_HDR10_PLUS: jint = 4;
+
+/// A binding to `android.view.Display`, obtained from
+/// [`crate::View::display`]. Use
+/// [`crate::View::set_refresh_rate_monitoring_enabled`] to learn when its
+/// refresh rate changes.
+#[repr(transparent)]
+pub struct Display<'local>(pub JObject<'local>);
+
+/// One of the resolution and refresh rate combinations a display supports,
+/// from `android.view.Display.Mode`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayMode {
+    pub mode_id: jint,
+    pub physical_width: jint,
+    pub physical_height: jint,
+    /// The refresh rate in frames per second.
+    pub refresh_rate: jfloat,
+}
+
+impl DisplayMode {
+    fn from_java<'local>(env: &mut JNIEnv<'local>, mode: &JObject<'local>) -> Self {
+        static GET_MODE_ID: CachedMethod =
+            CachedMethod::new("android/view/Display$Mode", "getModeId", "()I");
+        static GET_PHYSICAL_WIDTH: CachedMethod =
+            CachedMethod::new("android/view/Display$Mode", "getPhysicalWidth", "()I");
+        static GET_PHYSICAL_HEIGHT: CachedMethod =
+            CachedMethod::new("android/view/Display$Mode", "getPhysicalHeight", "()I");
+        static GET_REFRESH_RATE: CachedMethod =
+            CachedMethod::new("android/view/Display$Mode", "getRefreshRate", "()F");
+        Self {
+            mode_id: GET_MODE_ID.call(env, mode, &[]).i().unwrap(),
+            physical_width: GET_PHYSICAL_WIDTH.call(env, mode, &[]).i().unwrap(),
+            physical_height: GET_PHYSICAL_HEIGHT.call(env, mode, &[]).i().unwrap(),
+            refresh_rate: GET_REFRESH_RATE.call(env, mode, &[]).f().unwrap(),
+        }
+    }
+
+    /// The time between frames at this mode's refresh rate.
+    pub fn frame_interval_nanos(&self) -> jlong {
+        (1_000_000_000.0 / self.refresh_rate as f64).round() as jlong
+    }
+}
+
+/// What a display can show of HDR content, from
+/// `android.view.Display.HdrCapabilities`. Luminance values are in nits,
+/// and are 0 when the display doesn't report them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HdrCapabilities {
+    /// The `HDR_TYPE_*` constants the display supports.
+    pub supported_types: Vec<jint>,
+    pub desired_max_luminance: jfloat,
+    pub desired_max_average_luminance: jfloat,
+    pub desired_min_luminance: jfloat,
+}
+
+impl<'local> Display<'local> {
+    pub fn display_id(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_DISPLAY_ID: CachedMethod =
+            CachedMethod::new("android/view/Display", "getDisplayId", "()I");
+        GET_DISPLAY_ID.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    /// The current refresh rate in frames per second. On panels with a
+    /// variable refresh rate, this changes as the system switches modes.
+    pub fn refresh_rate(&self, env: &mut JNIEnv<'local>) -> jfloat {
+        static GET_REFRESH_RATE: CachedMethod =
+            CachedMethod::new("android/view/Display", "getRefreshRate", "()F");
+        GET_REFRESH_RATE.call(env, &self.0, &[]).f().unwrap()
+    }
+
+    pub fn mode(&self, env: &mut JNIEnv<'local>) -> DisplayMode {
+        static GET_MODE: CachedMethod = CachedMethod::new(
+            "android/view/Display",
+            "getMode",
+            "()Landroid/view/Display$Mode;",
+        );
+        let mode = GET_MODE.call(env, &self.0, &[]).l().unwrap();
+        DisplayMode::from_java(env, &mode)
+    }
+
+    pub fn supported_modes(&self, env: &mut JNIEnv<'local>) -> Vec<DisplayMode> {
+        static GET_SUPPORTED_MODES: CachedMethod = CachedMethod::new(
+            "android/view/Display",
+            "getSupportedModes",
+            "()[Landroid/view/Display$Mode;",
+        );
+        let modes: JObjectArray = GET_SUPPORTED_MODES
+            .call(env, &self.0, &[])
+            .l()
+            .unwrap()
+            .into();
+        let len = env.get_array_length(&modes).unwrap();
+        (0..len)
+            .map(|i| {
+                let mode = env.get_object_array_element(&modes, i).unwrap();
+                DisplayMode::from_java(env, &mode)
+            })
+            .collect()
+    }
+
+    /// Whether the display can show colors beyond sRGB.
+    pub fn is_wide_color_gamut(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_WIDE_COLOR_GAMUT: CachedMethod =
+            CachedMethod::new("android/view/Display", "isWideColorGamut", "()Z");
+        IS_WIDE_COLOR_GAMUT.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// The wide-gamut color space that the display composites in most
+    /// efficiently, or `None` if the display isn't wide-gamut or prefers
+    /// a color space not covered by [`ColorSpace`]. Requires API level 29;
+    /// always `None` before that.
+    pub fn preferred_wide_gamut_color_space(&self, env: &mut JNIEnv<'local>) -> Option<ColorSpace> {
+        static GET_PREFERRED_WIDE_GAMUT_COLOR_SPACE: CachedMethod = CachedMethod::new(
+            "android/view/Display",
+            "getPreferredWideGamutColorSpace",
+            "()Landroid/graphics/ColorSpace;",
+        );
+        static GET_ID: CachedMethod =
+            CachedMethod::new("android/graphics/ColorSpace", "getId", "()I");
+        if android_sdk_version(env) < 29 {
+            return None;
+        }
+        let color_space = GET_PREFERRED_WIDE_GAMUT_COLOR_SPACE
+            .call_or_report(env, &self.0, &[])?
+            .l()
+            .unwrap();
+        if color_space.as_raw().is_null() {
+            return None;
+        }
+        let id = GET_ID.call(env, &color_space, &[]).i().unwrap();
+        ColorSpace::try_from(id).ok()
+    }
+
+    /// Whether the display supports any HDR format.
+    pub fn is_hdr(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_HDR: CachedMethod = CachedMethod::new("android/view/Display", "isHdr", "()Z");
+        IS_HDR.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    pub fn hdr_capabilities(&self, env: &mut JNIEnv<'local>) -> HdrCapabilities {
+        static GET_HDR_CAPABILITIES: CachedMethod = CachedMethod::new(
+            "android/view/Display",
+            "getHdrCapabilities",
+            "()Landroid/view/Display$HdrCapabilities;",
+        );
+        static GET_SUPPORTED_HDR_TYPES: CachedMethod = CachedMethod::new(
+            "android/view/Display$HdrCapabilities",
+            "getSupportedHdrTypes",
+            "()[I",
+        );
+        static GET_DESIRED_MAX_LUMINANCE: CachedMethod = CachedMethod::new(
+            "android/view/Display$HdrCapabilities",
+            "getDesiredMaxLuminance",
+            "()F",
+        );
+        static GET_DESIRED_MAX_AVERAGE_LUMINANCE: CachedMethod = CachedMethod::new(
+            "android/view/Display$HdrCapabilities",
+            "getDesiredMaxAverageLuminance",
+            "()F",
+        );
+        static GET_DESIRED_MIN_LUMINANCE: CachedMethod = CachedMethod::new(
+            "android/view/Display$HdrCapabilities",
+            "getDesiredMinLuminance",
+            "()F",
+        );
+        let capabilities = GET_HDR_CAPABILITIES.call(env, &self.0, &[]).l().unwrap();
+        if capabilities.as_raw().is_null() {
+            return HdrCapabilities::default();
+        }
+        let types: JIntArray = GET_SUPPORTED_HDR_TYPES
+            .call(env, &capabilities, &[])
+            .l()
+            .unwrap()
+            .into();
+        let len = env.get_array_length(&types).unwrap() as usize;
+        let mut supported_types = vec![0; len];
+        env.get_int_array_region(&types, 0, &mut supported_types)
+            .unwrap();
+        HdrCapabilities {
+            supported_types,
+            desired_max_luminance: GET_DESIRED_MAX_LUMINANCE
+                .call(env, &capabilities, &[])
+                .f()
+                .unwrap(),
+            desired_max_average_luminance: GET_DESIRED_MAX_AVERAGE_LUMINANCE
+                .call(env, &capabilities, &[])
+                .f()
+                .unwrap(),
+            desired_min_luminance: GET_DESIRED_MIN_LUMINANCE
+                .call(env, &capabilities, &[])
+                .f()
+                .unwrap(),
+        }
+    }
+
+    /// How many times brighter than SDR white the display can currently
+    /// show HDR content, or `None` if the display doesn't report it.
+    /// The ratio changes with ambient light and brightness settings.
+    /// Requires API level 34; always `None` before that.
+    pub fn hdr_sdr_ratio(&self, env: &mut JNIEnv<'local>) -> Option<jfloat> {
+        static IS_HDR_SDR_RATIO_AVAILABLE: CachedMethod =
+            CachedMethod::new("android/view/Display", "isHdrSdrRatioAvailable", "()Z");
+        static GET_HDR_SDR_RATIO: CachedMethod =
+            CachedMethod::new("android/view/Display", "getHdrSdrRatio", "()F");
+        if android_sdk_version(env) < 34
+            || !IS_HDR_SDR_RATIO_AVAILABLE
+                .call_or_report(env, &self.0, &[])?
+                .z()
+                .unwrap()
+        {
+            return None;
+        }
+        Some(
+            GET_HDR_SDR_RATIO
+                .call_or_report(env, &self.0, &[])?
+                .f()
+                .unwrap(),
+        )
+    }
+}
@@ -0,0 +1,105 @@
+use jni::{
+    JNIEnv,
+    sys::{jfloat, jint},
+};
+
+use crate::{context::*, jni_cache::*, util::*};
+
+const FONT_WEIGHT_ADJUSTMENT_UNDEFINED: jint = jint::MAX;
+
+/// The user's display settings that a renderer should honor, mostly
+/// found under the platform's accessibility settings. Query the current
+/// values with [`DisplayPreferences::query`], and enable change
+/// notifications with [`crate::View::set_display_preferences_monitoring_enabled`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayPreferences {
+    /// The factor by which to scale text, 1.0 by default.
+    pub font_scale: jfloat,
+    /// How much to add to the weight of text, such as 300 when bold text
+    /// is turned on, or 0. Requires API level 31; always 0 before that.
+    pub font_weight_adjustment: jint,
+    /// Whether the user asked for high contrast text, which the platform
+    /// draws in black or white with a contrasting outline.
+    pub high_text_contrast: bool,
+    /// Whether the user turned off animations, by setting the animator
+    /// duration scale to 0. Transitions should then jump to their end
+    /// state rather than animate.
+    pub remove_animations: bool,
+}
+
+impl Default for DisplayPreferences {
+    fn default() -> Self {
+        Self {
+            font_scale: 1.0,
+            font_weight_adjustment: 0,
+            high_text_contrast: false,
+            remove_animations: false,
+        }
+    }
+}
+
+impl DisplayPreferences {
+    pub fn query<'local>(env: &mut JNIEnv<'local>, context: &Context<'local>) -> Self {
+        static GET_CONFIGURATION: CachedMethod = CachedMethod::new(
+            "android/content/res/Resources",
+            "getConfiguration",
+            "()Landroid/content/res/Configuration;",
+        );
+        static FONT_SCALE: CachedField =
+            CachedField::new("android/content/res/Configuration", "fontScale", "F");
+        static FONT_WEIGHT_ADJUSTMENT: CachedField = CachedField::new(
+            "android/content/res/Configuration",
+            "fontWeightAdjustment",
+            "I",
+        );
+        static SECURE_GET_INT: CachedStaticMethod = CachedStaticMethod::new(
+            "android/provider/Settings$Secure",
+            "getInt",
+            "(Landroid/content/ContentResolver;Ljava/lang/String;I)I",
+        );
+        static GLOBAL_GET_FLOAT: CachedStaticMethod = CachedStaticMethod::new(
+            "android/provider/Settings$Global",
+            "getFloat",
+            "(Landroid/content/ContentResolver;Ljava/lang/String;F)F",
+        );
+
+        let resources = context.resources(env);
+        let config = GET_CONFIGURATION.call(env, &resources.0, &[]).l().unwrap();
+        let font_scale = FONT_SCALE.get(env, &config).f().unwrap();
+        let font_weight_adjustment = if android_sdk_version(env) >= 31 {
+            match FONT_WEIGHT_ADJUSTMENT.get(env, &config).i().unwrap() {
+                FONT_WEIGHT_ADJUSTMENT_UNDEFINED => 0,
+                adjustment => adjustment,
+            }
+        } else {
+            0
+        };
+
+        // AccessibilityManager.isHighTextContrastEnabled is hidden, but
+        // the setting behind it is readable.
+        let resolver = context.content_resolver(env);
+        let key = new_java_string(env, "high_text_contrast_enabled");
+        let high_text_contrast = SECURE_GET_INT
+            .call(env, &[(&resolver.0).into(), (&key).into(), 0.into()])
+            .i()
+            .unwrap()
+            != 0;
+        let key = new_java_string(env, "animator_duration_scale");
+        let animator_duration_scale = GLOBAL_GET_FLOAT
+            .call(env, &[(&resolver.0).into(), (&key).into(), 1.0f32.into()])
+            .f()
+            .unwrap();
+
+        Self {
+            font_scale,
+            font_weight_adjustment,
+            high_text_contrast,
+            remove_animations: animator_duration_scale == 0.0,
+        }
+    }
+
+    /// Whether the user turned on bold text.
+    pub fn bold_text(&self) -> bool {
+        self.font_weight_adjustment > 0
+    }
+}
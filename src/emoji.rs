@@ -0,0 +1,60 @@
+//! Optional support for Jetpack EmojiCompat, which lets devices with
+//! outdated system fonts display newer emoji. The app must depend on
+//! `androidx.emoji2:emoji2` and initialize it, as described in the
+//! Jetpack documentation. When it's missing or still loading, these
+//! functions report only what the system fonts support.
+//!
+//! When EmojiCompat is loaded, the view also tells keyboards which emoji
+//! it can display, so they don't offer ones that would render as tofu.
+
+use jni::{JNIEnv, objects::JIntArray};
+use std::ops::Range;
+
+use crate::{jni_cache::*, util::*};
+
+/// Whether EmojiCompat is available and has finished loading its font.
+pub fn emoji_compat_is_loaded(env: &mut JNIEnv) -> bool {
+    static IS_LOADED: CachedStaticMethod = CachedStaticMethod::new(
+        "org/linebender/android/rustview/RustEmojiCompat",
+        "isLoaded",
+        "()Z",
+    );
+    IS_LOADED.call(env, &[]).z().unwrap()
+}
+
+/// Find the emoji in `text` that the system fonts can't display but
+/// EmojiCompat can. The returned byte ranges should be drawn with the
+/// EmojiCompat font. The result is empty if EmojiCompat isn't loaded.
+pub fn emoji_compat_process(env: &mut JNIEnv, text: &str) -> Vec<Range<usize>> {
+    static PROCESS: CachedStaticMethod = CachedStaticMethod::new(
+        "org/linebender/android/rustview/RustEmojiCompat",
+        "process",
+        "(Ljava/lang/String;)[I",
+    );
+    let string = new_java_string(env, text);
+    let offsets: JIntArray = PROCESS.call(env, &[(&string).into()]).l().unwrap().into();
+    let len = env.get_array_length(&offsets).unwrap() as usize;
+    let mut buf = vec![0; len];
+    env.get_int_array_region(&offsets, 0, &mut buf).unwrap();
+    let mut ranges = buf
+        .chunks_exact(2)
+        .map(|pair| {
+            utf16_to_byte_offset(text, pair[0] as usize)
+                ..utf16_to_byte_offset(text, pair[1] as usize)
+        })
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|range| range.start);
+    ranges
+}
+
+/// Whether `text`, typically a single grapheme cluster, will render as
+/// one glyph, using either the system fonts or EmojiCompat.
+pub fn has_glyph(env: &mut JNIEnv, text: &str) -> bool {
+    static HAS_GLYPH: CachedStaticMethod = CachedStaticMethod::new(
+        "org/linebender/android/rustview/RustEmojiCompat",
+        "hasGlyph",
+        "(Ljava/lang/String;)Z",
+    );
+    let text = new_java_string(env, text);
+    HAS_GLYPH.call(env, &[(&text).into()]).z().unwrap()
+}
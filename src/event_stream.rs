@@ -0,0 +1,561 @@
+//! An alternative to implementing [`ViewPeer`]: a peer that turns its
+//! callbacks into owned events on a channel, for apps built around their
+//! own event loop.
+//!
+//! [`event_stream`] returns an [`EventStreamPeer`], which is registered
+//! with [`register_view_peer`] like any other, and the [`EventStream`] that
+//! receives its events, which can be moved to another thread. Events don't
+//! borrow anything from the callback that produced them, so they can be
+//! handled after it returns.
+//!
+//! Since the peer answers the platform before the app sees an event, it
+//! makes some decisions itself: it reports touch, hover and generic motion
+//! events as handled, and key events too, except for the back, volume and
+//! media keys, which go on to the platform. With the `ime` feature, it
+//! keeps its own copy of the text being edited, in a [`CompositionState`],
+//! and sends the state after each change along with the keyboard's command.
+
+use jni::sys::jint;
+use ndk::{event::Keycode, native_window::NativeWindow};
+use std::sync::mpsc::{self, Receiver, RecvError, Sender, TryRecvError};
+#[cfg(feature = "ime")]
+use std::{
+    borrow::Cow,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+use ui_events::{keyboard::KeyboardEvent, pointer::PointerEvent};
+
+use crate::{
+    callback_ctx::*, events::*, geometry::*, media::*, surface::*, view::*, view_configuration::*,
+};
+#[cfg(feature = "ime")]
+use crate::{composition::*, ime::*, util::*};
+
+/// An event from an [`EventStreamPeer`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ViewEvent {
+    Attached,
+    Detached,
+    SizeChanged {
+        width: jint,
+        height: jint,
+    },
+    /// A touch, hover or generic motion event. Events that can't be
+    /// represented as a pointer event, such as joystick motion, are left
+    /// out.
+    Pointer(PointerEvent),
+    Key(KeyboardEvent),
+    Focus {
+        gained: bool,
+    },
+    WindowFocus {
+        focused: bool,
+    },
+    /// The window stays valid after [`ViewEvent::SurfaceDestroyed`], since
+    /// it's reference counted, but drawing to it no longer shows anything.
+    SurfaceCreated(NativeWindow),
+    SurfaceChanged {
+        window: NativeWindow,
+        format: jint,
+        width: jint,
+        height: jint,
+    },
+    SurfaceDestroyed,
+    /// A command from the keyboard, with the text state after applying it.
+    #[cfg(feature = "ime")]
+    Ime {
+        command: ImeCommand,
+        state: CompositionState,
+    },
+}
+
+/// An editing command from the keyboard, with the arguments of the
+/// corresponding `InputConnection` method. Offsets and lengths are in
+/// UTF-16 code units.
+#[cfg(feature = "ime")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ImeCommand {
+    SetComposingText {
+        text: String,
+        new_cursor_position: jint,
+    },
+    CommitText {
+        text: String,
+        new_cursor_position: jint,
+    },
+    SetComposingRegion {
+        start: jint,
+        end: jint,
+    },
+    FinishComposingText,
+    SetSelection {
+        start: jint,
+        end: jint,
+    },
+    DeleteSurroundingText {
+        before_length: jint,
+        after_length: jint,
+    },
+    DeleteSurroundingTextInCodePoints {
+        before_length: jint,
+        after_length: jint,
+    },
+    PerformEditorAction(jint),
+}
+
+/// The receiving end of an [`EventStreamPeer`]'s events.
+pub struct EventStream {
+    receiver: Receiver<ViewEvent>,
+    #[cfg(feature = "ime")]
+    ime_state: Arc<Mutex<CompositionState>>,
+}
+
+impl EventStream {
+    /// Wait for the next event. Returns an error once the peer has been
+    /// dropped and all of its events have been received.
+    pub fn recv(&self) -> Result<ViewEvent, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Return the next event if there is one, without waiting.
+    pub fn try_recv(&self) -> Result<ViewEvent, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Iterate over the events that have already arrived, without waiting.
+    pub fn pending(&self) -> impl Iterator<Item = ViewEvent> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Replace the peer's copy of the text being edited, after the app
+    /// changes the text other than through an [`ImeCommand`]. The keyboard
+    /// isn't told until it next asks for the text, so a change that it
+    /// must see at once, such as clearing the field, also needs
+    /// [`InputMethodManager::restart_input`] on the UI thread.
+    #[cfg(feature = "ime")]
+    pub fn set_ime_state(&self, state: CompositionState) {
+        *self.ime_state.lock().unwrap() = state;
+    }
+}
+
+impl Iterator for EventStream {
+    type Item = ViewEvent;
+
+    fn next(&mut self) -> Option<ViewEvent> {
+        self.recv().ok()
+    }
+}
+
+/// A [`ViewPeer`] that sends its callbacks to an [`EventStream`].
+pub struct EventStreamPeer {
+    sender: Sender<ViewEvent>,
+    view_configuration: Option<ViewConfiguration>,
+    #[cfg(feature = "ime")]
+    ime_state: Arc<Mutex<CompositionState>>,
+    #[cfg(feature = "ime")]
+    input_type: InputType,
+    #[cfg(feature = "ime")]
+    ime_options: ImeOptions,
+}
+
+/// Create a peer and the stream that receives its events.
+pub fn event_stream() -> (EventStreamPeer, EventStream) {
+    let (sender, receiver) = mpsc::channel();
+    #[cfg(feature = "ime")]
+    let ime_state = Arc::new(Mutex::new(CompositionState::default()));
+    let peer = EventStreamPeer {
+        sender,
+        view_configuration: None,
+        #[cfg(feature = "ime")]
+        ime_state: Arc::clone(&ime_state),
+        #[cfg(feature = "ime")]
+        input_type: InputType::CLASS_TEXT,
+        #[cfg(feature = "ime")]
+        ime_options: ImeOptions::empty(),
+    };
+    let stream = EventStream {
+        receiver,
+        #[cfg(feature = "ime")]
+        ime_state,
+    };
+    (peer, stream)
+}
+
+fn is_platform_key(key_code: Keycode) -> bool {
+    matches!(
+        key_code,
+        Keycode::Back | Keycode::VolumeUp | Keycode::VolumeDown | Keycode::VolumeMute
+    ) || MediaKey::from_keycode(key_code).is_some()
+}
+
+impl EventStreamPeer {
+    /// Set the input type and IME options reported to the keyboard when
+    /// the view gets an input connection. The default is plain text.
+    #[cfg(feature = "ime")]
+    pub fn with_input_type(mut self, input_type: InputType, ime_options: ImeOptions) -> Self {
+        self.input_type = input_type;
+        self.ime_options = ime_options;
+        self
+    }
+
+    fn send(&self, event: ViewEvent) {
+        // The stream may have been dropped; there's no one left to tell.
+        let _ = self.sender.send(event);
+    }
+
+    fn send_pointer_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        let vc = self
+            .view_configuration
+            .get_or_insert_with(|| ctx.view.view_configuration(&mut ctx.env));
+        if let Some(event) = event.to_pointer_event(&mut ctx.env, vc) {
+            self.send(ViewEvent::Pointer(event));
+        }
+        true
+    }
+
+    fn send_key<'local>(
+        &self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        if is_platform_key(key_code) {
+            return false;
+        }
+        self.send(ViewEvent::Key(event.to_keyboard_event(&mut ctx.env)));
+        true
+    }
+
+    #[cfg(feature = "ime")]
+    fn apply_ime_command(&self, command: ImeCommand) -> bool {
+        let mut state = self.ime_state.lock().unwrap();
+        match &command {
+            ImeCommand::SetComposingText {
+                text,
+                new_cursor_position,
+            } => state.set_composing_text(text, *new_cursor_position),
+            ImeCommand::CommitText {
+                text,
+                new_cursor_position,
+            } => state.commit_text(text, *new_cursor_position),
+            ImeCommand::SetComposingRegion { start, end } => {
+                state.set_composing_region(*start, *end)
+            }
+            ImeCommand::FinishComposingText => state.finish_composing_text(),
+            ImeCommand::SetSelection { start, end } => {
+                if !state.set_selection(*start, *end) {
+                    return false;
+                }
+            }
+            ImeCommand::DeleteSurroundingText {
+                before_length,
+                after_length,
+            } => state.delete_surrounding_text(*before_length, *after_length),
+            ImeCommand::DeleteSurroundingTextInCodePoints {
+                before_length,
+                after_length,
+            } => state.delete_surrounding_text_in_code_points(*before_length, *after_length),
+            ImeCommand::PerformEditorAction(_) => {}
+        }
+        let state = state.clone();
+        self.send(ViewEvent::Ime { command, state });
+        true
+    }
+}
+
+impl ViewPeer for EventStreamPeer {
+    fn on_size_changed(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        w: jint,
+        h: jint,
+        _oldw: jint,
+        _oldh: jint,
+    ) {
+        self.send(ViewEvent::SizeChanged {
+            width: w,
+            height: h,
+        });
+    }
+
+    fn on_key_down<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        self.send_key(ctx, key_code, event)
+    }
+
+    fn on_key_up<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        self.send_key(ctx, key_code, event)
+    }
+
+    fn on_touch_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        self.send_pointer_event(ctx, event)
+    }
+
+    fn on_generic_motion_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        self.send_pointer_event(ctx, event)
+    }
+
+    fn on_hover_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        self.send_pointer_event(ctx, event)
+    }
+
+    fn on_focus_changed<'local>(
+        &mut self,
+        _ctx: &mut CallbackCtx<'local>,
+        gain_focus: bool,
+        _direction: jint,
+        _previously_focused_rect: Option<&Rect<'local>>,
+    ) {
+        self.send(ViewEvent::Focus { gained: gain_focus });
+    }
+
+    fn on_window_focus_changed(&mut self, _ctx: &mut CallbackCtx, has_window_focus: bool) {
+        self.send(ViewEvent::WindowFocus {
+            focused: has_window_focus,
+        });
+    }
+
+    fn on_attached_to_window(&mut self, _ctx: &mut CallbackCtx) {
+        self.send(ViewEvent::Attached);
+    }
+
+    fn on_detached_from_window(&mut self, _ctx: &mut CallbackCtx) {
+        self.send(ViewEvent::Detached);
+    }
+
+    fn surface_created<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+    ) {
+        let window = holder.surface(&mut ctx.env).to_native_window(&mut ctx.env);
+        self.send(ViewEvent::SurfaceCreated(window));
+    }
+
+    fn surface_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+        format: jint,
+        width: jint,
+        height: jint,
+    ) {
+        let window = holder.surface(&mut ctx.env).to_native_window(&mut ctx.env);
+        self.send(ViewEvent::SurfaceChanged {
+            window,
+            format,
+            width,
+            height,
+        });
+    }
+
+    fn surface_destroyed<'local>(
+        &mut self,
+        _ctx: &mut CallbackCtx<'local>,
+        _holder: &SurfaceHolder<'local>,
+    ) {
+        self.send(ViewEvent::SurfaceDestroyed);
+    }
+
+    #[cfg(feature = "ime")]
+    fn as_input_connection(&mut self) -> Option<&mut dyn InputConnection> {
+        Some(self)
+    }
+}
+
+#[cfg(feature = "ime")]
+impl EventStreamPeer {
+    fn text_range(&self, range: impl FnOnce(&CompositionState) -> Range<usize>) -> String {
+        let state = self.ime_state.lock().unwrap();
+        state.text()[range(&state)].to_string()
+    }
+}
+
+#[cfg(feature = "ime")]
+impl InputConnection for EventStreamPeer {
+    fn on_create_input_connection<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        out_attrs: &EditorInfo<'local>,
+    ) {
+        let selection = self.ime_state.lock().unwrap().utf16_selection();
+        out_attrs.populate(
+            &mut ctx.env,
+            &EditorAttributes {
+                input_type: self.input_type,
+                ime_options: self.ime_options,
+                initial_selection: Some(selection),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn text_before_cursor<'slf>(
+        &'slf mut self,
+        _ctx: &mut CallbackCtx,
+        n: jint,
+    ) -> Option<Cow<'slf, str>> {
+        if n < 0 {
+            return None;
+        }
+        Some(Cow::Owned(self.text_range(|state| {
+            let (start, _) = state.utf16_selection();
+            let start = utf16_to_byte_offset(state.text(), (start - n).max(0) as usize);
+            start..state.selection().start
+        })))
+    }
+
+    fn text_after_cursor<'slf>(
+        &'slf mut self,
+        _ctx: &mut CallbackCtx,
+        n: jint,
+    ) -> Option<Cow<'slf, str>> {
+        if n < 0 {
+            return None;
+        }
+        Some(Cow::Owned(self.text_range(|state| {
+            let (_, end) = state.utf16_selection();
+            let end = utf16_to_byte_offset(state.text(), end.saturating_add(n) as usize);
+            state.selection().end..end
+        })))
+    }
+
+    fn selected_text<'slf>(&'slf mut self, _ctx: &mut CallbackCtx) -> Option<Cow<'slf, str>> {
+        let text = self.text_range(|state| state.selection());
+        (!text.is_empty()).then_some(Cow::Owned(text))
+    }
+
+    fn cursor_caps_mode(&mut self, ctx: &mut CallbackCtx, req_modes: CapsMode) -> CapsMode {
+        let state = self.ime_state.lock().unwrap();
+        let (offset, _) = state.utf16_selection();
+        caps_mode(&mut ctx.env, state.text(), offset as usize, req_modes)
+    }
+
+    fn extracted_text(&mut self, _ctx: &mut CallbackCtx) -> Option<ExtractedText> {
+        let state = self.ime_state.lock().unwrap();
+        let (selection_start, selection_end) = state.utf16_selection();
+        Some(ExtractedText {
+            text: state.text().to_string(),
+            start_offset: 0,
+            selection_start,
+            selection_end,
+        })
+    }
+
+    fn delete_surrounding_text(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        before_length: jint,
+        after_length: jint,
+    ) -> bool {
+        self.apply_ime_command(ImeCommand::DeleteSurroundingText {
+            before_length,
+            after_length,
+        })
+    }
+
+    fn delete_surrounding_text_in_code_points(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        before_length: jint,
+        after_length: jint,
+    ) -> bool {
+        self.apply_ime_command(ImeCommand::DeleteSurroundingTextInCodePoints {
+            before_length,
+            after_length,
+        })
+    }
+
+    fn set_composing_text(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        text: &str,
+        new_cursor_position: jint,
+    ) -> bool {
+        self.apply_ime_command(ImeCommand::SetComposingText {
+            text: text.to_string(),
+            new_cursor_position,
+        })
+    }
+
+    fn set_composing_region(&mut self, _ctx: &mut CallbackCtx, start: jint, end: jint) -> bool {
+        self.apply_ime_command(ImeCommand::SetComposingRegion { start, end })
+    }
+
+    fn finish_composing_text(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        self.apply_ime_command(ImeCommand::FinishComposingText)
+    }
+
+    fn commit_text(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        text: &str,
+        new_cursor_position: jint,
+    ) -> bool {
+        self.apply_ime_command(ImeCommand::CommitText {
+            text: text.to_string(),
+            new_cursor_position,
+        })
+    }
+
+    fn set_selection(&mut self, _ctx: &mut CallbackCtx, start: jint, end: jint) -> bool {
+        self.apply_ime_command(ImeCommand::SetSelection { start, end })
+    }
+
+    fn perform_editor_action(&mut self, _ctx: &mut CallbackCtx, editor_action: jint) -> bool {
+        self.apply_ime_command(ImeCommand::PerformEditorAction(editor_action))
+    }
+
+    fn begin_batch_edit(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        true
+    }
+
+    fn end_batch_edit(&mut self, _ctx: &mut CallbackCtx) -> bool {
+        false
+    }
+
+    fn send_key_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        self.send(ViewEvent::Key(event.to_keyboard_event(&mut ctx.env)));
+        true
+    }
+
+    fn request_cursor_updates(
+        &mut self,
+        _ctx: &mut CallbackCtx,
+        _cursor_update_mode: jint,
+    ) -> bool {
+        false
+    }
+}
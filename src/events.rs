@@ -1,7 +1,7 @@
 use dpi::PhysicalPosition;
 use jni::{
     JNIEnv,
-    objects::JObject,
+    objects::{JObject, JString},
     sys::{jfloat, jint, jlong},
 };
 use ndk::event::{
@@ -9,110 +9,150 @@ use ndk::event::{
     MotionEventFlags, Source, ToolType,
 };
 use num_enum::FromPrimitive;
+use std::time::{Duration, Instant};
 use ui_events::{
     ScrollDelta,
     keyboard::{KeyboardEvent, Modifiers},
     pointer::{ContactGeometry, PointerEvent, PointerId, PointerState, PointerUpdate},
 };
 
-use crate::ViewConfiguration;
+use crate::{
+    Context, ViewConfiguration, coordinates::*, input_device::*, jni_cache::*,
+    key_character_map::*, time::*, util::*,
+};
 
 #[repr(transparent)]
 pub struct KeyEvent<'local>(pub JObject<'local>);
 
 impl<'local> KeyEvent<'local> {
     pub fn device_id(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getDeviceId", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_DEVICE_ID: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getDeviceId", "()I");
+        GET_DEVICE_ID.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn source(&self, env: &mut JNIEnv<'local>) -> Source {
-        Source::from_primitive(
-            env.call_method(&self.0, "getSource", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap(),
-        )
+        static GET_SOURCE: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getSource", "()I");
+        Source::from_primitive(GET_SOURCE.call(env, &self.0, &[]).i().unwrap())
     }
 
     pub fn action(&self, env: &mut JNIEnv<'local>) -> KeyAction {
-        KeyAction::from_primitive(
-            env.call_method(&self.0, "getAction", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap(),
-        )
+        static GET_ACTION: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getAction", "()I");
+        KeyAction::from_primitive(GET_ACTION.call(env, &self.0, &[]).i().unwrap())
     }
 
     pub fn event_time(&self, env: &mut JNIEnv<'local>) -> jlong {
-        env.call_method(&self.0, "getEventTime", "()J", &[])
-            .unwrap()
-            .j()
-            .unwrap()
+        static GET_EVENT_TIME: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getEventTime", "()J");
+        GET_EVENT_TIME.call(env, &self.0, &[]).j().unwrap()
     }
 
     pub fn down_time(&self, env: &mut JNIEnv<'local>) -> jlong {
-        env.call_method(&self.0, "getDownTime", "()J", &[])
-            .unwrap()
-            .j()
-            .unwrap()
+        static GET_DOWN_TIME: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getDownTime", "()J");
+        GET_DOWN_TIME.call(env, &self.0, &[]).j().unwrap()
+    }
+
+    /// The time of this event, as an [`Instant`].
+    pub fn event_instant(&self, env: &mut JNIEnv<'local>) -> Instant {
+        let millis = self.event_time(env);
+        instant_from_uptime_millis(env, millis)
+    }
+
+    /// The time of the initial down event of this gesture or key
+    /// press, as an [`Instant`].
+    pub fn down_instant(&self, env: &mut JNIEnv<'local>) -> Instant {
+        let millis = self.down_time(env);
+        instant_from_uptime_millis(env, millis)
+    }
+
+    /// The time elapsed between the initial down event and this event.
+    pub fn duration_since_down(&self, env: &mut JNIEnv<'local>) -> Duration {
+        let event_time = self.event_time(env);
+        let down_time = self.down_time(env);
+        Duration::from_millis((event_time - down_time).max(0) as u64)
     }
 
     pub fn flags(&self, env: &mut JNIEnv<'local>) -> KeyEventFlags {
-        KeyEventFlags(
-            env.call_method(&self.0, "getFlags", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap() as u32,
-        )
+        static GET_FLAGS: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getFlags", "()I");
+        KeyEventFlags(GET_FLAGS.call(env, &self.0, &[]).i().unwrap() as u32)
     }
 
     pub fn meta_state(&self, env: &mut JNIEnv<'local>) -> MetaState {
-        MetaState(
-            env.call_method(&self.0, "getMetaState", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap() as u32,
-        )
+        static GET_META_STATE: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getMetaState", "()I");
+        MetaState(GET_META_STATE.call(env, &self.0, &[]).i().unwrap() as u32)
     }
 
     pub fn repeat_count(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getRepeatCount", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_REPEAT_COUNT: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getRepeatCount", "()I");
+        GET_REPEAT_COUNT.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn key_code(&self, env: &mut JNIEnv<'local>) -> Keycode {
-        Keycode::from_primitive(
-            env.call_method(&self.0, "getKeyCode", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap(),
-        )
+        static GET_KEY_CODE: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getKeyCode", "()I");
+        Keycode::from_primitive(GET_KEY_CODE.call(env, &self.0, &[]).i().unwrap())
     }
 
     pub fn scan_code(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getScanCode", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_SCAN_CODE: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getScanCode", "()I");
+        GET_SCAN_CODE.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn unicode_char(&self, env: &mut JNIEnv<'local>) -> Option<char> {
-        let i = env
-            .call_method(&self.0, "getUnicodeChar", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap();
+        static GET_UNICODE_CHAR: CachedMethod =
+            CachedMethod::new("android/view/KeyEvent", "getUnicodeChar", "()I");
+        let i = GET_UNICODE_CHAR.call(env, &self.0, &[]).i().unwrap();
         if i <= 0 {
             return None;
         }
         char::from_u32(i as _)
     }
 
+    /// The characters of an `ACTION_MULTIPLE` event with the key code
+    /// `KEYCODE_UNKNOWN`, such as text typed by some input methods as one
+    /// event, or `None` for any other event.
+    pub fn characters(&self, env: &mut JNIEnv<'local>) -> Option<String> {
+        static GET_CHARACTERS: CachedMethod = CachedMethod::new(
+            "android/view/KeyEvent",
+            "getCharacters",
+            "()Ljava/lang/String;",
+        );
+        let characters = GET_CHARACTERS.call(env, &self.0, &[]).l().unwrap();
+        if characters.is_null() {
+            return None;
+        }
+        Some(env.get_string(&JString::from(characters)).unwrap().into())
+    }
+
+    /// The device that generated this event, or `None` if it was
+    /// injected or the device has since been removed.
+    pub fn device(&self, env: &mut JNIEnv<'local>) -> Option<InputDevice<'local>> {
+        static GET_DEVICE: CachedMethod = CachedMethod::new(
+            "android/view/KeyEvent",
+            "getDevice",
+            "()Landroid/view/InputDevice;",
+        );
+        let device = GET_DEVICE.call(env, &self.0, &[]).l().unwrap();
+        (!device.is_null()).then_some(InputDevice(device))
+    }
+
+    /// The character map for the device that generated this event.
+    pub fn key_character_map(&self, env: &mut JNIEnv<'local>) -> KeyCharacterMap<'local> {
+        static GET_KEY_CHARACTER_MAP: CachedMethod = CachedMethod::new(
+            "android/view/KeyEvent",
+            "getKeyCharacterMap",
+            "()Landroid/view/KeyCharacterMap;",
+        );
+        KeyCharacterMap(GET_KEY_CHARACTER_MAP.call(env, &self.0, &[]).l().unwrap())
+    }
+
     pub fn to_keyboard_event(&self, env: &mut JNIEnv<'local>) -> KeyboardEvent {
         use ui_events::keyboard::{Key, KeyState, NamedKey, android};
 
@@ -148,174 +188,213 @@ pub struct MotionEvent<'local>(pub JObject<'local>);
 
 impl<'local> MotionEvent<'local> {
     pub fn device_id(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getDeviceId", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_DEVICE_ID: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getDeviceId", "()I");
+        GET_DEVICE_ID.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    /// The device that generated this event, or `None` if it was
+    /// injected or the device has since been removed.
+    pub fn device(&self, env: &mut JNIEnv<'local>) -> Option<InputDevice<'local>> {
+        static GET_DEVICE: CachedMethod = CachedMethod::new(
+            "android/view/MotionEvent",
+            "getDevice",
+            "()Landroid/view/InputDevice;",
+        );
+        let device = GET_DEVICE.call(env, &self.0, &[]).l().unwrap();
+        (!device.is_null()).then_some(InputDevice(device))
     }
 
     pub fn source(&self, env: &mut JNIEnv<'local>) -> Source {
-        Source::from_primitive(
-            env.call_method(&self.0, "getSource", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap(),
-        )
+        static GET_SOURCE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getSource", "()I");
+        Source::from_primitive(GET_SOURCE.call(env, &self.0, &[]).i().unwrap())
     }
 
     pub fn action(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getAction", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_ACTION: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getAction", "()I");
+        GET_ACTION.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn action_button(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getActionButton", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_ACTION_BUTTON: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getActionButton", "()I");
+        GET_ACTION_BUTTON.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn action_masked(&self, env: &mut JNIEnv<'local>) -> MotionAction {
-        MotionAction::from_primitive(
-            env.call_method(&self.0, "getActionMasked", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap(),
-        )
+        static GET_ACTION_MASKED: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getActionMasked", "()I");
+        MotionAction::from_primitive(GET_ACTION_MASKED.call(env, &self.0, &[]).i().unwrap())
     }
 
     pub fn action_index(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getActionIndex", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_ACTION_INDEX: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getActionIndex", "()I");
+        GET_ACTION_INDEX.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn button_state(&self, env: &mut JNIEnv<'local>) -> ButtonState {
-        ButtonState(
-            env.call_method(&self.0, "getButtonState", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap() as u32,
-        )
+        static GET_BUTTON_STATE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getButtonState", "()I");
+        ButtonState(GET_BUTTON_STATE.call(env, &self.0, &[]).i().unwrap() as u32)
     }
 
     pub fn event_time(&self, env: &mut JNIEnv<'local>) -> jlong {
-        env.call_method(&self.0, "getEventTime", "()J", &[])
-            .unwrap()
-            .j()
-            .unwrap()
+        static GET_EVENT_TIME: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getEventTime", "()J");
+        GET_EVENT_TIME.call(env, &self.0, &[]).j().unwrap()
     }
 
     pub fn event_time_nanos(&self, env: &mut JNIEnv<'local>) -> jlong {
-        env.call_method(&self.0, "getEventTimeNanos", "()J", &[])
-            .unwrap()
-            .j()
-            .unwrap()
+        static GET_EVENT_TIME_NANOS: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getEventTimeNanos", "()J");
+        GET_EVENT_TIME_NANOS.call(env, &self.0, &[]).j().unwrap()
     }
 
     pub fn historical_event_time_nanos(&self, env: &mut JNIEnv<'local>, pos: i32) -> jlong {
-        env.call_method(
-            &self.0,
+        static GET_HISTORICAL_EVENT_TIME_NANOS: CachedMethod = CachedMethod::new(
+            "android/view/MotionEvent",
             "getHistoricalEventTimeNanos",
             "(I)J",
-            &[pos.into()],
-        )
-        .unwrap()
-        .j()
-        .unwrap()
+        );
+        GET_HISTORICAL_EVENT_TIME_NANOS
+            .call(env, &self.0, &[pos.into()])
+            .j()
+            .unwrap()
     }
 
     pub fn down_time(&self, env: &mut JNIEnv<'local>) -> jlong {
-        env.call_method(&self.0, "getDownTime", "()J", &[])
-            .unwrap()
-            .j()
-            .unwrap()
+        static GET_DOWN_TIME: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getDownTime", "()J");
+        GET_DOWN_TIME.call(env, &self.0, &[]).j().unwrap()
+    }
+
+    /// The time of this event, as an [`Instant`].
+    pub fn event_instant(&self, env: &mut JNIEnv<'local>) -> Instant {
+        let millis = self.event_time(env);
+        instant_from_uptime_millis(env, millis)
+    }
+
+    /// The time of the initial down event of this gesture or key
+    /// press, as an [`Instant`].
+    pub fn down_instant(&self, env: &mut JNIEnv<'local>) -> Instant {
+        let millis = self.down_time(env);
+        instant_from_uptime_millis(env, millis)
+    }
+
+    /// The time elapsed between the initial down event and this event.
+    pub fn duration_since_down(&self, env: &mut JNIEnv<'local>) -> Duration {
+        let event_time = self.event_time(env);
+        let down_time = self.down_time(env);
+        Duration::from_millis((event_time - down_time).max(0) as u64)
     }
 
     pub fn flags(&self, env: &mut JNIEnv<'local>) -> MotionEventFlags {
-        MotionEventFlags(
-            env.call_method(&self.0, "getFlags", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap() as u32,
-        )
+        static GET_FLAGS: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getFlags", "()I");
+        MotionEventFlags(GET_FLAGS.call(env, &self.0, &[]).i().unwrap() as u32)
     }
 
     pub fn meta_state(&self, env: &mut JNIEnv<'local>) -> MetaState {
-        MetaState(
-            env.call_method(&self.0, "getMetaState", "()I", &[])
-                .unwrap()
-                .i()
-                .unwrap() as u32,
-        )
+        static GET_META_STATE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getMetaState", "()I");
+        MetaState(GET_META_STATE.call(env, &self.0, &[]).i().unwrap() as u32)
     }
 
     pub fn pointer_count(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getPointerCount", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_POINTER_COUNT: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getPointerCount", "()I");
+        GET_POINTER_COUNT.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn pointer_id(&self, env: &mut JNIEnv<'local>, pointer_index: jint) -> jint {
-        env.call_method(&self.0, "getPointerId", "(I)I", &[pointer_index.into()])
-            .unwrap()
+        static GET_POINTER_ID: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getPointerId", "(I)I");
+        GET_POINTER_ID
+            .call(env, &self.0, &[pointer_index.into()])
             .i()
             .unwrap()
     }
 
+    pub fn find_pointer_index(&self, env: &mut JNIEnv<'local>, pointer_id: jint) -> Option<jint> {
+        static FIND_POINTER_INDEX: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "findPointerIndex", "(I)I");
+        let index = FIND_POINTER_INDEX
+            .call(env, &self.0, &[pointer_id.into()])
+            .i()
+            .unwrap();
+        (index >= 0).then_some(index)
+    }
+
+    /// Whether the pointer that went up was cancelled, typically because
+    /// the system decided it was an accidental touch such as a palm.
+    /// This is set on `ACTION_POINTER_UP` and `ACTION_CANCEL` events
+    /// on Android 13 and later, and the pointer's input should be
+    /// discarded rather than committed.
+    pub fn is_canceled(&self, env: &mut JNIEnv<'local>) -> bool {
+        const FLAG_CANCELED: u32 = 0x20;
+        self.flags(env).0 & FLAG_CANCELED != 0
+    }
+
+    /// Whether the given pointer should be treated as an accidental
+    /// touch, either because the system classified it as a palm or
+    /// because it was cancelled.
+    pub fn is_palm(&self, env: &mut JNIEnv<'local>, pointer_index: jint) -> bool {
+        self.tool_type(env, pointer_index) == ToolType::Palm
+            || (self.action_masked(env) == MotionAction::PointerUp
+                && self.action_index(env) == pointer_index
+                && self.is_canceled(env))
+    }
+
     pub fn tool_type(&self, env: &mut JNIEnv<'local>, pointer_index: jint) -> ToolType {
+        static GET_TOOL_TYPE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getToolType", "(I)I");
         ToolType::from(
-            env.call_method(&self.0, "getToolType", "(I)I", &[pointer_index.into()])
-                .unwrap()
+            GET_TOOL_TYPE
+                .call(env, &self.0, &[pointer_index.into()])
                 .i()
                 .unwrap(),
         )
     }
 
     pub fn x(&self, env: &mut JNIEnv<'local>) -> jfloat {
-        env.call_method(&self.0, "getX", "()F", &[])
-            .unwrap()
-            .f()
-            .unwrap()
+        static GET_X: CachedMethod = CachedMethod::new("android/view/MotionEvent", "getX", "()F");
+        GET_X.call(env, &self.0, &[]).f().unwrap()
     }
 
     pub fn x_at(&self, env: &mut JNIEnv<'local>, pointer_index: jint) -> jfloat {
-        env.call_method(&self.0, "getX", "(I)F", &[pointer_index.into()])
-            .unwrap()
+        static GET_X: CachedMethod = CachedMethod::new("android/view/MotionEvent", "getX", "(I)F");
+        GET_X
+            .call(env, &self.0, &[pointer_index.into()])
             .f()
             .unwrap()
     }
 
     pub fn y(&self, env: &mut JNIEnv<'local>) -> jfloat {
-        env.call_method(&self.0, "getY", "()F", &[])
-            .unwrap()
-            .f()
-            .unwrap()
+        static GET_Y: CachedMethod = CachedMethod::new("android/view/MotionEvent", "getY", "()F");
+        GET_Y.call(env, &self.0, &[]).f().unwrap()
     }
 
     pub fn y_at(&self, env: &mut JNIEnv<'local>, pointer_index: jint) -> jfloat {
-        env.call_method(&self.0, "getY", "(I)F", &[pointer_index.into()])
-            .unwrap()
+        static GET_Y: CachedMethod = CachedMethod::new("android/view/MotionEvent", "getY", "(I)F");
+        GET_Y
+            .call(env, &self.0, &[pointer_index.into()])
             .f()
             .unwrap()
     }
 
     pub fn pressure(&self, env: &mut JNIEnv<'local>) -> jfloat {
-        env.call_method(&self.0, "getPressure", "()F", &[])
-            .unwrap()
-            .f()
-            .unwrap()
+        static GET_PRESSURE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getPressure", "()F");
+        GET_PRESSURE.call(env, &self.0, &[]).f().unwrap()
     }
 
     pub fn history_size(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.call_method(&self.0, "getHistorySize", "()I", &[])
-            .unwrap()
-            .i()
-            .unwrap()
+        static GET_HISTORY_SIZE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getHistorySize", "()I");
+        GET_HISTORY_SIZE.call(env, &self.0, &[]).i().unwrap()
     }
 
     pub fn historical_axis(
@@ -325,27 +404,95 @@ impl<'local> MotionEvent<'local> {
         pointer_index: i32,
         pos: i32,
     ) -> jfloat {
-        env.call_method(
-            &self.0,
+        static GET_HISTORICAL_AXIS_VALUE: CachedMethod = CachedMethod::new(
+            "android/view/MotionEvent",
             "getHistoricalAxisValue",
             "(III)F",
-            &[i32::from(axis).into(), pointer_index.into(), pos.into()],
-        )
-        .unwrap()
-        .f()
-        .unwrap()
+        );
+        GET_HISTORICAL_AXIS_VALUE
+            .call(
+                env,
+                &self.0,
+                &[i32::from(axis).into(), pointer_index.into(), pos.into()],
+            )
+            .f()
+            .unwrap()
     }
 
     pub fn axis(&self, env: &mut JNIEnv<'local>, axis: Axis, pointer_index: jint) -> jfloat {
-        env.call_method(
-            &self.0,
-            "getAxisValue",
-            "(II)F",
-            &[i32::from(axis).into(), pointer_index.into()],
-        )
-        .unwrap()
-        .f()
-        .unwrap()
+        static GET_AXIS_VALUE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "getAxisValue", "(II)F");
+        GET_AXIS_VALUE
+            .call(
+                env,
+                &self.0,
+                &[i32::from(axis).into(), pointer_index.into()],
+            )
+            .f()
+            .unwrap()
+    }
+
+    /// Make a copy of this event, as with `MotionEvent.obtain(MotionEvent)`.
+    pub fn obtain(&self, env: &mut JNIEnv<'local>) -> MotionEvent<'local> {
+        static OBTAIN: CachedStaticMethod = CachedStaticMethod::new(
+            "android/view/MotionEvent",
+            "obtain",
+            "(Landroid/view/MotionEvent;)Landroid/view/MotionEvent;",
+        );
+        MotionEvent(OBTAIN.call(env, &[(&self.0).into()]).l().unwrap())
+    }
+
+    /// Apply `transform` to the coordinates of this event, in place,
+    /// including its history. The framework may still use the events it
+    /// passes to callbacks, so transform a copy made with
+    /// [`MotionEvent::obtain`] instead.
+    pub fn transform(&self, env: &mut JNIEnv<'local>, transform: &AffineTransform) {
+        static TRANSFORM: CachedMethod = CachedMethod::new(
+            "android/view/MotionEvent",
+            "transform",
+            "(Landroid/graphics/Matrix;)V",
+        );
+        let matrix = transform.to_java(env);
+        TRANSFORM
+            .call(env, &self.0, &[(&matrix).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Return this event to the framework's pool. The event must not be
+    /// used afterward. Only recycle events that were obtained with
+    /// [`MotionEvent::obtain`]; the framework recycles the events
+    /// it passes to callbacks.
+    pub fn recycle(self, env: &mut JNIEnv<'local>) {
+        static RECYCLE: CachedMethod =
+            CachedMethod::new("android/view/MotionEvent", "recycle", "()V");
+        RECYCLE.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    /// Make a copy of this event that can be kept after the current
+    /// callback returns, or sent to another thread. Local references,
+    /// including the event passed to a callback, are only valid until
+    /// the callback returns.
+    pub fn to_owned_event(&self, env: &mut JNIEnv<'local>) -> OwnedMotionEvent {
+        let copy = self.obtain(env);
+        OwnedMotionEvent(TrackedGlobalRef::new(env, &copy.0, "OwnedMotionEvent"))
+    }
+
+    /// If this is a scroll event from a rotary encoder, such as the crown
+    /// or rotating bezel of a Wear OS watch, the distance to scroll in
+    /// pixels. Positive values scroll toward the end of the content, as
+    /// with `View.scrollBy`.
+    pub fn rotary_scroll_delta(
+        &self,
+        env: &mut JNIEnv<'local>,
+        vc: &ViewConfiguration,
+    ) -> Option<jfloat> {
+        if self.source(env) != Source::RotaryEncoder
+            || self.action_masked(env) != MotionAction::Scroll
+        {
+            return None;
+        }
+        Some(-self.axis(env, Axis::Scroll, 0) * vc.scaled_vertical_scroll_factor)
     }
 
     pub fn to_pointer_event(
@@ -472,6 +619,9 @@ impl<'local> MotionEvent<'local> {
                 state,
                 button,
             },
+            // A cancelled pointer-up means the pointer was rejected,
+            // e.g. as a palm, so its gesture shouldn't take effect.
+            MotionAction::PointerUp if self.is_canceled(env) => PointerEvent::Cancel(pointer),
             MotionAction::Up | MotionAction::PointerUp => PointerEvent::Up {
                 pointer,
                 state,
@@ -546,6 +696,143 @@ impl<'local> MotionEvent<'local> {
     }
 }
 
+/// A copy of a `MotionEvent` held by a global reference, so it can
+/// be used outside the callback that received the original event.
+///
+/// Dropping this without calling [`OwnedMotionEvent::recycle`] is safe;
+/// the event will be garbage collected rather than returned to the pool.
+pub struct OwnedMotionEvent(TrackedGlobalRef);
+
+impl OwnedMotionEvent {
+    /// Get a local reference to the event, for use with the accessors
+    /// on [`MotionEvent`]. `env` must be attached to the current thread.
+    pub fn as_local<'local>(&self, env: &mut JNIEnv<'local>) -> MotionEvent<'local> {
+        MotionEvent(env.new_local_ref(&self.0).unwrap())
+    }
+
+    /// Return the event to the framework's pool.
+    pub fn recycle(self, env: &mut JNIEnv) {
+        self.as_local(env).recycle(env);
+    }
+}
+
+/// A binding to `android.view.MotionPredictor`, available on Android 14
+/// (API level 34) and later, which predicts where a stylus will be in the
+/// near future so drawing apps can render ink ahead of the actual input.
+///
+/// This holds a global reference, so it can be kept in a peer across
+/// callbacks.
+pub struct MotionPredictor(TrackedGlobalRef);
+
+impl MotionPredictor {
+    pub fn new<'local>(env: &mut JNIEnv<'local>, context: &Context<'local>) -> Self {
+        let predictor = new_java_object(
+            env,
+            "android/view/MotionPredictor",
+            "(Landroid/content/Context;)V",
+            &[(&context.0).into()],
+        );
+        Self(TrackedGlobalRef::new(env, predictor, "MotionPredictor"))
+    }
+
+    /// Whether the device can predict events from the given input device
+    /// and source.
+    pub fn is_prediction_available(
+        &self,
+        env: &mut JNIEnv,
+        device_id: jint,
+        source: Source,
+    ) -> bool {
+        static IS_PREDICTION_AVAILABLE: CachedMethod = CachedMethod::new(
+            "android/view/MotionPredictor",
+            "isPredictionAvailable",
+            "(II)Z",
+        );
+        IS_PREDICTION_AVAILABLE
+            .call(
+                env,
+                self.0.as_obj(),
+                &[device_id.into(), i32::from(source).into()],
+            )
+            .z()
+            .unwrap()
+    }
+
+    /// Feed an event to the predictor. Call this for every motion event
+    /// in the gesture, before [`MotionPredictor::predict`].
+    pub fn record<'local>(&self, env: &mut JNIEnv<'local>, event: &MotionEvent<'local>) {
+        static RECORD: CachedMethod = CachedMethod::new(
+            "android/view/MotionPredictor",
+            "record",
+            "(Landroid/view/MotionEvent;)V",
+        );
+        RECORD
+            .call(env, self.0.as_obj(), &[(&event.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Predict the motion up to the given time, in the `System.nanoTime`
+    /// time base. Returns `None` if no prediction is available.
+    pub fn predict<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        prediction_time_nanos: jlong,
+    ) -> Option<MotionEvent<'local>> {
+        static PREDICT: CachedMethod = CachedMethod::new(
+            "android/view/MotionPredictor",
+            "predict",
+            "(J)Landroid/view/MotionEvent;",
+        );
+        let event = PREDICT
+            .call(env, self.0.as_obj(), &[prediction_time_nanos.into()])
+            .l()
+            .unwrap();
+        (!event.as_raw().is_null()).then_some(MotionEvent(event))
+    }
+
+    /// Predict the motion up to the given time and fill in
+    /// the `predicted` states of `update`, which must be for the pointer
+    /// with the given Android pointer ID. Each predicted state is a copy
+    /// of `update.current` with the predicted time, position, and pressure.
+    pub fn fill_predicted<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        prediction_time_nanos: jlong,
+        pointer_id: jint,
+        update: &mut PointerUpdate,
+    ) {
+        update.predicted.clear();
+        let Some(predicted) = self.predict(env, prediction_time_nanos) else {
+            return;
+        };
+        if let Some(index) = predicted.find_pointer_index(env, pointer_id) {
+            let hsz = predicted.history_size(env);
+            for pos in 0..hsz {
+                update.predicted.push(PointerState {
+                    time: predicted.historical_event_time_nanos(env, pos) as u64,
+                    position: PhysicalPosition::<f64> {
+                        x: predicted.historical_axis(env, Axis::X, index, pos) as f64,
+                        y: predicted.historical_axis(env, Axis::Y, index, pos) as f64,
+                    },
+                    pressure: predicted.historical_axis(env, Axis::Pressure, index, pos) * 0.5,
+                    ..update.current.clone()
+                });
+            }
+            update.predicted.push(PointerState {
+                time: predicted.event_time_nanos(env) as u64,
+                position: PhysicalPosition::<f64> {
+                    x: predicted.axis(env, Axis::X, index) as f64,
+                    y: predicted.axis(env, Axis::Y, index) as f64,
+                },
+                pressure: predicted.axis(env, Axis::Pressure, index) * 0.5,
+                ..update.current.clone()
+            });
+        }
+        predicted.recycle(env);
+    }
+}
+
 /// Convert `MetaState` to `Modifiers`.
 fn meta_state_to_modifiers(s: MetaState) -> Modifiers {
     let mut m = Modifiers::default();
@@ -0,0 +1,77 @@
+use jni::{JNIEnv, objects::JIntArray, sys::jint};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldState {
+    /// The device is fully open; the display surface is flat.
+    Flat,
+    /// The device is partially folded, e.g. in tabletop or book posture.
+    HalfOpened,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldOrientation {
+    Vertical,
+    Horizontal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldOcclusionType {
+    /// The fold doesn't hide any part of the display.
+    None,
+    /// The hinge fully hides the part of the display within its bounds.
+    Full,
+}
+
+/// A fold or hinge crossing the window, as reported by Jetpack WindowManager.
+/// The bounds are in the window's coordinate space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FoldingFeature {
+    pub left: jint,
+    pub top: jint,
+    pub right: jint,
+    pub bottom: jint,
+    pub state: FoldState,
+    pub orientation: FoldOrientation,
+    pub occlusion_type: FoldOcclusionType,
+    /// Whether the feature splits the window into two logical areas,
+    /// such that content should be laid out on either side of it.
+    pub is_separating: bool,
+}
+
+// Must match the encoding in RustFoldingFeatureTracker.java.
+const FIELDS_PER_FEATURE: usize = 8;
+
+impl FoldingFeature {
+    pub(crate) fn from_java_array<'local>(
+        env: &mut JNIEnv<'local>,
+        array: &JIntArray<'local>,
+    ) -> Vec<Self> {
+        let len = env.get_array_length(array).unwrap() as usize;
+        let mut data = vec![0; len];
+        env.get_int_array_region(array, 0, &mut data).unwrap();
+        data.chunks_exact(FIELDS_PER_FEATURE)
+            .map(|f| Self {
+                left: f[0],
+                top: f[1],
+                right: f[2],
+                bottom: f[3],
+                state: if f[4] != 0 {
+                    FoldState::HalfOpened
+                } else {
+                    FoldState::Flat
+                },
+                orientation: if f[5] != 0 {
+                    FoldOrientation::Horizontal
+                } else {
+                    FoldOrientation::Vertical
+                },
+                occlusion_type: if f[6] != 0 {
+                    FoldOcclusionType::Full
+                } else {
+                    FoldOcclusionType::None
+                },
+                is_separating: f[7] != 0,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,91 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jint, jlong},
+};
+
+use crate::{jni_cache::*, util::*};
+
+pub const FRAME_METRICS_UNKNOWN_DELAY_DURATION: jint = 0;
+pub const FRAME_METRICS_INPUT_HANDLING_DURATION: jint = 1;
+pub const FRAME_METRICS_ANIMATION_DURATION: jint = 2;
+pub const FRAME_METRICS_LAYOUT_MEASURE_DURATION: jint = 3;
+pub const FRAME_METRICS_DRAW_DURATION: jint = 4;
+pub const FRAME_METRICS_SYNC_DURATION: jint = 5;
+pub const FRAME_METRICS_COMMAND_ISSUE_DURATION: jint = 6;
+pub const FRAME_METRICS_SWAP_BUFFERS_DURATION: jint = 7;
+pub const FRAME_METRICS_TOTAL_DURATION: jint = 8;
+pub const FRAME_METRICS_FIRST_DRAW_FRAME: jint = 9;
+pub const FRAME_METRICS_INTENDED_VSYNC_TIMESTAMP: jint = 10;
+pub const FRAME_METRICS_VSYNC_TIMESTAMP: jint = 11;
+pub const FRAME_METRICS_GPU_DURATION: jint = 12;
+pub const FRAME_METRICS_DEADLINE: jint = 13;
+
+/// A binding to `android.view.FrameMetrics`. The object is reused by the
+/// framework, so it's only valid during the callback it's passed to.
+#[repr(transparent)]
+pub struct FrameMetrics<'local>(pub JObject<'local>);
+
+impl<'local> FrameMetrics<'local> {
+    /// Get one of the `FRAME_METRICS_*` metrics. Durations and timestamps
+    /// are in nanoseconds; [`FRAME_METRICS_FIRST_DRAW_FRAME`] is 1 or 0.
+    pub fn metric(&self, env: &mut JNIEnv<'local>, id: jint) -> jlong {
+        static GET_METRIC: CachedMethod =
+            CachedMethod::new("android/view/FrameMetrics", "getMetric", "(I)J");
+        GET_METRIC.call(env, &self.0, &[id.into()]).j().unwrap()
+    }
+
+    pub fn total_duration_nanos(&self, env: &mut JNIEnv<'local>) -> jlong {
+        self.metric(env, FRAME_METRICS_TOTAL_DURATION)
+    }
+
+    pub fn is_first_draw_frame(&self, env: &mut JNIEnv<'local>) -> bool {
+        self.metric(env, FRAME_METRICS_FIRST_DRAW_FRAME) != 0
+    }
+}
+
+/// A running count of frames and janky frames, for reporting a jank rate.
+/// A frame is janky if its total duration exceeded its deadline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JankStats {
+    pub frames: u64,
+    pub janky_frames: u64,
+}
+
+impl JankStats {
+    /// Record a frame. `frame_interval_nanos` is used as the deadline
+    /// on API levels before 31, which don't report one.
+    pub fn record<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        metrics: &FrameMetrics<'local>,
+        frame_interval_nanos: jlong,
+    ) {
+        // The first frame includes one-time setup, so it isn't counted.
+        if metrics.is_first_draw_frame(env) {
+            return;
+        }
+        let deadline = if android_sdk_version(env) >= 31 {
+            metrics.metric(env, FRAME_METRICS_DEADLINE)
+        } else {
+            frame_interval_nanos
+        };
+        self.frames += 1;
+        if metrics.total_duration_nanos(env) > deadline {
+            self.janky_frames += 1;
+        }
+    }
+
+    /// The fraction of recorded frames that were janky, from 0 to 1.
+    pub fn jank_rate(&self) -> f64 {
+        if self.frames == 0 {
+            0.0
+        } else {
+            self.janky_frames as f64 / self.frames as f64
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,337 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jint},
+};
+
+use crate::jni_cache::*;
+
+/// An `android.graphics.Rect` object. See [`IntRect`] for the equivalent
+/// owned Rust value.
+#[repr(transparent)]
+pub struct Rect<'local>(pub JObject<'local>);
+
+impl<'local> Rect<'local> {
+    pub fn new(env: &mut JNIEnv<'local>, rect: IntRect) -> Self {
+        Self(new_java_object(
+            env,
+            "android/graphics/Rect",
+            "(IIII)V",
+            &[
+                rect.left.into(),
+                rect.top.into(),
+                rect.right.into(),
+                rect.bottom.into(),
+            ],
+        ))
+    }
+
+    pub fn to_value(&self, env: &mut JNIEnv<'local>) -> IntRect {
+        IntRect {
+            left: self.left(env),
+            top: self.top(env),
+            right: self.right(env),
+            bottom: self.bottom(env),
+        }
+    }
+
+    pub fn left(&self, env: &mut JNIEnv<'local>) -> jint {
+        static LEFT: CachedField = CachedField::new("android/graphics/Rect", "left", "I");
+        LEFT.get(env, &self.0).i().unwrap()
+    }
+
+    pub fn top(&self, env: &mut JNIEnv<'local>) -> jint {
+        static TOP: CachedField = CachedField::new("android/graphics/Rect", "top", "I");
+        TOP.get(env, &self.0).i().unwrap()
+    }
+
+    pub fn right(&self, env: &mut JNIEnv<'local>) -> jint {
+        static RIGHT: CachedField = CachedField::new("android/graphics/Rect", "right", "I");
+        RIGHT.get(env, &self.0).i().unwrap()
+    }
+
+    pub fn bottom(&self, env: &mut JNIEnv<'local>) -> jint {
+        static BOTTOM: CachedField = CachedField::new("android/graphics/Rect", "bottom", "I");
+        BOTTOM.get(env, &self.0).i().unwrap()
+    }
+}
+
+/// A rectangle with integer pixel coordinates, the value of an
+/// `android.graphics.Rect`. The right and bottom edges are exclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IntRect {
+    pub left: jint,
+    pub top: jint,
+    pub right: jint,
+    pub bottom: jint,
+}
+
+impl IntRect {
+    pub fn width(&self) -> jint {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> jint {
+        self.bottom - self.top
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left >= self.right || self.top >= self.bottom
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.left && point.x < self.right && point.y >= self.top && point.y < self.bottom
+    }
+
+    pub fn contains_rect(&self, other: &IntRect) -> bool {
+        other.left >= self.left
+            && other.top >= self.top
+            && other.right <= self.right
+            && other.bottom <= self.bottom
+    }
+
+    pub fn intersects(&self, other: &IntRect) -> bool {
+        self.left < other.right
+            && other.left < self.right
+            && self.top < other.bottom
+            && other.top < self.bottom
+    }
+
+    /// The intersection of the two rectangles, or `None` if they don't
+    /// overlap.
+    pub fn intersect(&self, other: &IntRect) -> Option<IntRect> {
+        self.intersects(other).then(|| IntRect {
+            left: self.left.max(other.left),
+            top: self.top.max(other.top),
+            right: self.right.min(other.right),
+            bottom: self.bottom.min(other.bottom),
+        })
+    }
+
+    /// The smallest rectangle containing both.
+    pub fn union(&self, other: &IntRect) -> IntRect {
+        IntRect {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+}
+
+impl From<IntRect> for RectF {
+    fn from(rect: IntRect) -> Self {
+        Self {
+            left: rect.left as jfloat,
+            top: rect.top as jfloat,
+            right: rect.right as jfloat,
+            bottom: rect.bottom as jfloat,
+        }
+    }
+}
+
+/// A rectangle with floating-point coordinates, the value of an
+/// `android.graphics.RectF`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RectF {
+    pub left: jfloat,
+    pub top: jfloat,
+    pub right: jfloat,
+    pub bottom: jfloat,
+}
+
+impl RectF {
+    pub fn width(&self) -> jfloat {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> jfloat {
+        self.bottom - self.top
+    }
+
+    pub fn contains(&self, point: PointF) -> bool {
+        point.x >= self.left && point.x < self.right && point.y >= self.top && point.y < self.bottom
+    }
+
+    /// The smallest [`IntRect`] that contains this one, as with
+    /// `RectF.roundOut`.
+    pub fn round_out(&self) -> IntRect {
+        IntRect {
+            left: self.left.floor() as jint,
+            top: self.top.floor() as jint,
+            right: self.right.ceil() as jint,
+            bottom: self.bottom.ceil() as jint,
+        }
+    }
+
+    pub fn from_java<'local>(env: &mut JNIEnv<'local>, rect: &JObject<'local>) -> Self {
+        static LEFT: CachedField = CachedField::new("android/graphics/RectF", "left", "F");
+        static TOP: CachedField = CachedField::new("android/graphics/RectF", "top", "F");
+        static RIGHT: CachedField = CachedField::new("android/graphics/RectF", "right", "F");
+        static BOTTOM: CachedField = CachedField::new("android/graphics/RectF", "bottom", "F");
+        Self {
+            left: LEFT.get(env, rect).f().unwrap(),
+            top: TOP.get(env, rect).f().unwrap(),
+            right: RIGHT.get(env, rect).f().unwrap(),
+            bottom: BOTTOM.get(env, rect).f().unwrap(),
+        }
+    }
+
+    pub fn to_java<'local>(&self, env: &mut JNIEnv<'local>) -> JObject<'local> {
+        new_java_object(
+            env,
+            "android/graphics/RectF",
+            "(FFFF)V",
+            &[
+                self.left.into(),
+                self.top.into(),
+                self.right.into(),
+                self.bottom.into(),
+            ],
+        )
+    }
+}
+
+/// A point with integer pixel coordinates, the value of an
+/// `android.graphics.Point`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: jint,
+    pub y: jint,
+}
+
+impl Point {
+    pub fn from_java<'local>(env: &mut JNIEnv<'local>, point: &JObject<'local>) -> Self {
+        static X: CachedField = CachedField::new("android/graphics/Point", "x", "I");
+        static Y: CachedField = CachedField::new("android/graphics/Point", "y", "I");
+        Self {
+            x: X.get(env, point).i().unwrap(),
+            y: Y.get(env, point).i().unwrap(),
+        }
+    }
+
+    pub fn to_java<'local>(&self, env: &mut JNIEnv<'local>) -> JObject<'local> {
+        new_java_object(
+            env,
+            "android/graphics/Point",
+            "(II)V",
+            &[self.x.into(), self.y.into()],
+        )
+    }
+}
+
+/// A point with floating-point coordinates, the value of an
+/// `android.graphics.PointF`, and the form used for touch coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PointF {
+    pub x: jfloat,
+    pub y: jfloat,
+}
+
+impl PointF {
+    pub fn from_java<'local>(env: &mut JNIEnv<'local>, point: &JObject<'local>) -> Self {
+        static X: CachedField = CachedField::new("android/graphics/PointF", "x", "F");
+        static Y: CachedField = CachedField::new("android/graphics/PointF", "y", "F");
+        Self {
+            x: X.get(env, point).f().unwrap(),
+            y: Y.get(env, point).f().unwrap(),
+        }
+    }
+
+    pub fn to_java<'local>(&self, env: &mut JNIEnv<'local>) -> JObject<'local> {
+        new_java_object(
+            env,
+            "android/graphics/PointF",
+            "(FF)V",
+            &[self.x.into(), self.y.into()],
+        )
+    }
+}
+
+impl From<Point> for PointF {
+    fn from(point: Point) -> Self {
+        Self {
+            x: point.x as jfloat,
+            y: point.y as jfloat,
+        }
+    }
+}
+
+impl From<(jfloat, jfloat)> for PointF {
+    fn from((x, y): (jfloat, jfloat)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<PointF> for (jfloat, jfloat) {
+    fn from(point: PointF) -> Self {
+        (point.x, point.y)
+    }
+}
+
+#[cfg(feature = "kurbo")]
+mod kurbo_conversions {
+    use super::*;
+
+    impl From<IntRect> for kurbo::Rect {
+        fn from(rect: IntRect) -> Self {
+            Self::new(
+                rect.left.into(),
+                rect.top.into(),
+                rect.right.into(),
+                rect.bottom.into(),
+            )
+        }
+    }
+
+    impl From<RectF> for kurbo::Rect {
+        fn from(rect: RectF) -> Self {
+            Self::new(
+                rect.left.into(),
+                rect.top.into(),
+                rect.right.into(),
+                rect.bottom.into(),
+            )
+        }
+    }
+
+    impl From<kurbo::Rect> for RectF {
+        fn from(rect: kurbo::Rect) -> Self {
+            Self {
+                left: rect.x0 as jfloat,
+                top: rect.y0 as jfloat,
+                right: rect.x1 as jfloat,
+                bottom: rect.y1 as jfloat,
+            }
+        }
+    }
+
+    impl From<Point> for kurbo::Point {
+        fn from(point: Point) -> Self {
+            Self::new(point.x.into(), point.y.into())
+        }
+    }
+
+    impl From<PointF> for kurbo::Point {
+        fn from(point: PointF) -> Self {
+            Self::new(point.x.into(), point.y.into())
+        }
+    }
+
+    impl From<kurbo::Point> for PointF {
+        fn from(point: kurbo::Point) -> Self {
+            Self {
+                x: point.x as jfloat,
+                y: point.y as jfloat,
+            }
+        }
+    }
+
+    impl IntRect {
+        /// The smallest rectangle with integer coordinates that contains
+        /// `rect`.
+        pub fn from_kurbo_expanded(rect: kurbo::Rect) -> Self {
+            RectF::from(rect.expand()).round_out()
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Reference logic for `InputConnection.deleteSurroundingText`.
+//!
+//! Keyboards count the text to delete in UTF-16 code units or code points,
+//! and not all of them get the count right for emoji. A count that ends
+//! inside a surrogate pair, a ZWJ sequence, a flag or a character with
+//! combining marks would leave a broken fragment behind, so these
+//! functions widen each range to the enclosing grapheme cluster
+//! boundaries.
+
+use jni::sys::jint;
+use std::ops::Range;
+use unicode_segmentation::GraphemeCursor;
+
+/// The text to delete on either side of the selection, as byte ranges.
+/// `before` ends at the selection start and `after` starts at the
+/// selection end; either may be empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SurroundingTextDeletion {
+    pub before: Range<usize>,
+    pub after: Range<usize>,
+}
+
+impl SurroundingTextDeletion {
+    /// Delete both ranges from `text`, returning the new selection.
+    pub fn apply(&self, text: &mut String, selection: Range<usize>) -> Range<usize> {
+        text.replace_range(self.after.clone(), "");
+        text.replace_range(self.before.clone(), "");
+        let removed = self.before.len();
+        (selection.start - removed)..(selection.end - removed)
+    }
+}
+
+fn floor_grapheme_boundary(text: &str, offset: usize) -> usize {
+    let mut cursor = GraphemeCursor::new(offset, text.len(), true);
+    if cursor.is_boundary(text, 0).unwrap() {
+        return offset;
+    }
+    cursor.prev_boundary(text, 0).unwrap().unwrap_or(0)
+}
+
+fn ceil_grapheme_boundary(text: &str, offset: usize) -> usize {
+    let mut cursor = GraphemeCursor::new(offset, text.len(), true);
+    if cursor.is_boundary(text, 0).unwrap() {
+        return offset;
+    }
+    cursor.next_boundary(text, 0).unwrap().unwrap_or(text.len())
+}
+
+fn deletion(
+    text: &str,
+    selection: Range<usize>,
+    before_length: jint,
+    after_length: jint,
+    units: impl Fn(char) -> usize,
+) -> SurroundingTextDeletion {
+    let mut start = selection.start;
+    let mut remaining = before_length.max(0) as usize;
+    for c in text[..selection.start].chars().rev() {
+        if remaining == 0 {
+            break;
+        }
+        remaining = remaining.saturating_sub(units(c));
+        start -= c.len_utf8();
+    }
+    let mut end = selection.end;
+    let mut remaining = after_length.max(0) as usize;
+    for c in text[selection.end..].chars() {
+        if remaining == 0 {
+            break;
+        }
+        remaining = remaining.saturating_sub(units(c));
+        end += c.len_utf8();
+    }
+    let start = if start < selection.start {
+        floor_grapheme_boundary(text, start)
+    } else {
+        start
+    };
+    let end = if end > selection.end {
+        ceil_grapheme_boundary(text, end)
+    } else {
+        end
+    };
+    SurroundingTextDeletion {
+        before: start..selection.start,
+        after: selection.end..end,
+    }
+}
+
+/// Compute the text to delete for `deleteSurroundingText`, where the
+/// lengths are in UTF-16 code units. `selection` is the byte range of the
+/// current selection, which must lie on character boundaries. Lengths
+/// that run past either end of `text` are clamped.
+pub fn surrounding_text_deletion(
+    text: &str,
+    selection: Range<usize>,
+    before_length: jint,
+    after_length: jint,
+) -> SurroundingTextDeletion {
+    deletion(
+        text,
+        selection,
+        before_length,
+        after_length,
+        char::len_utf16,
+    )
+}
+
+/// Like [`surrounding_text_deletion`], but for
+/// `deleteSurroundingTextInCodePoints`, where the lengths are in code
+/// points.
+pub fn surrounding_text_deletion_in_code_points(
+    text: &str,
+    selection: Range<usize>,
+    before_length: jint,
+    after_length: jint,
+) -> SurroundingTextDeletion {
+    deletion(text, selection, before_length, after_length, |_| 1)
+}
@@ -1,22 +1,148 @@
-use jni::{JNIEnv, objects::JObject, sys::jint};
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jint},
+};
+
+use crate::jni_cache::*;
+
+pub const PIXEL_COPY_SUCCESS: jint = 0;
+pub const PIXEL_COPY_ERROR_UNKNOWN: jint = 1;
+pub const PIXEL_COPY_ERROR_TIMEOUT: jint = 2;
+pub const PIXEL_COPY_ERROR_SOURCE_NO_DATA: jint = 3;
+pub const PIXEL_COPY_ERROR_SOURCE_INVALID: jint = 4;
+pub const PIXEL_COPY_ERROR_DESTINATION_INVALID: jint = 5;
 
 #[repr(transparent)]
-pub struct Rect<'local>(pub JObject<'local>);
+pub struct Bitmap<'local>(pub JObject<'local>);
+
+impl<'local> Bitmap<'local> {
+    /// Create a mutable `ARGB_8888` bitmap.
+    pub fn create(env: &mut JNIEnv<'local>, width: jint, height: jint) -> Self {
+        static CREATE_BITMAP: CachedStaticMethod = CachedStaticMethod::new(
+            "android/graphics/Bitmap",
+            "createBitmap",
+            "(IILandroid/graphics/Bitmap$Config;)Landroid/graphics/Bitmap;",
+        );
+        let config = env
+            .get_static_field(
+                "android/graphics/Bitmap$Config",
+                "ARGB_8888",
+                "Landroid/graphics/Bitmap$Config;",
+            )
+            .unwrap()
+            .l()
+            .unwrap();
+        Self(
+            CREATE_BITMAP
+                .call(env, &[width.into(), height.into(), (&config).into()])
+                .l()
+                .unwrap(),
+        )
+    }
+
+    pub fn width(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_WIDTH: CachedMethod =
+            CachedMethod::new("android/graphics/Bitmap", "getWidth", "()I");
+        GET_WIDTH.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    pub fn height(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_HEIGHT: CachedMethod =
+            CachedMethod::new("android/graphics/Bitmap", "getHeight", "()I");
+        GET_HEIGHT.call(env, &self.0, &[]).i().unwrap()
+    }
 
-impl<'local> Rect<'local> {
-    pub fn left(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.get_field(&self.0, "left", "I").unwrap().i().unwrap()
+    /// Copy the pixels of an `ARGB_8888` bitmap, which are stored as
+    /// premultiplied RGBA bytes, row by row with no padding.
+    pub fn to_rgba(&self, env: &mut JNIEnv<'local>) -> Vec<u8> {
+        static GET_BYTE_COUNT: CachedMethod =
+            CachedMethod::new("android/graphics/Bitmap", "getByteCount", "()I");
+        static COPY_PIXELS_TO_BUFFER: CachedMethod = CachedMethod::new(
+            "android/graphics/Bitmap",
+            "copyPixelsToBuffer",
+            "(Ljava/nio/Buffer;)V",
+        );
+        let len = GET_BYTE_COUNT.call(env, &self.0, &[]).i().unwrap() as usize;
+        let mut data = vec![0u8; len];
+        let buffer = unsafe { env.new_direct_byte_buffer(data.as_mut_ptr(), len) }.unwrap();
+        COPY_PIXELS_TO_BUFFER
+            .call(env, &self.0, &[(&buffer).into()])
+            .v()
+            .unwrap();
+        env.delete_local_ref(buffer).unwrap();
+        data
     }
 
-    pub fn top(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.get_field(&self.0, "top", "I").unwrap().i().unwrap()
+    /// Replace the pixels of an `ARGB_8888` bitmap with premultiplied RGBA
+    /// bytes, in the layout returned by [`Bitmap::to_rgba`].
+    pub fn copy_from_rgba(&self, env: &mut JNIEnv<'local>, data: &[u8]) {
+        static COPY_PIXELS_FROM_BUFFER: CachedMethod = CachedMethod::new(
+            "android/graphics/Bitmap",
+            "copyPixelsFromBuffer",
+            "(Ljava/nio/Buffer;)V",
+        );
+        // The buffer is only read from.
+        let buffer =
+            unsafe { env.new_direct_byte_buffer(data.as_ptr() as *mut u8, data.len()) }.unwrap();
+        COPY_PIXELS_FROM_BUFFER
+            .call(env, &self.0, &[(&buffer).into()])
+            .v()
+            .unwrap();
+        env.delete_local_ref(buffer).unwrap();
+    }
+
+    /// Free the bitmap's pixel memory without waiting for garbage collection.
+    pub fn recycle(&self, env: &mut JNIEnv<'local>) {
+        static RECYCLE: CachedMethod =
+            CachedMethod::new("android/graphics/Bitmap", "recycle", "()V");
+        RECYCLE.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+/// A binding to `android.graphics.Canvas`, as returned by
+/// [`crate::SurfaceHolder::lock_canvas`] for software rendering.
+#[repr(transparent)]
+pub struct Canvas<'local>(pub JObject<'local>);
+
+impl<'local> Canvas<'local> {
+    pub fn width(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_WIDTH: CachedMethod =
+            CachedMethod::new("android/graphics/Canvas", "getWidth", "()I");
+        GET_WIDTH.call(env, &self.0, &[]).i().unwrap()
     }
 
-    pub fn right(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.get_field(&self.0, "right", "I").unwrap().i().unwrap()
+    pub fn height(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_HEIGHT: CachedMethod =
+            CachedMethod::new("android/graphics/Canvas", "getHeight", "()I");
+        GET_HEIGHT.call(env, &self.0, &[]).i().unwrap()
     }
 
-    pub fn bottom(&self, env: &mut JNIEnv<'local>) -> jint {
-        env.get_field(&self.0, "bottom", "I").unwrap().i().unwrap()
+    /// Draw `bitmap` with its top left corner at the given position.
+    pub fn draw_bitmap(
+        &self,
+        env: &mut JNIEnv<'local>,
+        bitmap: &Bitmap<'local>,
+        left: jfloat,
+        top: jfloat,
+    ) {
+        static DRAW_BITMAP: CachedMethod = CachedMethod::new(
+            "android/graphics/Canvas",
+            "drawBitmap",
+            "(Landroid/graphics/Bitmap;FFLandroid/graphics/Paint;)V",
+        );
+        DRAW_BITMAP
+            .call(
+                env,
+                &self.0,
+                &[
+                    (&bitmap.0).into(),
+                    left.into(),
+                    top.into(),
+                    (&JObject::null()).into(),
+                ],
+            )
+            .v()
+            .unwrap()
     }
 }
@@ -1,64 +1,136 @@
+use bitflags::bitflags;
 use jni::{
     JNIEnv,
-    objects::{JObject, JString},
+    objects::{GlobalRef, JIntArray, JObject, JString},
     sys::{JNI_TRUE, jboolean, jint, jlong},
 };
-use std::borrow::Cow;
-
-use crate::{binder::*, callback_ctx::*, events::KeyEvent, util::*, view::*};
-
-pub const INPUT_TYPE_MASK_CLASS: u32 = 0x0000000f;
-pub const INPUT_TYPE_MASK_VARIATION: u32 = 0x00000ff0;
-pub const INPUT_TYPE_MASK_FLAGS: u32 = 0x00fff000;
-pub const INPUT_TYPE_NULL: u32 = 0x00000000;
-pub const INPUT_TYPE_CLASS_TEXT: u32 = 0x00000001;
-pub const INPUT_TYPE_TEXT_FLAG_CAP_CHARACTERS: u32 = 0x00001000;
-pub const INPUT_TYPE_TEXT_FLAG_CAP_WORDS: u32 = 0x00002000;
-pub const INPUT_TYPE_TEXT_FLAG_CAP_SENTENCES: u32 = 0x00004000;
-pub const INPUT_TYPE_TEXT_FLAG_AUTO_CORRECT: u32 = 0x00008000;
-pub const INPUT_TYPE_TEXT_FLAG_AUTO_COMPLETE: u32 = 0x00010000;
-pub const INPUT_TYPE_TEXT_FLAG_MULTI_LINE: u32 = 0x00020000;
-pub const INPUT_TYPE_TEXT_FLAG_IME_MULTI_LINE: u32 = 0x00040000;
-pub const INPUT_TYPE_TEXT_FLAG_NO_SUGGESTIONS: u32 = 0x00080000;
-pub const INPUT_TYPE_TEXT_FLAG_ENABLE_TEXT_CONVERSION_SUGGESTIONS: u32 = 0x00100000;
-pub const INPUT_TYPE_TEXT_VARIATION_NORMAL: u32 = 0x00000000;
-pub const INPUT_TYPE_TEXT_VARIATION_URI: u32 = 0x00000010;
-pub const INPUT_TYPE_TEXT_VARIATION_EMAIL_ADDRESS: u32 = 0x00000020;
-pub const INPUT_TYPE_TEXT_VARIATION_EMAIL_SUBJECT: u32 = 0x00000030;
-pub const INPUT_TYPE_TEXT_VARIATION_SHORT_MESSAGE: u32 = 0x00000040;
-pub const INPUT_TYPE_TEXT_VARIATION_LONG_MESSAGE: u32 = 0x00000050;
-pub const INPUT_TYPE_TEXT_VARIATION_PERSON_NAME: u32 = 0x00000060;
-pub const INPUT_TYPE_TEXT_VARIATION_POSTAL_ADDRESS: u32 = 0x00000070;
-pub const INPUT_TYPE_TEXT_VARIATION_PASSWORD: u32 = 0x00000080;
-pub const INPUT_TYPE_TEXT_VARIATION_VISIBLE_PASSWORD: u32 = 0x00000090;
-pub const INPUT_TYPE_TEXT_VARIATION_WEB_EDIT_TEXT: u32 = 0x000000a0;
-pub const INPUT_TYPE_TEXT_VARIATION_FILTER: u32 = 0x000000b0;
-pub const INPUT_TYPE_TEXT_VARIATION_PHONETIC: u32 = 0x000000c0;
-pub const INPUT_TYPE_TEXT_VARIATION_WEB_EMAIL_ADDRESS: u32 = 0x000000d0;
-pub const INPUT_TYPE_TEXT_VARIATION_WEB_PASSWORD: u32 = 0x000000e0;
-pub const INPUT_TYPE_CLASS_NUMBER: u32 = 0x00000002;
-pub const INPUT_TYPE_NUMBER_FLAG_SIGNED: u32 = 0x00001000;
-pub const INPUT_TYPE_NUMBER_FLAG_DECIMAL: u32 = 0x00002000;
-pub const INPUT_TYPE_NUMBER_VARIATION_NORMAL: u32 = 0x00000000;
-pub const INPUT_TYPE_NUMBER_VARIATION_PASSWORD: u32 = 0x00000010;
-pub const INPUT_TYPE_CLASS_PHONE: u32 = 0x00000003;
-pub const INPUT_TYPE_CLASS_DATETIME: u32 = 0x00000004;
-pub const INPUT_TYPE_DATETIME_VARIATION_NORMAL: u32 = 0x00000000;
-pub const INPUT_TYPE_DATETIME_VARIATION_DATE: u32 = 0x00000010;
-pub const INPUT_TYPE_DATETIME_VARIATION_TIME: u32 = 0x00000020;
-
-pub const IME_FLAG_NO_PERSONALIZED_LEARNING: u32 = 0x1000000;
-pub const IME_FLAG_NO_FULLSCREEN: u32 = 0x2000000;
-pub const IME_FLAG_NAVIGATE_PREVIOUS: u32 = 0x4000000;
-pub const IME_FLAG_NAVIGATE_NEXT: u32 = 0x8000000;
-pub const IME_FLAG_NO_EXTRACT_UI: u32 = 0x10000000;
-pub const IME_FLAG_NO_ACCESSORY_ACTION: u32 = 0x20000000;
-pub const IME_FLAG_NO_ENTER_ACTION: u32 = 0x40000000;
-pub const IME_FLAG_FORCE_ASCII: u32 = 0x80000000;
-
-pub const CAP_MODE_CHARACTERS: u32 = INPUT_TYPE_TEXT_FLAG_CAP_CHARACTERS;
-pub const CAP_MODE_WORDS: u32 = INPUT_TYPE_TEXT_FLAG_CAP_WORDS;
-pub const CAP_MODE_SENTENCES: u32 = INPUT_TYPE_TEXT_FLAG_CAP_SENTENCES;
+use std::{borrow::Cow, cell::RefCell};
+
+#[cfg(feature = "trace")]
+use crate::trace::*;
+use crate::{
+    binder::*, callback_ctx::*, events::KeyEvent, ime_quirks::*, jni_cache::*, native_methods::*,
+    text_cache::*, util::*, view::*,
+};
+
+bitflags! {
+    /// `EditorInfo.inputType`. This combines a class, a variation within
+    /// that class, and flags; variations of different classes share
+    /// values, so only combine a variation with its own class.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct InputType: u32 {
+        const MASK_CLASS = 0x0000000f;
+        const MASK_VARIATION = 0x00000ff0;
+        const MASK_FLAGS = 0x00fff000;
+        const CLASS_TEXT = 0x00000001;
+        const TEXT_FLAG_CAP_CHARACTERS = 0x00001000;
+        const TEXT_FLAG_CAP_WORDS = 0x00002000;
+        const TEXT_FLAG_CAP_SENTENCES = 0x00004000;
+        const TEXT_FLAG_AUTO_CORRECT = 0x00008000;
+        const TEXT_FLAG_AUTO_COMPLETE = 0x00010000;
+        const TEXT_FLAG_MULTI_LINE = 0x00020000;
+        const TEXT_FLAG_IME_MULTI_LINE = 0x00040000;
+        const TEXT_FLAG_NO_SUGGESTIONS = 0x00080000;
+        const TEXT_FLAG_ENABLE_TEXT_CONVERSION_SUGGESTIONS = 0x00100000;
+        const TEXT_VARIATION_URI = 0x00000010;
+        const TEXT_VARIATION_EMAIL_ADDRESS = 0x00000020;
+        const TEXT_VARIATION_EMAIL_SUBJECT = 0x00000030;
+        const TEXT_VARIATION_SHORT_MESSAGE = 0x00000040;
+        const TEXT_VARIATION_LONG_MESSAGE = 0x00000050;
+        const TEXT_VARIATION_PERSON_NAME = 0x00000060;
+        const TEXT_VARIATION_POSTAL_ADDRESS = 0x00000070;
+        const TEXT_VARIATION_PASSWORD = 0x00000080;
+        const TEXT_VARIATION_VISIBLE_PASSWORD = 0x00000090;
+        const TEXT_VARIATION_WEB_EDIT_TEXT = 0x000000a0;
+        const TEXT_VARIATION_FILTER = 0x000000b0;
+        const TEXT_VARIATION_PHONETIC = 0x000000c0;
+        const TEXT_VARIATION_WEB_EMAIL_ADDRESS = 0x000000d0;
+        const TEXT_VARIATION_WEB_PASSWORD = 0x000000e0;
+        const CLASS_NUMBER = 0x00000002;
+        const NUMBER_FLAG_SIGNED = 0x00001000;
+        const NUMBER_FLAG_DECIMAL = 0x00002000;
+        const NUMBER_VARIATION_PASSWORD = 0x00000010;
+        const CLASS_PHONE = 0x00000003;
+        const CLASS_DATETIME = 0x00000004;
+        const DATETIME_VARIATION_DATE = 0x00000010;
+        const DATETIME_VARIATION_TIME = 0x00000020;
+    }
+}
+
+impl InputType {
+    /// The class, one of the `CLASS_*` values, or empty for
+    /// `TYPE_NULL`, which disables rich text input.
+    pub fn class(self) -> Self {
+        self & Self::MASK_CLASS
+    }
+
+    /// The variation within the class; empty is the normal variation.
+    pub fn variation(self) -> Self {
+        self & Self::MASK_VARIATION
+    }
+}
+
+bitflags! {
+    /// `EditorInfo.imeOptions`: an action in the low byte, which is one of
+    /// the `ACTION_*` values rather than a combination of them, plus flags.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct ImeOptions: u32 {
+        const MASK_ACTION = 0x000000ff;
+        const ACTION_NONE = 0x00000001;
+        const ACTION_GO = 0x00000002;
+        const ACTION_SEARCH = 0x00000003;
+        const ACTION_SEND = 0x00000004;
+        const ACTION_NEXT = 0x00000005;
+        const ACTION_DONE = 0x00000006;
+        const ACTION_PREVIOUS = 0x00000007;
+        const FLAG_NO_PERSONALIZED_LEARNING = 0x1000000;
+        const FLAG_NO_FULLSCREEN = 0x2000000;
+        const FLAG_NAVIGATE_PREVIOUS = 0x4000000;
+        const FLAG_NAVIGATE_NEXT = 0x8000000;
+        const FLAG_NO_EXTRACT_UI = 0x10000000;
+        const FLAG_NO_ACCESSORY_ACTION = 0x20000000;
+        const FLAG_NO_ENTER_ACTION = 0x40000000;
+        const FLAG_FORCE_ASCII = 0x80000000;
+    }
+}
+
+impl ImeOptions {
+    /// The action, one of the `ACTION_*` values, or empty if unspecified.
+    pub fn action(self) -> Self {
+        self & Self::MASK_ACTION
+    }
+}
+
+bitflags! {
+    /// The capitalization modes of `TextUtils.getCapsMode`, which share
+    /// their values with the corresponding [`InputType`] flags.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct CapsMode: u32 {
+        const CHARACTERS = InputType::TEXT_FLAG_CAP_CHARACTERS.bits();
+        const WORDS = InputType::TEXT_FLAG_CAP_WORDS.bits();
+        const SENTENCES = InputType::TEXT_FLAG_CAP_SENTENCES.bits();
+    }
+}
+
+macro_rules! jint_conversions {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for jint {
+                fn from(value: $ty) -> Self {
+                    value.bits() as jint
+                }
+            }
+
+            impl From<jint> for $ty {
+                fn from(value: jint) -> Self {
+                    Self::from_bits_retain(value as u32)
+                }
+            }
+        )*
+    };
+}
+
+jint_conversions!(InputType, ImeOptions, CapsMode);
 
 #[repr(transparent)]
 pub struct InputMethodManager<'local>(pub JObject<'local>);
@@ -70,15 +142,15 @@ impl<'local> InputMethodManager<'local> {
         view: &View<'local>,
         flags: jint,
     ) -> bool {
-        env.call_method(
-            &self.0,
+        static SHOW_SOFT_INPUT: CachedMethod = CachedMethod::new(
+            "android/view/inputmethod/InputMethodManager",
             "showSoftInput",
             "(Landroid/view/View;I)Z",
-            &[(&view.0).into(), flags.into()],
-        )
-        .unwrap()
-        .z()
-        .unwrap()
+        );
+        SHOW_SOFT_INPUT
+            .call(env, &self.0, &[(&view.0).into(), flags.into()])
+            .z()
+            .unwrap()
     }
 
     pub fn hide_soft_input_from_window(
@@ -87,27 +159,27 @@ impl<'local> InputMethodManager<'local> {
         window_token: &IBinder<'local>,
         flags: jint,
     ) -> bool {
-        env.call_method(
-            &self.0,
+        static HIDE_SOFT_INPUT_FROM_WINDOW: CachedMethod = CachedMethod::new(
+            "android/view/inputmethod/InputMethodManager",
             "hideSoftInputFromWindow",
             "(Landroid/os/IBinder;I)Z",
-            &[(&window_token.0).into(), flags.into()],
-        )
-        .unwrap()
-        .z()
-        .unwrap()
+        );
+        HIDE_SOFT_INPUT_FROM_WINDOW
+            .call(env, &self.0, &[(&window_token.0).into(), flags.into()])
+            .z()
+            .unwrap()
     }
 
     pub fn restart_input(&self, env: &mut JNIEnv<'local>, view: &View<'local>) {
-        env.call_method(
-            &self.0,
+        static RESTART_INPUT: CachedMethod = CachedMethod::new(
+            "android/view/inputmethod/InputMethodManager",
             "restartInput",
             "(Landroid/view/View;)V",
-            &[(&view.0).into()],
-        )
-        .unwrap()
-        .v()
-        .unwrap();
+        );
+        RESTART_INPUT
+            .call(env, &self.0, &[(&view.0).into()])
+            .v()
+            .unwrap();
     }
 
     pub fn update_selection(
@@ -119,21 +191,100 @@ impl<'local> InputMethodManager<'local> {
         candidates_start: jint,
         candidates_end: jint,
     ) {
-        env.call_method(
-            &self.0,
+        static UPDATE_SELECTION: CachedMethod = CachedMethod::new(
+            "android/view/inputmethod/InputMethodManager",
             "updateSelection",
             "(Landroid/view/View;IIII)V",
-            &[
-                (&view.0).into(),
-                sel_start.into(),
-                sel_end.into(),
-                candidates_start.into(),
-                candidates_end.into(),
-            ],
-        )
-        .unwrap()
-        .v()
-        .unwrap();
+        );
+        UPDATE_SELECTION
+            .call(
+                env,
+                &self.0,
+                &[
+                    (&view.0).into(),
+                    sel_start.into(),
+                    sel_end.into(),
+                    candidates_start.into(),
+                    candidates_end.into(),
+                ],
+            )
+            .v()
+            .unwrap();
+    }
+
+    /// The locale of the current IME subtype, such as the keyboard layout
+    /// the user picked, as a BCP 47 language tag. Editors can use it to
+    /// pick a spell-checking language or caps-mode rules when the field
+    /// has no hint locales of its own. `None` if the IME doesn't report
+    /// a subtype or the subtype has no locale.
+    pub fn current_input_method_locale(&self, env: &mut JNIEnv<'local>) -> Option<String> {
+        static GET_CURRENT_INPUT_METHOD_SUBTYPE: CachedMethod = CachedMethod::new(
+            "android/view/inputmethod/InputMethodManager",
+            "getCurrentInputMethodSubtype",
+            "()Landroid/view/inputmethod/InputMethodSubtype;",
+        );
+        static GET_LANGUAGE_TAG: CachedMethod = CachedMethod::new(
+            "android/view/inputmethod/InputMethodSubtype",
+            "getLanguageTag",
+            "()Ljava/lang/String;",
+        );
+        static GET_LOCALE: CachedMethod = CachedMethod::new(
+            "android/view/inputmethod/InputMethodSubtype",
+            "getLocale",
+            "()Ljava/lang/String;",
+        );
+        let subtype = GET_CURRENT_INPUT_METHOD_SUBTYPE
+            .call(env, &self.0, &[])
+            .l()
+            .unwrap();
+        if subtype.as_raw().is_null() {
+            return None;
+        }
+        let get_string = |env: &mut JNIEnv<'local>, method: &CachedMethod| {
+            let value = method.call(env, &subtype, &[]).l().unwrap();
+            if value.as_raw().is_null() {
+                return None;
+            }
+            let value = String::from(env.get_string(&JString::from(value)).unwrap());
+            (!value.is_empty()).then_some(value)
+        };
+        // Subtypes from before API level 24 only have the older
+        // `Locale.toString` form, such as `en_US`.
+        if android_sdk_version(env) >= 24
+            && let Some(tag) = get_string(env, &GET_LANGUAGE_TAG)
+        {
+            return Some(tag);
+        }
+        get_string(env, &GET_LOCALE).map(|locale| locale.replace('_', "-"))
+    }
+}
+
+/// The attributes of an editor, for [`EditorInfo::populate`].
+#[derive(Clone, Debug)]
+pub struct EditorAttributes<'a> {
+    pub input_type: InputType,
+    pub ime_options: ImeOptions,
+    /// The selection in UTF-16 code units, or `None` if it isn't known.
+    pub initial_selection: Option<(jint, jint)>,
+    pub initial_caps_mode: CapsMode,
+    /// Text shown in the keyboard's own editor, such as in fullscreen
+    /// mode, while the field is empty.
+    pub hint_text: Option<&'a str>,
+    /// As for [`EditorInfo::set_hint_locales`]. Empty leaves the hint
+    /// unset.
+    pub hint_locales: &'a [&'a str],
+}
+
+impl Default for EditorAttributes<'_> {
+    fn default() -> Self {
+        Self {
+            input_type: InputType::empty(),
+            ime_options: ImeOptions::empty(),
+            initial_selection: None,
+            initial_caps_mode: CapsMode::empty(),
+            hint_text: None,
+            hint_locales: &[],
+        }
     }
 }
 
@@ -141,32 +292,122 @@ impl<'local> InputMethodManager<'local> {
 pub struct EditorInfo<'local>(pub JObject<'local>);
 
 impl<'local> EditorInfo<'local> {
-    pub fn set_input_type(&self, env: &mut JNIEnv<'local>, value: u32) {
-        env.set_field(&self.0, "inputType", "I", (value as jint).into())
-            .unwrap();
+    /// Set all of the editor's attributes in a single call into Java,
+    /// rather than one JNI field write per attribute as the setters do.
+    /// This is the preferred way to fill in `EditorInfo` from
+    /// [`InputConnection::on_create_input_connection`].
+    pub fn populate(&self, env: &mut JNIEnv<'local>, attrs: &EditorAttributes) {
+        static POPULATE_EDITOR_INFO: CachedStaticMethod = CachedStaticMethod::new(
+            "org/linebender/android/rustview/RustInputConnection",
+            "populateEditorInfo",
+            "(Landroid/view/inputmethod/EditorInfo;IIIIILjava/lang/CharSequence;Ljava/lang/String;)V",
+        );
+        let (sel_start, sel_end) = attrs.initial_selection.unwrap_or((-1, -1));
+        let hint_text = match attrs.hint_text {
+            Some(text) => new_java_string(env, text).into(),
+            None => JObject::null(),
+        };
+        let hint_locales = if attrs.hint_locales.is_empty() {
+            JObject::null()
+        } else {
+            new_java_string(env, &attrs.hint_locales.join(",")).into()
+        };
+        POPULATE_EDITOR_INFO
+            .call(
+                env,
+                &[
+                    (&self.0).into(),
+                    jint::from(attrs.input_type).into(),
+                    jint::from(attrs.ime_options).into(),
+                    sel_start.into(),
+                    sel_end.into(),
+                    jint::from(attrs.initial_caps_mode).into(),
+                    (&hint_text).into(),
+                    (&hint_locales).into(),
+                ],
+            )
+            .v()
+            .unwrap()
     }
 
-    pub fn set_ime_options(&self, env: &mut JNIEnv<'local>, value: u32) {
-        env.set_field(&self.0, "imeOptions", "I", (value as jint).into())
-            .unwrap();
+    pub fn set_input_type(&self, env: &mut JNIEnv<'local>, value: InputType) {
+        static INPUT_TYPE: CachedField =
+            CachedField::new("android/view/inputmethod/EditorInfo", "inputType", "I");
+        INPUT_TYPE.set(env, &self.0, jint::from(value).into());
+    }
+
+    pub fn set_ime_options(&self, env: &mut JNIEnv<'local>, value: ImeOptions) {
+        static IME_OPTIONS: CachedField =
+            CachedField::new("android/view/inputmethod/EditorInfo", "imeOptions", "I");
+        IME_OPTIONS.set(env, &self.0, jint::from(value).into());
     }
 
     pub fn set_initial_sel_start(&self, env: &mut JNIEnv<'local>, value: jint) {
-        env.set_field(&self.0, "initialSelStart", "I", value.into())
-            .unwrap();
+        static INITIAL_SEL_START: CachedField = CachedField::new(
+            "android/view/inputmethod/EditorInfo",
+            "initialSelStart",
+            "I",
+        );
+        INITIAL_SEL_START.set(env, &self.0, value.into());
     }
 
     pub fn set_initial_sel_end(&self, env: &mut JNIEnv<'local>, value: jint) {
-        env.set_field(&self.0, "initialSelEnd", "I", value.into())
-            .unwrap();
+        static INITIAL_SEL_END: CachedField =
+            CachedField::new("android/view/inputmethod/EditorInfo", "initialSelEnd", "I");
+        INITIAL_SEL_END.set(env, &self.0, value.into());
     }
 
-    pub fn set_initial_caps_mode(&self, env: &mut JNIEnv<'local>, value: u32) {
-        env.set_field(&self.0, "initialCapsMode", "I", (value as jint).into())
-            .unwrap();
+    pub fn set_initial_caps_mode(&self, env: &mut JNIEnv<'local>, value: CapsMode) {
+        static INITIAL_CAPS_MODE: CachedField = CachedField::new(
+            "android/view/inputmethod/EditorInfo",
+            "initialCapsMode",
+            "I",
+        );
+        INITIAL_CAPS_MODE.set(env, &self.0, jint::from(value).into());
+    }
+
+    /// Tell the IME which languages the user is expected to type in this
+    /// field, as BCP 47 language tags in order of preference, so it can
+    /// switch keyboard layout and dictionary. An empty slice clears the
+    /// hint. Requires API level 24; does nothing before that.
+    pub fn set_hint_locales(&self, env: &mut JNIEnv<'local>, locales: &[&str]) {
+        static HINT_LOCALES: CachedField = CachedField::new(
+            "android/view/inputmethod/EditorInfo",
+            "hintLocales",
+            "Landroid/os/LocaleList;",
+        );
+        static FOR_LANGUAGE_TAGS: CachedStaticMethod = CachedStaticMethod::new(
+            "android/os/LocaleList",
+            "forLanguageTags",
+            "(Ljava/lang/String;)Landroid/os/LocaleList;",
+        );
+        if android_sdk_version(env) < 24 {
+            return;
+        }
+        let locale_list = if locales.is_empty() {
+            JObject::null()
+        } else {
+            let tags = new_java_string(env, &locales.join(","));
+            FOR_LANGUAGE_TAGS.call(env, &[(&tags).into()]).l().unwrap()
+        };
+        HINT_LOCALES.set(env, &self.0, (&locale_list).into());
     }
 }
 
+/// Text that a keyboard can show in its own editor, such as in fullscreen
+/// mode in landscape, returned by [`InputConnection::extracted_text`].
+/// Offsets are in UTF-16 code units.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtractedText {
+    /// The whole text, or a part of it around the selection.
+    pub text: String,
+    /// The offset of `text` within the whole text.
+    pub start_offset: jint,
+    /// The selection, relative to the start of `text`.
+    pub selection_start: jint,
+    pub selection_end: jint,
+}
+
 #[allow(unused_variables)]
 pub trait InputConnection {
     fn on_create_input_connection<'local>(
@@ -192,10 +433,15 @@ pub trait InputConnection {
     fn selected_text<'slf>(&'slf mut self, ctx: &mut CallbackCtx) -> Option<Cow<'slf, str>>;
     // TODO: styled version
 
-    fn cursor_caps_mode(&mut self, ctx: &mut CallbackCtx, req_modes: u32) -> u32;
+    fn cursor_caps_mode(&mut self, ctx: &mut CallbackCtx, req_modes: CapsMode) -> CapsMode;
 
-    // TODO: Do we need to bind getExtractedText? Gio's InputConnection
-    // just returns null.
+    /// The text for `getExtractedText`. Most keyboards are fine with
+    /// `None`, as Gio's `InputConnection` returns, though see
+    /// [`ImeQuirks::NEEDS_EXTRACTED_TEXT`]. Keyboards that ask to monitor
+    /// the text aren't sent updates, so they ask again when they need it.
+    fn extracted_text(&mut self, ctx: &mut CallbackCtx) -> Option<ExtractedText> {
+        None
+    }
 
     fn delete_surrounding_text(
         &mut self,
@@ -273,6 +519,31 @@ pub trait InputConnection {
     // just returns false.
 }
 
+thread_local! {
+    static TEXT_CACHE: RefCell<TextCache<GlobalRef>> = const { RefCell::new(TextCache::new()) };
+}
+
+fn cached_string<'local>(
+    env: &mut JNIEnv<'local>,
+    peer: jlong,
+    query: TextQuery,
+    text: &str,
+) -> JString<'local> {
+    TEXT_CACHE.with_borrow_mut(|cache| match cache.lookup(peer, query, text) {
+        TextLookup::Hit(string) => env.new_local_ref(string).unwrap().into(),
+        TextLookup::Repeat(slot) => {
+            let string = new_java_string(env, text);
+            *slot = Some(env.new_global_ref(&string).unwrap());
+            string
+        }
+        TextLookup::Miss => new_java_string(env, text),
+    })
+}
+
+pub(crate) fn clear_text_cache(peer: jlong) {
+    TEXT_CACHE.with_borrow_mut(|cache| cache.forget_peer(peer));
+}
+
 fn with_input_connection<'local, F, T: Default>(
     env: JNIEnv<'local>,
     view: View<'local>,
@@ -290,268 +561,398 @@ where
     })
 }
 
-pub(crate) extern "system" fn on_create_input_connection<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    out_attrs: EditorInfo<'local>,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.on_create_input_connection(ctx, &out_attrs);
-        true
-    }))
-}
+native_methods! {
+    fn input_connection_methods;
+
+    "onCreateInputConnectionNative" => fn on_create_input_connection<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        out_attrs: EditorInfo<'local>,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            detect_ime_quirks(&mut ctx.env, &ctx.view, peer);
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!("on_create_input_connection"),
+            );
+            ic.on_create_input_connection(ctx, &out_attrs);
+            true
+        }))
+    }
 
-pub(crate) extern "system" fn get_text_before_cursor<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    n: jint,
-) -> JString<'local> {
-    with_input_connection(env, view, peer, |ctx, ic| {
-        if let Some(result) = ic.text_before_cursor(ctx, n) {
-            ctx.env.new_string(result).unwrap()
-        } else {
-            JObject::null().into()
-        }
-    })
-}
+    "getTextBeforeCursorNative" => fn get_text_before_cursor<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        n: jint,
+    ) -> JString<'local> {
+        with_input_connection(env, view, peer, |ctx, ic| {
+            if let Some(result) = ic.text_before_cursor(ctx, n) {
+                cached_string(&mut ctx.env, peer, TextQuery::BeforeCursor, &result)
+            } else {
+                JObject::null().into()
+            }
+        })
+    }
 
-pub(crate) extern "system" fn get_text_after_cursor<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    n: jint,
-) -> JString<'local> {
-    with_input_connection(env, view, peer, |ctx, ic| {
-        if let Some(result) = ic.text_after_cursor(ctx, n) {
-            ctx.env.new_string(result).unwrap()
-        } else {
-            JObject::null().into()
-        }
-    })
-}
+    "getTextAfterCursorNative" => fn get_text_after_cursor<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        n: jint,
+    ) -> JString<'local> {
+        with_input_connection(env, view, peer, |ctx, ic| {
+            if let Some(result) = ic.text_after_cursor(ctx, n) {
+                cached_string(&mut ctx.env, peer, TextQuery::AfterCursor, &result)
+            } else {
+                JObject::null().into()
+            }
+        })
+    }
 
-pub(crate) extern "system" fn get_selected_text<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) -> JString<'local> {
-    with_input_connection(env, view, peer, |ctx, ic| {
-        if let Some(result) = ic.selected_text(ctx) {
-            ctx.env.new_string(result).unwrap()
-        } else {
-            JObject::null().into()
-        }
-    })
-}
+    "getSelectedTextNative" => fn get_selected_text<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) -> JString<'local> {
+        with_input_connection(env, view, peer, |ctx, ic| {
+            if let Some(result) = ic.selected_text(ctx) {
+                cached_string(&mut ctx.env, peer, TextQuery::Selected, &result)
+            } else {
+                JObject::null().into()
+            }
+        })
+    }
 
-pub(crate) extern "system" fn get_cursor_caps_mode<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    req_modes: jint,
-) -> jint {
-    with_input_connection(env, view, peer, |ctx, ic| {
-        ic.cursor_caps_mode(ctx, req_modes as u32) as jint
-    })
-}
+    "getCursorCapsModeNative" => fn get_cursor_caps_mode<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        req_modes: jint,
+    ) -> jint {
+        with_input_connection(env, view, peer, |ctx, ic| {
+            ic.cursor_caps_mode(ctx, req_modes.into()).into()
+        })
+    }
 
-pub(crate) extern "system" fn delete_surrounding_text<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    before_length: jint,
-    after_length: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.delete_surrounding_text(ctx, before_length, after_length)
-    }))
-}
+    "getExtractedTextNative" => fn get_extracted_text<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        out_offsets: JIntArray<'local>,
+    ) -> JString<'local> {
+        with_input_connection(env, view, peer, |ctx, ic| {
+            let extracted = ic.extracted_text(ctx).or_else(|| {
+                active_ime_quirks(peer)
+                    .contains(ImeQuirks::NEEDS_EXTRACTED_TEXT)
+                    .then(ExtractedText::default)
+            });
+            let Some(extracted) = extracted else {
+                return JObject::null().into();
+            };
+            let offsets = [
+                extracted.start_offset,
+                extracted.selection_start,
+                extracted.selection_end,
+            ];
+            ctx.env
+                .set_int_array_region(&out_offsets, 0, &offsets)
+                .unwrap();
+            new_java_string(&mut ctx.env, &extracted.text)
+        })
+    }
 
-pub(crate) extern "system" fn delete_surrounding_text_in_code_points<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    before_length: jint,
-    after_length: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.delete_surrounding_text_in_code_points(ctx, before_length, after_length)
-    }))
-}
+    "deleteSurroundingTextNative" => fn delete_surrounding_text<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        before_length: jint,
+        after_length: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!("delete_surrounding_text: {before_length}, {after_length}"),
+            );
+            ic.delete_surrounding_text(ctx, before_length, after_length)
+        }))
+    }
 
-pub(crate) extern "system" fn set_composing_text<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    text: JString<'local>,
-    new_cursor_position: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        let text = ctx.env.get_string(&text).unwrap();
-        let text = Cow::from(&text);
-        ic.set_composing_text(ctx, &text, new_cursor_position)
-    }))
-}
+    "deleteSurroundingTextInCodePointsNative" => fn delete_surrounding_text_in_code_points<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        before_length: jint,
+        after_length: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!(
+                    "delete_surrounding_text_in_code_points: {before_length}, {after_length}"
+                ),
+            );
+            ic.delete_surrounding_text_in_code_points(ctx, before_length, after_length)
+        }))
+    }
 
-pub(crate) extern "system" fn set_composing_region<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    start: jint,
-    end: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.set_composing_region(ctx, start, end)
-    }))
-}
+    "setComposingTextNative" => fn set_composing_text<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        text: JString<'local>,
+        new_cursor_position: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            let text = ctx.env.get_string(&text).unwrap();
+            let text = Cow::from(&text);
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!(
+                    "set_composing_text: {} chars, cursor {new_cursor_position}",
+                    text.chars().count()
+                ),
+            );
+            ic.set_composing_text(ctx, &text, new_cursor_position)
+        }))
+    }
 
-pub(crate) extern "system" fn finish_composing_text<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.finish_composing_text(ctx)
-    }))
-}
+    "setComposingRegionNative" => fn set_composing_region<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        start: jint,
+        end: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!("set_composing_region: {start}..{end}"),
+            );
+            if active_ime_quirks(peer).contains(ImeQuirks::NORMALIZE_COMPOSING_REGION) {
+                if start == end {
+                    return ic.finish_composing_text(ctx);
+                }
+                let (start, end) = (start.min(end).max(0), start.max(end).max(0));
+                return ic.set_composing_region(ctx, start, end);
+            }
+            ic.set_composing_region(ctx, start, end)
+        }))
+    }
 
-pub(crate) extern "system" fn commit_text<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    text: JString<'local>,
-    new_cursor_position: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        let text = ctx.env.get_string(&text).unwrap();
-        let text = Cow::from(&text);
-        ic.commit_text(ctx, &text, new_cursor_position)
-    }))
-}
+    "finishComposingTextNative" => fn finish_composing_text<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!("finish_composing_text"),
+            );
+            ic.finish_composing_text(ctx)
+        }))
+    }
 
-pub(crate) extern "system" fn set_selection<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    start: jint,
-    end: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.set_selection(ctx, start, end)
-    }))
-}
+    "commitTextNative" => fn commit_text<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        text: JString<'local>,
+        new_cursor_position: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            let text = ctx.env.get_string(&text).unwrap();
+            let text = Cow::from(&text);
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!(
+                    "commit_text: {} chars, cursor {new_cursor_position}",
+                    text.chars().count()
+                ),
+            );
+            ic.commit_text(ctx, &text, new_cursor_position)
+        }))
+    }
 
-pub(crate) extern "system" fn perform_editor_action<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    editor_action: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.perform_editor_action(ctx, editor_action)
-    }))
-}
+    "setSelectionNative" => fn set_selection<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        start: jint,
+        end: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!("set_selection: {start}..{end}"),
+            );
+            ic.set_selection(ctx, start, end)
+        }))
+    }
 
-pub(crate) extern "system" fn perform_context_menu_action<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    id: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.perform_context_menu_action(ctx, id)
-    }))
-}
+    "performEditorActionNative" => fn perform_editor_action<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        editor_action: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!("perform_editor_action: {editor_action}"),
+            );
+            ic.perform_editor_action(ctx, editor_action)
+        }))
+    }
 
-pub(crate) extern "system" fn begin_batch_edit<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.begin_batch_edit(ctx)
-    }))
-}
+    "performContextMenuActionNative" => fn perform_context_menu_action<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        id: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(
+                ctx,
+                TraceCategories::IME,
+                format_args!("perform_context_menu_action: {id}"),
+            );
+            ic.perform_context_menu_action(ctx, id)
+        }))
+    }
 
-pub(crate) extern "system" fn end_batch_edit<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.end_batch_edit(ctx)
-    }))
-}
+    "beginBatchEditNative" => fn begin_batch_edit<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(ctx, TraceCategories::IME, format_args!("begin_batch_edit"));
+            ic.begin_batch_edit(ctx)
+        }))
+    }
 
-pub(crate) extern "system" fn input_connection_send_key_event<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    event: KeyEvent<'local>,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.send_key_event(ctx, &event)
-    }))
-}
+    "endBatchEditNative" => fn end_batch_edit<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(ctx, TraceCategories::IME, format_args!("end_batch_edit"));
+            ic.end_batch_edit(ctx)
+        }))
+    }
 
-pub(crate) extern "system" fn input_connection_clear_meta_key_states<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    states: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.clear_meta_key_states(ctx, states)
-    }))
-}
+    "inputConnectionSendKeyEventNative" => fn input_connection_send_key_event<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        event: KeyEvent<'local>,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(ctx, TraceCategories::IME, format_args!("send_key_event"));
+            ic.send_key_event(ctx, &event)
+        }))
+    }
 
-pub(crate) extern "system" fn input_connection_report_fullscreen_mode<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    enabled: jboolean,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.report_fullscreen_mode(ctx, enabled == JNI_TRUE)
-    }))
-}
+    "inputConnectionClearMetaKeyStatesNative" => fn input_connection_clear_meta_key_states<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        states: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            ic.clear_meta_key_states(ctx, states)
+        }))
+    }
 
-pub(crate) extern "system" fn request_cursor_updates<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    cursor_update_mode: jint,
-) -> jboolean {
-    as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
-        ic.request_cursor_updates(ctx, cursor_update_mode)
-    }))
+    "inputConnectionReportFullscreenModeNative" =>
+    fn input_connection_report_fullscreen_mode<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        enabled: jboolean,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            ic.report_fullscreen_mode(ctx, enabled == JNI_TRUE)
+        }))
+    }
+
+    "requestCursorUpdatesNative" => fn request_cursor_updates<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        cursor_update_mode: jint,
+    ) -> jboolean {
+        as_jboolean(with_input_connection(env, view, peer, |ctx, ic| {
+            ic.request_cursor_updates(ctx, cursor_update_mode)
+        }))
+    }
+
+    "closeInputConnectionNative" => fn close_input_connection<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) {
+        with_input_connection(env, view, peer, |ctx, ic| {
+            #[cfg(feature = "trace")]
+            log(ctx, TraceCategories::IME, format_args!("close_connection"));
+            ic.close_connection(ctx);
+        });
+        clear_text_cache(peer);
+        forget_ime_quirks(peer);
+    }
 }
 
-pub(crate) extern "system" fn close_input_connection<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) {
-    with_input_connection(env, view, peer, |ctx, ic| {
-        ic.close_connection(ctx);
-    })
+pub(crate) fn register_natives(env: &mut JNIEnv) {
+    env.register_native_methods(
+        "org/linebender/android/rustview/RustView",
+        &input_connection_methods(),
+    )
+    .unwrap();
 }
 
-pub fn caps_mode(env: &mut JNIEnv, text: &str, off: usize, req_modes: u32) -> u32 {
-    let text = env.new_string(text).unwrap();
-    env.call_static_method(
+pub fn caps_mode(env: &mut JNIEnv, text: &str, off: usize, req_modes: CapsMode) -> CapsMode {
+    static GET_CAPS_MODE: CachedStaticMethod = CachedStaticMethod::new(
         "android/text/TextUtils",
         "getCapsMode",
         "(Ljava/lang/CharSequence;II)I",
-        &[
-            (&text).into(),
-            (off as jint).into(),
-            (req_modes as jint).into(),
-        ],
-    )
-    .unwrap()
-    .i()
-    .unwrap() as u32
+    );
+    let text = new_java_string(env, text);
+    GET_CAPS_MODE
+        .call(
+            env,
+            &[
+                (&text).into(),
+                (off as jint).into(),
+                jint::from(req_modes).into(),
+            ],
+        )
+        .i()
+        .unwrap()
+        .into()
 }
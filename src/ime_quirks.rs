@@ -0,0 +1,149 @@
+//! Workarounds for keyboards that deviate from the documented behavior
+//! of `InputConnection`.
+//!
+//! When the keyboard connects to a view, we look up its package in a
+//! table of known quirks and adjust the calls that reach the peer's
+//! [`crate::InputConnection`] to match. The built-in table covers the most
+//! widely installed keyboards; apps can add entries, or override the
+//! built-in ones, with [`register_ime_quirks`], such as when a user
+//! reports a problem with a keyboard that isn't listed.
+
+use bitflags::bitflags;
+use jni::{
+    JNIEnv,
+    objects::{JObject, JString},
+    sys::jlong,
+};
+use std::{cell::RefCell, collections::BTreeMap, sync::Mutex};
+
+use crate::{callback_ctx::*, jni_cache::*, view::*};
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct ImeQuirks: u32 {
+        /// The keyboard misbehaves, such as by losing track of the
+        /// cursor, when `getExtractedText` returns null. If the peer's
+        /// [`crate::InputConnection::extracted_text`] returns `None`, the
+        /// keyboard gets empty extracted text instead.
+        const NEEDS_EXTRACTED_TEXT = 1 << 0;
+        /// The keyboard passes `setComposingRegion` a reversed or negative
+        /// region, or an empty one to mean that composition is finished.
+        /// The region is put in order and clamped to zero before it
+        /// reaches the peer, and an empty region is passed on as
+        /// [`crate::InputConnection::finish_composing_text`].
+        const NORMALIZE_COMPOSING_REGION = 1 << 1;
+    }
+}
+
+// Keyed by the package of the keyboard's service.
+const BUILT_IN_QUIRKS: &[(&str, ImeQuirks)] = &[
+    // Gboard
+    (
+        "com.google.android.inputmethod.latin",
+        ImeQuirks::NORMALIZE_COMPOSING_REGION,
+    ),
+    // Samsung Keyboard
+    (
+        "com.samsung.android.honeyboard",
+        ImeQuirks::NEEDS_EXTRACTED_TEXT.union(ImeQuirks::NORMALIZE_COMPOSING_REGION),
+    ),
+    // Microsoft SwiftKey
+    ("com.touchtype.swiftkey", ImeQuirks::NEEDS_EXTRACTED_TEXT),
+];
+
+static REGISTERED_QUIRKS: Mutex<BTreeMap<String, ImeQuirks>> = Mutex::new(BTreeMap::new());
+
+/// Apply `quirks` to the keyboard whose service is in `package`, replacing
+/// any built-in entry for it. Register [`ImeQuirks::empty`] to turn off
+/// the built-in workarounds for a keyboard. Takes effect the next time
+/// the keyboard connects to a view.
+pub fn register_ime_quirks(package: &str, quirks: ImeQuirks) {
+    REGISTERED_QUIRKS
+        .lock()
+        .unwrap()
+        .insert(package.into(), quirks);
+}
+
+/// The quirks that apply to the keyboard whose service is in `package`.
+pub fn ime_quirks_for_package(package: &str) -> ImeQuirks {
+    if let Some(quirks) = REGISTERED_QUIRKS.lock().unwrap().get(package) {
+        return *quirks;
+    }
+    BUILT_IN_QUIRKS
+        .iter()
+        .find(|(name, _)| *name == package)
+        .map(|(_, quirks)| *quirks)
+        .unwrap_or_default()
+}
+
+struct ActiveIme {
+    package: String,
+    quirks: ImeQuirks,
+}
+
+thread_local! {
+    static ACTIVE_IMES: RefCell<BTreeMap<jlong, ActiveIme>> =
+        const { RefCell::new(BTreeMap::new()) };
+}
+
+fn default_input_method_package<'local>(
+    env: &mut JNIEnv<'local>,
+    view: &View<'local>,
+) -> Option<String> {
+    static GET_STRING: CachedStaticMethod = CachedStaticMethod::new(
+        "android/provider/Settings$Secure",
+        "getString",
+        "(Landroid/content/ContentResolver;Ljava/lang/String;)Ljava/lang/String;",
+    );
+    let resolver = view.context(env).content_resolver(env);
+    let name = new_java_string(env, "default_input_method");
+    let id: JObject = GET_STRING
+        .call(env, &[(&resolver.0).into(), (&name).into()])
+        .l()
+        .unwrap();
+    if id.is_null() {
+        return None;
+    }
+    // The ID is the component name of the service, such as
+    // `com.google.android.inputmethod.latin/com.android.inputmethod.latin.LatinIME`.
+    let id: String = env.get_string(&JString::from(id)).unwrap().into();
+    let package = id.split('/').next().unwrap_or_default();
+    (!package.is_empty()).then(|| package.to_string())
+}
+
+/// Look up the quirks of the keyboard that's connecting to the peer.
+pub(crate) fn detect_ime_quirks<'local>(
+    env: &mut JNIEnv<'local>,
+    view: &View<'local>,
+    peer: jlong,
+) {
+    let Some(package) = default_input_method_package(env, view) else {
+        forget_ime_quirks(peer);
+        return;
+    };
+    let quirks = ime_quirks_for_package(&package);
+    ACTIVE_IMES.with_borrow_mut(|active| active.insert(peer, ActiveIme { package, quirks }));
+}
+
+pub(crate) fn active_ime_quirks(peer: jlong) -> ImeQuirks {
+    ACTIVE_IMES.with_borrow(|active| active.get(&peer).map(|ime| ime.quirks).unwrap_or_default())
+}
+
+pub(crate) fn forget_ime_quirks(peer: jlong) {
+    ACTIVE_IMES.with_borrow_mut(|active| active.remove(&peer));
+}
+
+impl CallbackCtx<'_> {
+    /// The quirks of the keyboard connected to the view, which are
+    /// already worked around before its calls reach the peer.
+    pub fn ime_quirks(&self) -> ImeQuirks {
+        active_ime_quirks(self.peer_handle().id())
+    }
+
+    /// The package of the keyboard connected to the view, if known, such
+    /// as for including in bug reports about text input.
+    pub fn ime_package(&self) -> Option<String> {
+        let peer = self.peer_handle().id();
+        ACTIVE_IMES.with_borrow(|active| active.get(&peer).map(|ime| ime.package.clone()))
+    }
+}
@@ -0,0 +1,121 @@
+use jni::{
+    JNIEnv,
+    objects::{JIntArray, JObject, JString},
+    sys::jint,
+};
+use ndk::event::Source;
+
+use crate::{jni_cache::*, util::*};
+
+pub const INPUT_DEVICE_KEYBOARD_TYPE_NONE: jint = 0;
+pub const INPUT_DEVICE_KEYBOARD_TYPE_NON_ALPHABETIC: jint = 1;
+pub const INPUT_DEVICE_KEYBOARD_TYPE_ALPHABETIC: jint = 2;
+
+/// A binding to `android.view.InputDevice`, describing a keyboard, mouse,
+/// touchscreen, stylus, game controller or other input device. Use
+/// [`crate::View::set_input_device_monitoring_enabled`] to learn when
+/// devices are added or removed.
+#[repr(transparent)]
+pub struct InputDevice<'local>(pub JObject<'local>);
+
+impl<'local> InputDevice<'local> {
+    /// The device with the given ID, or `None` if it has been removed.
+    pub fn get(env: &mut JNIEnv<'local>, device_id: jint) -> Option<Self> {
+        static GET_DEVICE: CachedStaticMethod = CachedStaticMethod::new(
+            "android/view/InputDevice",
+            "getDevice",
+            "(I)Landroid/view/InputDevice;",
+        );
+        let device = GET_DEVICE.call(env, &[device_id.into()]).l().unwrap();
+        (!device.as_raw().is_null()).then_some(Self(device))
+    }
+
+    /// The IDs of all currently connected devices.
+    pub fn device_ids(env: &mut JNIEnv<'local>) -> Vec<jint> {
+        static GET_DEVICE_IDS: CachedStaticMethod =
+            CachedStaticMethod::new("android/view/InputDevice", "getDeviceIds", "()[I");
+        let array: JIntArray = GET_DEVICE_IDS.call(env, &[]).l().unwrap().into();
+        let len = env.get_array_length(&array).unwrap() as usize;
+        let mut ids = vec![0; len];
+        env.get_int_array_region(&array, 0, &mut ids).unwrap();
+        ids
+    }
+
+    pub fn id(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_ID: CachedMethod = CachedMethod::new("android/view/InputDevice", "getId", "()I");
+        GET_ID.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    pub fn name(&self, env: &mut JNIEnv<'local>) -> String {
+        static GET_NAME: CachedMethod = CachedMethod::new(
+            "android/view/InputDevice",
+            "getName",
+            "()Ljava/lang/String;",
+        );
+        let name: JString = GET_NAME.call(env, &self.0, &[]).l().unwrap().into();
+        env.get_string(&name).unwrap().into()
+    }
+
+    /// The input sources the device provides, as a bit mask of [`Source`]
+    /// values.
+    pub fn sources(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_SOURCES: CachedMethod =
+            CachedMethod::new("android/view/InputDevice", "getSources", "()I");
+        GET_SOURCES.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    pub fn supports_source(&self, env: &mut JNIEnv<'local>, source: Source) -> bool {
+        let source = i32::from(source);
+        self.sources(env) & source == source
+    }
+
+    /// Whether the device is a mouse or touchpad that drives a pointer,
+    /// so hover states are meaningful.
+    pub fn is_mouse(&self, env: &mut JNIEnv<'local>) -> bool {
+        self.supports_source(env, Source::Mouse) || self.supports_source(env, Source::Touchpad)
+    }
+
+    pub fn is_stylus(&self, env: &mut JNIEnv<'local>) -> bool {
+        self.supports_source(env, Source::Stylus)
+    }
+
+    pub fn is_game_controller(&self, env: &mut JNIEnv<'local>) -> bool {
+        self.supports_source(env, Source::Gamepad) || self.supports_source(env, Source::Joystick)
+    }
+
+    /// One of the `INPUT_DEVICE_KEYBOARD_TYPE_*` constants. Devices with
+    /// only a few keys, such as volume buttons, are non-alphabetic.
+    pub fn keyboard_type(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_KEYBOARD_TYPE: CachedMethod =
+            CachedMethod::new("android/view/InputDevice", "getKeyboardType", "()I");
+        GET_KEYBOARD_TYPE.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    /// Whether the device is virtual rather than physical, such as the
+    /// device that the IME and accessibility services inject keys through.
+    pub fn is_virtual(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_VIRTUAL: CachedMethod =
+            CachedMethod::new("android/view/InputDevice", "isVirtual", "()Z");
+        IS_VIRTUAL.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// Whether the device is connected externally, such as over USB or
+    /// Bluetooth, rather than built in. Always false before API level 29.
+    pub fn is_external(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_EXTERNAL: CachedMethod =
+            CachedMethod::new("android/view/InputDevice", "isExternal", "()Z");
+        android_sdk_version(env) >= 29 && IS_EXTERNAL.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    pub fn vendor_id(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_VENDOR_ID: CachedMethod =
+            CachedMethod::new("android/view/InputDevice", "getVendorId", "()I");
+        GET_VENDOR_ID.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    pub fn product_id(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_PRODUCT_ID: CachedMethod =
+            CachedMethod::new("android/view/InputDevice", "getProductId", "()I");
+        GET_PRODUCT_ID.call(env, &self.0, &[]).i().unwrap()
+    }
+}
@@ -0,0 +1,365 @@
+//! Validation and formatting for number, phone and date fields.
+//!
+//! An `EditText` with a numeric [`InputType`] filters what's typed or
+//! pasted through a key listener, such as `DigitsKeyListener`, that
+//! accepts the locale's digits and separators, and the IME shows a keypad
+//! but doesn't stop other characters from arriving. An editor that sets
+//! one of these input types should do the same filtering, with
+//! [`filter_input`], before applying a commit or composing text.
+
+use jni::{
+    JNIEnv,
+    objects::{GlobalRef, JString},
+    sys::{jchar, jdouble, jint},
+};
+use std::ops::Range;
+
+use crate::{ime::*, jni_cache::*, util::*};
+
+/// The characters a locale uses to write numbers, from
+/// `java.text.DecimalFormatSymbols`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberSymbols {
+    pub zero_digit: char,
+    pub decimal_separator: char,
+    pub grouping_separator: char,
+    pub minus_sign: char,
+}
+
+impl Default for NumberSymbols {
+    fn default() -> Self {
+        Self {
+            zero_digit: '0',
+            decimal_separator: '.',
+            grouping_separator: ',',
+            minus_sign: '-',
+        }
+    }
+}
+
+impl NumberSymbols {
+    /// The symbols for `locale`, a BCP 47 language tag, or for the default
+    /// locale if `None`.
+    pub fn for_locale(env: &mut JNIEnv, locale: Option<&str>) -> Self {
+        static GET_INSTANCE: CachedStaticMethod = CachedStaticMethod::new(
+            "java/text/DecimalFormatSymbols",
+            "getInstance",
+            "(Ljava/util/Locale;)Ljava/text/DecimalFormatSymbols;",
+        );
+        static GET_ZERO_DIGIT: CachedMethod =
+            CachedMethod::new("java/text/DecimalFormatSymbols", "getZeroDigit", "()C");
+        static GET_DECIMAL_SEPARATOR: CachedMethod = CachedMethod::new(
+            "java/text/DecimalFormatSymbols",
+            "getDecimalSeparator",
+            "()C",
+        );
+        static GET_GROUPING_SEPARATOR: CachedMethod = CachedMethod::new(
+            "java/text/DecimalFormatSymbols",
+            "getGroupingSeparator",
+            "()C",
+        );
+        static GET_MINUS_SIGN: CachedMethod =
+            CachedMethod::new("java/text/DecimalFormatSymbols", "getMinusSign", "()C");
+        let locale = java_locale(env, locale);
+        let symbols = GET_INSTANCE.call(env, &[(&locale).into()]).l().unwrap();
+        let get = |env: &mut JNIEnv, method: &CachedMethod, default: char| {
+            let c: jchar = method.call(env, &symbols, &[]).c().unwrap();
+            char::from_u32(c.into()).unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            zero_digit: get(env, &GET_ZERO_DIGIT, defaults.zero_digit),
+            decimal_separator: get(env, &GET_DECIMAL_SEPARATOR, defaults.decimal_separator),
+            grouping_separator: get(env, &GET_GROUPING_SEPARATOR, defaults.grouping_separator),
+            minus_sign: get(env, &GET_MINUS_SIGN, defaults.minus_sign),
+        }
+    }
+
+    /// The value of `c` as a digit, whether an ASCII digit or one of the
+    /// locale's own.
+    fn digit_value(&self, c: char) -> Option<u32> {
+        c.to_digit(10).or_else(|| {
+            let value = (c as u32).checked_sub(self.zero_digit as u32)?;
+            (value < 10).then_some(value)
+        })
+    }
+
+    /// Parse a number typed into a field with these symbols, ignoring
+    /// grouping separators, as `DigitsKeyListener` allows them to be
+    /// pasted. Returns `None` if the text isn't a number.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let mut normalized = String::with_capacity(text.len());
+        for (i, c) in text.trim().chars().enumerate() {
+            if let Some(digit) = self.digit_value(c) {
+                normalized.push(char::from_digit(digit, 10).unwrap());
+            } else if c == self.decimal_separator {
+                normalized.push('.');
+            } else if i == 0 && (c == self.minus_sign || c == '-') {
+                normalized.push('-');
+            } else if c != self.grouping_separator {
+                return None;
+            }
+        }
+        normalized.parse().ok()
+    }
+}
+
+/// Characters that `DialerKeyListener` accepts in phone numbers, besides
+/// digits.
+const PHONE_CHARS: &str = "+*#()-./ ,;NpPwW";
+
+/// Characters that `DateTimeKeyListener` and its date and time variants
+/// accept, besides digits.
+const DATE_CHARS: &str = "/-.";
+const TIME_CHARS: &str = ":AaMmPp ";
+
+/// The part of `inserted` that a field with the given input type accepts
+/// when it replaces `replaced` within `text`, both byte ranges, as the
+/// platform's key listener for that type would filter it. Text fields
+/// accept everything. In number fields, only digits are accepted, plus a
+/// minus sign at the start if the type is signed and a single decimal
+/// separator if it's decimal.
+pub fn filter_input(
+    input_type: InputType,
+    symbols: &NumberSymbols,
+    text: &str,
+    replaced: Range<usize>,
+    inserted: &str,
+) -> String {
+    let class = input_type.class();
+    if class == InputType::CLASS_NUMBER {
+        let before = &text[..replaced.start];
+        let after = &text[replaced.end..];
+        let signed = input_type.contains(InputType::NUMBER_FLAG_SIGNED);
+        let decimal = input_type.contains(InputType::NUMBER_FLAG_DECIMAL);
+        let is_minus = |c: char| c == symbols.minus_sign || c == '-';
+        let mut has_minus = before.chars().chain(after.chars()).any(is_minus);
+        let mut has_decimal = before
+            .chars()
+            .chain(after.chars())
+            .any(|c| c == symbols.decimal_separator);
+        let mut result = String::new();
+        for c in inserted.chars() {
+            if symbols.digit_value(c).is_some() {
+                result.push(c);
+            } else if signed && is_minus(c) && !has_minus && before.is_empty() && result.is_empty()
+            {
+                has_minus = true;
+                result.push(c);
+            } else if decimal && c == symbols.decimal_separator && !has_decimal {
+                has_decimal = true;
+                result.push(c);
+            }
+        }
+        result
+    } else if class == InputType::CLASS_PHONE {
+        inserted
+            .chars()
+            .filter(|c| c.is_ascii_digit() || PHONE_CHARS.contains(*c))
+            .collect()
+    } else if class == InputType::CLASS_DATETIME {
+        let variation = input_type.variation();
+        inserted
+            .chars()
+            .filter(|&c| {
+                c.is_ascii_digit()
+                    || (variation != InputType::DATETIME_VARIATION_TIME && DATE_CHARS.contains(c))
+                    || (variation != InputType::DATETIME_VARIATION_DATE && TIME_CHARS.contains(c))
+            })
+            .collect()
+    } else {
+        inserted.to_string()
+    }
+}
+
+/// Format a number for display in `locale`, a BCP 47 language tag, or the
+/// default locale if `None`, with grouping separators and at most
+/// `max_fraction_digits` digits after the decimal separator, as
+/// `java.text.NumberFormat` does.
+pub fn format_number(
+    env: &mut JNIEnv,
+    value: f64,
+    max_fraction_digits: jint,
+    locale: Option<&str>,
+) -> String {
+    static GET_INSTANCE: CachedStaticMethod = CachedStaticMethod::new(
+        "java/text/NumberFormat",
+        "getInstance",
+        "(Ljava/util/Locale;)Ljava/text/NumberFormat;",
+    );
+    static SET_MAXIMUM_FRACTION_DIGITS: CachedMethod =
+        CachedMethod::new("java/text/NumberFormat", "setMaximumFractionDigits", "(I)V");
+    static FORMAT: CachedMethod =
+        CachedMethod::new("java/text/NumberFormat", "format", "(D)Ljava/lang/String;");
+    let locale = java_locale(env, locale);
+    let format = GET_INSTANCE.call(env, &[(&locale).into()]).l().unwrap();
+    SET_MAXIMUM_FRACTION_DIGITS
+        .call(env, &format, &[max_fraction_digits.into()])
+        .v()
+        .unwrap();
+    let formatted: JString = FORMAT
+        .call(env, &format, &[(value as jdouble).into()])
+        .l()
+        .unwrap()
+        .into();
+    env.get_string(&formatted).unwrap().into()
+}
+
+/// Format a phone number as the dialer would, for the country with the
+/// given ISO 3166-1 code, such as `"US"`. Returns `None` if the number
+/// isn't valid for that country, in which case it should be left as typed.
+pub fn format_phone_number(env: &mut JNIEnv, number: &str, country_iso: &str) -> Option<String> {
+    static FORMAT_NUMBER: CachedStaticMethod = CachedStaticMethod::new(
+        "android/telephony/PhoneNumberUtils",
+        "formatNumber",
+        "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/String;",
+    );
+    let number = new_java_string(env, number);
+    let country_iso = new_java_string(env, country_iso);
+    let formatted = FORMAT_NUMBER
+        .call(env, &[(&number).into(), (&country_iso).into()])
+        .l()
+        .unwrap();
+    if formatted.as_raw().is_null() {
+        return None;
+    }
+    Some(env.get_string(&JString::from(formatted)).unwrap().into())
+}
+
+/// Whether `c` is part of the number itself, rather than a separator, as
+/// `PhoneNumberUtils.isNonSeparator` decides. Besides digits, this includes
+/// the dialer's wild, wait and pause characters.
+fn is_non_separator(c: char) -> bool {
+    c.is_ascii_digit() || matches!(c, '+' | '*' | '#' | 'N' | ',' | ';')
+}
+
+/// Reformats a phone number field as the user types, for an editor whose
+/// input type is [`InputType::CLASS_PHONE`], by passing each edit to the
+/// platform's `PhoneNumberFormattingTextWatcher`. Partial numbers are
+/// formatted as far as they go, as in the dialer. As with the platform's
+/// watcher, formatting stops once the user types or deletes anything but
+/// part of the number, such as a separator, so they can enter the number
+/// their own way, and resumes when the field is cleared.
+#[derive(Clone, Debug)]
+pub struct PhoneNumberFormatter {
+    country_iso: String,
+    stopped: bool,
+    // Created on the first edit that's formatted, since that's the first
+    // time there's a `JNIEnv`.
+    watcher: Option<GlobalRef>,
+}
+
+impl PhoneNumberFormatter {
+    /// A formatter for numbers in the country with the given ISO 3166-1
+    /// code, such as `"US"`.
+    pub fn new(country_iso: &str) -> Self {
+        Self {
+            country_iso: country_iso.to_string(),
+            stopped: false,
+            watcher: None,
+        }
+    }
+
+    /// A formatter for numbers in the default locale's country.
+    pub fn for_default_locale(env: &mut JNIEnv) -> Self {
+        static GET_COUNTRY: CachedMethod =
+            CachedMethod::new("java/util/Locale", "getCountry", "()Ljava/lang/String;");
+        let locale = java_locale(env, None);
+        let country: JString = GET_COUNTRY.call(env, &locale, &[]).l().unwrap().into();
+        let country = String::from(env.get_string(&country).unwrap());
+        Self::new(&country)
+    }
+
+    /// Apply an edit that replaces the byte range `replaced` of `text` with
+    /// `inserted`, returning the new text and the byte offset of the caret
+    /// after the inserted text. The caret keeps its place among the digits
+    /// when separators are added or removed around it.
+    pub fn apply_edit(
+        &mut self,
+        env: &mut JNIEnv,
+        text: &str,
+        replaced: Range<usize>,
+        inserted: &str,
+    ) -> (String, usize) {
+        let mut edited = String::with_capacity(text.len() + inserted.len());
+        edited.push_str(&text[..replaced.start]);
+        edited.push_str(inserted);
+        edited.push_str(&text[replaced.end..]);
+        let caret = replaced.start + inserted.len();
+        if edited.is_empty() {
+            self.stopped = false;
+            return (edited, caret);
+        }
+        if text[replaced]
+            .chars()
+            .chain(inserted.chars())
+            .any(|c| !is_non_separator(c))
+        {
+            self.stopped = true;
+        }
+        if self.stopped {
+            return (edited, caret);
+        }
+        self.reformat(env, &edited, caret)
+    }
+
+    /// Have the platform's watcher reformat `text` with the caret at the
+    /// byte offset `caret`, returning the new text and caret. The watcher
+    /// leaves the text as it is if it has nothing to format.
+    fn reformat(&mut self, env: &mut JNIEnv, text: &str, caret: usize) -> (String, usize) {
+        static SET_SELECTION: CachedStaticMethod = CachedStaticMethod::new(
+            "android/text/Selection",
+            "setSelection",
+            "(Landroid/text/Spannable;I)V",
+        );
+        static GET_SELECTION_END: CachedStaticMethod = CachedStaticMethod::new(
+            "android/text/Selection",
+            "getSelectionEnd",
+            "(Ljava/lang/CharSequence;)I",
+        );
+        static AFTER_TEXT_CHANGED: CachedMethod = CachedMethod::new(
+            "android/telephony/PhoneNumberFormattingTextWatcher",
+            "afterTextChanged",
+            "(Landroid/text/Editable;)V",
+        );
+        static TO_STRING: CachedMethod =
+            CachedMethod::new("java/lang/Object", "toString", "()Ljava/lang/String;");
+        let watcher = match &self.watcher {
+            Some(watcher) => watcher,
+            None => {
+                let country_iso = new_java_string(env, &self.country_iso);
+                let watcher = new_java_object(
+                    env,
+                    "android/telephony/PhoneNumberFormattingTextWatcher",
+                    "(Ljava/lang/String;)V",
+                    &[(&country_iso).into()],
+                );
+                self.watcher.insert(env.new_global_ref(&watcher).unwrap())
+            }
+        };
+        let string = new_java_string(env, text);
+        let editable = new_java_object(
+            env,
+            "android/text/SpannableStringBuilder",
+            "(Ljava/lang/CharSequence;)V",
+            &[(&string).into()],
+        );
+        let caret = byte_to_utf16_offset(text, caret) as jint;
+        SET_SELECTION
+            .call(env, &[(&editable).into(), caret.into()])
+            .v()
+            .unwrap();
+        AFTER_TEXT_CHANGED
+            .call(env, watcher.as_obj(), &[(&editable).into()])
+            .v()
+            .unwrap();
+        let formatted: JString = TO_STRING.call(env, &editable, &[]).l().unwrap().into();
+        let formatted = String::from(env.get_string(&formatted).unwrap());
+        let caret = GET_SELECTION_END
+            .call(env, &[(&editable).into()])
+            .i()
+            .unwrap();
+        let caret = utf16_to_byte_offset(&formatted, caret.max(0) as usize);
+        (formatted, caret)
+    }
+}
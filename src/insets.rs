@@ -0,0 +1,213 @@
+use jni::{JNIEnv, objects::JObject, sys::jint};
+
+use crate::{geometry::*, jni_cache::*};
+
+pub const WINDOW_INSETS_TYPE_STATUS_BARS: jint = 1 << 0;
+pub const WINDOW_INSETS_TYPE_NAVIGATION_BARS: jint = 1 << 1;
+pub const WINDOW_INSETS_TYPE_CAPTION_BAR: jint = 1 << 2;
+pub const WINDOW_INSETS_TYPE_IME: jint = 1 << 3;
+pub const WINDOW_INSETS_TYPE_SYSTEM_GESTURES: jint = 1 << 4;
+pub const WINDOW_INSETS_TYPE_MANDATORY_SYSTEM_GESTURES: jint = 1 << 5;
+pub const WINDOW_INSETS_TYPE_TAPPABLE_ELEMENT: jint = 1 << 6;
+pub const WINDOW_INSETS_TYPE_DISPLAY_CUTOUT: jint = 1 << 7;
+pub const WINDOW_INSETS_TYPE_SYSTEM_BARS: jint = WINDOW_INSETS_TYPE_STATUS_BARS
+    | WINDOW_INSETS_TYPE_NAVIGATION_BARS
+    | WINDOW_INSETS_TYPE_CAPTION_BAR;
+
+/// Distances, in pixels, from each edge of the view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Insets {
+    pub left: jint,
+    pub top: jint,
+    pub right: jint,
+    pub bottom: jint,
+}
+
+impl Insets {
+    fn from_java<'local>(env: &mut JNIEnv<'local>, insets: &JObject<'local>) -> Self {
+        static LEFT: CachedField = CachedField::new("android/graphics/Insets", "left", "I");
+        static TOP: CachedField = CachedField::new("android/graphics/Insets", "top", "I");
+        static RIGHT: CachedField = CachedField::new("android/graphics/Insets", "right", "I");
+        static BOTTOM: CachedField = CachedField::new("android/graphics/Insets", "bottom", "I");
+        Self {
+            left: LEFT.get(env, insets).i().unwrap(),
+            top: TOP.get(env, insets).i().unwrap(),
+            right: RIGHT.get(env, insets).i().unwrap(),
+            bottom: BOTTOM.get(env, insets).i().unwrap(),
+        }
+    }
+}
+
+/// The part of a view that isn't covered by system bars, the IME or the
+/// display cutout, in the view's own coordinates, in pixels. See
+/// [`crate::ViewPeer::on_safe_content_rect_changed`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SafeContentRect {
+    pub left: jint,
+    pub top: jint,
+    pub right: jint,
+    pub bottom: jint,
+}
+
+impl SafeContentRect {
+    pub fn width(&self) -> jint {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> jint {
+        self.bottom - self.top
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundedCornerPosition {
+    TopLeft,
+    TopRight,
+    BottomRight,
+    BottomLeft,
+}
+
+impl RoundedCornerPosition {
+    pub const ALL: [Self; 4] = [
+        Self::TopLeft,
+        Self::TopRight,
+        Self::BottomRight,
+        Self::BottomLeft,
+    ];
+
+    fn to_java(self) -> jint {
+        match self {
+            Self::TopLeft => 0,
+            Self::TopRight => 1,
+            Self::BottomRight => 2,
+            Self::BottomLeft => 3,
+        }
+    }
+}
+
+/// A rounded corner of the display, in the view's window coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundedCorner {
+    pub position: RoundedCornerPosition,
+    pub radius: jint,
+    pub center_x: jint,
+    pub center_y: jint,
+}
+
+#[repr(transparent)]
+pub struct WindowInsets<'local>(pub JObject<'local>);
+
+impl<'local> WindowInsets<'local> {
+    /// The insets for the given types, a combination of
+    /// `WINDOW_INSETS_TYPE_*` values. Requires API level 30.
+    pub fn insets(&self, env: &mut JNIEnv<'local>, type_mask: jint) -> Insets {
+        static GET_INSETS: CachedMethod = CachedMethod::new(
+            "android/view/WindowInsets",
+            "getInsets",
+            "(I)Landroid/graphics/Insets;",
+        );
+        let insets = GET_INSETS
+            .call(env, &self.0, &[type_mask.into()])
+            .l()
+            .unwrap();
+        Insets::from_java(env, &insets)
+    }
+
+    /// The display cutout, if the display has one and it overlaps
+    /// the window. Requires API level 28.
+    pub fn display_cutout(&self, env: &mut JNIEnv<'local>) -> Option<DisplayCutout<'local>> {
+        static GET_DISPLAY_CUTOUT: CachedMethod = CachedMethod::new(
+            "android/view/WindowInsets",
+            "getDisplayCutout",
+            "()Landroid/view/DisplayCutout;",
+        );
+        let cutout = GET_DISPLAY_CUTOUT.call(env, &self.0, &[]).l().unwrap();
+        (!cutout.as_raw().is_null()).then_some(DisplayCutout(cutout))
+    }
+
+    /// The rounded corner at the given position, if the display has one
+    /// there. Requires API level 31.
+    pub fn rounded_corner(
+        &self,
+        env: &mut JNIEnv<'local>,
+        position: RoundedCornerPosition,
+    ) -> Option<RoundedCorner> {
+        static GET_ROUNDED_CORNER: CachedMethod = CachedMethod::new(
+            "android/view/WindowInsets",
+            "getRoundedCorner",
+            "(I)Landroid/view/RoundedCorner;",
+        );
+        static GET_RADIUS: CachedMethod =
+            CachedMethod::new("android/view/RoundedCorner", "getRadius", "()I");
+        static GET_CENTER: CachedMethod = CachedMethod::new(
+            "android/view/RoundedCorner",
+            "getCenter",
+            "()Landroid/graphics/Point;",
+        );
+        static X: CachedField = CachedField::new("android/graphics/Point", "x", "I");
+        static Y: CachedField = CachedField::new("android/graphics/Point", "y", "I");
+        let corner = GET_ROUNDED_CORNER
+            .call(env, &self.0, &[position.to_java().into()])
+            .l()
+            .unwrap();
+        if corner.as_raw().is_null() {
+            return None;
+        }
+        let radius = GET_RADIUS.call(env, &corner, &[]).i().unwrap();
+        let center = GET_CENTER.call(env, &corner, &[]).l().unwrap();
+        Some(RoundedCorner {
+            position,
+            radius,
+            center_x: X.get(env, &center).i().unwrap(),
+            center_y: Y.get(env, &center).i().unwrap(),
+        })
+    }
+
+    /// All of the display's rounded corners that overlap the window.
+    pub fn rounded_corners(&self, env: &mut JNIEnv<'local>) -> Vec<RoundedCorner> {
+        RoundedCornerPosition::ALL
+            .into_iter()
+            .filter_map(|position| self.rounded_corner(env, position))
+            .collect()
+    }
+}
+
+#[repr(transparent)]
+pub struct DisplayCutout<'local>(pub JObject<'local>);
+
+impl<'local> DisplayCutout<'local> {
+    /// The insets that avoid the cutout entirely.
+    pub fn safe_insets(&self, env: &mut JNIEnv<'local>) -> Insets {
+        static GET_SAFE_INSET_LEFT: CachedMethod =
+            CachedMethod::new("android/view/DisplayCutout", "getSafeInsetLeft", "()I");
+        static GET_SAFE_INSET_TOP: CachedMethod =
+            CachedMethod::new("android/view/DisplayCutout", "getSafeInsetTop", "()I");
+        static GET_SAFE_INSET_RIGHT: CachedMethod =
+            CachedMethod::new("android/view/DisplayCutout", "getSafeInsetRight", "()I");
+        static GET_SAFE_INSET_BOTTOM: CachedMethod =
+            CachedMethod::new("android/view/DisplayCutout", "getSafeInsetBottom", "()I");
+        Insets {
+            left: GET_SAFE_INSET_LEFT.call(env, &self.0, &[]).i().unwrap(),
+            top: GET_SAFE_INSET_TOP.call(env, &self.0, &[]).i().unwrap(),
+            right: GET_SAFE_INSET_RIGHT.call(env, &self.0, &[]).i().unwrap(),
+            bottom: GET_SAFE_INSET_BOTTOM.call(env, &self.0, &[]).i().unwrap(),
+        }
+    }
+
+    /// The bounding rectangles of the cutouts themselves.
+    pub fn bounding_rects(&self, env: &mut JNIEnv<'local>) -> Vec<Rect<'local>> {
+        static GET_BOUNDING_RECTS: CachedMethod = CachedMethod::new(
+            "android/view/DisplayCutout",
+            "getBoundingRects",
+            "()Ljava/util/List;",
+        );
+        static SIZE: CachedMethod = CachedMethod::new("java/util/List", "size", "()I");
+        static GET: CachedMethod =
+            CachedMethod::new("java/util/List", "get", "(I)Ljava/lang/Object;");
+        let list = GET_BOUNDING_RECTS.call(env, &self.0, &[]).l().unwrap();
+        let size = SIZE.call(env, &list, &[]).i().unwrap();
+        (0..size)
+            .map(|i| Rect(GET.call(env, &list, &[i.into()]).l().unwrap()))
+            .collect()
+    }
+}
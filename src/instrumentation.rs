@@ -0,0 +1,249 @@
+//! Helpers for driving a view from an instrumented test.
+//!
+//! These inject input through `android.app.Instrumentation`, so they must be
+//! called from the instrumentation thread, not the UI thread. Each call waits
+//! until the event has been dispatched to the view, so the peer's
+//! callbacks have run by the time it returns. Coordinates are relative
+//! to the view's top-left corner.
+
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jint, jlong},
+};
+use ndk::event::{Keycode, MotionAction, Source};
+
+use crate::{events::*, jni_cache::*, view::*};
+
+#[repr(transparent)]
+pub struct Instrumentation<'local>(pub JObject<'local>);
+
+impl<'local> Instrumentation<'local> {
+    /// The instrumentation of the running test, as returned by
+    /// `androidx.test.platform.app.InstrumentationRegistry`.
+    pub fn from_registry(env: &mut JNIEnv<'local>) -> Self {
+        static GET_INSTRUMENTATION: CachedStaticMethod = CachedStaticMethod::new(
+            "androidx/test/platform/app/InstrumentationRegistry",
+            "getInstrumentation",
+            "()Landroid/app/Instrumentation;",
+        );
+        Self(GET_INSTRUMENTATION.call(env, &[]).l().unwrap())
+    }
+
+    pub fn send_pointer_sync(&self, env: &mut JNIEnv<'local>, event: &MotionEvent<'local>) {
+        static SEND_POINTER_SYNC: CachedMethod = CachedMethod::new(
+            "android/app/Instrumentation",
+            "sendPointerSync",
+            "(Landroid/view/MotionEvent;)V",
+        );
+        SEND_POINTER_SYNC
+            .call(env, &self.0, &[(&event.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn send_key_sync(&self, env: &mut JNIEnv<'local>, event: &KeyEvent<'local>) {
+        static SEND_KEY_SYNC: CachedMethod = CachedMethod::new(
+            "android/app/Instrumentation",
+            "sendKeySync",
+            "(Landroid/view/KeyEvent;)V",
+        );
+        SEND_KEY_SYNC
+            .call(env, &self.0, &[(&event.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn send_key_down_up_sync(&self, env: &mut JNIEnv<'local>, key_code: Keycode) {
+        static SEND_KEY_DOWN_UP_SYNC: CachedMethod =
+            CachedMethod::new("android/app/Instrumentation", "sendKeyDownUpSync", "(I)V");
+        SEND_KEY_DOWN_UP_SYNC
+            .call(env, &self.0, &[i32::from(key_code).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Send the key events needed to type `text` into the focused view.
+    pub fn send_string_sync(&self, env: &mut JNIEnv<'local>, text: &str) {
+        static SEND_STRING_SYNC: CachedMethod = CachedMethod::new(
+            "android/app/Instrumentation",
+            "sendStringSync",
+            "(Ljava/lang/String;)V",
+        );
+        let text = new_java_string(env, text);
+        SEND_STRING_SYNC
+            .call(env, &self.0, &[(&text).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Wait until the UI thread is idle, e.g. after the peer has posted
+    /// a frame callback in response to injected input.
+    pub fn wait_for_idle_sync(&self, env: &mut JNIEnv<'local>) {
+        static WAIT_FOR_IDLE_SYNC: CachedMethod =
+            CachedMethod::new("android/app/Instrumentation", "waitForIdleSync", "()V");
+        WAIT_FOR_IDLE_SYNC.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+fn uptime_millis(env: &mut JNIEnv) -> jlong {
+    static UPTIME_MILLIS: CachedStaticMethod =
+        CachedStaticMethod::new("android/os/SystemClock", "uptimeMillis", "()J");
+    UPTIME_MILLIS.call(env, &[]).j().unwrap()
+}
+
+fn obtain_touch<'local>(
+    env: &mut JNIEnv<'local>,
+    down_time: jlong,
+    event_time: jlong,
+    action: MotionAction,
+    x: jfloat,
+    y: jfloat,
+) -> MotionEvent<'local> {
+    static OBTAIN: CachedStaticMethod = CachedStaticMethod::new(
+        "android/view/MotionEvent",
+        "obtain",
+        "(JJIFFI)Landroid/view/MotionEvent;",
+    );
+    static SET_SOURCE: CachedMethod =
+        CachedMethod::new("android/view/MotionEvent", "setSource", "(I)V");
+    let event = OBTAIN
+        .call(
+            env,
+            &[
+                down_time.into(),
+                event_time.into(),
+                i32::from(action).into(),
+                x.into(),
+                y.into(),
+                0.into(),
+            ],
+        )
+        .l()
+        .unwrap();
+    SET_SOURCE
+        .call(env, &event, &[i32::from(Source::Touchscreen).into()])
+        .v()
+        .unwrap();
+    MotionEvent(event)
+}
+
+fn add_batch<'local>(
+    env: &mut JNIEnv<'local>,
+    event: &MotionEvent<'local>,
+    event_time: jlong,
+    x: jfloat,
+    y: jfloat,
+) {
+    static ADD_BATCH: CachedMethod =
+        CachedMethod::new("android/view/MotionEvent", "addBatch", "(JFFFFI)V");
+    ADD_BATCH
+        .call(
+            env,
+            &event.0,
+            &[
+                event_time.into(),
+                x.into(),
+                y.into(),
+                1.0f32.into(),
+                1.0f32.into(),
+                0.into(),
+            ],
+        )
+        .v()
+        .unwrap()
+}
+
+/// Tap the view at the given point.
+pub fn tap<'local>(
+    env: &mut JNIEnv<'local>,
+    instrumentation: &Instrumentation<'local>,
+    view: &View<'local>,
+    x: jfloat,
+    y: jfloat,
+) {
+    let (origin_x, origin_y) = view.location_on_screen(env);
+    let (origin_x, origin_y) = (origin_x as jfloat, origin_y as jfloat);
+    let (x, y) = (origin_x + x, origin_y + y);
+    let down_time = uptime_millis(env);
+    let down = obtain_touch(env, down_time, down_time, MotionAction::Down, x, y);
+    instrumentation.send_pointer_sync(env, &down);
+    down.recycle(env);
+    let up_time = uptime_millis(env);
+    let up = obtain_touch(env, down_time, up_time, MotionAction::Up, x, y);
+    instrumentation.send_pointer_sync(env, &up);
+    up.recycle(env);
+}
+
+/// Swipe in a straight line from one point to another over `duration_millis`.
+///
+/// The path is divided into `events * samples_per_event` evenly spaced
+/// samples. Each move event carries `samples_per_event` samples, all but
+/// the last as history, as the framework does when it batches input
+/// once per frame.
+#[allow(clippy::too_many_arguments)]
+pub fn swipe<'local>(
+    env: &mut JNIEnv<'local>,
+    instrumentation: &Instrumentation<'local>,
+    view: &View<'local>,
+    from: (jfloat, jfloat),
+    to: (jfloat, jfloat),
+    duration_millis: jlong,
+    events: jint,
+    samples_per_event: jint,
+) {
+    let (origin_x, origin_y) = view.location_on_screen(env);
+    let (origin_x, origin_y) = (origin_x as jfloat, origin_y as jfloat);
+    let events = events.max(1);
+    let samples_per_event = samples_per_event.max(1);
+    let total_samples = events * samples_per_event;
+    let sample = |i: jint| {
+        let t = i as jfloat / total_samples as jfloat;
+        (
+            origin_x + from.0 + (to.0 - from.0) * t,
+            origin_y + from.1 + (to.1 - from.1) * t,
+        )
+    };
+    let down_time = uptime_millis(env);
+    let sample_time = |i: jint| down_time + duration_millis * i as jlong / total_samples as jlong;
+
+    let (x, y) = sample(0);
+    let down = obtain_touch(env, down_time, down_time, MotionAction::Down, x, y);
+    instrumentation.send_pointer_sync(env, &down);
+    down.recycle(env);
+
+    for event_index in 0..events {
+        let first = event_index * samples_per_event + 1;
+        let (x, y) = sample(first);
+        let event = obtain_touch(env, down_time, sample_time(first), MotionAction::Move, x, y);
+        for i in (first + 1)..(first + samples_per_event) {
+            let (x, y) = sample(i);
+            add_batch(env, &event, sample_time(i), x, y);
+        }
+        instrumentation.send_pointer_sync(env, &event);
+        event.recycle(env);
+    }
+
+    let (x, y) = sample(total_samples);
+    let up = obtain_touch(
+        env,
+        down_time,
+        sample_time(total_samples),
+        MotionAction::Up,
+        x,
+        y,
+    );
+    instrumentation.send_pointer_sync(env, &up);
+    up.recycle(env);
+}
+
+/// Press and release each key in turn.
+pub fn key_sequence<'local>(
+    env: &mut JNIEnv<'local>,
+    instrumentation: &Instrumentation<'local>,
+    key_codes: &[Keycode],
+) {
+    for &key_code in key_codes {
+        instrumentation.send_key_down_up_sync(env, key_code);
+    }
+}
@@ -0,0 +1,266 @@
+use jni::{
+    JNIEnv,
+    objects::{JObject, JString},
+    sys::jint,
+};
+
+use crate::jni_cache::*;
+
+pub const ACTION_VIEW: &str = "android.intent.action.VIEW";
+pub const ACTION_SEND: &str = "android.intent.action.SEND";
+pub const ACTION_OPEN_DOCUMENT: &str = "android.intent.action.OPEN_DOCUMENT";
+pub const ACTION_CREATE_DOCUMENT: &str = "android.intent.action.CREATE_DOCUMENT";
+
+pub const CATEGORY_OPENABLE: &str = "android.intent.category.OPENABLE";
+
+pub const EXTRA_TEXT: &str = "android.intent.extra.TEXT";
+pub const EXTRA_SUBJECT: &str = "android.intent.extra.SUBJECT";
+pub const EXTRA_STREAM: &str = "android.intent.extra.STREAM";
+pub const EXTRA_TITLE: &str = "android.intent.extra.TITLE";
+pub const EXTRA_MIME_TYPES: &str = "android.intent.extra.MIME_TYPES";
+
+pub const FLAG_GRANT_READ_URI_PERMISSION: jint = 0x00000001;
+pub const FLAG_GRANT_WRITE_URI_PERMISSION: jint = 0x00000002;
+pub const FLAG_ACTIVITY_NEW_TASK: jint = 0x10000000;
+
+#[repr(transparent)]
+pub struct Uri<'local>(pub JObject<'local>);
+
+impl<'local> Uri<'local> {
+    pub fn parse(env: &mut JNIEnv<'local>, uri: &str) -> Self {
+        static PARSE: CachedStaticMethod = CachedStaticMethod::new(
+            "android/net/Uri",
+            "parse",
+            "(Ljava/lang/String;)Landroid/net/Uri;",
+        );
+        let uri = new_java_string(env, uri);
+        Self(PARSE.call(env, &[(&uri).into()]).l().unwrap())
+    }
+
+    pub fn to_string(&self, env: &mut JNIEnv<'local>) -> JString<'local> {
+        static TO_STRING: CachedMethod =
+            CachedMethod::new("android/net/Uri", "toString", "()Ljava/lang/String;");
+        TO_STRING.call(env, &self.0, &[]).l().unwrap().into()
+    }
+}
+
+#[repr(transparent)]
+pub struct Intent<'local>(pub JObject<'local>);
+
+impl<'local> Intent<'local> {
+    pub fn new(env: &mut JNIEnv<'local>, action: &str) -> Self {
+        let action = new_java_string(env, action);
+        Self(new_java_object(
+            env,
+            "android/content/Intent",
+            "(Ljava/lang/String;)V",
+            &[(&action).into()],
+        ))
+    }
+
+    pub fn set_data(&self, env: &mut JNIEnv<'local>, uri: &Uri<'local>) -> &Self {
+        static SET_DATA: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "setData",
+            "(Landroid/net/Uri;)Landroid/content/Intent;",
+        );
+        SET_DATA.call(env, &self.0, &[(&uri.0).into()]);
+        self
+    }
+
+    pub fn set_type(&self, env: &mut JNIEnv<'local>, mime_type: &str) -> &Self {
+        static SET_TYPE: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "setType",
+            "(Ljava/lang/String;)Landroid/content/Intent;",
+        );
+        let mime_type = new_java_string(env, mime_type);
+        SET_TYPE.call(env, &self.0, &[(&mime_type).into()]);
+        self
+    }
+
+    pub fn put_string_extra(&self, env: &mut JNIEnv<'local>, name: &str, value: &str) -> &Self {
+        static PUT_EXTRA: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "putExtra",
+            "(Ljava/lang/String;Ljava/lang/String;)Landroid/content/Intent;",
+        );
+        let name = new_java_string(env, name);
+        let value = new_java_string(env, value);
+        PUT_EXTRA.call(env, &self.0, &[(&name).into(), (&value).into()]);
+        self
+    }
+
+    pub fn put_string_array_extra(
+        &self,
+        env: &mut JNIEnv<'local>,
+        name: &str,
+        values: &[&str],
+    ) -> &Self {
+        static PUT_EXTRA: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "putExtra",
+            "(Ljava/lang/String;[Ljava/lang/String;)Landroid/content/Intent;",
+        );
+        let name = new_java_string(env, name);
+        let array = env
+            .new_object_array(values.len() as jint, "java/lang/String", JObject::null())
+            .unwrap();
+        for (i, value) in values.iter().enumerate() {
+            let value = new_java_string(env, value);
+            env.set_object_array_element(&array, i as jint, value)
+                .unwrap();
+        }
+        PUT_EXTRA.call(env, &self.0, &[(&name).into(), (&array).into()]);
+        self
+    }
+
+    pub fn put_uri_extra(&self, env: &mut JNIEnv<'local>, name: &str, uri: &Uri<'local>) -> &Self {
+        static PUT_EXTRA: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "putExtra",
+            "(Ljava/lang/String;Landroid/os/Parcelable;)Landroid/content/Intent;",
+        );
+        let name = new_java_string(env, name);
+        PUT_EXTRA.call(env, &self.0, &[(&name).into(), (&uri.0).into()]);
+        self
+    }
+
+    pub fn add_category(&self, env: &mut JNIEnv<'local>, category: &str) -> &Self {
+        static ADD_CATEGORY: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "addCategory",
+            "(Ljava/lang/String;)Landroid/content/Intent;",
+        );
+        let category = new_java_string(env, category);
+        ADD_CATEGORY.call(env, &self.0, &[(&category).into()]);
+        self
+    }
+
+    pub fn add_flags(&self, env: &mut JNIEnv<'local>, flags: jint) -> &Self {
+        static ADD_FLAGS: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "addFlags",
+            "(I)Landroid/content/Intent;",
+        );
+        ADD_FLAGS.call(env, &self.0, &[flags.into()]);
+        self
+    }
+
+    /// Wrap this intent in one that lets the user choose which app
+    /// handles it, with the given title.
+    pub fn create_chooser(&self, env: &mut JNIEnv<'local>, title: Option<&str>) -> Intent<'local> {
+        static CREATE_CHOOSER: CachedStaticMethod = CachedStaticMethod::new(
+            "android/content/Intent",
+            "createChooser",
+            "(Landroid/content/Intent;Ljava/lang/CharSequence;)Landroid/content/Intent;",
+        );
+        let title = match title {
+            Some(title) => new_java_string(env, title).into(),
+            None => JObject::null(),
+        };
+        Intent(
+            CREATE_CHOOSER
+                .call(env, &[(&self.0).into(), (&title).into()])
+                .l()
+                .unwrap(),
+        )
+    }
+
+    /// An intent that lets the user pick an existing document to open,
+    /// of any of the given MIME types. The types may include wildcards,
+    /// such as `image/*`.
+    pub fn open_document(env: &mut JNIEnv<'local>, mime_types: &[&str]) -> Intent<'local> {
+        let intent = Intent::new(env, ACTION_OPEN_DOCUMENT);
+        intent.add_category(env, CATEGORY_OPENABLE);
+        match mime_types {
+            [] => {
+                intent.set_type(env, "*/*");
+            }
+            [mime_type] => {
+                intent.set_type(env, mime_type);
+            }
+            _ => {
+                intent.set_type(env, "*/*").put_string_array_extra(
+                    env,
+                    EXTRA_MIME_TYPES,
+                    mime_types,
+                );
+            }
+        }
+        intent
+    }
+
+    /// An intent that lets the user choose where to create a new document,
+    /// with `suggested_name` as the initial file name.
+    pub fn create_document(
+        env: &mut JNIEnv<'local>,
+        mime_type: &str,
+        suggested_name: &str,
+    ) -> Intent<'local> {
+        let intent = Intent::new(env, ACTION_CREATE_DOCUMENT);
+        intent
+            .add_category(env, CATEGORY_OPENABLE)
+            .set_type(env, mime_type)
+            .put_string_extra(env, EXTRA_TITLE, suggested_name);
+        intent
+    }
+
+    /// An intent that opens the system share sheet for plain text.
+    pub fn share_text(
+        env: &mut JNIEnv<'local>,
+        text: &str,
+        subject: Option<&str>,
+        title: Option<&str>,
+    ) -> Intent<'local> {
+        let intent = Intent::new(env, ACTION_SEND);
+        intent
+            .set_type(env, "text/plain")
+            .put_string_extra(env, EXTRA_TEXT, text);
+        if let Some(subject) = subject {
+            intent.put_string_extra(env, EXTRA_SUBJECT, subject);
+        }
+        intent.create_chooser(env, title)
+    }
+
+    /// An intent that opens the system share sheet for the content at `uri`,
+    /// granting the receiving app permission to read it. For `content://`
+    /// URIs, the granted permission lasts until the receiving activity
+    /// finishes.
+    pub fn share_uri(
+        env: &mut JNIEnv<'local>,
+        uri: &Uri<'local>,
+        mime_type: &str,
+        title: Option<&str>,
+    ) -> Intent<'local> {
+        static NEW_RAW_URI: CachedStaticMethod = CachedStaticMethod::new(
+            "android/content/ClipData",
+            "newRawUri",
+            "(Ljava/lang/CharSequence;Landroid/net/Uri;)Landroid/content/ClipData;",
+        );
+        static SET_CLIP_DATA: CachedMethod = CachedMethod::new(
+            "android/content/Intent",
+            "setClipData",
+            "(Landroid/content/ClipData;)V",
+        );
+        let intent = Intent::new(env, ACTION_SEND);
+        intent
+            .set_type(env, mime_type)
+            .put_uri_extra(env, EXTRA_STREAM, uri)
+            .add_flags(env, FLAG_GRANT_READ_URI_PERMISSION);
+        // The permission grant only propagates through the chooser
+        // if the URI is also in the intent's clip data.
+        let label = new_java_string(env, "");
+        let clip = NEW_RAW_URI
+            .call(env, &[(&label).into(), (&uri.0).into()])
+            .l()
+            .unwrap();
+        SET_CLIP_DATA
+            .call(env, &intent.0, &[(&clip).into()])
+            .v()
+            .unwrap();
+        let chooser = intent.create_chooser(env, title);
+        chooser.add_flags(env, FLAG_GRANT_READ_URI_PERMISSION);
+        chooser
+    }
+}
@@ -0,0 +1,305 @@
+//! Lazily resolved JNI class, method, and field IDs.
+//!
+//! Looking up a method or field by name and signature on every call
+//! is a significant part of the cost of a JNI call, and callbacks like
+//! touch events and IME queries make many such calls. The wrappers
+//! in this crate declare their methods and fields as statics of the types
+//! in this module, which resolve the ID on first use and reuse it
+//! afterward. The framework classes we use are never unloaded,
+//! so the IDs stay valid for the life of the process.
+
+use jni::{
+    JNIEnv,
+    objects::{
+        GlobalRef, JClass, JFieldID, JMethodID, JObject, JStaticMethodID, JString, JValue,
+        JValueOwned,
+    },
+    signature::{JavaType, ReturnType, TypeSignature},
+    sys::jvalue,
+};
+use smallvec::SmallVec;
+use std::{str::FromStr, sync::OnceLock};
+
+use crate::diagnostics::*;
+
+// Enough for every method the crate calls, so converting the arguments
+// doesn't allocate.
+type JniArgs = SmallVec<[jvalue; 8]>;
+
+fn check_args(expected: &[JavaType], args: &[JValue], name: &str) -> JniArgs {
+    assert_eq!(
+        expected.len(),
+        args.len(),
+        "wrong number of arguments for {name}"
+    );
+    for (expected, arg) in expected.iter().zip(args) {
+        let matches = match expected {
+            JavaType::Primitive(p) => arg.primitive_type() == Some(*p),
+            JavaType::Object(_) | JavaType::Array(_) => matches!(arg, JValue::Object(_)),
+            JavaType::Method(_) => false,
+        };
+        assert!(matches, "wrong argument type for {name}");
+    }
+    args.iter().map(|arg| arg.as_jni()).collect()
+}
+
+#[cfg(feature = "jni-checks")]
+fn count_local_ref(value: &JValueOwned) {
+    if let JValueOwned::Object(obj) = value
+        && !obj.is_null()
+    {
+        crate::jni_checks::count_local_ref();
+    }
+}
+
+struct ResolvedMethod {
+    id: JMethodID,
+    args: Vec<JavaType>,
+    ret: ReturnType,
+}
+
+/// An instance method, resolved on first call.
+pub(crate) struct CachedMethod {
+    class: &'static str,
+    name: &'static str,
+    sig: &'static str,
+    resolved: OnceLock<ResolvedMethod>,
+}
+
+impl CachedMethod {
+    pub(crate) const fn new(class: &'static str, name: &'static str, sig: &'static str) -> Self {
+        Self {
+            class,
+            name,
+            sig,
+            resolved: OnceLock::new(),
+        }
+    }
+
+    fn try_resolve(&self, env: &mut JNIEnv) -> jni::errors::Result<&ResolvedMethod> {
+        if let Some(resolved) = self.resolved.get() {
+            return Ok(resolved);
+        }
+        let TypeSignature { args, ret } = TypeSignature::from_str(self.sig).unwrap();
+        let id = env.get_method_id(self.class, self.name, self.sig)?;
+        Ok(self
+            .resolved
+            .get_or_init(|| ResolvedMethod { id, args, ret }))
+    }
+
+    fn resolve(&self, env: &mut JNIEnv) -> &ResolvedMethod {
+        let resolved = self.try_resolve(env);
+        unwrap_or_report(env, resolved, || self.member())
+    }
+
+    fn member(&self) -> String {
+        format!("{}.{}", self.class, self.name)
+    }
+
+    pub(crate) fn call<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        obj: &JObject,
+        args: &[JValue],
+    ) -> JValueOwned<'local> {
+        let result = self.try_call(env, obj, args);
+        let value = unwrap_or_report(env, result, || self.member());
+        #[cfg(feature = "jni-checks")]
+        count_local_ref(&value);
+        value
+    }
+
+    /// Like [`CachedMethod::call`], but if the method is missing or
+    /// throws, reports the failure to the diagnostic handler, clears the
+    /// exception, and returns `None` instead of panicking.
+    pub(crate) fn call_or_report<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        obj: &JObject,
+        args: &[JValue],
+    ) -> Option<JValueOwned<'local>> {
+        let result = match self.try_resolve(env) {
+            Ok(_) => self.try_call(env, obj, args),
+            Err(error) => Err(error),
+        };
+        let value = ok_or_report(env, result, || self.member())?;
+        #[cfg(feature = "jni-checks")]
+        count_local_ref(&value);
+        Some(value)
+    }
+
+    /// Like [`CachedMethod::call`], but returns an error, leaving the
+    /// exception pending, if the method throws.
+    pub(crate) fn try_call<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        obj: &JObject,
+        args: &[JValue],
+    ) -> jni::errors::Result<JValueOwned<'local>> {
+        #[cfg(feature = "thread-checks")]
+        if self.class.starts_with("org/linebender/android/rustview/") {
+            crate::thread_checks::check_ui_thread(self.name);
+        }
+        let resolved = self.resolve(env);
+        let args = check_args(&resolved.args, args, self.name);
+        // SAFETY: The ID was resolved from this signature on a class
+        // the object is an instance of, and the arguments were checked
+        // against the signature above.
+        unsafe { env.call_method_unchecked(obj, resolved.id, resolved.ret.clone(), &args) }
+    }
+}
+
+struct ResolvedStaticMethod {
+    class: GlobalRef,
+    id: JStaticMethodID,
+    args: Vec<JavaType>,
+    ret: ReturnType,
+}
+
+/// A static method, resolved on first call. This also keeps a global
+/// reference to the class.
+pub(crate) struct CachedStaticMethod {
+    class: &'static str,
+    name: &'static str,
+    sig: &'static str,
+    resolved: OnceLock<ResolvedStaticMethod>,
+}
+
+impl CachedStaticMethod {
+    pub(crate) const fn new(class: &'static str, name: &'static str, sig: &'static str) -> Self {
+        Self {
+            class,
+            name,
+            sig,
+            resolved: OnceLock::new(),
+        }
+    }
+
+    fn resolve(&self, env: &mut JNIEnv) -> &ResolvedStaticMethod {
+        self.resolved.get_or_init(|| {
+            let TypeSignature { args, ret } = TypeSignature::from_str(self.sig).unwrap();
+            let class = env.find_class(self.class);
+            let class = unwrap_or_report(env, class, || self.member());
+            let id = env.get_static_method_id(&class, self.name, self.sig);
+            let id = unwrap_or_report(env, id, || self.member());
+            let class = env.new_global_ref(class).unwrap();
+            ResolvedStaticMethod {
+                class,
+                id,
+                args,
+                ret,
+            }
+        })
+    }
+
+    fn member(&self) -> String {
+        format!("{}.{}", self.class, self.name)
+    }
+
+    pub(crate) fn call<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        args: &[JValue],
+    ) -> JValueOwned<'local> {
+        let result = self.try_call(env, args);
+        let value = unwrap_or_report(env, result, || self.member());
+        #[cfg(feature = "jni-checks")]
+        count_local_ref(&value);
+        value
+    }
+
+    /// Like [`CachedStaticMethod::call`], but returns an error, leaving
+    /// the exception pending, if the method throws.
+    pub(crate) fn try_call<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        args: &[JValue],
+    ) -> jni::errors::Result<JValueOwned<'local>> {
+        let resolved = self.resolve(env);
+        let args = check_args(&resolved.args, args, self.name);
+        let class = <&JClass>::from(resolved.class.as_obj());
+        // SAFETY: The ID was resolved from this signature on this class,
+        // and the arguments were checked against the signature above.
+        unsafe { env.call_static_method_unchecked(class, resolved.id, resolved.ret.clone(), &args) }
+    }
+}
+
+struct ResolvedField {
+    id: JFieldID,
+    ty: JavaType,
+}
+
+/// An instance field, resolved on first access.
+pub(crate) struct CachedField {
+    class: &'static str,
+    name: &'static str,
+    sig: &'static str,
+    resolved: OnceLock<ResolvedField>,
+}
+
+impl CachedField {
+    pub(crate) const fn new(class: &'static str, name: &'static str, sig: &'static str) -> Self {
+        Self {
+            class,
+            name,
+            sig,
+            resolved: OnceLock::new(),
+        }
+    }
+
+    fn resolve(&self, env: &mut JNIEnv) -> &ResolvedField {
+        self.resolved.get_or_init(|| {
+            let ty = JavaType::from_str(self.sig).unwrap();
+            let id = env.get_field_id(self.class, self.name, self.sig);
+            let id = unwrap_or_report(env, id, || self.member());
+            ResolvedField { id, ty }
+        })
+    }
+
+    fn member(&self) -> String {
+        format!("{}.{}", self.class, self.name)
+    }
+
+    pub(crate) fn get<'local>(
+        &self,
+        env: &mut JNIEnv<'local>,
+        obj: &JObject,
+    ) -> JValueOwned<'local> {
+        let resolved = self.resolve(env);
+        let ret = match &resolved.ty {
+            JavaType::Primitive(p) => ReturnType::Primitive(*p),
+            JavaType::Array(_) => ReturnType::Array,
+            _ => ReturnType::Object,
+        };
+        let result = env.get_field_unchecked(obj, resolved.id, ret);
+        let value = unwrap_or_report(env, result, || self.member());
+        #[cfg(feature = "jni-checks")]
+        count_local_ref(&value);
+        value
+    }
+
+    pub(crate) fn set(&self, env: &mut JNIEnv, obj: &JObject, value: JValue) {
+        let resolved = self.resolve(env);
+        check_args(std::slice::from_ref(&resolved.ty), &[value], self.name);
+        let result = env.set_field_unchecked(obj, resolved.id, value);
+        unwrap_or_report(env, result, || self.member())
+    }
+}
+
+/// Create a Java string, reporting a failure like the cached members do.
+pub(crate) fn new_java_string<'local>(env: &mut JNIEnv<'local>, s: &str) -> JString<'local> {
+    let result = env.new_string(s);
+    unwrap_or_report(env, result, || "java/lang/String.<init>".into())
+}
+
+/// Construct an object of `class`, reporting a failure like the cached
+/// members do.
+pub(crate) fn new_java_object<'local>(
+    env: &mut JNIEnv<'local>,
+    class: &str,
+    sig: &str,
+    args: &[JValue],
+) -> JObject<'local> {
+    let result = env.new_object(class, sig, args);
+    unwrap_or_report(env, result, || format!("{class}.<init>"))
+}
@@ -0,0 +1,165 @@
+//! Debug checks, enabled by the `jni-checks` feature, for JNI misuse
+//! that otherwise surfaces as a crash far from its cause.
+//!
+//! * A Java exception left pending when a callback returns is rethrown
+//!   by the VM on the Java side, with no hint of which Rust code caused
+//!   it. We log the exception and the callback before it's rethrown.
+//! * Local references made in a callback live until it returns, and
+//!   older devices abort when a callback holds more than 512 of them.
+//!   We count the object references returned by this crate's bindings in
+//!   each callback and warn when the count gets close to that limit,
+//!   which usually means a loop that should use `JNIEnv::with_local_frame`.
+//!   The count is an upper bound, since references deleted early are
+//!   still counted.
+//! * A peer is dropped when its view is detached, so objects such as
+//!   popups and camera previews created during its callbacks should be
+//!   released along with it. We warn about any that are still alive,
+//!   since they keep Java objects, and often the whole activity, from
+//!   being collected.
+
+use jni::{JNIEnv, objects::JString, sys::jlong};
+use std::{cell::Cell, collections::BTreeMap, panic::Location, sync::Mutex};
+
+const LOCAL_REF_WARNING_THRESHOLD: usize = 400;
+
+thread_local! {
+    static CURRENT_PEER: Cell<Option<jlong>> = const { Cell::new(None) };
+    static LOCAL_REFS: Cell<usize> = const { Cell::new(0) };
+    static NEXT_CALLBACK_NAME: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+// Global references can be dropped on any thread, so the live counts
+// are shared, keyed by peer and then by the kind of object.
+static LIVE_GLOBAL_REFS: Mutex<BTreeMap<jlong, BTreeMap<&'static str, usize>>> =
+    Mutex::new(BTreeMap::new());
+
+/// Record that one of the crate's bindings returned a local reference.
+pub(crate) fn count_local_ref() {
+    LOCAL_REFS.set(LOCAL_REFS.get() + 1);
+}
+
+/// Name the callback that's about to enter, for shims generated by
+/// `peer_callbacks!`, whose caller location would otherwise point at
+/// the macro invocation rather than the callback.
+pub(crate) fn set_next_callback_name(name: &'static str) {
+    NEXT_CALLBACK_NAME.set(Some(name));
+}
+
+pub(crate) fn take_next_callback_name() -> Option<&'static str> {
+    NEXT_CALLBACK_NAME.take()
+}
+
+/// The checks for one peer callback, from entry until just before it
+/// returns to Java. Callbacks can nest, such as when a peer call makes
+/// Java call back into the crate, so the outer callback's state is
+/// saved and restored.
+pub(crate) struct CallbackChecks<'local> {
+    env: JNIEnv<'local>,
+    name: Option<&'static str>,
+    outer_peer: Option<jlong>,
+    outer_local_refs: usize,
+}
+
+impl<'local> CallbackChecks<'local> {
+    pub(crate) fn enter(env: &JNIEnv<'local>, peer: jlong, name: Option<&'static str>) -> Self {
+        Self {
+            // SAFETY: The clone is only used on this thread, within the
+            // callback that `env` was passed to.
+            env: unsafe { env.unsafe_clone() },
+            name,
+            outer_peer: CURRENT_PEER.replace(Some(peer)),
+            outer_local_refs: LOCAL_REFS.replace(0),
+        }
+    }
+
+    pub(crate) fn finish(mut self, location: &Location) {
+        let location = match self.name {
+            Some(name) => name.to_string(),
+            None => location.to_string(),
+        };
+        let local_refs = LOCAL_REFS.replace(self.outer_local_refs);
+        CURRENT_PEER.set(self.outer_peer);
+        if local_refs >= LOCAL_REF_WARNING_THRESHOLD {
+            log::warn!(
+                target: "android-view",
+                "callback {location} created up to {local_refs} local references; \
+                 use JNIEnv::with_local_frame in loops that call into Java",
+            );
+        }
+        if self.env.exception_check().unwrap_or(false) {
+            let Ok(exception) = self.env.exception_occurred() else {
+                return;
+            };
+            let _ = self.env.exception_clear();
+            let description = self
+                .env
+                .call_method(&exception, "toString", "()Ljava/lang/String;", &[])
+                .and_then(|value| value.l())
+                .ok()
+                .and_then(|string| {
+                    let string = JString::from(string);
+                    self.env.get_string(&string).ok().map(String::from)
+                })
+                .unwrap_or_default();
+            log::error!(
+                target: "android-view",
+                "callback {location} returned with a pending Java exception: {description}",
+            );
+            let _ = self.env.throw(exception);
+        }
+    }
+}
+
+/// A live global reference created during a callback for a peer.
+pub(crate) struct GlobalRefTracker {
+    peer: jlong,
+    kind: &'static str,
+}
+
+impl GlobalRefTracker {
+    /// Start tracking a global reference to an object of the given kind,
+    /// if it's being created during a peer callback.
+    pub(crate) fn new(kind: &'static str) -> Option<Self> {
+        let peer = CURRENT_PEER.get()?;
+        let mut live = LIVE_GLOBAL_REFS.lock().unwrap();
+        *live.entry(peer).or_default().entry(kind).or_default() += 1;
+        Some(Self { peer, kind })
+    }
+}
+
+impl Drop for GlobalRefTracker {
+    fn drop(&mut self) {
+        let mut live = LIVE_GLOBAL_REFS.lock().unwrap();
+        let Some(kinds) = live.get_mut(&self.peer) else {
+            return;
+        };
+        if let Some(count) = kinds.get_mut(self.kind) {
+            *count -= 1;
+            if *count == 0 {
+                kinds.remove(self.kind);
+            }
+        }
+        if kinds.is_empty() {
+            live.remove(&self.peer);
+        }
+    }
+}
+
+/// Check that a detached peer, which has been dropped, didn't leave
+/// global references behind.
+pub(crate) fn check_peer_released(peer: jlong) {
+    let live = LIVE_GLOBAL_REFS.lock().unwrap();
+    let Some(kinds) = live.get(&peer) else {
+        return;
+    };
+    let summary = kinds
+        .iter()
+        .map(|(kind, count)| format!("{count} {kind}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    log::warn!(
+        target: "android-view",
+        "peer {peer} was detached, but global references created in its callbacks \
+         are still alive: {summary}",
+    );
+}
@@ -0,0 +1,72 @@
+use jni::{JNIEnv, objects::JObject, sys::jint};
+use ndk::event::{Keycode, MetaState};
+
+use crate::jni_cache::*;
+
+/// Set in a value returned by [`KeyCharacterMap::get`] when the key is a
+/// dead key; the rest of the value is the combining accent.
+pub const KEY_CHARACTER_MAP_COMBINING_ACCENT: u32 = 0x80000000;
+pub const KEY_CHARACTER_MAP_COMBINING_ACCENT_MASK: u32 = 0x7fffffff;
+
+pub const KEYBOARD_TYPE_NUMERIC: jint = 1;
+pub const KEYBOARD_TYPE_PREDICTIVE: jint = 2;
+pub const KEYBOARD_TYPE_ALPHA: jint = 3;
+pub const KEYBOARD_TYPE_FULL: jint = 4;
+pub const KEYBOARD_TYPE_SPECIAL_FUNCTION: jint = 5;
+
+/// A binding to `android.view.KeyCharacterMap`, which maps keys to
+/// characters according to the keyboard layout the user has selected
+/// for the device.
+#[repr(transparent)]
+pub struct KeyCharacterMap<'local>(pub JObject<'local>);
+
+impl<'local> KeyCharacterMap<'local> {
+    /// Load the map for an input device, as given by
+    /// [`crate::KeyEvent::device_id`].
+    pub fn load(env: &mut JNIEnv<'local>, device_id: jint) -> Self {
+        static LOAD: CachedStaticMethod = CachedStaticMethod::new(
+            "android/view/KeyCharacterMap",
+            "load",
+            "(I)Landroid/view/KeyCharacterMap;",
+        );
+        Self(LOAD.call(env, &[device_id.into()]).l().unwrap())
+    }
+
+    /// The character for a key with the given meta state, 0 if none, or
+    /// a combining accent with [`KEY_CHARACTER_MAP_COMBINING_ACCENT`]
+    /// set if the key is a dead key.
+    pub fn get(&self, env: &mut JNIEnv<'local>, key_code: Keycode, meta_state: MetaState) -> u32 {
+        static GET: CachedMethod =
+            CachedMethod::new("android/view/KeyCharacterMap", "get", "(II)I");
+        GET.call(
+            env,
+            &self.0,
+            &[i32::from(key_code).into(), (meta_state.0 as jint).into()],
+        )
+        .i()
+        .unwrap() as u32
+    }
+
+    /// One of the `KEYBOARD_TYPE_*` constants.
+    pub fn keyboard_type(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_KEYBOARD_TYPE: CachedMethod =
+            CachedMethod::new("android/view/KeyCharacterMap", "getKeyboardType", "()I");
+        GET_KEYBOARD_TYPE.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    /// Combine a combining accent from a dead key with the following
+    /// character, e.g. U+0301 and 'e' give 'é'. Returns `None` if the
+    /// combination isn't defined.
+    pub fn dead_char(env: &mut JNIEnv<'local>, accent: u32, c: char) -> Option<char> {
+        static GET_DEAD_CHAR: CachedStaticMethod =
+            CachedStaticMethod::new("android/view/KeyCharacterMap", "getDeadChar", "(II)I");
+        let result = GET_DEAD_CHAR
+            .call(env, &[(accent as jint).into(), (c as jint).into()])
+            .i()
+            .unwrap();
+        if result <= 0 {
+            return None;
+        }
+        char::from_u32(result as _)
+    }
+}
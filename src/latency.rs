@@ -0,0 +1,119 @@
+use jni::{JNIEnv, sys::jlong};
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{events::*, time::*, util::*};
+
+/// Latency percentiles reported by [`InputLatencyTracker::report`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// The number of samples the percentiles were computed from.
+    pub samples: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Measures the time from input events to the submission of the frame
+/// that first reflects them, as an approximation of touch-to-photon
+/// latency that excludes only the compositor and display.
+///
+/// Call [`InputLatencyTracker::record_motion_event`] for each input event
+/// that will change the rendered content, and
+/// [`InputLatencyTracker::frame_submitted`] right after presenting each
+/// frame. Every event recorded since the previous frame produces one
+/// sample. Only the most recent samples are kept, up to the capacity.
+pub struct InputLatencyTracker {
+    pending: Vec<jlong>,
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl InputLatencyTracker {
+    /// Create a tracker that keeps up to `capacity` samples, which must
+    /// not be zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must not be zero");
+        Self {
+            pending: Vec::new(),
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record an event with a timestamp in the `System.nanoTime` time
+    /// base.
+    pub fn record_event_nanos(&mut self, event_time_nanos: jlong) {
+        self.pending.push(event_time_nanos);
+    }
+
+    /// Record a motion event, using its nanosecond timestamp where the
+    /// platform provides one (API level 34 and higher), and its
+    /// millisecond timestamp otherwise.
+    pub fn record_motion_event<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        event: &MotionEvent<'local>,
+    ) {
+        let nanos = if android_sdk_version(env) >= 34 {
+            event.event_time_nanos(env)
+        } else {
+            event.event_time(env) * 1_000_000
+        };
+        self.record_event_nanos(nanos);
+    }
+
+    /// Record that a frame was submitted at the given time, in the
+    /// `System.nanoTime` time base, producing a sample for each event
+    /// recorded since the previous frame.
+    pub fn frame_submitted_at(&mut self, submit_time_nanos: jlong) {
+        for event_time_nanos in self.pending.drain(..) {
+            let latency = (submit_time_nanos - event_time_nanos).max(0) as u64;
+            if self.samples.len() == self.capacity {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(Duration::from_nanos(latency));
+        }
+    }
+
+    /// Record that a frame was just submitted.
+    pub fn frame_submitted(&mut self, env: &mut JNIEnv) {
+        let now = monotonic_now_nanos(env);
+        self.frame_submitted_at(now);
+    }
+
+    /// Return the latency below which the given fraction of samples
+    /// fall, where `fraction` is between 0 and 1, or `None` if there are
+    /// no samples.
+    pub fn percentile(&self, fraction: f64) -> Option<Duration> {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        percentile_of_sorted(&sorted, fraction)
+    }
+
+    /// Summarize the recorded samples, or return `None` if there are
+    /// none.
+    pub fn report(&self) -> Option<LatencyReport> {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        Some(LatencyReport {
+            samples: sorted.len(),
+            p50: percentile_of_sorted(&sorted, 0.5)?,
+            p90: percentile_of_sorted(&sorted, 0.9)?,
+            p99: percentile_of_sorted(&sorted, 0.99)?,
+            max: *sorted.last()?,
+        })
+    }
+
+    /// Discard all samples and pending events.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.samples.clear();
+    }
+}
+
+fn percentile_of_sorted(sorted: &[Duration], fraction: f64) -> Option<Duration> {
+    let last = sorted.len().checked_sub(1)?;
+    let index = (fraction.clamp(0.0, 1.0) * last as f64).round() as usize;
+    Some(sorted[index])
+}
@@ -0,0 +1,166 @@
+use jni::{JNIEnv, objects::JObject, sys::jint};
+use num_enum::{FromPrimitive, IntoPrimitive};
+
+use crate::jni_cache::*;
+
+/// The mode of a [`MeasureSpec`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum MeasureSpecMode {
+    /// The parent imposes no constraint on the size.
+    #[num_enum(default)]
+    Unspecified = 0,
+    /// The view must be exactly the given size.
+    Exactly = 1 << 30,
+    /// The view may be as large as it wants up to the given size.
+    AtMost = i32::MIN,
+}
+
+/// A measure spec as passed to [`crate::ViewPeer::on_measure`], which
+/// packs a [`MeasureSpecMode`] and a size in pixels into one int.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MeasureSpec(pub jint);
+
+impl MeasureSpec {
+    const MODE_MASK: jint = -1 << 30;
+
+    pub fn new(size: jint, mode: MeasureSpecMode) -> Self {
+        Self((size & !Self::MODE_MASK) | (jint::from(mode) & Self::MODE_MASK))
+    }
+
+    pub fn mode(self) -> MeasureSpecMode {
+        MeasureSpecMode::from_primitive(self.0 & Self::MODE_MASK)
+    }
+
+    /// The size in pixels. This is meaningless if the mode is
+    /// [`MeasureSpecMode::Unspecified`].
+    pub fn size(self) -> jint {
+        self.0 & !Self::MODE_MASK
+    }
+
+    /// The largest size the view may have, or `None` if unconstrained.
+    pub fn max_size(self) -> Option<jint> {
+        match self.mode() {
+            MeasureSpecMode::Unspecified => None,
+            MeasureSpecMode::Exactly | MeasureSpecMode::AtMost => Some(self.size()),
+        }
+    }
+}
+
+impl From<MeasureSpec> for jint {
+    fn from(spec: MeasureSpec) -> Self {
+        spec.0
+    }
+}
+
+/// Equivalent to `View.MeasureSpec.makeMeasureSpec`.
+pub fn make_measure_spec(size: jint, mode: MeasureSpecMode) -> MeasureSpec {
+    MeasureSpec::new(size, mode)
+}
+
+/// Bits of a measured size that hold the size itself.
+pub const MEASURED_SIZE_MASK: jint = 0x00ffffff;
+/// Bits of a measured size that hold state flags.
+pub const MEASURED_STATE_MASK: jint = -0x01000000;
+/// Set in a measured size when the view wanted to be larger than its
+/// parent allowed.
+pub const MEASURED_STATE_TOO_SMALL: jint = 0x01000000;
+/// The shift that moves height state bits into the width state position,
+/// for combining a child's states in one int.
+pub const MEASURED_HEIGHT_STATE_SHIFT: u32 = 16;
+
+/// Equivalent to `View.resolveSizeAndState`: reconcile the size the view
+/// wants with the constraint in `spec`. The result includes
+/// [`MEASURED_STATE_TOO_SMALL`] if the view had to be made smaller, plus
+/// any state bits from `child_measured_state`, and can be returned from
+/// [`crate::ViewPeer::on_measure`] as is.
+pub fn resolve_size_and_state(size: jint, spec: MeasureSpec, child_measured_state: jint) -> jint {
+    let spec_size = spec.size();
+    let result = match spec.mode() {
+        MeasureSpecMode::AtMost if spec_size < size => spec_size | MEASURED_STATE_TOO_SMALL,
+        MeasureSpecMode::AtMost | MeasureSpecMode::Unspecified => size,
+        MeasureSpecMode::Exactly => spec_size,
+    };
+    result | (child_measured_state & MEASURED_STATE_MASK)
+}
+
+/// Like [`resolve_size_and_state`], but without the state bits.
+pub fn resolve_size(size: jint, spec: MeasureSpec) -> jint {
+    resolve_size_and_state(size, spec, 0) & MEASURED_SIZE_MASK
+}
+
+/// Equivalent to `View.getDefaultSize`, which the default `onMeasure` uses:
+/// `size` if unconstrained, otherwise the size from the spec. Note that
+/// unlike [`resolve_size`], this fills an `AT_MOST` spec completely.
+pub fn default_size(size: jint, spec: MeasureSpec) -> jint {
+    match spec.mode() {
+        MeasureSpecMode::Unspecified => size,
+        MeasureSpecMode::AtMost | MeasureSpecMode::Exactly => spec.size(),
+    }
+}
+
+/// Measure a view that has a natural content size, such as text, the way
+/// framework widgets do: the desired size is used where the parent allows
+/// it, and clamped, with [`MEASURED_STATE_TOO_SMALL`] set, where it
+/// doesn't. The minimum size, e.g. from `getSuggestedMinimumWidth`, is
+/// applied first. The result can be returned from
+/// [`crate::ViewPeer::on_measure`].
+pub fn measure_desired_size(
+    desired: (jint, jint),
+    min: (jint, jint),
+    width_spec: MeasureSpec,
+    height_spec: MeasureSpec,
+) -> (jint, jint) {
+    (
+        resolve_size_and_state(desired.0.max(min.0), width_spec, 0),
+        resolve_size_and_state(desired.1.max(min.1), height_spec, 0),
+    )
+}
+
+/// A width or height in [`LayoutParams`] that fills the parent.
+pub const LAYOUT_PARAMS_MATCH_PARENT: jint = -1;
+/// A width or height in [`LayoutParams`] that fits the content.
+pub const LAYOUT_PARAMS_WRAP_CONTENT: jint = -2;
+
+/// A binding to `android.view.ViewGroup.LayoutParams`. Changes take
+/// effect when the params are set on the view again, e.g. with
+/// [`crate::View::set_layout_params`].
+#[repr(transparent)]
+pub struct LayoutParams<'local>(pub JObject<'local>);
+
+impl<'local> LayoutParams<'local> {
+    /// Create params with the given width and height, each either a size
+    /// in pixels or one of the `LAYOUT_PARAMS_*` constants.
+    pub fn new(env: &mut JNIEnv<'local>, width: jint, height: jint) -> Self {
+        Self(new_java_object(
+            env,
+            "android/view/ViewGroup$LayoutParams",
+            "(II)V",
+            &[width.into(), height.into()],
+        ))
+    }
+
+    pub fn width(&self, env: &mut JNIEnv<'local>) -> jint {
+        static WIDTH: CachedField =
+            CachedField::new("android/view/ViewGroup$LayoutParams", "width", "I");
+        WIDTH.get(env, &self.0).i().unwrap()
+    }
+
+    pub fn set_width(&self, env: &mut JNIEnv<'local>, width: jint) {
+        static WIDTH: CachedField =
+            CachedField::new("android/view/ViewGroup$LayoutParams", "width", "I");
+        WIDTH.set(env, &self.0, width.into());
+    }
+
+    pub fn height(&self, env: &mut JNIEnv<'local>) -> jint {
+        static HEIGHT: CachedField =
+            CachedField::new("android/view/ViewGroup$LayoutParams", "height", "I");
+        HEIGHT.get(env, &self.0).i().unwrap()
+    }
+
+    pub fn set_height(&self, env: &mut JNIEnv<'local>, height: jint) {
+        static HEIGHT: CachedField =
+            CachedField::new("android/view/ViewGroup$LayoutParams", "height", "I");
+        HEIGHT.set(env, &self.0, height.into());
+    }
+}
@@ -1,28 +1,208 @@
+//! Implement an Android view in Rust.
+//!
+//! The core view, surface and event plumbing is always available. Larger
+//! optional subsystems are behind cargo features, all enabled by default:
+//!
+//! * `ime`: text input through `InputConnection`, plus the editing
+//!   helpers built on it, such as [`CompositionState`].
+//! * `accessibility`: exposing virtual views to accessibility services
+//!   through `AccessibilityNodeProvider`.
+//! * `graphics`: bitmaps, software rendering through
+//!   [`SurfaceHolder::lock_canvas`], pixel copies of the surface, and
+//!   damage tracking with [`DirtyRegion`]. The geometry types, such as
+//!   [`IntRect`], are always available.
+//!
+//! The `thread-checks` feature, off by default, adds debug checks that
+//! peer callbacks and calls into the view happen on the UI thread, and
+//! logs a clear diagnostic through the `log` crate when they don't.
+//!
+//! The `jni-checks` feature, also off by default, adds debug checks for
+//! Java exceptions left pending by callbacks, callbacks that create too
+//! many local references, and global references that outlive their peer,
+//! and logs each problem it finds with the callback involved.
+//!
+//! The `trace` feature, also off by default, can log summaries of
+//! touch, key, IME, surface and accessibility callbacks, selected at run
+//! time with a system property, to debug problems on users' devices.
+//!
+//! The `kurbo` feature, also off by default, adds conversions between
+//! the geometry types in this crate, such as [`RectF`], and their
+//! `kurbo` equivalents.
+//!
+//! The `capi` feature, also off by default, exports a C interface in the
+//! [`capi`] module, so that view peers can be written in other native
+//! languages, such as C++ or Zig, while this crate handles JNI.
+//!
+//! The `raw-window-handle` feature, also off by default, adds
+//! [`ViewWindow`] and [`WindowAdapter`], for hosting code written against
+//! winit's Android backend.
+//!
+//! Apps that only need to render to a surface and handle touch and key
+//! events can disable default features to reduce compile time and code
+//! size. The [`prelude`] module re-exports the most commonly used items.
+//!
+//! Apps built around their own event loop can use [`event_stream()`]
+//! rather than implementing [`ViewPeer`], and receive owned
+//! [`ViewEvent`]s from an [`EventStream`].
+
 #![deny(unsafe_op_in_unsafe_fn)]
 
 pub use jni;
 pub use ndk;
 
+#[cfg(feature = "accessibility")]
 mod accessibility;
+#[cfg(feature = "accessibility")]
 pub use accessibility::*;
+mod animation;
+pub use animation::*;
 mod binder;
 pub use binder::*;
 mod bundle;
 pub use bundle::*;
 mod callback_ctx;
 pub use callback_ctx::*;
+mod camera;
+pub use camera::*;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod channel;
+pub use channel::*;
+mod color_space;
+pub use color_space::*;
+#[cfg(feature = "ime")]
+mod composition;
+#[cfg(feature = "ime")]
+pub use composition::*;
+mod connectivity;
+pub use connectivity::*;
+mod content;
+pub use content::*;
 mod context;
 pub use context::*;
+mod coordinates;
+pub use coordinates::*;
+#[cfg(feature = "graphics")]
+mod damage;
+#[cfg(feature = "graphics")]
+pub use damage::*;
+mod dead_keys;
+pub use dead_keys::*;
+pub mod diagnostics;
+mod display;
+pub use display::*;
+mod display_preferences;
+pub use display_preferences::*;
+#[cfg(feature = "ime")]
+mod emoji;
+#[cfg(feature = "ime")]
+pub use emoji::*;
+mod event_stream;
+pub use event_stream::*;
 mod events;
 pub use events::*;
+mod fold;
+pub use fold::*;
+mod frame_metrics;
+pub use frame_metrics::*;
+mod geometry;
+pub use geometry::*;
+#[cfg(feature = "ime")]
+mod grapheme;
+#[cfg(feature = "ime")]
+pub use grapheme::*;
+#[cfg(feature = "graphics")]
 mod graphics;
+#[cfg(feature = "graphics")]
 pub use graphics::*;
+#[cfg(feature = "ime")]
 mod ime;
+#[cfg(feature = "ime")]
 pub use ime::*;
+#[cfg(feature = "ime")]
+mod ime_quirks;
+#[cfg(feature = "ime")]
+pub use ime_quirks::*;
+#[cfg(feature = "ime")]
+mod input_format;
+#[cfg(feature = "ime")]
+pub use input_format::*;
+mod input_device;
+pub use input_device::*;
+mod insets;
+pub use insets::*;
+pub mod instrumentation;
+mod intent;
+pub use intent::*;
+mod jni_cache;
+#[cfg(feature = "jni-checks")]
+mod jni_checks;
+mod key_character_map;
+pub use key_character_map::*;
+mod latency;
+pub use latency::*;
+mod layout;
+pub use layout::*;
+mod media;
+pub use media::*;
+mod meta_keys;
+pub use meta_keys::*;
+mod native_methods;
+mod platform_views;
+pub use platform_views::*;
+mod popup;
+pub use popup::*;
+mod power;
+pub use power::*;
+pub mod prelude;
+mod process_state;
+pub use process_state::*;
+mod scheduler;
+pub use scheduler::*;
+mod speech;
+pub use speech::*;
 mod surface;
 pub use surface::*;
+mod surface_control;
+pub use surface_control::*;
+mod tasks;
+pub use tasks::*;
+mod text_boundaries;
+pub use text_boundaries::*;
+#[cfg(feature = "ime")]
+mod text_cache;
+mod text_direction;
+pub use text_direction::*;
+#[cfg(feature = "thread-checks")]
+mod thread_checks;
+mod time;
+pub use time::*;
+#[cfg(feature = "trace")]
+mod trace;
+#[cfg(feature = "trace")]
+pub use trace::*;
+mod tts;
+pub use tts::*;
+mod user_dictionary;
+pub use user_dictionary::*;
 mod util;
 mod view;
 pub use view::*;
+mod view_config;
+pub use view_config::*;
 mod view_configuration;
 pub use view_configuration::*;
+mod view_events;
+pub use view_events::*;
+mod view_factory;
+pub use view_factory::*;
+mod view_group;
+pub use view_group::*;
+mod view_state;
+pub use view_state::*;
+#[cfg(feature = "raw-window-handle")]
+mod window_adapter;
+#[cfg(feature = "raw-window-handle")]
+pub use window_adapter::*;
+mod window_manager;
+pub use window_manager::*;
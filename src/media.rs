@@ -0,0 +1,267 @@
+use jni::{
+    JNIEnv,
+    objects::{GlobalRef, JObject},
+};
+use ndk::event::Keycode;
+
+use crate::{jni_cache::*, surface::*};
+
+/// The kind of player a [`VideoSurfaceBinding`] drives, which determines
+/// how the surface is handed to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VideoPlayerKind {
+    /// `android.media.MediaPlayer`, using `setSurface`.
+    MediaPlayer,
+    /// Any `androidx.media3.common.Player`, such as ExoPlayer, using
+    /// `setVideoSurface`. The app must depend on Media3 itself.
+    Media3,
+}
+
+/// Keeps a video player attached to the view's surface across the surface's
+/// lifecycle. Forward [`ViewPeer::surface_created`] and
+/// [`ViewPeer::surface_destroyed`] to the methods of the same name; the
+/// player is attached whenever a surface exists, including when the
+/// surface is recreated after the app returns from the background,
+/// and detached before the surface is destroyed.
+///
+/// This holds global references, so it can be kept in a peer across
+/// callbacks.
+///
+/// [`ViewPeer::surface_created`]: crate::ViewPeer::surface_created
+/// [`ViewPeer::surface_destroyed`]: crate::ViewPeer::surface_destroyed
+pub struct VideoSurfaceBinding {
+    player: Option<(GlobalRef, VideoPlayerKind)>,
+    surface: Option<GlobalRef>,
+}
+
+impl VideoSurfaceBinding {
+    pub fn new() -> Self {
+        Self {
+            player: None,
+            surface: None,
+        }
+    }
+
+    /// Set the player to attach, detaching the previous one, if any.
+    /// If a surface already exists, the new player is attached immediately.
+    pub fn set_player<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        player: Option<(&JObject<'local>, VideoPlayerKind)>,
+    ) {
+        if let Some((old, kind)) = self.player.take() {
+            set_player_surface(env, old.as_obj(), kind, &JObject::null());
+        }
+        self.player = player.map(|(player, kind)| (env.new_global_ref(player).unwrap(), kind));
+        if let (Some((player, kind)), Some(surface)) = (&self.player, &self.surface) {
+            set_player_surface(env, player.as_obj(), *kind, surface.as_obj());
+        }
+    }
+
+    pub fn surface_created<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        holder: &SurfaceHolder<'local>,
+    ) {
+        let surface = holder.surface(env);
+        if let Some((player, kind)) = &self.player {
+            set_player_surface(env, player.as_obj(), *kind, &surface.0);
+        }
+        self.surface = Some(env.new_global_ref(&surface.0).unwrap());
+    }
+
+    /// Detach the player. This must be called before `surface_destroyed`
+    /// returns, since the player may not use the surface afterward.
+    pub fn surface_destroyed(&mut self, env: &mut JNIEnv) {
+        if let Some((player, kind)) = &self.player {
+            set_player_surface(env, player.as_obj(), *kind, &JObject::null());
+        }
+        self.surface = None;
+    }
+}
+
+impl Default for VideoSurfaceBinding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn set_player_surface(
+    env: &mut JNIEnv,
+    player: &JObject,
+    kind: VideoPlayerKind,
+    surface: &JObject,
+) {
+    match kind {
+        VideoPlayerKind::MediaPlayer => {
+            static SET_SURFACE: CachedMethod = CachedMethod::new(
+                "android/media/MediaPlayer",
+                "setSurface",
+                "(Landroid/view/Surface;)V",
+            );
+            SET_SURFACE
+                .call(env, player, &[surface.into()])
+                .v()
+                .unwrap()
+        }
+        VideoPlayerKind::Media3 => {
+            // Media3 is optional, so this isn't cached.
+            env.call_method(
+                player,
+                "setVideoSurface",
+                "(Landroid/view/Surface;)V",
+                &[surface.into()],
+            )
+            .unwrap()
+            .v()
+            .unwrap()
+        }
+    }
+}
+
+/// A media transport key, as sent by TV remotes, headsets and media
+/// keyboards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaKey {
+    Play,
+    Pause,
+    PlayPause,
+    Stop,
+    Next,
+    Previous,
+    FastForward,
+    Rewind,
+    SkipForward,
+    SkipBackward,
+    StepForward,
+    StepBackward,
+    Record,
+}
+
+impl MediaKey {
+    pub fn from_keycode(key_code: Keycode) -> Option<Self> {
+        Some(match key_code {
+            Keycode::MediaPlay => Self::Play,
+            Keycode::MediaPause => Self::Pause,
+            Keycode::MediaPlayPause => Self::PlayPause,
+            Keycode::MediaStop => Self::Stop,
+            Keycode::MediaNext => Self::Next,
+            Keycode::MediaPrevious => Self::Previous,
+            Keycode::MediaFastForward => Self::FastForward,
+            Keycode::MediaRewind => Self::Rewind,
+            Keycode::MediaSkipForward => Self::SkipForward,
+            Keycode::MediaSkipBackward => Self::SkipBackward,
+            Keycode::MediaStepForward => Self::StepForward,
+            Keycode::MediaStepBackward => Self::StepBackward,
+            Keycode::MediaRecord => Self::Record,
+            _ => return None,
+        })
+    }
+
+    pub fn to_keycode(self) -> Keycode {
+        match self {
+            Self::Play => Keycode::MediaPlay,
+            Self::Pause => Keycode::MediaPause,
+            Self::PlayPause => Keycode::MediaPlayPause,
+            Self::Stop => Keycode::MediaStop,
+            Self::Next => Keycode::MediaNext,
+            Self::Previous => Keycode::MediaPrevious,
+            Self::FastForward => Keycode::MediaFastForward,
+            Self::Rewind => Keycode::MediaRewind,
+            Self::SkipForward => Keycode::MediaSkipForward,
+            Self::SkipBackward => Keycode::MediaSkipBackward,
+            Self::StepForward => Keycode::MediaStepForward,
+            Self::StepBackward => Keycode::MediaStepBackward,
+            Self::Record => Keycode::MediaRecord,
+        }
+    }
+
+    /// Whether this key starts, stops or toggles playback.
+    pub fn is_play_pause(self) -> bool {
+        matches!(
+            self,
+            Self::Play | Self::Pause | Self::PlayPause | Self::Stop
+        )
+    }
+
+    /// Whether this key seeks within the current item.
+    pub fn is_seek(self) -> bool {
+        matches!(
+            self,
+            Self::FastForward
+                | Self::Rewind
+                | Self::SkipForward
+                | Self::SkipBackward
+                | Self::StepForward
+                | Self::StepBackward
+        )
+    }
+
+    /// Whether this key moves to another item.
+    pub fn is_track_change(self) -> bool {
+        matches!(self, Self::Next | Self::Previous)
+    }
+}
+
+/// A minimal binding to `android.media.MediaPlayer`, for simple playback
+/// into a [`VideoSurfaceBinding`].
+#[repr(transparent)]
+pub struct MediaPlayer<'local>(pub JObject<'local>);
+
+impl<'local> MediaPlayer<'local> {
+    pub fn new(env: &mut JNIEnv<'local>) -> Self {
+        Self(new_java_object(
+            env,
+            "android/media/MediaPlayer",
+            "()V",
+            &[],
+        ))
+    }
+
+    /// Set the path or URL to play.
+    pub fn set_data_source(&self, env: &mut JNIEnv<'local>, path: &str) {
+        static SET_DATA_SOURCE: CachedMethod = CachedMethod::new(
+            "android/media/MediaPlayer",
+            "setDataSource",
+            "(Ljava/lang/String;)V",
+        );
+        let path = new_java_string(env, path);
+        SET_DATA_SOURCE
+            .call(env, &self.0, &[(&path).into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn set_looping(&self, env: &mut JNIEnv<'local>, looping: bool) {
+        static SET_LOOPING: CachedMethod =
+            CachedMethod::new("android/media/MediaPlayer", "setLooping", "(Z)V");
+        SET_LOOPING
+            .call(env, &self.0, &[looping.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Prepare the player synchronously. Avoid this for network sources,
+    /// since it blocks until enough data is buffered.
+    pub fn prepare(&self, env: &mut JNIEnv<'local>) {
+        static PREPARE: CachedMethod =
+            CachedMethod::new("android/media/MediaPlayer", "prepare", "()V");
+        PREPARE.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    pub fn start(&self, env: &mut JNIEnv<'local>) {
+        static START: CachedMethod = CachedMethod::new("android/media/MediaPlayer", "start", "()V");
+        START.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    pub fn pause(&self, env: &mut JNIEnv<'local>) {
+        static PAUSE: CachedMethod = CachedMethod::new("android/media/MediaPlayer", "pause", "()V");
+        PAUSE.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    pub fn release(&self, env: &mut JNIEnv<'local>) {
+        static RELEASE: CachedMethod =
+            CachedMethod::new("android/media/MediaPlayer", "release", "()V");
+        RELEASE.call(env, &self.0, &[]).v().unwrap()
+    }
+}
@@ -0,0 +1,149 @@
+//! Sticky and locked modifier tracking, equivalent to the platform's
+//! `MetaKeyKeyListener`.
+//!
+//! Some keyboards, and accessibility setups for users who can't hold
+//! several keys at once, deliver modifiers as separate key presses.
+//! Pressing and releasing a modifier on its own latches it for the next
+//! key; pressing it twice in a row locks it until it's pressed again.
+//! Holding a modifier while pressing another key works as usual.
+
+use jni::{JNIEnv, sys::jint};
+use ndk::event::{KeyAction, Keycode, MetaState};
+
+use crate::events::KeyEvent;
+
+pub const META_SHIFT_ON: u32 = 0x1;
+pub const META_ALT_ON: u32 = 0x2;
+pub const META_SYM_ON: u32 = 0x4;
+pub const META_CTRL_ON: u32 = 0x1000;
+pub const META_META_ON: u32 = 0x10000;
+
+const MODIFIERS: [u32; 5] = [
+    META_SHIFT_ON,
+    META_ALT_ON,
+    META_SYM_ON,
+    META_CTRL_ON,
+    META_META_ON,
+];
+
+fn modifier_index(key_code: Keycode) -> Option<usize> {
+    let flag = match key_code {
+        Keycode::ShiftLeft | Keycode::ShiftRight => META_SHIFT_ON,
+        Keycode::AltLeft | Keycode::AltRight => META_ALT_ON,
+        Keycode::Sym => META_SYM_ON,
+        Keycode::CtrlLeft | Keycode::CtrlRight => META_CTRL_ON,
+        Keycode::MetaLeft | Keycode::MetaRight => META_META_ON,
+        _ => return None,
+    };
+    MODIFIERS.iter().position(|&m| m == flag)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Latch {
+    #[default]
+    Off,
+    /// Held down, with no other key pressed yet.
+    Pressed,
+    /// Held down while another key was pressed, so it won't latch.
+    PressedAndUsed,
+    /// Released without being used; applies to the next key.
+    Sticky,
+    Locked,
+}
+
+/// Tracks the latched state of the Shift, Alt, Sym, Ctrl and Meta
+/// modifiers across key events.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetaKeyTracker {
+    latches: [Latch; MODIFIERS.len()],
+}
+
+impl MetaKeyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a key press. Returns true if the key is a modifier, in
+    /// which case it shouldn't be handled further.
+    pub fn key_down(&mut self, key_code: Keycode) -> bool {
+        let Some(index) = modifier_index(key_code) else {
+            for latch in &mut self.latches {
+                match *latch {
+                    Latch::Pressed => *latch = Latch::PressedAndUsed,
+                    Latch::Sticky => *latch = Latch::Off,
+                    _ => {}
+                }
+            }
+            return false;
+        };
+        let latch = &mut self.latches[index];
+        *latch = match *latch {
+            Latch::Off => Latch::Pressed,
+            Latch::Sticky => Latch::Locked,
+            Latch::Locked => Latch::Off,
+            held => held,
+        };
+        true
+    }
+
+    /// Record a key release. Returns true if the key is a modifier.
+    pub fn key_up(&mut self, key_code: Keycode) -> bool {
+        let Some(index) = modifier_index(key_code) else {
+            return false;
+        };
+        let latch = &mut self.latches[index];
+        *latch = match *latch {
+            Latch::Pressed => Latch::Sticky,
+            Latch::PressedAndUsed => Latch::Off,
+            other => other,
+        };
+        true
+    }
+
+    /// The `META_*_ON` flags of the modifiers that are held, latched or
+    /// locked.
+    pub fn active_modifiers(&self) -> u32 {
+        MODIFIERS
+            .iter()
+            .zip(self.latches)
+            .filter(|(_, latch)| *latch != Latch::Off)
+            .fold(0, |flags, (&flag, _)| flags | flag)
+    }
+
+    /// Combine the tracked modifiers with the meta state reported by an
+    /// event.
+    pub fn meta_state(&self, event_meta_state: MetaState) -> MetaState {
+        MetaState(event_meta_state.0 | self.active_modifiers())
+    }
+
+    /// Update the tracker for a key event and return the effective meta
+    /// state for it. Returns `None` if the event is for a modifier key,
+    /// which the tracker has consumed.
+    pub fn handle_key_event<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        event: &KeyEvent<'local>,
+    ) -> Option<MetaState> {
+        let key_code = event.key_code(env);
+        // The state for this key includes any sticky modifiers, which
+        // pressing it then clears.
+        let meta_state = self.meta_state(event.meta_state(env));
+        let is_modifier = match event.action(env) {
+            KeyAction::Down => self.key_down(key_code),
+            KeyAction::Up => self.key_up(key_code),
+            _ => modifier_index(key_code).is_some(),
+        };
+        (!is_modifier).then_some(meta_state)
+    }
+
+    /// Implement `InputConnection.clearMetaKeyStates`, which keyboards
+    /// call to release sticky and locked modifiers. `states` is a mask of
+    /// `META_*_ON` flags.
+    pub fn clear_meta_key_states(&mut self, states: jint) {
+        for (&flag, latch) in MODIFIERS.iter().zip(&mut self.latches) {
+            if states as u32 & flag != 0 {
+                *latch = Latch::Off;
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Generation of native methods and their JNI signatures.
+//!
+//! Registering a native method used to need two things kept in sync by
+//! hand: an `extern "system"` function and a `NativeMethod` entry naming
+//! the Java method, with a JNI signature string matching the function's
+//! parameters. [`native_methods!`] generates the entries for the functions
+//! declared inside it, deriving each signature from the Rust parameter
+//! types through [`JniType`]. For callbacks that only pass their arguments
+//! on to the peer, [`peer_callbacks!`] also generates the function itself,
+//! so adding one takes a Java `native` declaration and a single entry in
+//! the macro.
+//!
+//! A few entries still have literal signatures: the stand-ins registered
+//! when the `accessibility` or `ime` feature is off, which take Java types
+//! that aren't bound without the feature, and the peer constructors that
+//! apps pass to `register_view_class` and `register_view_group_class`.
+
+use jni::{
+    objects::{JByteArray, JIntArray, JObjectArray, JString},
+    sys::{jboolean, jfloat, jint, jlong},
+};
+
+#[cfg(feature = "accessibility")]
+use crate::accessibility::*;
+#[cfg(feature = "graphics")]
+use crate::graphics::*;
+#[cfg(feature = "ime")]
+use crate::ime::*;
+use crate::{
+    bundle::*, context::*, events::*, frame_metrics::*, geometry::*, insets::*, intent::*,
+    surface::*,
+};
+
+/// A type that can be passed to or returned from a native method, with
+/// its JNI type signature.
+pub(crate) trait JniType {
+    const SIGNATURE: &'static str;
+}
+
+macro_rules! jni_types {
+    ($($ty:ty => $sig:literal,)*) => {
+        $(impl JniType for $ty {
+            const SIGNATURE: &'static str = $sig;
+        })*
+    };
+}
+
+jni_types! {
+    () => "V",
+    jboolean => "Z",
+    jint => "I",
+    jlong => "J",
+    jfloat => "F",
+    JString<'_> => "Ljava/lang/String;",
+    JByteArray<'_> => "[B",
+    JIntArray<'_> => "[I",
+    // The only object arrays passed to native methods are string arrays.
+    JObjectArray<'_> => "[Ljava/lang/String;",
+    Bundle<'_> => "Landroid/os/Bundle;",
+    Context<'_> => "Landroid/content/Context;",
+    FrameMetrics<'_> => "Landroid/view/FrameMetrics;",
+    KeyEvent<'_> => "Landroid/view/KeyEvent;",
+    MotionEvent<'_> => "Landroid/view/MotionEvent;",
+    Rect<'_> => "Landroid/graphics/Rect;",
+    SurfaceHolder<'_> => "Landroid/view/SurfaceHolder;",
+    Uri<'_> => "Landroid/net/Uri;",
+    WindowInsets<'_> => "Landroid/view/WindowInsets;",
+}
+
+#[cfg(feature = "accessibility")]
+jni_types! {
+    AccessibilityNodeInfo<'_> => "Landroid/view/accessibility/AccessibilityNodeInfo;",
+}
+
+#[cfg(feature = "graphics")]
+jni_types! {
+    Bitmap<'_> => "Landroid/graphics/Bitmap;",
+}
+
+#[cfg(feature = "ime")]
+jni_types! {
+    EditorInfo<'_> => "Landroid/view/inputmethod/EditorInfo;",
+}
+
+pub(crate) fn method_signature(args: &[&str], ret: &str) -> String {
+    format!("({}){ret}", args.concat())
+}
+
+/// The signature of a peer callback, which takes the peer ID as a `long`
+/// before the given arguments.
+pub(crate) fn peer_callback_signature(args: &[&str], ret: &str) -> String {
+    format!("(J{}){ret}", args.concat())
+}
+
+/// Declare native methods, generating each `extern "system"` function as
+/// written and a function that returns their `NativeMethod` entries.
+///
+/// ```ignore
+/// native_methods! {
+///     fn listener_methods;
+///
+///     "onErrorNative" => fn on_error<'local>(
+///         env: JNIEnv<'local>,
+///         _class: JClass<'local>,
+///         listener: jlong,
+///         error: jint,
+///     ) {
+///         with_listener(listener, |l| l.on_error(&mut env, error));
+///     }
+/// }
+/// ```
+///
+/// The first two parameters are the `JNIEnv`, which is always mutable,
+/// and the receiver, which don't appear in the signature. Attributes
+/// before an entry, such as `#[cfg(...)]`, apply to both its function
+/// and its `NativeMethod` entry.
+macro_rules! native_methods {
+    (@ret) => { () };
+    (@ret $ret:ty) => { $ret };
+    (
+        fn $methods:ident;
+        $(
+            $(#[$attr:meta])*
+            $java_name:literal => fn $name:ident<$lt:lifetime>(
+                $env:ident: $env_ty:ty,
+                $this:ident: $this_ty:ty
+                $(, $arg:ident: $ty:ty)* $(,)?
+            ) $(-> $ret:ty)? $body:block
+        )*
+    ) => {
+        $(
+            $(#[$attr])*
+            #[allow(unused_mut)]
+            extern "system" fn $name<$lt>(
+                mut $env: $env_ty,
+                $this: $this_ty,
+                $($arg: $ty),*
+            ) $(-> $ret)? $body
+        )*
+
+        fn $methods() -> Vec<jni::NativeMethod> {
+            let mut methods = Vec::new();
+            $(
+                $(#[$attr])*
+                {
+                    #[allow(clippy::extra_unused_lifetimes)]
+                    fn signature<$lt>() -> String {
+                        $crate::native_methods::method_signature(
+                            &[$(<$ty as $crate::native_methods::JniType>::SIGNATURE),*],
+                            <$crate::native_methods::native_methods!(@ret $($ret)?)
+                                as $crate::native_methods::JniType>::SIGNATURE,
+                        )
+                    }
+                    methods.push(jni::NativeMethod {
+                        name: $java_name.into(),
+                        sig: signature().into(),
+                        fn_ptr: $name as *mut std::ffi::c_void,
+                    });
+                }
+            )*
+            methods
+        }
+    };
+}
+
+pub(crate) use native_methods;
+
+/// Declare peer callbacks, generating an `extern "system"` shim for each
+/// and a function that returns their `NativeMethod` entries.
+///
+/// ```ignore
+/// peer_callbacks! {
+///     fn timer_methods;
+///
+///     "onTimerNative" => fn on_timer<'local>(timer_id: jint) |ctx, peer| {
+///         peer.on_timer(ctx, timer_id);
+///     }
+/// }
+/// ```
+///
+/// The body runs inside [`crate::view::with_peer`], and its value, which
+/// must match the declared return type, is returned to Java. If the peer
+/// is gone, the return type's default is returned instead. Attributes
+/// before an entry, such as `#[cfg(...)]`, apply to both its shim and its
+/// `NativeMethod` entry.
+macro_rules! peer_callbacks {
+    (@ret) => { () };
+    (@ret $ret:ty) => { $ret };
+    (
+        fn $methods:ident;
+        $(
+            $(#[$attr:meta])*
+            $java_name:literal => fn $name:ident<$lt:lifetime>($($arg:ident: $ty:ty),* $(,)?)
+                $(-> $ret:ty)? |$ctx:ident, $peer:ident| $body:block
+        )*
+    ) => {
+        $(
+            $(#[$attr])*
+            extern "system" fn $name<$lt>(
+                env: jni::JNIEnv<$lt>,
+                view: $crate::view::View<$lt>,
+                peer: jni::sys::jlong,
+                $($arg: $ty),*
+            ) $(-> $ret)? {
+                #[cfg(feature = "jni-checks")]
+                $crate::jni_checks::set_next_callback_name(stringify!($name));
+                $crate::view::with_peer(env, view, peer, |$ctx, $peer| $body)
+            }
+        )*
+
+        fn $methods() -> Vec<jni::NativeMethod> {
+            let mut methods = Vec::new();
+            $(
+                $(#[$attr])*
+                {
+                    #[allow(clippy::extra_unused_lifetimes)]
+                    fn signature<$lt>() -> String {
+                        $crate::native_methods::peer_callback_signature(
+                            &[$(<$ty as $crate::native_methods::JniType>::SIGNATURE),*],
+                            <$crate::native_methods::peer_callbacks!(@ret $($ret)?)
+                                as $crate::native_methods::JniType>::SIGNATURE,
+                        )
+                    }
+                    methods.push(jni::NativeMethod {
+                        name: $java_name.into(),
+                        sig: signature().into(),
+                        fn_ptr: $name as *mut std::ffi::c_void,
+                    });
+                }
+            )*
+            methods
+        }
+    };
+}
+
+pub(crate) use peer_callbacks;
@@ -0,0 +1,210 @@
+//! Hybrid composition of platform views with Rust-rendered content.
+//!
+//! A [`crate::View`]'s surface is placed behind its window, and the
+//! window punches a hole through which the surface shows. Platform views
+//! added to the same [`ViewGroup`] after the Rust view are drawn by the
+//! window on top of that hole, so they composite correctly over the Rust
+//! scene. [`PlatformViews`] tracks where each embedded view should appear
+//! and keeps the platform views in sync with the Rust scene's layout.
+
+use jni::{JNIEnv, objects::JObject, sys::jint};
+use std::collections::BTreeMap;
+
+use crate::{jni_cache::*, layout::*, util::*, view::Visibility, view_group::*};
+
+/// Identifies a platform view embedded with [`PlatformViews::embed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PlatformViewId(u64);
+
+/// Where an embedded platform view appears, in pixels relative to the
+/// hosting [`ViewGroup`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlatformViewBounds {
+    pub left: jint,
+    pub top: jint,
+    pub right: jint,
+    pub bottom: jint,
+}
+
+impl PlatformViewBounds {
+    pub fn width(&self) -> jint {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> jint {
+        self.bottom - self.top
+    }
+}
+
+struct EmbeddedView {
+    view: TrackedGlobalRef,
+    bounds: Option<PlatformViewBounds>,
+    applied: Option<PlatformViewBounds>,
+    dirty: bool,
+}
+
+/// The set of platform views embedded in a Rust-managed container.
+///
+/// Set each view's bounds as the Rust scene is laid out, then call
+/// [`PlatformViews::sync`] once per frame, typically from
+/// [`crate::ViewPeer::do_frame`], to move the platform views to match.
+/// A [`ViewGroupPeer`] should also forward its measure and layout passes
+/// to [`PlatformViews::measure`] and [`PlatformViews::layout`], so a
+/// platform-initiated layout doesn't move the views elsewhere.
+#[derive(Default)]
+pub struct PlatformViews {
+    views: BTreeMap<PlatformViewId, EmbeddedView>,
+    next_id: u64,
+}
+
+impl PlatformViews {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a platform view to the group, above any previously added
+    /// children. The view is hidden until bounds are set for it.
+    pub fn embed<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        group: &ViewGroup<'local>,
+        view: &JObject<'local>,
+    ) -> PlatformViewId {
+        set_visibility(env, view, Visibility::Invisible);
+        let params = LayoutParams::new(env, 0, 0);
+        group.add_view(env, view, -1, &params);
+        let id = PlatformViewId(self.next_id);
+        self.next_id += 1;
+        self.views.insert(
+            id,
+            EmbeddedView {
+                view: TrackedGlobalRef::new(env, view, "embedded platform view"),
+                bounds: None,
+                applied: None,
+                dirty: false,
+            },
+        );
+        id
+    }
+
+    /// Remove an embedded view from the group.
+    pub fn remove<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        group: &ViewGroup<'local>,
+        id: PlatformViewId,
+    ) {
+        if let Some(embedded) = self.views.remove(&id) {
+            group.remove_view(env, embedded.view.as_obj());
+        }
+    }
+
+    /// Return the platform view for the given ID.
+    pub fn view(&self, id: PlatformViewId) -> Option<&JObject<'static>> {
+        self.views.get(&id).map(|embedded| embedded.view.as_obj())
+    }
+
+    /// Set where the view should appear, or `None` to hide it, for
+    /// example because it has scrolled out of the Rust scene. This takes
+    /// effect on the next call to [`PlatformViews::sync`].
+    pub fn set_bounds(&mut self, id: PlatformViewId, bounds: Option<PlatformViewBounds>) {
+        let embedded = self.views.get_mut(&id);
+        if let Some(embedded) = embedded.filter(|embedded| embedded.bounds != bounds) {
+            embedded.bounds = bounds;
+            embedded.dirty = true;
+        }
+    }
+
+    pub fn bounds(&self, id: PlatformViewId) -> Option<PlatformViewBounds> {
+        self.views.get(&id).and_then(|embedded| embedded.bounds)
+    }
+
+    /// Move, resize, show or hide any views whose bounds have changed
+    /// since the last sync, without waiting for a full layout pass.
+    pub fn sync(&mut self, env: &mut JNIEnv) {
+        for embedded in self.views.values_mut() {
+            if !embedded.dirty {
+                continue;
+            }
+            embedded.dirty = false;
+            let view = embedded.view.as_obj();
+            match embedded.bounds {
+                Some(bounds) => {
+                    let resized = embedded.applied.is_none_or(|applied| {
+                        applied.width() != bounds.width() || applied.height() != bounds.height()
+                    });
+                    if resized {
+                        measure_view_exactly(env, view, bounds);
+                    }
+                    layout_view(env, view, bounds);
+                    if embedded.applied.is_none() {
+                        set_visibility(env, view, Visibility::Visible);
+                    }
+                }
+                None => {
+                    if embedded.applied.is_some() {
+                        set_visibility(env, view, Visibility::Invisible);
+                    }
+                }
+            }
+            embedded.applied = embedded.bounds;
+        }
+    }
+
+    /// Measure every visible embedded view to exactly its bounds. Call
+    /// this from [`ViewGroupPeer::on_measure_children`].
+    pub fn measure(&self, env: &mut JNIEnv) {
+        for embedded in self.views.values() {
+            if let Some(bounds) = embedded.bounds {
+                measure_view_exactly(env, embedded.view.as_obj(), bounds);
+            }
+        }
+    }
+
+    /// Position every visible embedded view at its bounds. Call this
+    /// from [`ViewGroupPeer::on_layout_children`].
+    pub fn layout(&mut self, env: &mut JNIEnv) {
+        for embedded in self.views.values() {
+            if let Some(bounds) = embedded.bounds {
+                layout_view(env, embedded.view.as_obj(), bounds);
+            }
+        }
+        self.sync(env);
+    }
+}
+
+fn measure_view_exactly(env: &mut JNIEnv, view: &JObject, bounds: PlatformViewBounds) {
+    static MEASURE: CachedMethod = CachedMethod::new("android/view/View", "measure", "(II)V");
+    let width_spec = MeasureSpec::new(bounds.width(), MeasureSpecMode::Exactly);
+    let height_spec = MeasureSpec::new(bounds.height(), MeasureSpecMode::Exactly);
+    MEASURE
+        .call(env, view, &[width_spec.0.into(), height_spec.0.into()])
+        .v()
+        .unwrap()
+}
+
+fn layout_view(env: &mut JNIEnv, view: &JObject, bounds: PlatformViewBounds) {
+    static LAYOUT: CachedMethod = CachedMethod::new("android/view/View", "layout", "(IIII)V");
+    LAYOUT
+        .call(
+            env,
+            view,
+            &[
+                bounds.left.into(),
+                bounds.top.into(),
+                bounds.right.into(),
+                bounds.bottom.into(),
+            ],
+        )
+        .v()
+        .unwrap()
+}
+
+fn set_visibility(env: &mut JNIEnv, view: &JObject, visibility: Visibility) {
+    static SET_VISIBILITY: CachedMethod =
+        CachedMethod::new("android/view/View", "setVisibility", "(I)V");
+    SET_VISIBILITY
+        .call(env, view, &[jint::from(visibility).into()])
+        .v()
+        .unwrap()
+}
@@ -0,0 +1,235 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jint},
+};
+
+use crate::{jni_cache::*, util::*, view::*};
+
+/// A popup window anchored to a view, for dropdowns, autocomplete lists,
+/// tooltips and context menus. The content is usually another Rust view,
+/// for example one created with the Java `RustViewFactory`, but can be
+/// any platform view.
+///
+/// When the popup is dismissed, for any reason, the anchor view's peer
+/// receives [`ViewPeer::on_popup_dismissed`] with the ID passed to
+/// [`Popup::new`].
+pub struct Popup(TrackedGlobalRef);
+
+impl Popup {
+    /// Create a popup with the given content and size, which can be a
+    /// size in pixels or one of the `LAYOUT_PARAMS_*` constants. The
+    /// popup isn't shown until one of the `show` methods is called.
+    pub fn new<'local>(
+        env: &mut JNIEnv<'local>,
+        anchor: &View<'local>,
+        content: &JObject<'local>,
+        width: jint,
+        height: jint,
+        id: jint,
+    ) -> Self {
+        let popup = new_java_object(
+            env,
+            "org/linebender/android/rustview/RustPopup",
+            "(Lorg/linebender/android/rustview/RustView;Landroid/view/View;III)V",
+            &[
+                (&anchor.0).into(),
+                content.into(),
+                width.into(),
+                height.into(),
+                id.into(),
+            ],
+        );
+        Self(TrackedGlobalRef::new(env, popup, "Popup"))
+    }
+
+    /// Show the popup below the anchor view's bottom left corner, offset
+    /// by the given number of pixels. The platform moves the popup above
+    /// the anchor if there isn't room below it.
+    pub fn show_as_dropdown(&self, env: &mut JNIEnv, x_offset: jint, y_offset: jint) {
+        static SHOW_AS_DROP_DOWN: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "showAsDropDown",
+            "(II)V",
+        );
+        SHOW_AS_DROP_DOWN
+            .call(env, &self.0, &[x_offset.into(), y_offset.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Show the popup with its top left corner at the given position in
+    /// the anchor view's coordinates, such as under a text caret.
+    pub fn show_at(&self, env: &mut JNIEnv, x: jint, y: jint) {
+        static SHOW_AT_ANCHOR_OFFSET: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "showAtAnchorOffset",
+            "(II)V",
+        );
+        SHOW_AT_ANCHOR_OFFSET
+            .call(env, &self.0, &[x.into(), y.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Move or resize a showing popup. The position is in the anchor
+    /// view's coordinates, as for [`Popup::show_at`].
+    pub fn update(&self, env: &mut JNIEnv, x: jint, y: jint, width: jint, height: jint) {
+        static UPDATE_AT_ANCHOR_OFFSET: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "updateAtAnchorOffset",
+            "(IIII)V",
+        );
+        UPDATE_AT_ANCHOR_OFFSET
+            .call(
+                env,
+                &self.0,
+                &[x.into(), y.into(), width.into(), height.into()],
+            )
+            .v()
+            .unwrap()
+    }
+
+    /// Set whether a touch outside the popup dismisses it.
+    pub fn set_dismiss_on_outside_touch(&self, env: &mut JNIEnv, dismiss: bool) {
+        static SET_DISMISS_ON_OUTSIDE_TOUCH: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "setDismissOnOutsideTouch",
+            "(Z)V",
+        );
+        SET_DISMISS_ON_OUTSIDE_TOUCH
+            .call(env, &self.0, &[dismiss.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Set whether the popup takes focus, and with it key events and the
+    /// back button, from the anchor's window. Popups that accompany text
+    /// input, such as autocomplete lists, should not be focusable.
+    pub fn set_focusable(&self, env: &mut JNIEnv, focusable: bool) {
+        static SET_FOCUSABLE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "setFocusable",
+            "(Z)V",
+        );
+        SET_FOCUSABLE
+            .call(env, &self.0, &[focusable.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Set the elevation, in pixels, which determines the popup's shadow.
+    pub fn set_elevation(&self, env: &mut JNIEnv, elevation: jfloat) {
+        static SET_ELEVATION: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "setElevation",
+            "(F)V",
+        );
+        SET_ELEVATION
+            .call(env, &self.0, &[elevation.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn is_showing(&self, env: &mut JNIEnv) -> bool {
+        static IS_SHOWING: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "isShowing",
+            "()Z",
+        );
+        IS_SHOWING.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    pub fn dismiss(&self, env: &mut JNIEnv) {
+        static DISMISS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustPopup",
+            "dismiss",
+            "()V",
+        );
+        DISMISS.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+/// A popup listing replacements for a misspelled or autocorrected word,
+/// styled like the platform's own text suggestions, with an optional
+/// action to delete the word. The suggestions come from the caller, for
+/// example from a spell checker or from the `SuggestionSpan`s a keyboard
+/// attached to committed text.
+///
+/// Picking an item calls [`ViewPeer::on_suggestion_picked`] or
+/// [`ViewPeer::on_suggestion_delete`] on the anchor view's peer and then
+/// dismisses the popup, which calls [`ViewPeer::on_popup_dismissed`].
+/// The popup doesn't take focus, so the keyboard stays up.
+pub struct SuggestionsPopup(TrackedGlobalRef);
+
+impl SuggestionsPopup {
+    /// Show the popup with its top left corner at the given position in
+    /// the anchor view's coordinates, typically just below the word.
+    /// `delete_label` is the localized label for the delete action, or
+    /// `None` to omit it.
+    pub fn show<'local>(
+        env: &mut JNIEnv<'local>,
+        anchor: &View<'local>,
+        id: jint,
+        suggestions: &[&str],
+        delete_label: Option<&str>,
+        x: jint,
+        y: jint,
+    ) -> Self {
+        static SHOW_AT_ANCHOR_OFFSET: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustSuggestionsPopup",
+            "showAtAnchorOffset",
+            "(II)V",
+        );
+        let array = env
+            .new_object_array(
+                suggestions.len() as jint,
+                "java/lang/String",
+                JObject::null(),
+            )
+            .unwrap();
+        for (i, suggestion) in suggestions.iter().enumerate() {
+            let suggestion = new_java_string(env, suggestion);
+            env.set_object_array_element(&array, i as jint, suggestion)
+                .unwrap();
+        }
+        let delete_label = match delete_label {
+            Some(label) => JObject::from(new_java_string(env, label)),
+            None => JObject::null(),
+        };
+        let popup = new_java_object(
+            env,
+            "org/linebender/android/rustview/RustSuggestionsPopup",
+            "(Lorg/linebender/android/rustview/RustView;I[Ljava/lang/String;Ljava/lang/String;)V",
+            &[
+                (&anchor.0).into(),
+                id.into(),
+                (&array).into(),
+                (&delete_label).into(),
+            ],
+        );
+        SHOW_AT_ANCHOR_OFFSET
+            .call(env, &popup, &[x.into(), y.into()])
+            .v()
+            .unwrap();
+        Self(TrackedGlobalRef::new(env, popup, "SuggestionsPopup"))
+    }
+
+    pub fn is_showing(&self, env: &mut JNIEnv) -> bool {
+        static IS_SHOWING: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustSuggestionsPopup",
+            "isShowing",
+            "()Z",
+        );
+        IS_SHOWING.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    pub fn dismiss(&self, env: &mut JNIEnv) {
+        static DISMISS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustSuggestionsPopup",
+            "dismiss",
+            "()V",
+        );
+        DISMISS.call(env, &self.0, &[]).v().unwrap()
+    }
+}
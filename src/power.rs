@@ -0,0 +1,56 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jint},
+};
+
+use crate::jni_cache::*;
+
+pub const POWER_SERVICE: &str = "power";
+
+pub const THERMAL_STATUS_NONE: jint = 0;
+pub const THERMAL_STATUS_LIGHT: jint = 1;
+pub const THERMAL_STATUS_MODERATE: jint = 2;
+pub const THERMAL_STATUS_SEVERE: jint = 3;
+pub const THERMAL_STATUS_CRITICAL: jint = 4;
+pub const THERMAL_STATUS_EMERGENCY: jint = 5;
+pub const THERMAL_STATUS_SHUTDOWN: jint = 6;
+
+#[repr(transparent)]
+pub struct PowerManager<'local>(pub JObject<'local>);
+
+impl<'local> PowerManager<'local> {
+    pub fn is_power_save_mode(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_POWER_SAVE_MODE: CachedMethod =
+            CachedMethod::new("android/os/PowerManager", "isPowerSaveMode", "()Z");
+        IS_POWER_SAVE_MODE.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// One of the `THERMAL_STATUS_*` constants. Requires API level 29.
+    pub fn current_thermal_status(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_CURRENT_THERMAL_STATUS: CachedMethod =
+            CachedMethod::new("android/os/PowerManager", "getCurrentThermalStatus", "()I");
+        GET_CURRENT_THERMAL_STATUS
+            .call(env, &self.0, &[])
+            .i()
+            .unwrap()
+    }
+
+    /// The forecast thermal headroom, where 1.0 corresponds to
+    /// [`THERMAL_STATUS_SEVERE`], or `None` if the forecast isn't available.
+    /// The forecast covers `forecast_seconds` into the future, up to 60.
+    /// Requires API level 30.
+    pub fn thermal_headroom(
+        &self,
+        env: &mut JNIEnv<'local>,
+        forecast_seconds: jint,
+    ) -> Option<jfloat> {
+        static GET_THERMAL_HEADROOM: CachedMethod =
+            CachedMethod::new("android/os/PowerManager", "getThermalHeadroom", "(I)F");
+        let headroom = GET_THERMAL_HEADROOM
+            .call(env, &self.0, &[forecast_seconds.into()])
+            .f()
+            .unwrap();
+        (!headroom.is_nan()).then_some(headroom)
+    }
+}
@@ -0,0 +1,21 @@
+//! The items most apps need to implement and register a view peer.
+//!
+//! ```ignore
+//! use android_view::prelude::*;
+//! ```
+
+pub use jni::{
+    JNIEnv,
+    sys::{jint, jlong},
+};
+pub use ndk::event::Keycode;
+
+#[cfg(feature = "accessibility")]
+pub use crate::{AccessibilityNodeInfo, AccessibilityNodeProvider};
+pub use crate::{
+    CallbackCtx, Context, KeyEvent, MotionEvent, Surface, SurfaceHolder, View, ViewConfiguration,
+    ViewPeer, for_each_peer, register_view_class, register_view_peer, with_peer_as,
+    with_peer_ctx_as, with_task_peer, with_view_peer,
+};
+#[cfg(feature = "ime")]
+pub use crate::{EditorInfo, InputConnection};
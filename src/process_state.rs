@@ -0,0 +1,125 @@
+//! State that must survive Android killing the app's process while it's in
+//! the background. The Java `RustProcessState` class saves the state of
+//! the whole app into one `Bundle`, typically from the activity's
+//! `onSaveInstanceState`, and restores it when the activity is created
+//! again in a new process. The blob covers:
+//!
+//! - Every view that was given a key with `RustView.setStateKey`, through
+//!   [`crate::ViewPeer::on_save_state`] and
+//!   [`crate::ViewPeer::on_restore_state`]. A view created after the
+//!   restore gets its state when its key is set.
+//! - App-wide Rust state that doesn't belong to any view, such as a
+//!   document model shared by several views, through the hooks registered
+//!   with [`register_process_state_hook`].
+//!
+//! Process death is hard to reproduce on demand, so
+//! `RustProcessState.simulateProcessDeath` runs the same sequence from an
+//! instrumented test: it saves the state, round-trips it through a
+//! `Parcel` as the system would, and calls [`ProcessStateHook::reset`] on
+//! every hook. The test then recreates the activity and restores the
+//! returned state, and can check that the views show what they did before.
+
+use jni::{
+    JNIEnv,
+    objects::{JByteArray, JClass, JString},
+};
+use std::{collections::BTreeMap, sync::Mutex};
+
+use crate::{bundle::*, native_methods::*};
+
+/// App-wide state that is saved and restored along with the views'.
+pub trait ProcessStateHook: Send {
+    /// Serialize the state, in any format that [`ProcessStateHook::restore`]
+    /// understands. Keep it small, since the system limits the size of
+    /// saved state, typically to well under a megabyte for the whole app.
+    fn save(&mut self) -> Vec<u8>;
+
+    /// Restore state returned by [`ProcessStateHook::save`], possibly in an
+    /// earlier process.
+    fn restore(&mut self, state: &[u8]);
+
+    /// Return to the state of a freshly started process. This is only
+    /// called when a test simulates process death, since the process
+    /// would otherwise start from scratch anyway.
+    fn reset(&mut self);
+}
+
+struct Hooks {
+    hooks: BTreeMap<String, Box<dyn ProcessStateHook>>,
+    // State restored before its hook was registered.
+    pending: BTreeMap<String, Vec<u8>>,
+}
+
+static HOOKS: Mutex<Hooks> = Mutex::new(Hooks {
+    hooks: BTreeMap::new(),
+    pending: BTreeMap::new(),
+});
+
+/// Register a hook that saves and restores app-wide state under `key`,
+/// which must be stable across app launches. If state for `key` was
+/// already restored, the hook gets it right away.
+///
+/// The hooks are called on the UI thread while a lock is held, so they
+/// must not register other hooks.
+pub fn register_process_state_hook(key: &str, hook: impl 'static + ProcessStateHook) {
+    let mut hooks = HOOKS.lock().unwrap();
+    let mut hook = Box::new(hook);
+    if let Some(state) = hooks.pending.remove(key) {
+        hook.restore(&state);
+    }
+    hooks.hooks.insert(key.into(), hook);
+}
+
+native_methods! {
+    fn hook_methods;
+
+    "saveHooksNative" => fn save_hooks<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        out_state: Bundle<'local>,
+    ) {
+        let mut hooks = HOOKS.lock().unwrap();
+        let Hooks { hooks, pending } = &mut *hooks;
+        for (key, hook) in hooks.iter_mut() {
+            out_state.put_byte_array(&mut env, key, &hook.save());
+        }
+        // Keep state that no hook has claimed yet, in case its hook is only
+        // registered later in this process's life.
+        for (key, state) in pending.iter() {
+            out_state.put_byte_array(&mut env, key, state);
+        }
+    }
+
+    "restoreHookNative" => fn restore_hook<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        key: JString<'local>,
+        state: JByteArray<'local>,
+    ) {
+        let key: String = env.get_string(&key).unwrap().into();
+        let state = env.convert_byte_array(&state).unwrap();
+        let mut hooks = HOOKS.lock().unwrap();
+        match hooks.hooks.get_mut(&key) {
+            Some(hook) => hook.restore(&state),
+            None => {
+                hooks.pending.insert(key, state);
+            }
+        }
+    }
+
+    "resetHooksNative" => fn reset_hooks<'local>(_env: JNIEnv<'local>, _class: JClass<'local>) {
+        let mut hooks = HOOKS.lock().unwrap();
+        hooks.pending.clear();
+        for hook in hooks.hooks.values_mut() {
+            hook.reset();
+        }
+    }
+}
+
+pub(crate) fn register_natives(env: &mut JNIEnv) {
+    env.register_native_methods(
+        "org/linebender/android/rustview/RustProcessState",
+        &hook_methods(),
+    )
+    .unwrap();
+}
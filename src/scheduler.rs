@@ -0,0 +1,103 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jint, jlong},
+};
+
+use crate::jni_cache::*;
+
+/// Timers for a view, obtained from [`crate::View::scheduler`], for
+/// things like cursor blinking, fading text handles and tooltip delays.
+/// Each timer has a peer-chosen ID, and calls [`crate::ViewPeer::on_timer`]
+/// with that ID when it fires.
+///
+/// Timers only run while the view is attached to a visible window. When
+/// the window is hidden or the view is detached, they're paused with
+/// the time they had left, and resume from there when the view becomes
+/// visible again, so nothing fires while the user can't see it.
+#[repr(transparent)]
+pub struct ViewScheduler<'local>(pub JObject<'local>);
+
+impl<'local> ViewScheduler<'local> {
+    /// Fire timer `id` once, after `delay_millis`. Replaces any timer
+    /// with the same ID.
+    pub fn schedule_once(&self, env: &mut JNIEnv<'local>, id: jint, delay_millis: jlong) {
+        self.schedule(env, id, delay_millis, 0);
+    }
+
+    /// Fire timer `id` every `interval_millis`, starting after
+    /// `delay_millis`, until it's canceled. Replaces any timer with the
+    /// same ID.
+    pub fn schedule_repeating(
+        &self,
+        env: &mut JNIEnv<'local>,
+        id: jint,
+        delay_millis: jlong,
+        interval_millis: jlong,
+    ) {
+        assert!(interval_millis > 0);
+        self.schedule(env, id, delay_millis, interval_millis);
+    }
+
+    fn schedule(
+        &self,
+        env: &mut JNIEnv<'local>,
+        id: jint,
+        delay_millis: jlong,
+        interval_millis: jlong,
+    ) {
+        static SCHEDULE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustViewScheduler",
+            "schedule",
+            "(IJJ)V",
+        );
+        SCHEDULE
+            .call(
+                env,
+                &self.0,
+                &[id.into(), delay_millis.into(), interval_millis.into()],
+            )
+            .v()
+            .unwrap()
+    }
+
+    pub fn cancel(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static CANCEL: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustViewScheduler",
+            "cancel",
+            "(I)V",
+        );
+        CANCEL.call(env, &self.0, &[id.into()]).v().unwrap()
+    }
+
+    pub fn cancel_all(&self, env: &mut JNIEnv<'local>) {
+        static CANCEL_ALL: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustViewScheduler",
+            "cancelAll",
+            "()V",
+        );
+        CANCEL_ALL.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    /// Whether timer `id` is scheduled, including while it's paused.
+    /// A one-shot timer is no longer scheduled once it has fired.
+    pub fn is_scheduled(&self, env: &mut JNIEnv<'local>, id: jint) -> bool {
+        static IS_SCHEDULED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustViewScheduler",
+            "isScheduled",
+            "(I)Z",
+        );
+        IS_SCHEDULED.call(env, &self.0, &[id.into()]).z().unwrap()
+    }
+
+    /// Whether timers are currently running, rather than paused because
+    /// the view is detached or its window isn't visible.
+    pub fn is_active(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_ACTIVE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustViewScheduler",
+            "isActive",
+            "()Z",
+        );
+        IS_ACTIVE.call(env, &self.0, &[]).z().unwrap()
+    }
+}
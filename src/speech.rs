@@ -0,0 +1,251 @@
+use jni::{
+    JNIEnv,
+    objects::{JClass, JObject, JObjectArray, JString},
+    sys::{jfloat, jint, jlong},
+};
+use std::sync::Once;
+
+use crate::{context::*, jni_cache::*, native_methods::*, util::*};
+
+pub const SPEECH_ERROR_NETWORK_TIMEOUT: jint = 1;
+pub const SPEECH_ERROR_NETWORK: jint = 2;
+pub const SPEECH_ERROR_AUDIO: jint = 3;
+pub const SPEECH_ERROR_SERVER: jint = 4;
+pub const SPEECH_ERROR_CLIENT: jint = 5;
+pub const SPEECH_ERROR_SPEECH_TIMEOUT: jint = 6;
+pub const SPEECH_ERROR_NO_MATCH: jint = 7;
+pub const SPEECH_ERROR_RECOGNIZER_BUSY: jint = 8;
+pub const SPEECH_ERROR_INSUFFICIENT_PERMISSIONS: jint = 9;
+
+/// Receives the results of a [`SpeechRecognizer`]. All methods are called
+/// on the UI thread.
+#[allow(unused_variables)]
+pub trait SpeechRecognitionListener {
+    fn on_ready_for_speech(&mut self, env: &mut JNIEnv) {}
+
+    fn on_beginning_of_speech(&mut self, env: &mut JNIEnv) {}
+
+    /// The input level changed, in decibels.
+    fn on_rms_changed(&mut self, env: &mut JNIEnv, rms_db: jfloat) {}
+
+    fn on_end_of_speech(&mut self, env: &mut JNIEnv) {}
+
+    /// `error` is one of the `SPEECH_ERROR_*` constants. No further results
+    /// are delivered for the current session.
+    fn on_error(&mut self, env: &mut JNIEnv, error: jint) {}
+
+    /// Intermediate hypotheses, if partial results were requested,
+    /// most likely first.
+    fn on_partial_results(&mut self, env: &mut JNIEnv, results: &[String]) {}
+
+    /// The final hypotheses, most likely first.
+    fn on_results(&mut self, env: &mut JNIEnv, results: &[String]) {}
+}
+
+type ListenerBox = Box<dyn SpeechRecognitionListener>;
+
+fn with_listener(listener: jlong, f: impl FnOnce(&mut dyn SpeechRecognitionListener)) {
+    // SAFETY: The handle was created by `SpeechRecognizer::new` and stays
+    // valid until `SpeechRecognizer::destroy`, after which the Java side
+    // no longer calls back.
+    let listener = unsafe { &mut *(listener as *mut ListenerBox) };
+    f(&mut **listener);
+}
+
+fn to_strings(env: &mut JNIEnv, array: &JObjectArray) -> Vec<String> {
+    let len = env.get_array_length(array).unwrap();
+    (0..len)
+        .map(|i| {
+            let s: JString = env.get_object_array_element(array, i).unwrap().into();
+            env.get_string(&s).unwrap().into()
+        })
+        .collect()
+}
+
+native_methods! {
+    fn listener_methods;
+
+    "onReadyForSpeechNative" => fn on_ready_for_speech<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        listener: jlong,
+    ) {
+        with_listener(listener, |l| l.on_ready_for_speech(&mut env));
+    }
+
+    "onBeginningOfSpeechNative" => fn on_beginning_of_speech<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        listener: jlong,
+    ) {
+        with_listener(listener, |l| l.on_beginning_of_speech(&mut env));
+    }
+
+    "onRmsChangedNative" => fn on_rms_changed<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        listener: jlong,
+        rms_db: jfloat,
+    ) {
+        with_listener(listener, |l| l.on_rms_changed(&mut env, rms_db));
+    }
+
+    "onEndOfSpeechNative" => fn on_end_of_speech<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        listener: jlong,
+    ) {
+        with_listener(listener, |l| l.on_end_of_speech(&mut env));
+    }
+
+    "onErrorNative" => fn on_error<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        listener: jlong,
+        error: jint,
+    ) {
+        with_listener(listener, |l| l.on_error(&mut env, error));
+    }
+
+    "onResultsNative" => fn on_results<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        listener: jlong,
+        results: JObjectArray<'local>,
+    ) {
+        let results = to_strings(&mut env, &results);
+        with_listener(listener, |l| l.on_results(&mut env, &results));
+    }
+
+    "onPartialResultsNative" => fn on_partial_results<'local>(
+        env: JNIEnv<'local>,
+        _class: JClass<'local>,
+        listener: jlong,
+        results: JObjectArray<'local>,
+    ) {
+        let results = to_strings(&mut env, &results);
+        with_listener(listener, |l| l.on_partial_results(&mut env, &results));
+    }
+}
+
+fn register_natives(env: &mut JNIEnv) {
+    static REGISTER_NATIVES: Once = Once::new();
+    REGISTER_NATIVES.call_once(|| {
+        env.register_native_methods(
+            "org/linebender/android/rustview/RustSpeechRecognizer",
+            &listener_methods(),
+        )
+        .unwrap();
+    });
+}
+
+/// A binding to `android.speech.SpeechRecognizer`, for dictation.
+/// It must be created and used on the UI thread, and the app must hold
+/// the `RECORD_AUDIO` permission.
+///
+/// This holds a global reference, so it can be kept in a peer across
+/// callbacks. Call [`SpeechRecognizer::destroy`] when it's no longer
+/// needed; dropping it without doing so leaks the listener.
+pub struct SpeechRecognizer {
+    recognizer: TrackedGlobalRef,
+    listener: jlong,
+}
+
+impl SpeechRecognizer {
+    pub fn is_recognition_available<'local>(
+        env: &mut JNIEnv<'local>,
+        context: &Context<'local>,
+    ) -> bool {
+        static IS_RECOGNITION_AVAILABLE: CachedStaticMethod = CachedStaticMethod::new(
+            "org/linebender/android/rustview/RustSpeechRecognizer",
+            "isRecognitionAvailable",
+            "(Landroid/content/Context;)Z",
+        );
+        IS_RECOGNITION_AVAILABLE
+            .call(env, &[(&context.0).into()])
+            .z()
+            .unwrap()
+    }
+
+    pub fn new<'local>(
+        env: &mut JNIEnv<'local>,
+        context: &Context<'local>,
+        listener: impl 'static + SpeechRecognitionListener,
+    ) -> Self {
+        register_natives(env);
+        let listener: ListenerBox = Box::new(listener);
+        let listener = Box::into_raw(Box::new(listener)) as jlong;
+        let recognizer = new_java_object(
+            env,
+            "org/linebender/android/rustview/RustSpeechRecognizer",
+            "(Landroid/content/Context;J)V",
+            &[(&context.0).into(), listener.into()],
+        );
+        Self {
+            recognizer: TrackedGlobalRef::new(env, recognizer, "SpeechRecognizer"),
+            listener,
+        }
+    }
+
+    /// Start listening. `language` is an IETF language tag such as
+    /// `"en-US"`, or `None` for the device's default.
+    pub fn start_listening(&self, env: &mut JNIEnv, language: Option<&str>, partial_results: bool) {
+        static START_LISTENING: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustSpeechRecognizer",
+            "startListening",
+            "(Ljava/lang/String;Z)V",
+        );
+        let language = match language {
+            Some(language) => new_java_string(env, language).into(),
+            None => JObject::null(),
+        };
+        START_LISTENING
+            .call(
+                env,
+                self.recognizer.as_obj(),
+                &[(&language).into(), partial_results.into()],
+            )
+            .v()
+            .unwrap()
+    }
+
+    /// Stop listening and deliver the results for the speech so far.
+    pub fn stop_listening(&self, env: &mut JNIEnv) {
+        static STOP_LISTENING: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustSpeechRecognizer",
+            "stopListening",
+            "()V",
+        );
+        STOP_LISTENING
+            .call(env, self.recognizer.as_obj(), &[])
+            .v()
+            .unwrap()
+    }
+
+    /// Stop listening without delivering results.
+    pub fn cancel(&self, env: &mut JNIEnv) {
+        static CANCEL: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustSpeechRecognizer",
+            "cancel",
+            "()V",
+        );
+        CANCEL.call(env, self.recognizer.as_obj(), &[]).v().unwrap()
+    }
+
+    /// Release the recognizer and free the listener. This must not be
+    /// called from within a listener method.
+    pub fn destroy(self, env: &mut JNIEnv) {
+        static DESTROY: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustSpeechRecognizer",
+            "destroy",
+            "()V",
+        );
+        DESTROY
+            .call(env, self.recognizer.as_obj(), &[])
+            .v()
+            .unwrap();
+        // SAFETY: The Java side no longer calls back after `destroy`,
+        // and the handle was created by `Box::into_raw` in `new`.
+        drop(unsafe { Box::from_raw(self.listener as *mut ListenerBox) });
+    }
+}
@@ -1,6 +1,20 @@
-use jni::{JNIEnv, objects::JObject};
+use jni::{JNIEnv, objects::JObject, sys::jint};
 use ndk::native_window::NativeWindow;
 
+use crate::jni_cache::*;
+#[cfg(feature = "graphics")]
+use crate::{geometry::*, graphics::*};
+
+pub const PIXEL_FORMAT_OPAQUE: jint = -1;
+pub const PIXEL_FORMAT_TRANSPARENT: jint = -2;
+pub const PIXEL_FORMAT_TRANSLUCENT: jint = -3;
+pub const PIXEL_FORMAT_RGBA_8888: jint = 1;
+pub const PIXEL_FORMAT_RGBX_8888: jint = 2;
+pub const PIXEL_FORMAT_RGB_565: jint = 4;
+/// Half-float components, for extended-range and HDR content.
+pub const PIXEL_FORMAT_RGBA_F16: jint = 0x16;
+pub const PIXEL_FORMAT_RGBA_1010102: jint = 0x2b;
+
 #[repr(transparent)]
 pub struct Surface<'local>(pub JObject<'local>);
 
@@ -15,11 +29,77 @@ pub struct SurfaceHolder<'local>(pub JObject<'local>);
 
 impl<'local> SurfaceHolder<'local> {
     pub fn surface(&self, env: &mut JNIEnv<'local>) -> Surface<'local> {
-        Surface(
-            env.call_method(&self.0, "getSurface", "()Landroid/view/Surface;", &[])
-                .unwrap()
-                .l()
-                .unwrap(),
-        )
+        static GET_SURFACE: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceHolder",
+            "getSurface",
+            "()Landroid/view/Surface;",
+        );
+        Surface(GET_SURFACE.call(env, &self.0, &[]).l().unwrap())
+    }
+
+    /// Lock the surface for software rendering, with a canvas to draw
+    /// the next frame into. `dirty` is the area the caller intends to
+    /// redraw, or `None` for the whole surface. Returns the canvas and
+    /// the area that must actually be redrawn, which may be larger than
+    /// `dirty` because the previous buffer's contents aren't always
+    /// available. Returns `None` if the surface isn't ready.
+    ///
+    /// The canvas must be passed to [`SurfaceHolder::unlock_canvas_and_post`]
+    /// when drawing is done.
+    #[cfg(feature = "graphics")]
+    pub fn lock_canvas(
+        &self,
+        env: &mut JNIEnv<'local>,
+        dirty: Option<IntRect>,
+    ) -> Option<(Canvas<'local>, IntRect)> {
+        static LOCK_CANVAS: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceHolder",
+            "lockCanvas",
+            "(Landroid/graphics/Rect;)Landroid/graphics/Canvas;",
+        );
+        let rect = dirty.map(|dirty| Rect::new(env, dirty));
+        let null = JObject::null();
+        let rect_arg = rect.as_ref().map_or(&null, |rect| &rect.0);
+        let canvas = LOCK_CANVAS
+            .call(env, &self.0, &[rect_arg.into()])
+            .l()
+            .unwrap();
+        if canvas.as_raw().is_null() {
+            return None;
+        }
+        let canvas = Canvas(canvas);
+        let dirty = match rect {
+            Some(rect) => rect.to_value(env),
+            None => IntRect {
+                left: 0,
+                top: 0,
+                right: canvas.width(env),
+                bottom: canvas.height(env),
+            },
+        };
+        Some((canvas, dirty))
+    }
+
+    /// Post the frame drawn into a canvas from [`SurfaceHolder::lock_canvas`].
+    #[cfg(feature = "graphics")]
+    pub fn unlock_canvas_and_post(&self, env: &mut JNIEnv<'local>, canvas: Canvas<'local>) {
+        static UNLOCK_CANVAS_AND_POST: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceHolder",
+            "unlockCanvasAndPost",
+            "(Landroid/graphics/Canvas;)V",
+        );
+        UNLOCK_CANVAS_AND_POST
+            .call(env, &self.0, &[(&canvas.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Set the pixel format of the surface. Use [`PIXEL_FORMAT_TRANSLUCENT`]
+    /// to let views behind the surface show through where the content
+    /// is transparent.
+    pub fn set_format(&self, env: &mut JNIEnv<'local>, format: jint) {
+        static SET_FORMAT: CachedMethod =
+            CachedMethod::new("android/view/SurfaceHolder", "setFormat", "(I)V");
+        SET_FORMAT.call(env, &self.0, &[format.into()]).v().unwrap()
     }
 }
@@ -0,0 +1,483 @@
+use jni::{
+    JNIEnv,
+    objects::JObject,
+    sys::{jfloat, jint, jlong},
+};
+
+#[cfg(feature = "graphics")]
+use crate::damage::*;
+use crate::{binder::*, context::*, jni_cache::*, util::*, view::*};
+
+pub const HARDWARE_BUFFER_RGBA_8888: jint = 1;
+pub const HARDWARE_BUFFER_RGBX_8888: jint = 2;
+pub const HARDWARE_BUFFER_RGBA_FP16: jint = 0x16;
+pub const HARDWARE_BUFFER_RGBA_1010102: jint = 0x2b;
+
+pub const HARDWARE_BUFFER_USAGE_GPU_SAMPLED_IMAGE: jlong = 1 << 8;
+pub const HARDWARE_BUFFER_USAGE_GPU_COLOR_OUTPUT: jlong = 1 << 9;
+pub const HARDWARE_BUFFER_USAGE_COMPOSER_OVERLAY: jlong = 1 << 11;
+pub const HARDWARE_BUFFER_USAGE_FRONT_BUFFER: jlong = 1 << 32;
+
+#[repr(transparent)]
+pub struct SurfaceControl<'local>(pub JObject<'local>);
+
+impl<'local> SurfaceControl<'local> {
+    /// Create a new layer as a child of `parent`. The layer is initially
+    /// hidden and has no content. Requires API level 29.
+    pub fn new_child(
+        env: &mut JNIEnv<'local>,
+        parent: &SurfaceControl<'local>,
+        name: &str,
+    ) -> Self {
+        static SET_NAME: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Builder",
+            "setName",
+            "(Ljava/lang/String;)Landroid/view/SurfaceControl$Builder;",
+        );
+        static SET_PARENT: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Builder",
+            "setParent",
+            "(Landroid/view/SurfaceControl;)Landroid/view/SurfaceControl$Builder;",
+        );
+        static SET_HIDDEN: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Builder",
+            "setHidden",
+            "(Z)Landroid/view/SurfaceControl$Builder;",
+        );
+        static BUILD: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Builder",
+            "build",
+            "()Landroid/view/SurfaceControl;",
+        );
+        let builder = new_java_object(env, "android/view/SurfaceControl$Builder", "()V", &[]);
+        let name = new_java_string(env, name);
+        SET_NAME.call(env, &builder, &[(&name).into()]);
+        SET_PARENT.call(env, &builder, &[(&parent.0).into()]);
+        SET_HIDDEN.call(env, &builder, &[true.into()]);
+        Self(BUILD.call(env, &builder, &[]).l().unwrap())
+    }
+
+    pub fn is_valid(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_VALID: CachedMethod =
+            CachedMethod::new("android/view/SurfaceControl", "isValid", "()Z");
+        IS_VALID.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// Release this reference to the layer. The layer is removed once
+    /// all references, including the one held by the compositor,
+    /// are released.
+    pub fn release(&self, env: &mut JNIEnv<'local>) {
+        static RELEASE: CachedMethod =
+            CachedMethod::new("android/view/SurfaceControl", "release", "()V");
+        RELEASE.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+/// A binding to `SurfaceControl.Transaction`. Changes are applied
+/// atomically when [`SurfaceControlTransaction::apply`] is called.
+#[repr(transparent)]
+pub struct SurfaceControlTransaction<'local>(pub JObject<'local>);
+
+impl<'local> SurfaceControlTransaction<'local> {
+    pub fn new(env: &mut JNIEnv<'local>) -> Self {
+        Self(new_java_object(
+            env,
+            "android/view/SurfaceControl$Transaction",
+            "()V",
+            &[],
+        ))
+    }
+
+    pub fn set_visibility(
+        &self,
+        env: &mut JNIEnv<'local>,
+        sc: &SurfaceControl<'local>,
+        visible: bool,
+    ) -> &Self {
+        static SET_VISIBILITY: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Transaction",
+            "setVisibility",
+            "(Landroid/view/SurfaceControl;Z)Landroid/view/SurfaceControl$Transaction;",
+        );
+        SET_VISIBILITY.call(env, &self.0, &[(&sc.0).into(), visible.into()]);
+        self
+    }
+
+    /// Set the Z order of the layer relative to its siblings.
+    pub fn set_layer(
+        &self,
+        env: &mut JNIEnv<'local>,
+        sc: &SurfaceControl<'local>,
+        z: jint,
+    ) -> &Self {
+        static SET_LAYER: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Transaction",
+            "setLayer",
+            "(Landroid/view/SurfaceControl;I)Landroid/view/SurfaceControl$Transaction;",
+        );
+        SET_LAYER.call(env, &self.0, &[(&sc.0).into(), z.into()]);
+        self
+    }
+
+    /// Attach a buffer to the layer. Requires API level 33.
+    pub fn set_buffer(
+        &self,
+        env: &mut JNIEnv<'local>,
+        sc: &SurfaceControl<'local>,
+        buffer: &HardwareBuffer<'local>,
+    ) -> &Self {
+        static SET_BUFFER: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Transaction",
+            "setBuffer",
+            "(Landroid/view/SurfaceControl;Landroid/hardware/HardwareBuffer;)Landroid/view/SurfaceControl$Transaction;",
+        );
+        SET_BUFFER.call(env, &self.0, &[(&sc.0).into(), (&buffer.0).into()]);
+        self
+    }
+
+    /// Tell the compositor which part of the layer's new buffer changed,
+    /// in buffer coordinates, so it can skip recompositing the rest.
+    /// Requires API level 33.
+    #[cfg(feature = "graphics")]
+    pub fn set_damage_region(
+        &self,
+        env: &mut JNIEnv<'local>,
+        sc: &SurfaceControl<'local>,
+        region: &DirtyRegion,
+    ) -> &Self {
+        static SET_DAMAGE_REGION: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Transaction",
+            "setDamageRegion",
+            "(Landroid/view/SurfaceControl;Landroid/graphics/Region;)Landroid/view/SurfaceControl$Transaction;",
+        );
+        let region = region.to_java(env);
+        SET_DAMAGE_REGION.call(env, &self.0, &[(&sc.0).into(), (&region).into()]);
+        self
+    }
+
+    /// Tell the compositor that the layer's content goes brighter than SDR
+    /// white, by `current_ratio` times at most, and that it would look
+    /// best with `desired_ratio` times the SDR white level of headroom.
+    /// Both are 1.0 for SDR content. The buffer must use an extended-range
+    /// data space. Requires API level 34.
+    pub fn set_extended_range_brightness(
+        &self,
+        env: &mut JNIEnv<'local>,
+        sc: &SurfaceControl<'local>,
+        current_ratio: jfloat,
+        desired_ratio: jfloat,
+    ) -> &Self {
+        static SET_EXTENDED_RANGE_BRIGHTNESS: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Transaction",
+            "setExtendedRangeBrightness",
+            "(Landroid/view/SurfaceControl;FF)Landroid/view/SurfaceControl$Transaction;",
+        );
+        SET_EXTENDED_RANGE_BRIGHTNESS.call(
+            env,
+            &self.0,
+            &[(&sc.0).into(), current_ratio.into(), desired_ratio.into()],
+        );
+        self
+    }
+
+    /// Ask for at most `headroom` times the SDR white level of headroom
+    /// for the layer's HDR content, or 0 to let the system decide.
+    /// Does nothing before API level 35.
+    pub fn set_desired_hdr_headroom(
+        &self,
+        env: &mut JNIEnv<'local>,
+        sc: &SurfaceControl<'local>,
+        headroom: jfloat,
+    ) -> &Self {
+        static SET_DESIRED_HDR_HEADROOM: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControl$Transaction",
+            "setDesiredHdrHeadroom",
+            "(Landroid/view/SurfaceControl;F)Landroid/view/SurfaceControl$Transaction;",
+        );
+        if android_sdk_version(env) >= 35 {
+            SET_DESIRED_HDR_HEADROOM.call(env, &self.0, &[(&sc.0).into(), headroom.into()]);
+        }
+        self
+    }
+
+    pub fn apply(&self, env: &mut JNIEnv<'local>) {
+        static APPLY: CachedMethod =
+            CachedMethod::new("android/view/SurfaceControl$Transaction", "apply", "()V");
+        APPLY.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    pub fn close(&self, env: &mut JNIEnv<'local>) {
+        static CLOSE: CachedMethod =
+            CachedMethod::new("android/view/SurfaceControl$Transaction", "close", "()V");
+        CLOSE.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+/// A binding to `android.hardware.HardwareBuffer`. To render into the
+/// buffer natively, convert it with `AHardwareBuffer_fromHardwareBuffer`
+/// and import it into the graphics API.
+#[repr(transparent)]
+pub struct HardwareBuffer<'local>(pub JObject<'local>);
+
+impl<'local> HardwareBuffer<'local> {
+    pub fn create(
+        env: &mut JNIEnv<'local>,
+        width: jint,
+        height: jint,
+        format: jint,
+        layers: jint,
+        usage: jlong,
+    ) -> Self {
+        static CREATE: CachedStaticMethod = CachedStaticMethod::new(
+            "android/hardware/HardwareBuffer",
+            "create",
+            "(IIIIJ)Landroid/hardware/HardwareBuffer;",
+        );
+        Self(
+            CREATE
+                .call(
+                    env,
+                    &[
+                        width.into(),
+                        height.into(),
+                        format.into(),
+                        layers.into(),
+                        usage.into(),
+                    ],
+                )
+                .l()
+                .unwrap(),
+        )
+    }
+
+    pub fn close(&self, env: &mut JNIEnv<'local>) {
+        static CLOSE: CachedMethod =
+            CachedMethod::new("android/hardware/HardwareBuffer", "close", "()V");
+        CLOSE.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+/// A layer above the view's own surface, backed by a single buffer
+/// that is displayed while it is being rendered to. This is the basis
+/// for low-latency "wet ink": the app draws the stroke in progress
+/// into the front buffer, which appears on screen without waiting
+/// for a buffer swap, and then commits the finished stroke to
+/// the main, double-buffered scene and clears the front buffer.
+///
+/// Front-buffer rendering requires API level 33 and hardware support;
+/// [`FrontBufferedLayer::new`] returns `None` if the buffer can't be
+/// allocated.
+pub struct FrontBufferedLayer {
+    surface_control: TrackedGlobalRef,
+    buffer: TrackedGlobalRef,
+}
+
+impl FrontBufferedLayer {
+    pub fn new<'local>(
+        env: &mut JNIEnv<'local>,
+        view: &View<'local>,
+        width: jint,
+        height: jint,
+        format: jint,
+    ) -> Option<Self> {
+        let parent = view.surface_control(env)?;
+        let sc = SurfaceControl::new_child(env, &parent, "android-view front buffer");
+        static CREATE: CachedStaticMethod = CachedStaticMethod::new(
+            "android/hardware/HardwareBuffer",
+            "create",
+            "(IIIIJ)Landroid/hardware/HardwareBuffer;",
+        );
+        let buffer = CREATE
+            .try_call(
+                env,
+                &[
+                    width.into(),
+                    height.into(),
+                    format.into(),
+                    1.into(),
+                    (HARDWARE_BUFFER_USAGE_FRONT_BUFFER
+                        | HARDWARE_BUFFER_USAGE_GPU_COLOR_OUTPUT
+                        | HARDWARE_BUFFER_USAGE_COMPOSER_OVERLAY)
+                        .into(),
+                ],
+            )
+            .and_then(|value| value.l());
+        let buffer = match buffer {
+            Ok(buffer) => HardwareBuffer(buffer),
+            Err(_) => {
+                // The allocation throws if the usage isn't supported.
+                let _ = env.exception_clear();
+                sc.release(env);
+                return None;
+            }
+        };
+        let transaction = SurfaceControlTransaction::new(env);
+        transaction
+            .set_buffer(env, &sc, &buffer)
+            .set_layer(env, &sc, jint::MAX)
+            .set_visibility(env, &sc, true)
+            .apply(env);
+        transaction.close(env);
+        Some(Self {
+            surface_control: TrackedGlobalRef::new(env, &sc.0, "FrontBufferedLayer"),
+            buffer: TrackedGlobalRef::new(env, &buffer.0, "HardwareBuffer"),
+        })
+    }
+
+    /// The buffer to render into.
+    pub fn buffer<'local>(&self, env: &mut JNIEnv<'local>) -> HardwareBuffer<'local> {
+        HardwareBuffer(env.new_local_ref(&self.buffer).unwrap())
+    }
+
+    pub fn surface_control<'local>(&self, env: &mut JNIEnv<'local>) -> SurfaceControl<'local> {
+        SurfaceControl(env.new_local_ref(&self.surface_control).unwrap())
+    }
+
+    pub fn set_visible(&self, env: &mut JNIEnv, visible: bool) {
+        let sc = self.surface_control(env);
+        let transaction = SurfaceControlTransaction::new(env);
+        transaction.set_visibility(env, &sc, visible).apply(env);
+        transaction.close(env);
+    }
+
+    /// Remove the layer and free the buffer.
+    pub fn release(self, env: &mut JNIEnv) {
+        self.buffer(env).close(env);
+        self.surface_control(env).release(env);
+    }
+}
+
+/// A binding to `SurfaceControlViewHost.SurfacePackage`, a handle to the
+/// content of a [`SurfaceControlViewHost`] that can be embedded with
+/// [`View::set_child_surface_package`]. A package is `Parcelable`, so
+/// it can be sent to a host in another process.
+#[repr(transparent)]
+pub struct SurfacePackage<'local>(pub JObject<'local>);
+
+impl<'local> SurfacePackage<'local> {
+    pub fn surface_control(&self, env: &mut JNIEnv<'local>) -> SurfaceControl<'local> {
+        static GET_SURFACE_CONTROL: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControlViewHost$SurfacePackage",
+            "getSurfaceControl",
+            "()Landroid/view/SurfaceControl;",
+        );
+        SurfaceControl(GET_SURFACE_CONTROL.call(env, &self.0, &[]).l().unwrap())
+    }
+
+    pub fn release(&self, env: &mut JNIEnv<'local>) {
+        static RELEASE: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControlViewHost$SurfacePackage",
+            "release",
+            "()V",
+        );
+        RELEASE.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+/// A binding to `SurfaceControlViewHost`, which renders a view hierarchy
+/// that isn't attached to any window into a [`SurfacePackage`]. The
+/// embedding side passes its [`View::host_token`] and [`View::display`]
+/// to the embedded side, which creates the host and sends back the
+/// package. Requires API level 30.
+pub struct SurfaceControlViewHost(TrackedGlobalRef);
+
+impl SurfaceControlViewHost {
+    pub fn new<'local>(
+        env: &mut JNIEnv<'local>,
+        context: &Context<'local>,
+        display: &JObject<'local>,
+        host_token: &IBinder<'local>,
+    ) -> Self {
+        let host = new_java_object(
+            env,
+            "android/view/SurfaceControlViewHost",
+            "(Landroid/content/Context;Landroid/view/Display;Landroid/os/IBinder;)V",
+            &[(&context.0).into(), display.into(), (&host_token.0).into()],
+        );
+        Self(TrackedGlobalRef::new(env, host, "SurfaceControlViewHost"))
+    }
+
+    /// Set the root of the hosted view hierarchy and its size in pixels.
+    pub fn set_view(&self, env: &mut JNIEnv, view: &JObject, width: jint, height: jint) {
+        static SET_VIEW: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControlViewHost",
+            "setView",
+            "(Landroid/view/View;II)V",
+        );
+        SET_VIEW
+            .call(env, &self.0, &[view.into(), width.into(), height.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Resize the hosted view hierarchy, typically after the embedding
+    /// view has been resized.
+    pub fn relayout(&self, env: &mut JNIEnv, width: jint, height: jint) {
+        static RELAYOUT: CachedMethod =
+            CachedMethod::new("android/view/SurfaceControlViewHost", "relayout", "(II)V");
+        RELAYOUT
+            .call(env, &self.0, &[width.into(), height.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn surface_package<'local>(&self, env: &mut JNIEnv<'local>) -> SurfacePackage<'local> {
+        static GET_SURFACE_PACKAGE: CachedMethod = CachedMethod::new(
+            "android/view/SurfaceControlViewHost",
+            "getSurfacePackage",
+            "()Landroid/view/SurfaceControlViewHost$SurfacePackage;",
+        );
+        SurfacePackage(GET_SURFACE_PACKAGE.call(env, &self.0, &[]).l().unwrap())
+    }
+
+    /// Detach the hosted view hierarchy and release its surface.
+    pub fn release(self, env: &mut JNIEnv) {
+        static RELEASE: CachedMethod =
+            CachedMethod::new("android/view/SurfaceControlViewHost", "release", "()V");
+        RELEASE.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+/// A binding to `AttachedSurfaceControl`, the layer of a view's window,
+/// as returned by [`View::root_surface_control`]. Requires API level 33.
+#[repr(transparent)]
+pub struct AttachedSurfaceControl<'local>(pub JObject<'local>);
+
+impl<'local> AttachedSurfaceControl<'local> {
+    /// Return a transaction that reparents `child` onto the window's
+    /// layer, or `None` if the window can't currently host children.
+    pub fn build_reparent_transaction(
+        &self,
+        env: &mut JNIEnv<'local>,
+        child: &SurfaceControl<'local>,
+    ) -> Option<SurfaceControlTransaction<'local>> {
+        static BUILD_REPARENT_TRANSACTION: CachedMethod = CachedMethod::new(
+            "android/view/AttachedSurfaceControl",
+            "buildReparentTransaction",
+            "(Landroid/view/SurfaceControl;)Landroid/view/SurfaceControl$Transaction;",
+        );
+        let transaction = BUILD_REPARENT_TRANSACTION
+            .call(env, &self.0, &[(&child.0).into()])
+            .l()
+            .unwrap();
+        (!transaction.is_null()).then_some(SurfaceControlTransaction(transaction))
+    }
+
+    /// Apply the transaction in sync with the window's next frame.
+    /// Returns false if the transaction couldn't be scheduled.
+    pub fn apply_transaction_on_draw(
+        &self,
+        env: &mut JNIEnv<'local>,
+        transaction: &SurfaceControlTransaction<'local>,
+    ) -> bool {
+        static APPLY_TRANSACTION_ON_DRAW: CachedMethod = CachedMethod::new(
+            "android/view/AttachedSurfaceControl",
+            "applyTransactionOnDraw",
+            "(Landroid/view/SurfaceControl$Transaction;)Z",
+        );
+        APPLY_TRANSACTION_ON_DRAW
+            .call(env, &self.0, &[(&transaction.0).into()])
+            .z()
+            .unwrap()
+    }
+}
@@ -0,0 +1,372 @@
+//! Async tasks that run on the UI thread, tied to a view peer.
+//!
+//! [`CallbackCtx::spawn`] starts a future that's polled on the UI thread,
+//! from the main looper, whenever it's woken, which may happen from any
+//! thread. Tasks are dropped along with their peer when the view is
+//! detached, so a task never outlives the view it works on.
+//!
+//! Operations that complete through a later peer callback, such as a pixel
+//! copy or a permission request, have `_async` variants on [`CallbackCtx`]
+//! that return a future for the result instead. A result claimed by such
+//! a future isn't passed to the peer's callback.
+//!
+//! Tasks can't hold the peer across an `.await`, since other callbacks
+//! need it in the meantime. Instead, they borrow it again after each
+//! `.await` with [`with_task_peer`].
+
+use jni::{
+    JNIEnv, JavaVM,
+    objects::{GlobalRef, JObject},
+    sys::{jint, jlong, jobject},
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::BTreeMap,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, Mutex, OnceLock},
+    task::{Context as TaskContext, Poll, Wake, Waker},
+};
+
+#[cfg(feature = "graphics")]
+use crate::graphics::*;
+use crate::{callback_ctx::*, intent::*, native_methods::*, view::*};
+
+static JAVA_VM: OnceLock<JavaVM> = OnceLock::new();
+
+// Tasks woken since the peer's last run, which may be woken from any
+// thread. A task can appear more than once.
+static READY: Mutex<Vec<(jlong, u64)>> = Mutex::new(Vec::new());
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+#[derive(Default)]
+struct PeerTasks {
+    next_id: u64,
+    tasks: BTreeMap<u64, Task>,
+}
+
+// What a task being polled needs to reach its peer.
+struct Polling {
+    env: *mut jni::sys::JNIEnv,
+    view: jobject,
+    handle: WeakPeerHandle,
+}
+
+thread_local! {
+    static TASKS: RefCell<BTreeMap<jlong, PeerTasks>> = RefCell::default();
+    static POLLING: RefCell<Option<Polling>> = const { RefCell::new(None) };
+    static PENDING_REQUESTS: RefCell<BTreeMap<RequestKey, Rc<RefCell<PendingRequest>>>> =
+        RefCell::default();
+}
+
+fn wake_tasks(env: &mut JNIEnv, view: &JObject) {
+    // Not a `CachedMethod`, since this is the one `RustView` method
+    // that's meant to be called from any thread.
+    env.call_method(view, "wakeTasks", "()V", &[])
+        .unwrap()
+        .v()
+        .unwrap();
+}
+
+struct TaskWaker {
+    peer: jlong,
+    task: u64,
+    view: GlobalRef,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        READY.lock().unwrap().push((self.peer, self.task));
+        let Some(vm) = JAVA_VM.get() else {
+            return;
+        };
+        let mut env = vm.attach_current_thread_as_daemon().unwrap();
+        wake_tasks(&mut env, self.view.as_obj());
+    }
+}
+
+impl<'local> CallbackCtx<'local> {
+    /// Start a task on the UI thread, which is first polled after the
+    /// current callback returns. The task is dropped if the peer is
+    /// dropped before it finishes.
+    pub fn spawn(&mut self, future: impl Future<Output = ()> + 'static) {
+        JAVA_VM.get_or_init(|| self.env.get_java_vm().unwrap());
+        let peer = self.peer_handle().id();
+        let task = TASKS.with_borrow_mut(|tasks| {
+            let peer_tasks = tasks.entry(peer).or_default();
+            let id = peer_tasks.next_id;
+            peer_tasks.next_id += 1;
+            peer_tasks.tasks.insert(id, Box::pin(future));
+            id
+        });
+        READY.lock().unwrap().push((peer, task));
+        wake_tasks(&mut self.env, &self.view.0);
+    }
+
+    fn pending_request<T>(&self, kind: RequestKind, code: jint) -> RequestFuture<T> {
+        let key = RequestKey {
+            peer: self.peer_handle().id(),
+            kind,
+            code,
+        };
+        let slot = Rc::new(RefCell::new(PendingRequest::default()));
+        PENDING_REQUESTS.with_borrow_mut(|pending| pending.insert(key, Rc::clone(&slot)));
+        RequestFuture {
+            key,
+            slot,
+            _output: PhantomData,
+        }
+    }
+
+    /// Like [`View::request_pixel_copy`], but returns a future for the
+    /// result, or `None` if there's no surface to copy from.
+    #[cfg(feature = "graphics")]
+    pub fn request_pixel_copy_async(
+        &mut self,
+        request_id: jint,
+    ) -> Option<RequestFuture<PixelCopyResult>> {
+        let future = self.pending_request(RequestKind::PixelCopy, request_id);
+        self.view
+            .request_pixel_copy(&mut self.env, request_id)
+            .then_some(future)
+    }
+
+    /// Like [`View::start_document_request`], but returns a future for
+    /// the chosen document's URI, which is `None` if the user canceled,
+    /// or returns `None` if the request couldn't be started.
+    pub fn start_document_request_async(
+        &mut self,
+        intent: &Intent<'local>,
+        request_code: jint,
+    ) -> Option<RequestFuture<Option<GlobalRef>>> {
+        let future = self.pending_request(RequestKind::Document, request_code);
+        self.view
+            .start_document_request(&mut self.env, intent, request_code)
+            .then_some(future)
+    }
+
+    /// Like [`View::request_permissions`], but returns a future for each
+    /// permission and whether it was granted, or `None` if the request
+    /// couldn't be made.
+    pub fn request_permissions_async(
+        &mut self,
+        permissions: &[&str],
+        request_code: jint,
+    ) -> Option<RequestFuture<Vec<(String, bool)>>> {
+        let future = self.pending_request(RequestKind::Permissions, request_code);
+        self.view
+            .request_permissions(&mut self.env, permissions, request_code)
+            .then_some(future)
+    }
+}
+
+/// Call `f` with the peer of the task being polled, and return its result.
+/// Returns `None` without calling `f` if this isn't called from a task
+/// spawned with [`CallbackCtx::spawn`], or if the peer is gone.
+pub fn with_task_peer<T>(
+    f: impl for<'local> FnOnce(&mut CallbackCtx<'local>, &mut dyn ViewPeer) -> T,
+) -> Option<T> {
+    let (env, view, handle) = POLLING.with_borrow(|polling| {
+        let polling = polling.as_ref()?;
+        Some((polling.env, polling.view, polling.handle.clone()))
+    })?;
+    // SAFETY: These are the `JNIEnv` and view of the `runTasksNative`
+    // call that's polling the task on this thread, which are valid
+    // until the poll returns.
+    let env = unsafe { JNIEnv::from_raw(env) }.unwrap();
+    let view = View(unsafe { JObject::from_raw(view) });
+    handle.with(env, view, f)
+}
+
+native_methods! {
+    fn task_methods;
+
+    "runTasksNative" => fn run_tasks<'local>(env: JNIEnv<'local>, view: View<'local>, peer: jlong) {
+        let mut ready = {
+            let mut all_ready = READY.lock().unwrap();
+            let (ready, rest) = all_ready
+                .drain(..)
+                .partition::<Vec<_>, _>(|(p, _)| *p == peer);
+            *all_ready = rest;
+            ready.into_iter().map(|(_, task)| task).collect::<Vec<_>>()
+        };
+        ready.sort_unstable();
+        ready.dedup();
+        let Some(handle) = peer_handle(peer) else {
+            return;
+        };
+        let view_ref = env.new_global_ref(&view.0).unwrap();
+        for id in ready {
+            let task = TASKS.with_borrow_mut(|tasks| tasks.get_mut(&peer)?.tasks.remove(&id));
+            let Some(mut task) = task else {
+                continue;
+            };
+            let waker = Waker::from(Arc::new(TaskWaker {
+                peer,
+                task: id,
+                view: view_ref.clone(),
+            }));
+            let outer = POLLING.replace(Some(Polling {
+                env: env.get_raw(),
+                view: view.0.as_raw(),
+                handle: handle.clone(),
+            }));
+            let poll = task.as_mut().poll(&mut TaskContext::from_waker(&waker));
+            POLLING.set(outer);
+            if poll.is_pending() {
+                TASKS.with_borrow_mut(|tasks| {
+                    // The peer may have been detached while the task ran.
+                    if let Some(peer_tasks) = tasks.get_mut(&peer) {
+                        peer_tasks.tasks.insert(id, task);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Drop the tasks and pending requests of a peer that's being dropped.
+pub(crate) fn drop_tasks(peer: jlong) {
+    READY.lock().unwrap().retain(|(p, _)| *p != peer);
+    // The tasks are dropped outside the borrow, since dropping one can
+    // drop a request future, which unregisters itself.
+    let tasks = TASKS.with_borrow_mut(|tasks| tasks.remove(&peer));
+    drop(tasks);
+    PENDING_REQUESTS.with_borrow_mut(|pending| pending.retain(|key, _| key.peer != peer));
+}
+
+pub(crate) fn register_natives(env: &mut JNIEnv) {
+    env.register_native_methods("org/linebender/android/rustview/RustView", &task_methods())
+        .unwrap();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum RequestKind {
+    #[cfg(feature = "graphics")]
+    PixelCopy,
+    Document,
+    Permissions,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct RequestKey {
+    peer: jlong,
+    kind: RequestKind,
+    code: jint,
+}
+
+#[derive(Default)]
+struct PendingRequest {
+    value: Option<Box<dyn Any>>,
+    waker: Option<Waker>,
+}
+
+/// The result of [`CallbackCtx::request_pixel_copy_async`]. The bitmap is
+/// recycled as soon as the copy is delivered, so its pixels are copied
+/// out first.
+#[cfg(feature = "graphics")]
+#[derive(Clone, Debug)]
+pub struct PixelCopyResult {
+    /// One of the `PixelCopy` result codes, where 0 is success.
+    pub result: jint,
+    pub width: jint,
+    pub height: jint,
+    /// The pixels in RGBA order, or empty if the copy failed.
+    pub rgba: Vec<u8>,
+}
+
+/// A future for the result of a request that completes through a peer
+/// callback. Dropping it before it completes lets the result go to the
+/// peer's callback as usual.
+pub struct RequestFuture<T> {
+    key: RequestKey,
+    slot: Rc<RefCell<PendingRequest>>,
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> Future for RequestFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<T> {
+        let mut slot = self.slot.borrow_mut();
+        match slot.value.take() {
+            Some(value) => Poll::Ready(*value.downcast().unwrap()),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for RequestFuture<T> {
+    fn drop(&mut self) {
+        let _ = PENDING_REQUESTS.try_with(|pending| {
+            let mut pending = pending.borrow_mut();
+            if pending
+                .get(&self.key)
+                .is_some_and(|slot| Rc::ptr_eq(slot, &self.slot))
+            {
+                pending.remove(&self.key);
+            }
+        });
+    }
+}
+
+/// A request that a future is waiting for, claimed by the callback that
+/// delivers its result.
+pub(crate) struct RequestCompleter(Rc<RefCell<PendingRequest>>);
+
+impl RequestCompleter {
+    /// Claim the request with the given key, if a future is waiting for
+    /// it.
+    pub(crate) fn take(peer: jlong, kind: RequestKind, code: jint) -> Option<Self> {
+        let key = RequestKey { peer, kind, code };
+        PENDING_REQUESTS
+            .with_borrow_mut(|pending| pending.remove(&key))
+            .map(Self)
+    }
+
+    pub(crate) fn complete<T: 'static>(self, value: T) {
+        let waker = {
+            let mut slot = self.0.borrow_mut();
+            slot.value = Some(Box::new(value));
+            slot.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelCopyResult {
+    pub(crate) fn from_bitmap<'local>(
+        env: &mut JNIEnv<'local>,
+        result: jint,
+        bitmap: &Bitmap<'local>,
+    ) -> Self {
+        if result != 0 || bitmap.0.as_raw().is_null() {
+            return Self {
+                result,
+                width: 0,
+                height: 0,
+                rgba: Vec::new(),
+            };
+        }
+        Self {
+            result,
+            width: bitmap.width(env),
+            height: bitmap.height(env),
+            rgba: bitmap.to_rgba(env),
+        }
+    }
+}
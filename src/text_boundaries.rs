@@ -0,0 +1,215 @@
+//! Word and sentence boundaries from the platform's `BreakIterator`.
+//!
+//! Finding words by looking for spaces breaks down for languages written
+//! without them, such as Thai, Japanese and Chinese, where the platform
+//! uses dictionaries to find words. These functions run ICU's
+//! `android.icu.text.BreakIterator`, or `java.text.BreakIterator` before
+//! API level 24, over the text and return byte offsets into it.
+
+use jni::{JNIEnv, objects::JString, sys::jint};
+use std::ops::Range;
+
+#[cfg(feature = "ime")]
+use crate::ime::CapsMode;
+use crate::{jni_cache::*, util::*};
+
+/// The kind of boundaries to find.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextBreakKind {
+    /// Boundaries around words, and around the runs of spaces and
+    /// punctuation between them.
+    Word,
+    /// Boundaries after the end of each sentence, including its trailing
+    /// spaces.
+    Sentence,
+}
+
+struct BreakIteratorMethods {
+    get_word_instance: CachedStaticMethod,
+    get_sentence_instance: CachedStaticMethod,
+    set_text: CachedMethod,
+    next: CachedMethod,
+}
+
+static ICU_BREAK_ITERATOR: BreakIteratorMethods = BreakIteratorMethods {
+    get_word_instance: CachedStaticMethod::new(
+        "android/icu/text/BreakIterator",
+        "getWordInstance",
+        "(Ljava/util/Locale;)Landroid/icu/text/BreakIterator;",
+    ),
+    get_sentence_instance: CachedStaticMethod::new(
+        "android/icu/text/BreakIterator",
+        "getSentenceInstance",
+        "(Ljava/util/Locale;)Landroid/icu/text/BreakIterator;",
+    ),
+    set_text: CachedMethod::new(
+        "android/icu/text/BreakIterator",
+        "setText",
+        "(Ljava/lang/String;)V",
+    ),
+    next: CachedMethod::new("android/icu/text/BreakIterator", "next", "()I"),
+};
+
+static JAVA_TEXT_BREAK_ITERATOR: BreakIteratorMethods = BreakIteratorMethods {
+    get_word_instance: CachedStaticMethod::new(
+        "java/text/BreakIterator",
+        "getWordInstance",
+        "(Ljava/util/Locale;)Ljava/text/BreakIterator;",
+    ),
+    get_sentence_instance: CachedStaticMethod::new(
+        "java/text/BreakIterator",
+        "getSentenceInstance",
+        "(Ljava/util/Locale;)Ljava/text/BreakIterator;",
+    ),
+    set_text: CachedMethod::new(
+        "java/text/BreakIterator",
+        "setText",
+        "(Ljava/lang/String;)V",
+    ),
+    next: CachedMethod::new("java/text/BreakIterator", "next", "()I"),
+};
+
+/// `BreakIterator.DONE`.
+const DONE: jint = -1;
+
+/// The boundaries of the given kind in `text`, as ascending byte offsets,
+/// always including 0 and the length of the text. `locale` is a BCP 47
+/// language tag, such as the one from
+/// [`crate::InputMethodManager::current_input_method_locale`], or `None`
+/// for the default locale.
+pub fn text_boundaries(
+    env: &mut JNIEnv,
+    text: &str,
+    kind: TextBreakKind,
+    locale: Option<&str>,
+) -> Vec<usize> {
+    let methods = if android_sdk_version(env) >= 24 {
+        &ICU_BREAK_ITERATOR
+    } else {
+        &JAVA_TEXT_BREAK_ITERATOR
+    };
+    env.with_local_frame(8, |env| -> jni::errors::Result<_> {
+        let locale = java_locale(env, locale);
+        let get_instance = match kind {
+            TextBreakKind::Word => &methods.get_word_instance,
+            TextBreakKind::Sentence => &methods.get_sentence_instance,
+        };
+        let iterator = get_instance.call(env, &[(&locale).into()]).l().unwrap();
+        let java_text: JString = new_java_string(env, text);
+        methods
+            .set_text
+            .call(env, &iterator, &[(&java_text).into()])
+            .v()
+            .unwrap();
+        // The iterator starts at the first boundary, 0, and returns the
+        // rest in UTF-16 offsets, which we convert in a single pass.
+        let mut boundaries = vec![0];
+        let mut chars = text.char_indices();
+        let mut units = 0;
+        loop {
+            let boundary = methods.next.call(env, &iterator, &[]).i().unwrap();
+            if boundary == DONE {
+                break;
+            }
+            let mut offset = text.len();
+            for (i, c) in chars.by_ref() {
+                if units >= boundary as usize {
+                    offset = i;
+                    units += c.len_utf16();
+                    break;
+                }
+                units += c.len_utf16();
+            }
+            if offset > *boundaries.last().unwrap() {
+                boundaries.push(offset);
+            }
+        }
+        if *boundaries.last().unwrap() != text.len() {
+            boundaries.push(text.len());
+        }
+        Ok(boundaries)
+    })
+    .unwrap()
+}
+
+/// Whether a segment between two word boundaries is a word, rather than
+/// spaces or punctuation.
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
+}
+
+/// The word segments of `text`, as byte ranges, leaving out the spaces
+/// and punctuation between them, for word-by-word navigation and for
+/// finding misspelled words.
+pub fn word_ranges(env: &mut JNIEnv, text: &str, locale: Option<&str>) -> Vec<Range<usize>> {
+    text_boundaries(env, text, TextBreakKind::Word, locale)
+        .windows(2)
+        .map(|pair| pair[0]..pair[1])
+        .filter(|range| is_word(&text[range.clone()]))
+        .collect()
+}
+
+/// The word that contains, or ends at, the byte offset `offset`, such as
+/// to select it on a double tap.
+pub fn word_range_at(
+    env: &mut JNIEnv,
+    text: &str,
+    offset: usize,
+    locale: Option<&str>,
+) -> Option<Range<usize>> {
+    let words = word_ranges(env, text, locale);
+    words
+        .iter()
+        .find(|word| word.contains(&offset))
+        .or_else(|| words.iter().find(|word| word.end == offset))
+        .cloned()
+}
+
+/// The last boundary before `offset`, or 0 if there's none.
+pub fn boundary_before(boundaries: &[usize], offset: usize) -> usize {
+    let i = boundaries.partition_point(|&boundary| boundary < offset);
+    if i == 0 { 0 } else { boundaries[i - 1] }
+}
+
+/// The first boundary after `offset`, or the last boundary if there's none.
+pub fn boundary_after(boundaries: &[usize], offset: usize) -> usize {
+    let i = boundaries.partition_point(|&boundary| boundary <= offset);
+    boundaries
+        .get(i)
+        .or(boundaries.last())
+        .copied()
+        .unwrap_or(offset)
+}
+
+/// The capitalization modes in effect at the byte offset `offset`, among
+/// those in `req_modes`, for [`crate::InputConnection::cursor_caps_mode`].
+/// Unlike `TextUtils.getCapsMode`, which looks for spaces and periods,
+/// this uses the locale's word and sentence boundaries.
+#[cfg(feature = "ime")]
+pub fn text_caps_mode(
+    env: &mut JNIEnv,
+    text: &str,
+    offset: usize,
+    req_modes: CapsMode,
+    locale: Option<&str>,
+) -> CapsMode {
+    let mut modes = req_modes & CapsMode::CHARACTERS;
+    if req_modes.contains(CapsMode::WORDS) {
+        let boundaries = text_boundaries(env, text, TextBreakKind::Word, locale);
+        let start = boundary_before(&boundaries, offset);
+        if !is_word(&text[start..offset]) {
+            modes |= CapsMode::WORDS;
+        }
+    }
+    if req_modes.contains(CapsMode::SENTENCES) {
+        // The end of the text is always a boundary, so follow the text
+        // before the caret with a capital letter, which starts a new
+        // sentence only after one has ended.
+        let probe = format!("{}A", &text[..offset]);
+        let boundaries = text_boundaries(env, &probe, TextBreakKind::Sentence, locale);
+        if boundaries.binary_search(&offset).is_ok() {
+            modes |= CapsMode::SENTENCES;
+        }
+    }
+    modes
+}
@@ -0,0 +1,161 @@
+//! Reuse of Java strings for repeated IME text queries.
+//!
+//! Keyboards tend to query the same text around the cursor several times
+//! per keystroke, so we hand out the same Java string again while the text
+//! hasn't changed. Java strings are immutable, so sharing them is safe.
+//! Keeping a string costs a global reference, so a string is only kept
+//! once the same query has returned the same text twice; a keystroke that
+//! changes the text then costs no more than it would without the cache.
+//!
+//! The cache is generic over the string type so that it can be tested
+//! and benchmarked without a JVM; the IME glue stores global references.
+
+use jni::sys::jlong;
+use std::collections::{BTreeMap, btree_map::Entry};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum TextQuery {
+    BeforeCursor,
+    AfterCursor,
+    Selected,
+}
+
+struct CachedText<S> {
+    text: String,
+    string: Option<S>,
+}
+
+pub(crate) struct TextCache<S> {
+    entries: BTreeMap<(jlong, TextQuery), CachedText<S>>,
+}
+
+pub(crate) enum TextLookup<'a, S> {
+    /// A string was kept for this text; hand it out again.
+    Hit(&'a S),
+    /// The query returned the same text last time, so the string created
+    /// for it now should be kept in the slot.
+    Repeat(&'a mut Option<S>),
+    /// The text changed since the last time the query was made.
+    Miss,
+}
+
+impl<S> TextCache<S> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn lookup(
+        &mut self,
+        peer: jlong,
+        query: TextQuery,
+        text: &str,
+    ) -> TextLookup<'_, S> {
+        let cached = match self.entries.entry((peer, query)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(CachedText {
+                    text: text.to_owned(),
+                    string: None,
+                });
+                return TextLookup::Miss;
+            }
+        };
+        if cached.text != text {
+            // Reuse the buffer, since the text usually stays about the
+            // same length.
+            cached.text.clear();
+            cached.text.push_str(text);
+            cached.string = None;
+            return TextLookup::Miss;
+        }
+        match &mut cached.string {
+            Some(string) => TextLookup::Hit(string),
+            slot @ None => TextLookup::Repeat(slot),
+        }
+    }
+
+    pub(crate) fn forget_peer(&mut self, peer: jlong) {
+        self.entries.retain(|(p, _), _| *p != peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for a global reference, counting how many were created.
+    fn lookup(cache: &mut TextCache<u32>, created: &mut u32, text: &str) -> bool {
+        match cache.lookup(1, TextQuery::BeforeCursor, text) {
+            TextLookup::Hit(_) => true,
+            TextLookup::Repeat(slot) => {
+                *created += 1;
+                *slot = Some(*created);
+                false
+            }
+            TextLookup::Miss => false,
+        }
+    }
+
+    #[test]
+    fn text_cache_keeps_string_only_when_repeated() {
+        let mut cache = TextCache::new();
+        let mut created = 0;
+        assert!(!lookup(&mut cache, &mut created, "Hello"));
+        assert_eq!(created, 0);
+        assert!(!lookup(&mut cache, &mut created, "Hello"));
+        assert_eq!(created, 1);
+        assert!(lookup(&mut cache, &mut created, "Hello"));
+        assert!(lookup(&mut cache, &mut created, "Hello"));
+        assert_eq!(created, 1);
+    }
+
+    #[test]
+    fn text_cache_treats_first_empty_text_as_miss() {
+        let mut cache = TextCache::new();
+        let mut created = 0;
+        assert!(!lookup(&mut cache, &mut created, ""));
+        assert_eq!(created, 0);
+    }
+
+    #[test]
+    fn text_cache_does_not_keep_strings_while_typing() {
+        let mut cache = TextCache::new();
+        let mut created = 0;
+        let mut text = String::new();
+        for c in "Hello, world".chars() {
+            text.push(c);
+            assert!(!lookup(&mut cache, &mut created, &text));
+        }
+        assert_eq!(created, 0);
+    }
+
+    #[test]
+    fn text_cache_separates_queries_and_peers() {
+        let mut cache = TextCache::new();
+        for _ in 0..2 {
+            cache.lookup(1, TextQuery::BeforeCursor, "a");
+        }
+        if let TextLookup::Repeat(slot) = cache.lookup(1, TextQuery::BeforeCursor, "a") {
+            *slot = Some(1);
+        }
+        assert!(matches!(
+            cache.lookup(1, TextQuery::AfterCursor, "a"),
+            TextLookup::Miss
+        ));
+        assert!(matches!(
+            cache.lookup(2, TextQuery::BeforeCursor, "a"),
+            TextLookup::Miss
+        ));
+        assert!(matches!(
+            cache.lookup(1, TextQuery::BeforeCursor, "a"),
+            TextLookup::Hit(1)
+        ));
+        cache.forget_peer(1);
+        assert!(matches!(
+            cache.lookup(1, TextQuery::BeforeCursor, "a"),
+            TextLookup::Miss
+        ));
+    }
+}
@@ -0,0 +1,144 @@
+//! Helpers for right-to-left text.
+//!
+//! Android mirrors its own widgets when the layout direction is RTL, but a
+//! view that draws its own text has to do the equivalent itself: arrow
+//! keys and horizontal swipes move the caret visually, so in an RTL
+//! paragraph, left means forward in the text. IMEs pick their keyboard's
+//! direction from the language, so an editor that expects RTL input should
+//! pass an RTL language to [`crate::EditorInfo::set_hint_locales`]; there's
+//! no separate direction hint.
+
+use num_enum::{FromPrimitive, IntoPrimitive};
+
+/// `View.LAYOUT_DIRECTION_*`, after resolution, so never `INHERIT` or
+/// `LOCALE`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, FromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum LayoutDirection {
+    #[num_enum(default)]
+    Ltr = 0,
+    Rtl = 1,
+}
+
+impl LayoutDirection {
+    pub fn is_rtl(self) -> bool {
+        self == Self::Rtl
+    }
+}
+
+/// A direction in the logical order of the text, as opposed to on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LogicalDirection {
+    /// Toward the start of the text.
+    Backward,
+    /// Toward the end of the text.
+    Forward,
+}
+
+/// The logical direction of a horizontal movement, such as a left or right
+/// arrow key or a horizontal swipe over a selection handle, in a paragraph
+/// with the given direction.
+pub fn logical_direction(paragraph: LayoutDirection, toward_right: bool) -> LogicalDirection {
+    if toward_right != paragraph.is_rtl() {
+        LogicalDirection::Forward
+    } else {
+        LogicalDirection::Backward
+    }
+}
+
+/// Mirror an x coordinate within a width, for laying out or hit-testing
+/// content that's drawn left-to-right and flipped for RTL.
+pub fn mirror_x(x: f64, width: f64) -> f64 {
+    width - x
+}
+
+fn is_strong_rtl(c: char) -> bool {
+    matches!(
+        c as u32,
+        // Hebrew, Arabic, Syriac, Thaana, NKo, Samaritan, Mandaic, and
+        // the Arabic supplements and extensions.
+        0x0590..=0x08ff
+            // Hebrew and Arabic presentation forms.
+            | 0xfb1d..=0xfdff
+            | 0xfe70..=0xfeff
+            // Historic RTL scripts and Arabic mathematical symbols.
+            | 0x10800..=0x10fff
+            | 0x1e800..=0x1efff
+    ) && c.is_alphabetic()
+}
+
+/// The direction of a paragraph, from its first strong character, as
+/// `TextDirectionHeuristics.FIRSTSTRONG_LTR` determines it, or `None` if
+/// it has no letters. Letters in RTL scripts are recognized by block, which
+/// covers the scripts Android ships fonts for; full Unicode bidi classes
+/// would need the `unicode-bidi` crate.
+pub fn paragraph_direction(text: &str) -> Option<LayoutDirection> {
+    text.chars().find(|c| c.is_alphabetic()).map(|c| {
+        if is_strong_rtl(c) {
+            LayoutDirection::Rtl
+        } else {
+            LayoutDirection::Ltr
+        }
+    })
+}
+
+/// The direction of each paragraph in `text`, separated by newlines,
+/// with paragraphs that have no letters taking `default`, as in a plain
+/// text editor whose paragraphs each follow their own content.
+pub fn paragraph_directions(
+    text: &str,
+    default: LayoutDirection,
+) -> impl Iterator<Item = LayoutDirection> + '_ {
+    text.split('\n')
+        .map(move |paragraph| paragraph_direction(paragraph).unwrap_or(default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use LayoutDirection::{Ltr, Rtl};
+
+    // From the demo's `LOREM`, whose second paragraph has Hebrew in the
+    // middle of Latin text.
+    const MIXED: &str = "Vivamus venenatis et gהתעשייה בנושא האינטרנטa nibh nec pharetra.";
+    const HEBREW: &str = "התעשייה בנושא האינטרנטa nibh nec pharetra.";
+
+    #[test]
+    fn first_strong_character_decides() {
+        assert_eq!(paragraph_direction(MIXED), Some(Ltr));
+        assert_eq!(paragraph_direction(HEBREW), Some(Rtl));
+        // Digits and punctuation aren't strong.
+        assert_eq!(paragraph_direction("1. התעשייה"), Some(Rtl));
+        assert_eq!(paragraph_direction(" 42, "), None);
+        assert_eq!(paragraph_direction(""), None);
+    }
+
+    #[test]
+    fn paragraphs_follow_their_own_content() {
+        let text = format!("{MIXED}\n\n{HEBREW}");
+        assert_eq!(
+            paragraph_directions(&text, Ltr).collect::<Vec<_>>(),
+            [Ltr, Ltr, Rtl]
+        );
+        assert_eq!(
+            paragraph_directions(&text, Rtl).collect::<Vec<_>>(),
+            [Ltr, Rtl, Rtl]
+        );
+    }
+
+    #[test]
+    fn horizontal_movement() {
+        let ltr = paragraph_direction(MIXED).unwrap();
+        assert_eq!(logical_direction(ltr, true), LogicalDirection::Forward);
+        assert_eq!(logical_direction(ltr, false), LogicalDirection::Backward);
+        let rtl = paragraph_direction(HEBREW).unwrap();
+        assert_eq!(logical_direction(rtl, true), LogicalDirection::Backward);
+        assert_eq!(logical_direction(rtl, false), LogicalDirection::Forward);
+    }
+
+    #[test]
+    fn mirroring() {
+        assert_eq!(mirror_x(10.0, 100.0), 90.0);
+        assert_eq!(mirror_x(mirror_x(10.0, 100.0), 100.0), 10.0);
+    }
+}
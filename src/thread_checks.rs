@@ -0,0 +1,63 @@
+//! Debug checks, enabled by the `thread-checks` feature, that callbacks
+//! and calls into `RustView` happen on the UI thread.
+//!
+//! A `View<'local>` or `JNIEnv` that is stashed and then used from
+//! another thread, such as a render thread, otherwise fails with an
+//! obscure JNI abort or a generic `SendWrapper` panic. These checks log
+//! a diagnostic naming the offending call before panicking, so the
+//! cause shows up in logcat.
+
+use jni::sys::jlong;
+use std::{
+    sync::OnceLock,
+    thread::{self, ThreadId},
+};
+
+static UI_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// Record the current thread as the UI thread. Views are constructed
+/// on the UI thread, so this is called when a peer is registered.
+pub(crate) fn record_ui_thread() {
+    UI_THREAD.get_or_init(|| thread::current().id());
+}
+
+fn fail(message: String) -> ! {
+    log::error!(target: "android-view", "{message}");
+    panic!("{message}");
+}
+
+fn describe_current_thread() -> String {
+    let current = thread::current();
+    match current.name() {
+        Some(name) => format!("{name:?} ({:?})", current.id()),
+        None => format!("{:?}", current.id()),
+    }
+}
+
+/// Check that a method of a view wrapper is being called on the UI
+/// thread.
+pub(crate) fn check_ui_thread(method: &str) {
+    let Some(ui_thread) = UI_THREAD.get() else {
+        return;
+    };
+    if thread::current().id() != *ui_thread {
+        fail(format!(
+            "{method} called on thread {}, but views may only be used on the UI thread; \
+             a View or JNIEnv from a callback must not be kept and used from another thread",
+            describe_current_thread(),
+        ));
+    }
+}
+
+/// Check that a callback for a peer arrived on the thread that
+/// registered it. `valid` is the result of `SendWrapper::valid`
+/// for the peer.
+pub(crate) fn check_peer_thread(id: jlong, valid: bool) {
+    if !valid {
+        fail(format!(
+            "callback for peer {id} arrived on thread {}, which isn't the thread that \
+             registered the peer",
+            describe_current_thread(),
+        ));
+    }
+}
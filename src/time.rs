@@ -0,0 +1,62 @@
+//! Conversions between Android timestamps and [`Instant`].
+//!
+//! Key and motion event times from `getEventTime` and `getDownTime` are
+//! milliseconds in the `SystemClock.uptimeMillis` time base, while
+//! `getEventTimeNanos` and Choreographer frame times are nanoseconds in
+//! the `System.nanoTime` time base. On Android both of these, and
+//! [`Instant`], read `CLOCK_MONOTONIC`, so each conversion is a fixed
+//! offset, which is measured once and then reused.
+
+use jni::{JNIEnv, sys::jlong};
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use crate::jni_cache::*;
+
+/// Return the current time in the `System.nanoTime` time base.
+pub fn monotonic_now_nanos(env: &mut JNIEnv) -> jlong {
+    static NANO_TIME: CachedStaticMethod =
+        CachedStaticMethod::new("java/lang/System", "nanoTime", "()J");
+    NANO_TIME.call(env, &[]).j().unwrap()
+}
+
+fn base(env: &mut JNIEnv) -> (Instant, jlong) {
+    static BASE: OnceLock<(Instant, jlong)> = OnceLock::new();
+    *BASE.get_or_init(|| {
+        let nanos = monotonic_now_nanos(env);
+        (Instant::now(), nanos)
+    })
+}
+
+/// Convert a time in the `System.nanoTime` time base, such as a frame
+/// time passed to [`crate::ViewPeer::do_frame`], to an [`Instant`].
+pub fn instant_from_nanos(env: &mut JNIEnv, nanos: jlong) -> Instant {
+    let (base_instant, base_nanos) = base(env);
+    let offset = nanos - base_nanos;
+    let magnitude = Duration::from_nanos(offset.unsigned_abs());
+    if offset >= 0 {
+        base_instant + magnitude
+    } else {
+        // An `Instant` can't represent times before boot on every
+        // platform, so clamp rather than panic.
+        base_instant.checked_sub(magnitude).unwrap_or(base_instant)
+    }
+}
+
+/// Convert a time in the `SystemClock.uptimeMillis` time base, such as
+/// [`crate::MotionEvent::event_time`], to an [`Instant`].
+pub fn instant_from_uptime_millis(env: &mut JNIEnv, millis: jlong) -> Instant {
+    instant_from_nanos(env, millis.saturating_mul(1_000_000))
+}
+
+/// Convert an [`Instant`] to the `System.nanoTime` time base, for
+/// comparison with frame times.
+pub fn instant_to_nanos(env: &mut JNIEnv, instant: Instant) -> jlong {
+    let (base_instant, base_nanos) = base(env);
+    match instant.checked_duration_since(base_instant) {
+        Some(after) => base_nanos + after.as_nanos() as jlong,
+        None => base_nanos - base_instant.duration_since(instant).as_nanos() as jlong,
+    }
+}
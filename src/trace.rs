@@ -0,0 +1,154 @@
+//! Logging of selected categories of peer callbacks, enabled by the
+//! `trace` feature, for debugging problems that only show up on some
+//! devices or input methods.
+//!
+//! The categories to log are read from the `debug.android_view.trace`
+//! system property, a comma-separated list of `touch`, `key`, `ime`,
+//! `surface` and `accessibility`, or `all`. The property can be set on a
+//! release build with `adb shell setprop`, and is read again whenever a
+//! view is attached to a window, so reopening the screen picks up a
+//! change. Apps can also choose the categories in code with
+//! [`set_trace_categories`], such as from a hidden debug menu.
+//!
+//! Each callback is logged at info level through the `log` crate, with
+//! the target `android-view::trace`, so the app must install a logger,
+//! such as `android_logger`. Only summaries are logged: for example, the
+//! length of text committed by the IME, never the text itself. While no
+//! category is enabled, the cost is one atomic load per callback.
+
+use bitflags::bitflags;
+use ndk::event::Keycode;
+use std::{
+    ffi::{CStr, c_char, c_int},
+    fmt,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
+
+use crate::{callback_ctx::*, events::*};
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct TraceCategories: u32 {
+        /// Touch, hover, trackball and generic motion events.
+        const TOUCH = 1 << 0;
+        const KEY = 1 << 1;
+        /// Calls from the input method through `InputConnection`.
+        const IME = 1 << 2;
+        /// Creation, changes and destruction of the view's surface.
+        const SURFACE = 1 << 3;
+        /// Calls from accessibility services.
+        const ACCESSIBILITY = 1 << 4;
+    }
+}
+
+static CATEGORIES: AtomicU32 = AtomicU32::new(0);
+static OVERRIDDEN: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "C" {
+    // Part of bionic's libc on every Android version.
+    fn __system_property_get(name: *const c_char, value: *mut c_char) -> c_int;
+}
+
+const PROP_VALUE_MAX: usize = 92;
+
+fn read_property() -> TraceCategories {
+    let mut value = [0 as c_char; PROP_VALUE_MAX];
+    // SAFETY: The name is NUL-terminated, and the buffer has the size
+    // that bionic requires.
+    unsafe { __system_property_get(c"debug.android_view.trace".as_ptr(), value.as_mut_ptr()) };
+    // SAFETY: bionic NUL-terminates the value, which is empty if the
+    // property isn't set.
+    let value = unsafe { CStr::from_ptr(value.as_ptr()) };
+    parse_categories(&value.to_string_lossy())
+}
+
+fn parse_categories(value: &str) -> TraceCategories {
+    value
+        .split(',')
+        .map(|name| match name.trim() {
+            "all" => TraceCategories::all(),
+            "touch" => TraceCategories::TOUCH,
+            "key" => TraceCategories::KEY,
+            "ime" => TraceCategories::IME,
+            "surface" => TraceCategories::SURFACE,
+            "accessibility" => TraceCategories::ACCESSIBILITY,
+            _ => TraceCategories::empty(),
+        })
+        .collect()
+}
+
+/// Log the given categories, overriding the system property, or follow
+/// the property again if `categories` is `None`.
+pub fn set_trace_categories(categories: Option<TraceCategories>) {
+    OVERRIDDEN.store(categories.is_some(), Ordering::Relaxed);
+    let categories = categories.unwrap_or_else(read_property);
+    CATEGORIES.store(categories.bits(), Ordering::Relaxed);
+}
+
+/// The categories that are currently logged.
+pub fn trace_categories() -> TraceCategories {
+    TraceCategories::from_bits_truncate(CATEGORIES.load(Ordering::Relaxed))
+}
+
+pub(crate) fn reload_property() {
+    if !OVERRIDDEN.load(Ordering::Relaxed) {
+        CATEGORIES.store(read_property().bits(), Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn enabled(category: TraceCategories) -> bool {
+    trace_categories().contains(category)
+}
+
+pub(crate) fn log(ctx: &CallbackCtx, category: TraceCategories, args: fmt::Arguments) {
+    if enabled(category) {
+        log::info!(
+            target: "android-view::trace",
+            "peer {}: {args}",
+            ctx.peer_handle().id()
+        );
+    }
+}
+
+pub(crate) fn motion_event<'local>(
+    ctx: &mut CallbackCtx<'local>,
+    callback: &str,
+    event: &MotionEvent<'local>,
+) {
+    if !enabled(TraceCategories::TOUCH) {
+        return;
+    }
+    let env = &mut ctx.env;
+    let action = event.action_masked(env);
+    let pointer_count = event.pointer_count(env);
+    let (x, y) = (event.x(env), event.y(env));
+    let source = event.source(env);
+    log(
+        ctx,
+        TraceCategories::TOUCH,
+        format_args!("{callback}: {action:?}, {pointer_count} pointers, ({x}, {y}), {source:?}"),
+    );
+}
+
+pub(crate) fn key_event<'local>(
+    ctx: &mut CallbackCtx<'local>,
+    callback: &str,
+    key_code: Keycode,
+    event: &KeyEvent<'local>,
+) {
+    if !enabled(TraceCategories::KEY) {
+        return;
+    }
+    let env = &mut ctx.env;
+    let action = event.action(env);
+    let repeat_count = event.repeat_count(env);
+    let meta_state = event.meta_state(env);
+    let source = event.source(env);
+    log(
+        ctx,
+        TraceCategories::KEY,
+        format_args!(
+            "{callback}: {key_code:?}, {action:?}, repeat {repeat_count}, {meta_state:?}, {source:?}"
+        ),
+    );
+}
@@ -0,0 +1,74 @@
+use jni::JNIEnv;
+
+use crate::{jni_cache::*, util::*, view::*};
+
+/// A binding to `android.speech.tts.TextToSpeech`. Initialization and
+/// utterance completion are reported to the view's peer through
+/// [`ViewPeer::on_text_to_speech_init`] and [`ViewPeer::on_utterance_done`].
+///
+/// This holds a global reference, so it can be kept in a peer across
+/// callbacks. Call [`TextToSpeech::shutdown`] when it's no longer needed.
+pub struct TextToSpeech(TrackedGlobalRef);
+
+impl TextToSpeech {
+    pub fn new<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) -> Self {
+        let tts = new_java_object(
+            env,
+            "org/linebender/android/rustview/RustTextToSpeech",
+            "(Lorg/linebender/android/rustview/RustView;)V",
+            &[(&view.0).into()],
+        );
+        Self(TrackedGlobalRef::new(env, tts, "TextToSpeech"))
+    }
+
+    /// Queue `text` to be spoken. If `flush` is `true`, anything already
+    /// queued or being spoken is dropped. Returns `false` if the text
+    /// couldn't be queued, e.g. because initialization hasn't finished.
+    pub fn speak(&self, env: &mut JNIEnv, text: &str, flush: bool, utterance_id: &str) -> bool {
+        static SPEAK: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustTextToSpeech",
+            "speak",
+            "(Ljava/lang/String;ZLjava/lang/String;)Z",
+        );
+        let text = new_java_string(env, text);
+        let utterance_id = new_java_string(env, utterance_id);
+        SPEAK
+            .call(
+                env,
+                self.0.as_obj(),
+                &[(&text).into(), flush.into(), (&utterance_id).into()],
+            )
+            .z()
+            .unwrap()
+    }
+
+    pub fn is_speaking(&self, env: &mut JNIEnv) -> bool {
+        static IS_SPEAKING: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustTextToSpeech",
+            "isSpeaking",
+            "()Z",
+        );
+        IS_SPEAKING.call(env, self.0.as_obj(), &[]).z().unwrap()
+    }
+
+    /// Stop speaking and discard everything queued.
+    pub fn stop(&self, env: &mut JNIEnv) {
+        static STOP: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustTextToSpeech",
+            "stop",
+            "()V",
+        );
+        STOP.call(env, self.0.as_obj(), &[]).v().unwrap()
+    }
+
+    /// Release the engine. This must be called when the engine is no
+    /// longer needed, e.g. when the view is detached.
+    pub fn shutdown(self, env: &mut JNIEnv) {
+        static SHUTDOWN: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustTextToSpeech",
+            "shutdown",
+            "()V",
+        );
+        SHUTDOWN.call(env, self.0.as_obj(), &[]).v().unwrap()
+    }
+}
@@ -0,0 +1,204 @@
+use jni::{
+    JNIEnv,
+    objects::{JObject, JObjectArray, JString},
+    sys::jint,
+};
+use std::io;
+
+use crate::{content::*, context::*, jni_cache::*};
+
+/// The frequency the system's own "add to dictionary" action uses for new
+/// words, on a scale from 1 to 255.
+pub const USER_WORD_DEFAULT_FREQUENCY: jint = 250;
+
+/// A word the user added to their personal dictionary, from the
+/// `android.provider.UserDictionary` provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserWord {
+    pub word: String,
+    /// How often the word is used, from 1 to 255.
+    pub frequency: jint,
+    /// The locale the word applies to, as a BCP 47 language tag, or `None`
+    /// if it applies to all locales.
+    pub locale: Option<String>,
+    /// Text that the IME offers to expand into the word, if any.
+    pub shortcut: Option<String>,
+}
+
+/// Whether a word stored for `word_locale` applies to `locale`. The
+/// provider stores locales in `Locale.toString` form, such as `en_US`,
+/// and a word stored for a language alone applies to all its regions.
+fn locale_matches(word_locale: &str, locale: &str) -> bool {
+    let word_locale = word_locale.replace('_', "-").to_ascii_lowercase();
+    let locale = locale.to_ascii_lowercase();
+    locale == word_locale
+        || locale
+            .strip_prefix(word_locale.as_str())
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+/// Like [`take_exception`], but reports the `SecurityException` thrown
+/// when the app isn't allowed to use the dictionary as such.
+fn take_provider_exception(env: &mut JNIEnv) -> io::Error {
+    let error = take_exception(env);
+    if error.to_string().starts_with("java.lang.SecurityException") {
+        io::Error::new(io::ErrorKind::PermissionDenied, error)
+    } else {
+        error
+    }
+}
+
+fn get_optional_string<'local>(
+    env: &mut JNIEnv<'local>,
+    cursor: &JObject<'local>,
+    column: jint,
+) -> Option<String> {
+    static GET_STRING: CachedMethod = CachedMethod::new(
+        "android/database/Cursor",
+        "getString",
+        "(I)Ljava/lang/String;",
+    );
+    let value = GET_STRING.call(env, cursor, &[column.into()]).l().unwrap();
+    if value.as_raw().is_null() {
+        return None;
+    }
+    Some(env.get_string(&JString::from(value)).unwrap().into())
+}
+
+/// The words in the user's personal dictionary that apply to `locale`,
+/// a BCP 47 language tag, including words for all locales, or every word
+/// if `locale` is `None`. An editor can use these to avoid flagging the
+/// user's own words as misspelled and to rank them in suggestions.
+///
+/// Since API level 23, only the default IME and spell checker can read
+/// the dictionary, so this fails with [`io::ErrorKind::PermissionDenied`]
+/// for other apps on those versions.
+pub fn user_dictionary_words<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &Context<'local>,
+    locale: Option<&str>,
+) -> io::Result<Vec<UserWord>> {
+    static QUERY: CachedMethod = CachedMethod::new(
+        "android/content/ContentResolver",
+        "query",
+        "(Landroid/net/Uri;[Ljava/lang/String;Ljava/lang/String;[Ljava/lang/String;\
+         Ljava/lang/String;)Landroid/database/Cursor;",
+    );
+    static MOVE_TO_NEXT: CachedMethod =
+        CachedMethod::new("android/database/Cursor", "moveToNext", "()Z");
+    static GET_INT: CachedMethod = CachedMethod::new("android/database/Cursor", "getInt", "(I)I");
+    static CLOSE: CachedMethod = CachedMethod::new("android/database/Cursor", "close", "()V");
+    const COLUMNS: [&str; 4] = ["word", "frequency", "locale", "shortcut"];
+    let uri = env
+        .get_static_field(
+            "android/provider/UserDictionary$Words",
+            "CONTENT_URI",
+            "Landroid/net/Uri;",
+        )
+        .unwrap()
+        .l()
+        .unwrap();
+    let projection: JObjectArray = env
+        .new_object_array(COLUMNS.len() as jint, "java/lang/String", JObject::null())
+        .unwrap();
+    for (i, column) in COLUMNS.iter().enumerate() {
+        let column = new_java_string(env, column);
+        env.set_object_array_element(&projection, i as jint, column)
+            .unwrap();
+    }
+    let resolver = context.content_resolver(env);
+    let null = JObject::null();
+    let cursor = QUERY
+        .try_call(
+            env,
+            &resolver.0,
+            &[
+                (&uri).into(),
+                (&projection).into(),
+                (&null).into(),
+                (&null).into(),
+                (&null).into(),
+            ],
+        )
+        .and_then(|value| value.l())
+        .map_err(|_| take_provider_exception(env))?;
+    if cursor.as_raw().is_null() {
+        return Err(io::ErrorKind::NotFound.into());
+    }
+    let mut words = Vec::new();
+    while MOVE_TO_NEXT.call(env, &cursor, &[]).z().unwrap() {
+        let word_locale = get_optional_string(env, &cursor, 2).filter(|l| !l.is_empty());
+        if let (Some(locale), Some(word_locale)) = (locale, &word_locale)
+            && !locale_matches(word_locale, locale)
+        {
+            continue;
+        }
+        let Some(word) = get_optional_string(env, &cursor, 0) else {
+            continue;
+        };
+        words.push(UserWord {
+            word,
+            frequency: GET_INT.call(env, &cursor, &[1.into()]).i().unwrap(),
+            locale: word_locale.map(|l| l.replace('_', "-")),
+            shortcut: get_optional_string(env, &cursor, 3).filter(|s| !s.is_empty()),
+        });
+    }
+    CLOSE.call(env, &cursor, &[]);
+    Ok(words)
+}
+
+/// Add a word to the user's personal dictionary, for an editor's "add to
+/// dictionary" action on a word it flagged as unknown. `locale` is a
+/// BCP 47 language tag, or `None` to add the word for all locales.
+/// `frequency` is from 1 to 255; [`USER_WORD_DEFAULT_FREQUENCY`] matches
+/// the system's own action.
+///
+/// Since API level 23, only the default IME and spell checker can write
+/// to the dictionary, so this fails with
+/// [`io::ErrorKind::PermissionDenied`] for other apps on those versions.
+/// Such apps can instead start the `android.settings.USER_DICTIONARY_INSERT`
+/// activity with the word as the `word` extra, letting the user confirm it.
+pub fn add_user_dictionary_word<'local>(
+    env: &mut JNIEnv<'local>,
+    context: &Context<'local>,
+    word: &str,
+    frequency: jint,
+    shortcut: Option<&str>,
+    locale: Option<&str>,
+) -> io::Result<()> {
+    static ADD_WORD: CachedStaticMethod = CachedStaticMethod::new(
+        "android/provider/UserDictionary$Words",
+        "addWord",
+        "(Landroid/content/Context;Ljava/lang/String;ILjava/lang/String;Ljava/util/Locale;)V",
+    );
+    static FOR_LANGUAGE_TAG: CachedStaticMethod = CachedStaticMethod::new(
+        "java/util/Locale",
+        "forLanguageTag",
+        "(Ljava/lang/String;)Ljava/util/Locale;",
+    );
+    let word = new_java_string(env, word);
+    let shortcut = match shortcut {
+        Some(shortcut) => JObject::from(new_java_string(env, shortcut)),
+        None => JObject::null(),
+    };
+    let locale = match locale {
+        Some(tag) => {
+            let tag = new_java_string(env, tag);
+            FOR_LANGUAGE_TAG.call(env, &[(&tag).into()]).l().unwrap()
+        }
+        None => JObject::null(),
+    };
+    ADD_WORD
+        .try_call(
+            env,
+            &[
+                (&context.0).into(),
+                (&word).into(),
+                frequency.into(),
+                (&shortcut).into(),
+                (&locale).into(),
+            ],
+        )
+        .map_err(|_| take_provider_exception(env))?;
+    Ok(())
+}
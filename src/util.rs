@@ -1,5 +1,104 @@
-use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean};
+use jni::{
+    JNIEnv,
+    objects::{GlobalRef, JObject},
+    sys::{JNI_FALSE, JNI_TRUE, jboolean, jint},
+};
+use std::{ops::Deref, sync::OnceLock};
+
+use crate::jni_cache::*;
 
 pub(crate) fn as_jboolean(flag: bool) -> jboolean {
     if flag { JNI_TRUE } else { JNI_FALSE }
 }
+
+/// The device's API level, from `Build.VERSION.SDK_INT`.
+pub(crate) fn android_sdk_version(env: &mut JNIEnv) -> jint {
+    static SDK_INT: OnceLock<jint> = OnceLock::new();
+    *SDK_INT.get_or_init(|| {
+        env.get_static_field("android/os/Build$VERSION", "SDK_INT", "I")
+            .unwrap()
+            .i()
+            .unwrap()
+    })
+}
+
+/// A global reference held by one of the crate's owned wrappers, such as
+/// [`crate::Popup`]. With the `jni-checks` feature, references created
+/// during a peer callback are counted until dropped, so ones that outlive
+/// the peer can be reported.
+pub(crate) struct TrackedGlobalRef {
+    global: GlobalRef,
+    #[cfg(feature = "jni-checks")]
+    _tracker: Option<crate::jni_checks::GlobalRefTracker>,
+}
+
+impl TrackedGlobalRef {
+    pub(crate) fn new<'local>(
+        env: &JNIEnv,
+        obj: impl AsRef<JObject<'local>>,
+        #[cfg_attr(not(feature = "jni-checks"), allow(unused_variables))] kind: &'static str,
+    ) -> Self {
+        Self {
+            global: env.new_global_ref(obj).unwrap(),
+            #[cfg(feature = "jni-checks")]
+            _tracker: crate::jni_checks::GlobalRefTracker::new(kind),
+        }
+    }
+}
+
+impl Deref for TrackedGlobalRef {
+    type Target = GlobalRef;
+
+    fn deref(&self) -> &GlobalRef {
+        &self.global
+    }
+}
+
+impl AsRef<JObject<'static>> for TrackedGlobalRef {
+    fn as_ref(&self) -> &JObject<'static> {
+        self.global.as_obj()
+    }
+}
+
+/// Convert an offset in UTF-16 code units to a byte offset in `text`.
+/// An offset past the end maps to the end, and one inside a surrogate
+/// pair maps to the start of that character.
+#[cfg(feature = "ime")]
+pub(crate) fn utf16_to_byte_offset(text: &str, utf16_offset: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        units += c.len_utf16();
+        if units > utf16_offset {
+            return i;
+        }
+    }
+    text.len()
+}
+
+/// Convert a byte offset in `text` to an offset in UTF-16 code units.
+#[cfg(feature = "ime")]
+pub(crate) fn byte_to_utf16_offset(text: &str, byte_offset: usize) -> usize {
+    text[..byte_offset].chars().map(char::len_utf16).sum()
+}
+
+/// A `java.util.Locale` for a BCP 47 language tag, or the default locale
+/// if `None`.
+pub(crate) fn java_locale<'local>(
+    env: &mut JNIEnv<'local>,
+    locale: Option<&str>,
+) -> JObject<'local> {
+    static FOR_LANGUAGE_TAG: CachedStaticMethod = CachedStaticMethod::new(
+        "java/util/Locale",
+        "forLanguageTag",
+        "(Ljava/lang/String;)Ljava/util/Locale;",
+    );
+    static GET_DEFAULT: CachedStaticMethod =
+        CachedStaticMethod::new("java/util/Locale", "getDefault", "()Ljava/util/Locale;");
+    match locale {
+        Some(tag) => {
+            let tag = new_java_string(env, tag);
+            FOR_LANGUAGE_TAG.call(env, &[(&tag).into()]).l().unwrap()
+        }
+        None => GET_DEFAULT.call(env, &[]).l().unwrap(),
+    }
+}
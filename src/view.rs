@@ -1,13 +1,14 @@
 use jni::{
     JNIEnv, NativeMethod,
     descriptors::Desc,
-    objects::{JClass, JIntArray, JObject},
-    sys::{JNI_TRUE, jboolean, jint, jlong},
+    objects::{GlobalRef, JClass, JIntArray, JObject, JObjectArray, JString},
+    sys::{JNI_TRUE, jboolean, jfloat, jint, jlong},
 };
-use ndk::event::Keycode;
-use num_enum::FromPrimitive;
+use ndk::event::{Keycode, Source};
+use num_enum::{FromPrimitive, IntoPrimitive};
 use send_wrapper::SendWrapper;
 use std::{
+    any::Any,
     cell::RefCell,
     collections::BTreeMap,
     ffi::c_void,
@@ -18,727 +19,2648 @@ use std::{
     },
 };
 
+#[cfg(feature = "accessibility")]
+use crate::accessibility::*;
+#[cfg(feature = "graphics")]
+use crate::graphics::*;
+#[cfg(feature = "ime")]
+use crate::ime::*;
+#[cfg(feature = "trace")]
+use crate::trace::*;
 use crate::{
-    accessibility::*, binder::*, callback_ctx::*, context::*, events::*, graphics::*, ime::*,
-    surface::*, util::*, view_configuration::*,
+    animation::*, binder::*, bundle::*, callback_ctx::*, channel::*, connectivity::*, context::*,
+    coordinates::*, display::*, display_preferences::*, events::*, fold::*, frame_metrics::*,
+    geometry::*, insets::*, intent::*, jni_cache::*, layout::*, media::*, native_methods::*,
+    power::*, scheduler::*, surface::*, surface_control::*, tasks::*, text_direction::*, util::*,
+    view_config::*, view_configuration::*, view_state::*,
 };
 
+pub const FOCUS_BACKWARD: jint = 0x01;
+pub const FOCUS_FORWARD: jint = 0x02;
+pub const FOCUS_LEFT: jint = 0x11;
+pub const FOCUS_UP: jint = 0x21;
+pub const FOCUS_RIGHT: jint = 0x42;
+pub const FOCUS_DOWN: jint = 0x82;
+
+pub const SNACKBAR_LENGTH_INDEFINITE: jint = -2;
+pub const SNACKBAR_LENGTH_SHORT: jint = -1;
+pub const SNACKBAR_LENGTH_LONG: jint = 0;
+
+/// `View.VISIBLE`, `View.INVISIBLE` or `View.GONE`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum Visibility {
+    #[num_enum(default)]
+    Visible = 0,
+    Invisible = 4,
+    Gone = 8,
+}
+
 #[repr(transparent)]
 pub struct View<'local>(pub JObject<'local>);
 
 impl<'local> View<'local> {
     pub fn post_frame_callback(&self, env: &mut JNIEnv<'local>) {
-        env.call_method(&self.0, "postFrameCallback", "()V", &[])
-            .unwrap()
-            .v()
-            .unwrap()
+        static POST_FRAME_CALLBACK: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "postFrameCallback",
+            "()V",
+        );
+        POST_FRAME_CALLBACK.call(env, &self.0, &[]).v().unwrap()
     }
 
     pub fn remove_frame_callback(&self, env: &mut JNIEnv<'local>) {
-        env.call_method(&self.0, "removeFrameCallback", "()V", &[])
-            .unwrap()
-            .v()
-            .unwrap()
+        static REMOVE_FRAME_CALLBACK: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "removeFrameCallback",
+            "()V",
+        );
+        REMOVE_FRAME_CALLBACK.call(env, &self.0, &[]).v().unwrap()
     }
 
     pub fn post_delayed(&self, env: &mut JNIEnv<'local>, delay_millis: jlong) -> bool {
-        env.call_method(&self.0, "postDelayed", "(J)Z", &[delay_millis.into()])
-            .unwrap()
+        static POST_DELAYED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "postDelayed",
+            "(J)Z",
+        );
+        POST_DELAYED
+            .call(env, &self.0, &[delay_millis.into()])
             .z()
             .unwrap()
     }
 
     pub fn remove_delayed_callbacks(&self, env: &mut JNIEnv<'local>) -> bool {
-        env.call_method(&self.0, "removeDelayedCallbacks", "()Z", &[])
-            .unwrap()
+        static REMOVE_DELAYED_CALLBACKS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "removeDelayedCallbacks",
+            "()Z",
+        );
+        REMOVE_DELAYED_CALLBACKS
+            .call(env, &self.0, &[])
             .z()
             .unwrap()
     }
 
+    /// Whether the peer should be rendering. See
+    /// [`ViewPeer::on_rendering_active_changed`].
+    pub fn is_rendering_active(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_RENDERING_ACTIVE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "isRenderingActive",
+            "()Z",
+        );
+        IS_RENDERING_ACTIVE.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// Also require window focus for rendering to be active. This is off
+    /// by default, because in multi-window mode, a window without focus
+    /// is still visible.
+    pub fn set_pause_rendering_without_window_focus(&self, env: &mut JNIEnv<'local>, pause: bool) {
+        static SET_PAUSE_RENDERING_WITHOUT_WINDOW_FOCUS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setPauseRenderingWithoutWindowFocus",
+            "(Z)V",
+        );
+        SET_PAUSE_RENDERING_WITHOUT_WINDOW_FOCUS
+            .call(env, &self.0, &[pause.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// The view's timers. See [`ViewScheduler`].
+    pub fn scheduler(&self, env: &mut JNIEnv<'local>) -> ViewScheduler<'local> {
+        static GET_SCHEDULER: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getScheduler",
+            "()Lorg/linebender/android/rustview/RustViewScheduler;",
+        );
+        ViewScheduler(GET_SCHEDULER.call(env, &self.0, &[]).l().unwrap())
+    }
+
     pub fn is_focused(&self, env: &mut JNIEnv<'local>) -> bool {
-        env.call_method(&self.0, "isFocused", "()Z", &[])
+        static IS_FOCUSED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "isFocused",
+            "()Z",
+        );
+        IS_FOCUSED.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// The view's resolved layout direction, which follows the locale
+    /// unless the app or a parent overrides it. Views that draw their own
+    /// content should mirror it, and text without a direction of its own
+    /// should follow it.
+    pub fn layout_direction(&self, env: &mut JNIEnv<'local>) -> LayoutDirection {
+        static GET_LAYOUT_DIRECTION: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getLayoutDirection",
+            "()I",
+        );
+        GET_LAYOUT_DIRECTION
+            .call(env, &self.0, &[])
+            .i()
             .unwrap()
-            .z()
+            .into()
+    }
+
+    /// Request that the rest of the gesture started by `event` be
+    /// dispatched without batching. See [`View::set_unbuffered_dispatch_enabled`]
+    /// to do this for every gesture.
+    pub fn request_unbuffered_dispatch(
+        &self,
+        env: &mut JNIEnv<'local>,
+        event: &MotionEvent<'local>,
+    ) {
+        static REQUEST_UNBUFFERED_DISPATCH: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "requestUnbufferedDispatch",
+            "(Landroid/view/MotionEvent;)V",
+        );
+        REQUEST_UNBUFFERED_DISPATCH
+            .call(env, &self.0, &[(&event.0).into()])
+            .v()
             .unwrap()
     }
 
-    pub fn input_method_manager(&self, env: &mut JNIEnv<'local>) -> InputMethodManager<'local> {
-        InputMethodManager(
-            env.get_field(
-                &self.0,
-                "mInputMethodManager",
-                "Landroid/view/inputmethod/InputMethodManager;",
-            )
+    /// Enable or disable unbuffered dispatch for all touch gestures.
+    ///
+    /// By default, Android batches motion events and delivers them once
+    /// per frame, with the intermediate samples available as history.
+    /// Drawing apps that need to process every sample as soon as it
+    /// arrives can turn this on, at the cost of more frequent callbacks
+    /// and higher power usage.
+    pub fn set_unbuffered_dispatch_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_UNBUFFERED_DISPATCH_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setUnbufferedDispatchEnabled",
+            "(Z)V",
+        );
+        SET_UNBUFFERED_DISPATCH_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
             .unwrap()
-            .l()
-            .unwrap(),
-        )
     }
 
-    pub fn context(&self, env: &mut JNIEnv<'local>) -> Context<'local> {
-        Context(
-            env.call_method(&self.0, "getContext", "()Landroid/content/Context;", &[])
-                .unwrap()
-                .l()
-                .unwrap(),
-        )
+    /// Opacity, from 0 (transparent) to 1 (opaque).
+    pub fn set_alpha(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_ALPHA: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setAlpha",
+            "(F)V",
+        );
+        SET_ALPHA.call(env, &self.0, &[value.into()]).v().unwrap()
     }
 
-    pub fn view_configuration(&self, env: &mut JNIEnv<'local>) -> ViewConfiguration {
-        ViewConfiguration::new(&self.0, env)
+    pub fn set_translation_x(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_TRANSLATION_X: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setTranslationX",
+            "(F)V",
+        );
+        SET_TRANSLATION_X
+            .call(env, &self.0, &[value.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn set_translation_y(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_TRANSLATION_Y: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setTranslationY",
+            "(F)V",
+        );
+        SET_TRANSLATION_Y
+            .call(env, &self.0, &[value.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn set_translation_z(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_TRANSLATION_Z: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setTranslationZ",
+            "(F)V",
+        );
+        SET_TRANSLATION_Z
+            .call(env, &self.0, &[value.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn set_scale_x(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_SCALE_X: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setScaleX",
+            "(F)V",
+        );
+        SET_SCALE_X.call(env, &self.0, &[value.into()]).v().unwrap()
+    }
+
+    pub fn set_scale_y(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_SCALE_Y: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setScaleY",
+            "(F)V",
+        );
+        SET_SCALE_Y.call(env, &self.0, &[value.into()]).v().unwrap()
+    }
+
+    /// Rotation around the pivot point, in degrees.
+    pub fn set_rotation(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_ROTATION: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setRotation",
+            "(F)V",
+        );
+        SET_ROTATION
+            .call(env, &self.0, &[value.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Base elevation, in pixels, used for shadows and Z ordering among siblings.
+    pub fn set_elevation(&self, env: &mut JNIEnv<'local>, value: jfloat) {
+        static SET_ELEVATION: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setElevation",
+            "(F)V",
+        );
+        SET_ELEVATION
+            .call(env, &self.0, &[value.into()])
+            .v()
+            .unwrap()
     }
 
-    pub fn window_token(&self, env: &mut JNIEnv<'local>) -> IBinder<'local> {
-        IBinder(
-            env.call_method(&self.0, "getWindowToken", "()Landroid/os/IBinder;", &[])
-                .unwrap()
+    /// Start a property animation of the view. The peer's
+    /// [`ViewPeer::on_animation_end`] and [`ViewPeer::on_animation_cancel`]
+    /// are called with `animation_id` when the animation finishes.
+    pub fn animate(
+        &self,
+        env: &mut JNIEnv<'local>,
+        animation_id: jint,
+    ) -> ViewPropertyAnimator<'local> {
+        static ANIMATE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "animate",
+            "(I)Landroid/view/ViewPropertyAnimator;",
+        );
+        ViewPropertyAnimator(
+            ANIMATE
+                .call(env, &self.0, &[animation_id.into()])
                 .l()
                 .unwrap(),
         )
     }
-}
 
-#[allow(unused_variables)]
-pub trait ViewPeer {
-    fn on_measure(
-        &mut self,
-        ctx: &mut CallbackCtx,
-        width_spec: jint,
-        height_spec: jint,
-    ) -> Option<(jint, jint)> {
-        None
+    /// Cover the view with the theme's background color whenever a new
+    /// surface is created, until [`View::notify_first_frame_rendered`]
+    /// is called. This hides the black flash that a SurfaceView shows
+    /// before its first frame.
+    pub fn set_hide_until_first_frame(&self, env: &mut JNIEnv<'local>, hide: bool) {
+        static SET_HIDE_UNTIL_FIRST_FRAME: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setHideUntilFirstFrame",
+            "(Z)V",
+        );
+        SET_HIDE_UNTIL_FIRST_FRAME
+            .call(env, &self.0, &[hide.into()])
+            .v()
+            .unwrap()
     }
 
-    fn on_layout(
-        &mut self,
-        ctx: &mut CallbackCtx,
-        changed: bool,
-        left: jint,
-        top: jint,
-        right: jint,
-        bottom: jint,
-    ) {
+    /// Report that a frame has been presented to the current surface.
+    /// Call this after presenting the first frame rendered at the size
+    /// passed to [`ViewPeer::surface_changed`]. Subsequent calls for
+    /// the same surface are ignored.
+    pub fn notify_first_frame_rendered(&self, env: &mut JNIEnv<'local>) {
+        static ON_FIRST_FRAME_RENDERED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "onFirstFrameRendered",
+            "()V",
+        );
+        ON_FIRST_FRAME_RENDERED.call(env, &self.0, &[]).v().unwrap()
     }
 
-    fn on_size_changed(&mut self, ctx: &mut CallbackCtx, w: jint, h: jint, oldw: jint, oldh: jint) {
+    /// Place the surface above the window, rather than behind it. This
+    /// must be called before the view is attached to a window.
+    pub fn set_z_order_on_top(&self, env: &mut JNIEnv<'local>, on_top: bool) {
+        static SET_Z_ORDER_ON_TOP: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setZOrderOnTop",
+            "(Z)V",
+        );
+        SET_Z_ORDER_ON_TOP
+            .call(env, &self.0, &[on_top.into()])
+            .v()
+            .unwrap()
     }
 
-    fn on_key_down<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        key_code: Keycode,
-        event: &KeyEvent<'local>,
-    ) -> bool {
-        false
+    /// Place the surface above other surface views in the window, but
+    /// still behind the window itself. This must be called before
+    /// the view is attached to a window.
+    pub fn set_z_order_media_overlay(&self, env: &mut JNIEnv<'local>, is_media_overlay: bool) {
+        static SET_Z_ORDER_MEDIA_OVERLAY: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setZOrderMediaOverlay",
+            "(Z)V",
+        );
+        SET_Z_ORDER_MEDIA_OVERLAY
+            .call(env, &self.0, &[is_media_overlay.into()])
+            .v()
+            .unwrap()
     }
 
-    fn on_key_up<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        key_code: Keycode,
-        event: &KeyEvent<'local>,
-    ) -> bool {
-        false
+    /// Prevent the surface's content from appearing in screenshots,
+    /// screen recordings, or on non-secure displays. This must be called
+    /// before the view is attached to a window; to change it later,
+    /// use [`View::set_window_secure`].
+    pub fn set_secure(&self, env: &mut JNIEnv<'local>, secure: bool) {
+        static SET_SECURE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setSecure",
+            "(Z)V",
+        );
+        SET_SECURE.call(env, &self.0, &[secure.into()]).v().unwrap()
     }
 
-    fn on_trackball_event<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        event: &MotionEvent<'local>,
-    ) -> bool {
-        false
+    /// Set or clear `FLAG_SECURE` on the host activity's window, which
+    /// protects the whole window, including the surface, and takes effect
+    /// immediately. Returns `false` if the view isn't hosted by an activity.
+    pub fn set_window_secure(&self, env: &mut JNIEnv<'local>, secure: bool) -> bool {
+        static SET_WINDOW_SECURE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setWindowSecure",
+            "(Z)Z",
+        );
+        SET_WINDOW_SECURE
+            .call(env, &self.0, &[secure.into()])
+            .z()
+            .unwrap()
     }
 
-    fn on_touch_event<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        event: &MotionEvent<'local>,
-    ) -> bool {
-        false
+    /// Set the color mode of the host activity's window to one of the
+    /// `WINDOW_COLOR_MODE_*` constants. The window must be in
+    /// [`WINDOW_COLOR_MODE_WIDE_COLOR_GAMUT`](crate::WINDOW_COLOR_MODE_WIDE_COLOR_GAMUT)
+    /// for wide-gamut content in the surface to be shown as such; see
+    /// [`ColorSpace`](crate::ColorSpace). Returns `false` if the view isn't
+    /// hosted by an activity.
+    pub fn set_window_color_mode(&self, env: &mut JNIEnv<'local>, mode: jint) -> bool {
+        static SET_WINDOW_COLOR_MODE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setWindowColorMode",
+            "(I)Z",
+        );
+        SET_WINDOW_COLOR_MODE
+            .call(env, &self.0, &[mode.into()])
+            .z()
+            .unwrap()
     }
 
-    fn on_generic_motion_event<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        event: &MotionEvent<'local>,
-    ) -> bool {
-        false
+    /// Ask for at most `headroom` times the SDR white level of headroom
+    /// for HDR content in the view's surface, or 0 to let the system
+    /// decide. The window must be in
+    /// [`WINDOW_COLOR_MODE_HDR`](crate::WINDOW_COLOR_MODE_HDR) for HDR
+    /// content to be shown. Returns `false`, doing nothing, before API
+    /// level 35.
+    pub fn set_desired_hdr_headroom(&self, env: &mut JNIEnv<'local>, headroom: jfloat) -> bool {
+        static SET_DESIRED_HDR_HEADROOM: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setDesiredHdrHeadroom",
+            "(F)V",
+        );
+        if android_sdk_version(env) < 35 {
+            return false;
+        }
+        SET_DESIRED_HDR_HEADROOM
+            .call(env, &self.0, &[headroom.into()])
+            .v()
+            .unwrap();
+        true
     }
 
-    fn on_hover_event<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        event: &MotionEvent<'local>,
+    /// Hand media keys that the peer doesn't handle to a
+    /// `android.media.session.MediaSession`, given its token, by setting
+    /// the host activity's media controller. Pass `None` to stop. Returns
+    /// `false` if the view isn't hosted by an activity.
+    pub fn set_media_session_token(
+        &self,
+        env: &mut JNIEnv<'local>,
+        token: Option<&JObject<'local>>,
     ) -> bool {
-        false
+        static SET_MEDIA_SESSION_TOKEN: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setMediaSessionToken",
+            "(Landroid/media/session/MediaSession$Token;)Z",
+        );
+        let null = JObject::null();
+        SET_MEDIA_SESSION_TOKEN
+            .call(env, &self.0, &[token.unwrap_or(&null).into()])
+            .z()
+            .unwrap()
     }
 
-    fn on_focus_changed<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        gain_focus: bool,
-        direction: jint,
-        previously_focused_rect: Option<&Rect<'local>>,
-    ) {
+    /// Asynchronously copy the current contents of the surface into
+    /// a bitmap. [`ViewPeer::on_pixel_copy_finished`] is called with
+    /// `request_id` when the copy completes. Returns `false` if there is
+    /// no surface to copy from.
+    #[cfg(feature = "graphics")]
+    pub fn request_pixel_copy(&self, env: &mut JNIEnv<'local>, request_id: jint) -> bool {
+        static REQUEST_PIXEL_COPY: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "requestPixelCopy",
+            "(I)Z",
+        );
+        REQUEST_PIXEL_COPY
+            .call(env, &self.0, &[request_id.into()])
+            .z()
+            .unwrap()
     }
 
-    fn on_window_focus_changed(&mut self, ctx: &mut CallbackCtx, has_window_focus: bool) {}
-
-    fn on_attached_to_window(&mut self, ctx: &mut CallbackCtx) {}
+    /// Start or stop delivering the host window's frame metrics
+    /// to [`ViewPeer::on_frame_metrics_available`].
+    pub fn set_frame_metrics_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_FRAME_METRICS_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setFrameMetricsEnabled",
+            "(Z)V",
+        );
+        SET_FRAME_METRICS_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
+    }
 
-    fn on_detached_from_window(&mut self, ctx: &mut CallbackCtx) {}
+    pub fn power_manager(&self, env: &mut JNIEnv<'local>) -> PowerManager<'local> {
+        let context = self.context(env);
+        PowerManager(context.system_service(env, POWER_SERVICE))
+    }
 
-    fn on_window_visibility_changed(&mut self, ctx: &mut CallbackCtx, visibility: jint) {}
+    /// Start or stop delivering thermal status and power save mode
+    /// changes to the peer while the view is attached.
+    pub fn set_power_monitoring_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_POWER_MONITORING_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setPowerMonitoringEnabled",
+            "(Z)V",
+        );
+        SET_POWER_MONITORING_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
+    }
 
-    fn surface_created<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        holder: &SurfaceHolder<'local>,
-    ) {
+    pub fn connectivity_manager(&self, env: &mut JNIEnv<'local>) -> ConnectivityManager<'local> {
+        let context = self.context(env);
+        ConnectivityManager(context.system_service(env, CONNECTIVITY_SERVICE))
     }
 
-    fn surface_changed<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        holder: &SurfaceHolder<'local>,
-        format: jint,
-        width: jint,
-        height: jint,
-    ) {
+    /// Start or stop delivering changes to the default network to
+    /// [`ViewPeer::on_network_changed`] while the view is attached.
+    /// The app must hold the `ACCESS_NETWORK_STATE` permission.
+    pub fn set_network_monitoring_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_NETWORK_MONITORING_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setNetworkMonitoringEnabled",
+            "(Z)V",
+        );
+        SET_NETWORK_MONITORING_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
     }
 
-    fn surface_destroyed<'local>(
-        &mut self,
-        ctx: &mut CallbackCtx<'local>,
-        holder: &SurfaceHolder<'local>,
+    /// Enable or disable [`ViewPeer::on_display_preferences_changed`]
+    /// while the view is attached.
+    pub fn set_display_preferences_monitoring_enabled(
+        &self,
+        env: &mut JNIEnv<'local>,
+        enabled: bool,
     ) {
+        static SET_DISPLAY_PREFERENCES_MONITORING_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setDisplayPreferencesMonitoringEnabled",
+            "(Z)V",
+        );
+        SET_DISPLAY_PREFERENCES_MONITORING_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
     }
 
-    fn do_frame(&mut self, ctx: &mut CallbackCtx, frame_time_nanos: jlong) {}
-
-    fn delayed_callback(&mut self, ctx: &mut CallbackCtx) {}
-
-    fn as_accessibility_node_provider(&mut self) -> Option<&mut dyn AccessibilityNodeProvider> {
-        None
+    /// Enable or disable [`ViewPeer::on_hardware_keyboard_changed`] and
+    /// [`ViewPeer::on_keyboard_layout_changed`] while the view is attached.
+    /// Attaching or detaching a keyboard is a configuration change, so the
+    /// activity must declare that it handles `keyboard|keyboardHidden`
+    /// changes; otherwise it's recreated instead.
+    pub fn set_keyboard_monitoring_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_KEYBOARD_MONITORING_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setKeyboardMonitoringEnabled",
+            "(Z)V",
+        );
+        SET_KEYBOARD_MONITORING_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
     }
 
-    fn as_input_connection(&mut self) -> Option<&mut dyn InputConnection> {
-        None
+    /// Whether a hardware keyboard is attached and not hidden, such as by
+    /// folding it behind the screen.
+    pub fn has_hardware_keyboard(&self, env: &mut JNIEnv<'local>) -> bool {
+        static HAS_HARDWARE_KEYBOARD: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "hasHardwareKeyboard",
+            "()Z",
+        );
+        HAS_HARDWARE_KEYBOARD.call(env, &self.0, &[]).z().unwrap()
     }
-}
+
+    /// Enable or disable [`ViewPeer::on_ime_visibility_changed`] while the
+    /// view is attached.
+    pub fn set_ime_visibility_monitoring_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_IME_VISIBILITY_MONITORING_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setImeVisibilityMonitoringEnabled",
+            "(Z)V",
+        );
+        SET_IME_VISIBILITY_MONITORING_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Whether the IME is currently shown over the view's window. Unlike
+    /// the result of `InputMethodManager.showSoftInput`, which only says
+    /// whether the request was sent, this reflects what the user sees.
+    /// On API level 30 and later it comes from the window insets; before
+    /// that it's estimated from the part of the window that's hidden.
+    pub fn is_ime_visible(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_IME_VISIBLE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "isImeVisible",
+            "()Z",
+        );
+        IS_IME_VISIBLE.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// The height of the part of the window covered by the IME, in
+    /// pixels, or 0 if it isn't visible. See [`View::is_ime_visible`].
+    pub fn ime_height(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_IME_HEIGHT: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getImeHeight",
+            "()I",
+        );
+        GET_IME_HEIGHT.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    /// Enable or disable [`ViewPeer::on_input_device_added`],
+    /// [`ViewPeer::on_input_device_removed`] and
+    /// [`ViewPeer::on_input_device_changed`] while the view is attached.
+    pub fn set_input_device_monitoring_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_INPUT_DEVICE_MONITORING_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setInputDeviceMonitoringEnabled",
+            "(Z)V",
+        );
+        SET_INPUT_DEVICE_MONITORING_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Show a Material snackbar anchored to the view. `duration` is one of
+    /// the `SNACKBAR_LENGTH_*` constants. This requires the app to depend on
+    /// the Material Components library; see `RustSnackbar.java`.
+    pub fn show_snackbar(&self, env: &mut JNIEnv<'local>, text: &str, duration: jint) {
+        static SHOW: CachedStaticMethod = CachedStaticMethod::new(
+            "org/linebender/android/rustview/RustSnackbar",
+            "show",
+            "(Landroid/view/View;Ljava/lang/String;I)V",
+        );
+        let text = new_java_string(env, text);
+        SHOW.call(env, &[(&self.0).into(), (&text).into(), duration.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Launch `intent`, typically from [`Intent::open_document`] or
+    /// [`Intent::create_document`], through the host activity. The chosen
+    /// document is delivered to [`ViewPeer::on_document_result`] with
+    /// `request_code`, which must not collide with request codes the
+    /// activity uses itself. Returns `false` if the view isn't hosted by
+    /// an activity or nothing can handle the intent.
+    ///
+    /// The host activity must forward `onActivityResult` to the view.
+    pub fn start_document_request(
+        &self,
+        env: &mut JNIEnv<'local>,
+        intent: &Intent<'local>,
+        request_code: jint,
+    ) -> bool {
+        static START_DOCUMENT_REQUEST: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "startDocumentRequest",
+            "(Landroid/content/Intent;I)Z",
+        );
+        START_DOCUMENT_REQUEST
+            .call(env, &self.0, &[(&intent.0).into(), request_code.into()])
+            .z()
+            .unwrap()
+    }
+
+    /// Ask the user to grant runtime permissions, through the host
+    /// activity. The results are delivered to
+    /// [`ViewPeer::on_request_permissions_result`] with `request_code`,
+    /// which must not collide with request codes the activity uses itself.
+    /// Returns `false` if the view isn't hosted by an activity.
+    ///
+    /// The host activity must forward `onRequestPermissionsResult`
+    /// to the view.
+    pub fn request_permissions(
+        &self,
+        env: &mut JNIEnv<'local>,
+        permissions: &[&str],
+        request_code: jint,
+    ) -> bool {
+        static REQUEST_PERMISSIONS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "requestPermissions",
+            "([Ljava/lang/String;I)Z",
+        );
+        let array = env
+            .new_object_array(
+                permissions.len() as jint,
+                "java/lang/String",
+                JObject::null(),
+            )
+            .unwrap();
+        for (i, permission) in permissions.iter().enumerate() {
+            let permission = new_java_string(env, permission);
+            env.set_object_array_element(&array, i as jint, permission)
+                .unwrap();
+        }
+        REQUEST_PERMISSIONS
+            .call(env, &self.0, &[(&array).into(), request_code.into()])
+            .z()
+            .unwrap()
+    }
+
+    /// Whether the app should explain why it needs `permission` before
+    /// requesting it, because the user has previously denied it.
+    pub fn should_show_request_permission_rationale(
+        &self,
+        env: &mut JNIEnv<'local>,
+        permission: &str,
+    ) -> bool {
+        static SHOULD_SHOW_REQUEST_PERMISSION_RATIONALE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "shouldShowRequestPermissionRationale",
+            "(Ljava/lang/String;)Z",
+        );
+        let permission = new_java_string(env, permission);
+        SHOULD_SHOW_REQUEST_PERMISSION_RATIONALE
+            .call(env, &self.0, &[(&permission).into()])
+            .z()
+            .unwrap()
+    }
+
+    pub fn holder(&self, env: &mut JNIEnv<'local>) -> SurfaceHolder<'local> {
+        static GET_HOLDER: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getHolder",
+            "()Landroid/view/SurfaceHolder;",
+        );
+        SurfaceHolder(GET_HOLDER.call(env, &self.0, &[]).l().unwrap())
+    }
+
+    #[cfg(feature = "ime")]
+    pub fn input_method_manager(&self, env: &mut JNIEnv<'local>) -> InputMethodManager<'local> {
+        static M_INPUT_METHOD_MANAGER: CachedField = CachedField::new(
+            "org/linebender/android/rustview/RustView",
+            "mInputMethodManager",
+            "Landroid/view/inputmethod/InputMethodManager;",
+        );
+        InputMethodManager(M_INPUT_METHOD_MANAGER.get(env, &self.0).l().unwrap())
+    }
+
+    pub fn context(&self, env: &mut JNIEnv<'local>) -> Context<'local> {
+        static GET_CONTEXT: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getContext",
+            "()Landroid/content/Context;",
+        );
+        Context(GET_CONTEXT.call(env, &self.0, &[]).l().unwrap())
+    }
+
+    #[cfg(feature = "accessibility")]
+    pub fn accessibility_manager(&self, env: &mut JNIEnv<'local>) -> AccessibilityManager<'local> {
+        let context = self.context(env);
+        AccessibilityManager(context.system_service(env, ACCESSIBILITY_SERVICE))
+    }
+
+    /// Dispatch an event through the parent, as the framework does for
+    /// events from virtual descendants. Returns `false` if the view
+    /// has no parent or the event was not sent.
+    #[cfg(feature = "accessibility")]
+    pub fn request_send_accessibility_event(
+        &self,
+        env: &mut JNIEnv<'local>,
+        event: &AccessibilityEvent<'local>,
+    ) -> bool {
+        static GET_PARENT: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getParent",
+            "()Landroid/view/ViewParent;",
+        );
+        static REQUEST_SEND_ACCESSIBILITY_EVENT: CachedMethod = CachedMethod::new(
+            "android/view/ViewParent",
+            "requestSendAccessibilityEvent",
+            "(Landroid/view/View;Landroid/view/accessibility/AccessibilityEvent;)Z",
+        );
+        let parent = GET_PARENT.call(env, &self.0, &[]).l().unwrap();
+        if parent.as_raw().is_null() {
+            return false;
+        }
+        REQUEST_SEND_ACCESSIBILITY_EVENT
+            .call(env, &parent, &[(&self.0).into(), (&event.0).into()])
+            .z()
+            .unwrap()
+    }
+
+    /// Whether the host activity is in multi-window mode. Always `false`
+    /// if the view isn't hosted by an activity.
+    pub fn is_in_multi_window_mode(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_IN_MULTI_WINDOW_MODE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "isInMultiWindowMode",
+            "()Z",
+        );
+        IS_IN_MULTI_WINDOW_MODE.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    /// Whether the host activity is in picture-in-picture mode. Always
+    /// `false` if the view isn't hosted by an activity.
+    pub fn is_in_picture_in_picture_mode(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_IN_PICTURE_IN_PICTURE_MODE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "isInPictureInPictureMode",
+            "()Z",
+        );
+        IS_IN_PICTURE_IN_PICTURE_MODE
+            .call(env, &self.0, &[])
+            .z()
+            .unwrap()
+    }
+
+    /// The bounds of the window containing the view, in screen pixels.
+    /// Before API level 30, these are the bounds of the view's root view,
+    /// which fills the window.
+    pub fn window_bounds(&self, env: &mut JNIEnv<'local>) -> Rect<'local> {
+        static GET_CURRENT_WINDOW_METRICS: CachedMethod = CachedMethod::new(
+            "android/view/WindowManager",
+            "getCurrentWindowMetrics",
+            "()Landroid/view/WindowMetrics;",
+        );
+        static GET_BOUNDS: CachedMethod = CachedMethod::new(
+            "android/view/WindowMetrics",
+            "getBounds",
+            "()Landroid/graphics/Rect;",
+        );
+        static GET_ROOT_VIEW: CachedMethod =
+            CachedMethod::new("android/view/View", "getRootView", "()Landroid/view/View;");
+        static GET_WIDTH: CachedMethod = CachedMethod::new("android/view/View", "getWidth", "()I");
+        static GET_HEIGHT: CachedMethod =
+            CachedMethod::new("android/view/View", "getHeight", "()I");
+        if android_sdk_version(env) < 30 {
+            let (screen_x, screen_y) = self.location_on_screen(env);
+            let (window_x, window_y) = self.location_in_window(env);
+            let root = GET_ROOT_VIEW.call(env, &self.0, &[]).l().unwrap();
+            let width = GET_WIDTH.call(env, &root, &[]).i().unwrap();
+            let height = GET_HEIGHT.call(env, &root, &[]).i().unwrap();
+            let left = screen_x - window_x;
+            let top = screen_y - window_y;
+            let bounds = IntRect {
+                left,
+                top,
+                right: left + width,
+                bottom: top + height,
+            };
+            return Rect::new(env, bounds);
+        }
+        let context = self.context(env);
+        let window_manager = context.system_service(env, WINDOW_SERVICE);
+        let metrics = GET_CURRENT_WINDOW_METRICS
+            .call(env, &window_manager, &[])
+            .l()
+            .unwrap();
+        Rect(GET_BOUNDS.call(env, &metrics, &[]).l().unwrap())
+    }
+
+    /// The position of the view's top left corner in its window.
+    pub fn location_in_window(&self, env: &mut JNIEnv<'local>) -> (jint, jint) {
+        static GET_LOCATION_IN_WINDOW: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getLocationInWindow",
+            "([I)V",
+        );
+        self.location(env, &GET_LOCATION_IN_WINDOW)
+    }
+
+    /// The position of the view's top left corner on the screen.
+    pub fn location_on_screen(&self, env: &mut JNIEnv<'local>) -> (jint, jint) {
+        static GET_LOCATION_ON_SCREEN: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getLocationOnScreen",
+            "([I)V",
+        );
+        self.location(env, &GET_LOCATION_ON_SCREEN)
+    }
+
+    fn location(&self, env: &mut JNIEnv<'local>, method: &CachedMethod) -> (jint, jint) {
+        let location = env.new_int_array(2).unwrap();
+        method
+            .call(env, &self.0, &[(&location).into()])
+            .v()
+            .unwrap();
+        let mut buf = [0; 2];
+        env.get_int_array_region(&location, 0, &mut buf).unwrap();
+        (buf[0], buf[1])
+    }
+
+    /// The view's current position in its window and on the screen,
+    /// for converting between coordinate spaces.
+    pub fn coordinate_space(&self, env: &mut JNIEnv<'local>) -> CoordinateSpace {
+        CoordinateSpace {
+            origin_in_window: self.location_in_window(env),
+            origin_on_screen: self.location_on_screen(env),
+        }
+    }
+
+    /// The view's own transform relative to its parent, from its scale,
+    /// rotation and translation properties, including those set by
+    /// [`View::animate`].
+    pub fn matrix(&self, env: &mut JNIEnv<'local>) -> AffineTransform {
+        static GET_MATRIX: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getMatrix",
+            "()Landroid/graphics/Matrix;",
+        );
+        let matrix = GET_MATRIX.call(env, &self.0, &[]).l().unwrap();
+        AffineTransform::from_java(env, &matrix)
+    }
+
+    /// The transform from the view's coordinates to its window's,
+    /// through the transforms and scroll offsets of all of its ancestors,
+    /// for example when the view is in a scaled container. Before API
+    /// level 29, this only includes the translation to
+    /// [`View::location_in_window`].
+    pub fn transform_to_window(&self, env: &mut JNIEnv<'local>) -> AffineTransform {
+        static TRANSFORM_MATRIX_TO_GLOBAL: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "transformMatrixToGlobal",
+            "(Landroid/graphics/Matrix;)V",
+        );
+        if android_sdk_version(env) < 29 {
+            let (x, y) = self.location_in_window(env);
+            return AffineTransform::translate(x as jfloat, y as jfloat);
+        }
+        let matrix = AffineTransform::IDENTITY.to_java(env);
+        TRANSFORM_MATRIX_TO_GLOBAL
+            .call(env, &self.0, &[(&matrix).into()])
+            .v()
+            .unwrap();
+        AffineTransform::from_java(env, &matrix)
+    }
+
+    /// The transform from window coordinates to the view's, the inverse
+    /// of [`View::transform_to_window`], or `None` if the view or an
+    /// ancestor is scaled to zero.
+    pub fn transform_from_window(&self, env: &mut JNIEnv<'local>) -> Option<AffineTransform> {
+        self.transform_to_window(env).inverse()
+    }
+
+    pub fn view_configuration(&self, env: &mut JNIEnv<'local>) -> ViewConfiguration {
+        ViewConfiguration::new(&self.0, env)
+    }
+
+    /// Add an extra surface layered over the view, such as a low-latency
+    /// layer for ink or a text cursor, identified by a peer-chosen ID.
+    /// Its lifecycle is reported through
+    /// [`ViewPeer::overlay_surface_created`] and related callbacks, just
+    /// like the view's own surface.
+    ///
+    /// The overlay is a sibling `SurfaceView` that the crate adds to the
+    /// view's parent while the view is attached, with the same layout
+    /// params, so the parent must stack its children, as `FrameLayout`
+    /// does. It sits above the view's surface and, unless `above_window`
+    /// is true, below the window's other content. Touches pass through
+    /// it to the view. Does nothing if an overlay with that ID exists.
+    pub fn add_overlay_surface(&self, env: &mut JNIEnv<'local>, id: jint, above_window: bool) {
+        static ADD_OVERLAY_SURFACE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "addOverlaySurface",
+            "(IZ)V",
+        );
+        ADD_OVERLAY_SURFACE
+            .call(env, &self.0, &[id.into(), above_window.into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn remove_overlay_surface(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static REMOVE_OVERLAY_SURFACE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "removeOverlaySurface",
+            "(I)V",
+        );
+        REMOVE_OVERLAY_SURFACE
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap();
+    }
+
+    pub fn has_overlay_surface(&self, env: &mut JNIEnv<'local>, id: jint) -> bool {
+        static HAS_OVERLAY_SURFACE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "hasOverlaySurface",
+            "(I)Z",
+        );
+        HAS_OVERLAY_SURFACE
+            .call(env, &self.0, &[id.into()])
+            .z()
+            .unwrap()
+    }
+
+    /// The layer of the view's surface, or `None` if the surface hasn't
+    /// been created. Requires API level 29.
+    pub fn surface_control(&self, env: &mut JNIEnv<'local>) -> Option<SurfaceControl<'local>> {
+        static GET_SURFACE_CONTROL: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getSurfaceControl",
+            "()Landroid/view/SurfaceControl;",
+        );
+        let sc = GET_SURFACE_CONTROL.call(env, &self.0, &[]).l().unwrap();
+        (!sc.is_null()).then_some(SurfaceControl(sc))
+    }
+
+    /// The token of the window the view is attached to, or `None` if the
+    /// view isn't attached.
+    pub fn window_token(&self, env: &mut JNIEnv<'local>) -> Option<IBinder<'local>> {
+        static GET_WINDOW_TOKEN: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getWindowToken",
+            "()Landroid/os/IBinder;",
+        );
+        let token = GET_WINDOW_TOKEN.call(env, &self.0, &[]).l().unwrap();
+        (!token.is_null()).then_some(IBinder(token))
+    }
+
+    /// The token that a [`SurfaceControlViewHost`] uses to route input
+    /// and accessibility to content embedded in this view, or `None` if
+    /// the view isn't attached. Requires API level 30.
+    pub fn host_token(&self, env: &mut JNIEnv<'local>) -> Option<IBinder<'local>> {
+        static GET_HOST_TOKEN: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getHostToken",
+            "()Landroid/os/IBinder;",
+        );
+        let token = GET_HOST_TOKEN.call(env, &self.0, &[]).l().unwrap();
+        (!token.is_null()).then_some(IBinder(token))
+    }
+
+    /// The display the view is shown on, or `None` if the view isn't
+    /// attached.
+    pub fn display(&self, env: &mut JNIEnv<'local>) -> Option<Display<'local>> {
+        static GET_DISPLAY: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getDisplay",
+            "()Landroid/view/Display;",
+        );
+        let display = GET_DISPLAY.call(env, &self.0, &[]).l().unwrap();
+        (!display.as_raw().is_null()).then_some(Display(display))
+    }
+
+    /// Enable or disable [`ViewPeer::on_refresh_rate_changed`] while the
+    /// view is attached.
+    pub fn set_refresh_rate_monitoring_enabled(&self, env: &mut JNIEnv<'local>, enabled: bool) {
+        static SET_REFRESH_RATE_MONITORING_ENABLED: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setRefreshRateMonitoringEnabled",
+            "(Z)V",
+        );
+        SET_REFRESH_RATE_MONITORING_ENABLED
+            .call(env, &self.0, &[enabled.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Embed the content of a [`SurfaceControlViewHost`] in this view's
+    /// surface. Requires API level 30.
+    pub fn set_child_surface_package(
+        &self,
+        env: &mut JNIEnv<'local>,
+        package: &SurfacePackage<'local>,
+    ) {
+        static SET_CHILD_SURFACE_PACKAGE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setChildSurfacePackage",
+            "(Landroid/view/SurfaceControlViewHost$SurfacePackage;)V",
+        );
+        SET_CHILD_SURFACE_PACKAGE
+            .call(env, &self.0, &[(&package.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// The layer of the view's window, or `None` if the view isn't
+    /// attached. Requires API level 33.
+    pub fn root_surface_control(
+        &self,
+        env: &mut JNIEnv<'local>,
+    ) -> Option<AttachedSurfaceControl<'local>> {
+        static GET_ROOT_SURFACE_CONTROL: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getRootSurfaceControl",
+            "()Landroid/view/AttachedSurfaceControl;",
+        );
+        let sc = GET_ROOT_SURFACE_CONTROL
+            .call(env, &self.0, &[])
+            .l()
+            .unwrap();
+        (!sc.is_null()).then_some(AttachedSurfaceControl(sc))
+    }
+
+    /// Find the view that should take focus when moving in `direction`,
+    /// one of the `FOCUS_*` constants, from this view. Returns `None` if
+    /// there is no such view.
+    pub fn focus_search(
+        &self,
+        env: &mut JNIEnv<'local>,
+        direction: jint,
+    ) -> Option<JObject<'local>> {
+        static FOCUS_SEARCH: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "focusSearch",
+            "(I)Landroid/view/View;",
+        );
+        let result = FOCUS_SEARCH
+            .call(env, &self.0, &[direction.into()])
+            .l()
+            .unwrap();
+        (!result.as_raw().is_null()).then_some(result)
+    }
+
+    /// Move focus to the view found by [`View::focus_search`], as the
+    /// framework does for unhandled d-pad keys. Returns true if a view
+    /// took focus.
+    pub fn move_focus(&self, env: &mut JNIEnv<'local>, direction: jint) -> bool {
+        static REQUEST_FOCUS: CachedMethod =
+            CachedMethod::new("android/view/View", "requestFocus", "(I)Z");
+        let Some(next) = self.focus_search(env, direction) else {
+            return false;
+        };
+        REQUEST_FOCUS
+            .call(env, &next, &[direction.into()])
+            .z()
+            .unwrap()
+    }
+
+    pub fn request_focus(&self, env: &mut JNIEnv<'local>) -> bool {
+        static REQUEST_FOCUS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "requestFocus",
+            "()Z",
+        );
+        REQUEST_FOCUS.call(env, &self.0, &[]).z().unwrap()
+    }
+
+    pub fn set_focusable(&self, env: &mut JNIEnv<'local>, focusable: bool) {
+        static SET_FOCUSABLE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setFocusable",
+            "(Z)V",
+        );
+        SET_FOCUSABLE
+            .call(env, &self.0, &[focusable.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn id(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_ID: CachedMethod =
+            CachedMethod::new("org/linebender/android/rustview/RustView", "getId", "()I");
+        GET_ID.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    pub fn set_id(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_ID: CachedMethod =
+            CachedMethod::new("org/linebender/android/rustview/RustView", "setId", "(I)V");
+        SET_ID.call(env, &self.0, &[id.into()]).v().unwrap()
+    }
+
+    /// Generate an ID that doesn't collide with IDs from resources, for
+    /// use with [`View::set_id`].
+    pub fn generate_view_id(env: &mut JNIEnv<'local>) -> jint {
+        static GENERATE_VIEW_ID: CachedStaticMethod =
+            CachedStaticMethod::new("android/view/View", "generateViewId", "()I");
+        GENERATE_VIEW_ID.call(env, &[]).i().unwrap()
+    }
+
+    /// Override the view that takes focus when moving up from this one.
+    pub fn set_next_focus_up_id(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_NEXT_FOCUS_UP_ID: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setNextFocusUpId",
+            "(I)V",
+        );
+        SET_NEXT_FOCUS_UP_ID
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Override the view that takes focus when moving down from this one.
+    pub fn set_next_focus_down_id(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_NEXT_FOCUS_DOWN_ID: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setNextFocusDownId",
+            "(I)V",
+        );
+        SET_NEXT_FOCUS_DOWN_ID
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Override the view that takes focus when moving left from this one.
+    pub fn set_next_focus_left_id(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_NEXT_FOCUS_LEFT_ID: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setNextFocusLeftId",
+            "(I)V",
+        );
+        SET_NEXT_FOCUS_LEFT_ID
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Override the view that takes focus when moving right from this one.
+    pub fn set_next_focus_right_id(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_NEXT_FOCUS_RIGHT_ID: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setNextFocusRightId",
+            "(I)V",
+        );
+        SET_NEXT_FOCUS_RIGHT_ID
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Override the view that takes focus when moving forward from this
+    /// one out of its keyboard navigation cluster, such as with Tab.
+    pub fn set_next_cluster_forward_id(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_NEXT_CLUSTER_FORWARD_ID: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setNextClusterForwardId",
+            "(I)V",
+        );
+        SET_NEXT_CLUSTER_FORWARD_ID
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Make the view a keyboard navigation cluster, a group that the user
+    /// moves into and out of as a whole with Meta+Tab, as a toolbar or
+    /// sidebar is on a desktop.
+    pub fn set_keyboard_navigation_cluster(&self, env: &mut JNIEnv<'local>, is_cluster: bool) {
+        static SET_KEYBOARD_NAVIGATION_CLUSTER: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setKeyboardNavigationCluster",
+            "(Z)V",
+        );
+        SET_KEYBOARD_NAVIGATION_CLUSTER
+            .call(env, &self.0, &[is_cluster.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn is_keyboard_navigation_cluster(&self, env: &mut JNIEnv<'local>) -> bool {
+        static IS_KEYBOARD_NAVIGATION_CLUSTER: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "isKeyboardNavigationCluster",
+            "()Z",
+        );
+        IS_KEYBOARD_NAVIGATION_CLUSTER
+            .call(env, &self.0, &[])
+            .z()
+            .unwrap()
+    }
+
+    /// Make the view the one that takes focus when the window, or the
+    /// keyboard navigation cluster containing it, gets focus.
+    pub fn set_focused_by_default(&self, env: &mut JNIEnv<'local>, is_focused_by_default: bool) {
+        static SET_FOCUSED_BY_DEFAULT: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setFocusedByDefault",
+            "(Z)V",
+        );
+        SET_FOCUSED_BY_DEFAULT
+            .call(env, &self.0, &[is_focused_by_default.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Make screen readers visit the view before the view with the given
+    /// ID, overriding the order they would otherwise take from the layout.
+    /// This is how a view fits into the reading order of a screen that
+    /// mixes it with platform views.
+    pub fn set_accessibility_traversal_before(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_ACCESSIBILITY_TRAVERSAL_BEFORE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setAccessibilityTraversalBefore",
+            "(I)V",
+        );
+        SET_ACCESSIBILITY_TRAVERSAL_BEFORE
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Make screen readers visit the view after the view with the given ID.
+    pub fn set_accessibility_traversal_after(&self, env: &mut JNIEnv<'local>, id: jint) {
+        static SET_ACCESSIBILITY_TRAVERSAL_AFTER: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setAccessibilityTraversalAfter",
+            "(I)V",
+        );
+        SET_ACCESSIBILITY_TRAVERSAL_AFTER
+            .call(env, &self.0, &[id.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Let screen readers focus the view as a whole, reading out its
+    /// content description, even if the peer exposes no accessibility
+    /// nodes of its own.
+    pub fn set_screen_reader_focusable(&self, env: &mut JNIEnv<'local>, focusable: bool) {
+        static SET_SCREEN_READER_FOCUSABLE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setScreenReaderFocusable",
+            "(Z)V",
+        );
+        SET_SCREEN_READER_FOCUSABLE
+            .call(env, &self.0, &[focusable.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// The layout parameters the view's parent uses to size it, or `None`
+    /// if the view hasn't been added to a parent.
+    pub fn layout_params(&self, env: &mut JNIEnv<'local>) -> Option<LayoutParams<'local>> {
+        static GET_LAYOUT_PARAMS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getLayoutParams",
+            "()Landroid/view/ViewGroup$LayoutParams;",
+        );
+        let params = GET_LAYOUT_PARAMS.call(env, &self.0, &[]).l().unwrap();
+        (!params.as_raw().is_null()).then_some(LayoutParams(params))
+    }
+
+    /// Set the layout parameters, which also requests a new layout.
+    pub fn set_layout_params(&self, env: &mut JNIEnv<'local>, params: &LayoutParams<'local>) {
+        static SET_LAYOUT_PARAMS: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "setLayoutParams",
+            "(Landroid/view/ViewGroup$LayoutParams;)V",
+        );
+        SET_LAYOUT_PARAMS
+            .call(env, &self.0, &[(&params.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Change the requested width and height, each either a size in
+    /// pixels or one of the `LAYOUT_PARAMS_*` constants, keeping any
+    /// other layout parameters. Returns `false` if the view has no
+    /// layout parameters yet.
+    pub fn set_layout_size(&self, env: &mut JNIEnv<'local>, width: jint, height: jint) -> bool {
+        let Some(params) = self.layout_params(env) else {
+            return false;
+        };
+        params.set_width(env, width);
+        params.set_height(env, height);
+        self.set_layout_params(env, &params);
+        true
+    }
+
+    /// The minimum width and height from the view's attributes and
+    /// background, for use with [`measure_desired_size`].
+    pub fn suggested_minimum_size(&self, env: &mut JNIEnv<'local>) -> (jint, jint) {
+        static GET_SUGGESTED_MINIMUM_WIDTH: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getSuggestedMinimumWidth",
+            "()I",
+        );
+        static GET_SUGGESTED_MINIMUM_HEIGHT: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getSuggestedMinimumHeight",
+            "()I",
+        );
+        (
+            GET_SUGGESTED_MINIMUM_WIDTH
+                .call(env, &self.0, &[])
+                .i()
+                .unwrap(),
+            GET_SUGGESTED_MINIMUM_HEIGHT
+                .call(env, &self.0, &[])
+                .i()
+                .unwrap(),
+        )
+    }
+
+    /// Invalidate the whole view. This redraws the view's own content,
+    /// such as its background, but not what the peer renders to the
+    /// surface; track that with a [`crate::DirtyRegion`] instead.
+    pub fn invalidate(&self, env: &mut JNIEnv<'local>) {
+        static INVALIDATE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "invalidate",
+            "()V",
+        );
+        INVALIDATE.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    /// Invalidate part of the view, in its own coordinates. See
+    /// [`View::invalidate`].
+    pub fn invalidate_rect(&self, env: &mut JNIEnv<'local>, rect: IntRect) {
+        static INVALIDATE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "invalidate",
+            "(IIII)V",
+        );
+        INVALIDATE
+            .call(
+                env,
+                &self.0,
+                &[
+                    rect.left.into(),
+                    rect.top.into(),
+                    rect.right.into(),
+                    rect.bottom.into(),
+                ],
+            )
+            .v()
+            .unwrap()
+    }
+
+    /// The part of the view that isn't clipped by its ancestors or the
+    /// window, in the view's own coordinates, or `None` if it's entirely
+    /// hidden. A [`crate::DirtyRegion`] can be clipped to this to avoid
+    /// redrawing what can't be seen.
+    pub fn local_visible_rect(&self, env: &mut JNIEnv<'local>) -> Option<IntRect> {
+        static GET_LOCAL_VISIBLE_RECT: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getLocalVisibleRect",
+            "(Landroid/graphics/Rect;)Z",
+        );
+        let rect = Rect::new(env, IntRect::default());
+        GET_LOCAL_VISIBLE_RECT
+            .call(env, &self.0, &[(&rect.0).into()])
+            .z()
+            .unwrap()
+            .then(|| rect.to_value(env))
+    }
+
+    pub fn request_layout(&self, env: &mut JNIEnv<'local>) {
+        static REQUEST_LAYOUT: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "requestLayout",
+            "()V",
+        );
+        REQUEST_LAYOUT.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+#[allow(unused_variables)]
+pub trait ViewPeer: Any {
+    /// The standard view attributes to apply each time the view is
+    /// attached, rather than setting each of them from
+    /// [`ViewPeer::on_attached_to_window`].
+    fn view_config(&self) -> ViewConfig {
+        ViewConfig::default()
+    }
+
+    /// Return the measured width and height, or `None` to use the
+    /// default measurement. See [`MeasureSpec`] for how to interpret
+    /// the specs.
+    fn on_measure(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        width_spec: MeasureSpec,
+        height_spec: MeasureSpec,
+    ) -> Option<(jint, jint)> {
+        None
+    }
+
+    fn on_layout(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        changed: bool,
+        left: jint,
+        top: jint,
+        right: jint,
+        bottom: jint,
+    ) {
+    }
+
+    fn on_size_changed(&mut self, ctx: &mut CallbackCtx, w: jint, h: jint, oldw: jint, oldh: jint) {
+    }
+
+    fn on_key_down<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    fn on_key_up<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key_code: Keycode,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    fn on_trackball_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    fn on_touch_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    fn on_generic_motion_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    /// Called for scroll events from a rotary encoder, such as a Wear OS
+    /// watch crown, before [`ViewPeer::on_generic_motion_event`], which
+    /// gets the event if this returns false. `delta` is in pixels and is
+    /// positive to scroll toward the end of the content. The view must
+    /// have focus to receive rotary input.
+    fn on_rotary_input(&mut self, ctx: &mut CallbackCtx, delta: jfloat) -> bool {
+        false
+    }
+
+    fn on_hover_event<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        event: &MotionEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    fn on_focus_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        gain_focus: bool,
+        direction: jint,
+        previously_focused_rect: Option<&Rect<'local>>,
+    ) {
+    }
+
+    /// Called when a media key is pressed and wasn't handled by
+    /// [`ViewPeer::on_key_down`]. If this returns false, the key goes to
+    /// the media session set with [`View::set_media_session_token`], if
+    /// any.
+    fn on_media_key<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        key: MediaKey,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    /// Called when a d-pad arrow key wasn't handled by
+    /// [`ViewPeer::on_key_down`]. `direction` is one of `FOCUS_UP`,
+    /// `FOCUS_DOWN`, `FOCUS_LEFT` or `FOCUS_RIGHT`. Return true after
+    /// moving focus within the view's content; if this returns false,
+    /// the platform moves focus to the next view in that direction.
+    fn on_unhandled_dpad_navigation<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        direction: jint,
+        event: &KeyEvent<'local>,
+    ) -> bool {
+        false
+    }
+
+    fn on_window_focus_changed(&mut self, ctx: &mut CallbackCtx, has_window_focus: bool) {}
+
+    fn on_attached_to_window(&mut self, ctx: &mut CallbackCtx) {}
+
+    fn on_detached_from_window(&mut self, ctx: &mut CallbackCtx) {}
+
+    fn on_window_visibility_changed(&mut self, ctx: &mut CallbackCtx, visibility: Visibility) {}
+
+    fn on_apply_window_insets<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        insets: &WindowInsets<'local>,
+    ) {
+    }
+
+    /// Called when the part of the view that isn't covered by system bars,
+    /// the IME or the display cutout changes, whether because the insets
+    /// changed or because the view moved or was resized. Content that
+    /// must stay visible and interactive should be laid out within it.
+    fn on_safe_content_rect_changed(&mut self, ctx: &mut CallbackCtx, rect: SafeContentRect) {}
+
+    /// Called when the host activity forwards a multi-window mode change.
+    fn on_multi_window_mode_changed(&mut self, ctx: &mut CallbackCtx, in_multi_window_mode: bool) {}
+
+    /// Called when the host activity forwards a picture-in-picture mode
+    /// change. While in picture-in-picture mode, the view is small and
+    /// not interactive, so non-essential animation should be paused.
+    fn on_picture_in_picture_mode_changed(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        in_picture_in_picture_mode: bool,
+    ) {
+    }
+
+    /// Called with the current folding features when the app uses
+    /// `RustFoldingFeatureTracker`. The slice is empty when no fold
+    /// crosses the window.
+    fn on_folding_features_changed(&mut self, ctx: &mut CallbackCtx, features: &[FoldingFeature]) {}
+
+    fn surface_created<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+    ) {
+    }
+
+    fn surface_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+        format: jint,
+        width: jint,
+        height: jint,
+    ) {
+    }
+
+    fn surface_destroyed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        holder: &SurfaceHolder<'local>,
+    ) {
+    }
+
+    /// Called when the surface of an overlay added with
+    /// [`View::add_overlay_surface`] is created.
+    fn overlay_surface_created<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        id: jint,
+        holder: &SurfaceHolder<'local>,
+    ) {
+    }
+
+    fn overlay_surface_changed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        id: jint,
+        holder: &SurfaceHolder<'local>,
+        format: jint,
+        width: jint,
+        height: jint,
+    ) {
+    }
+
+    /// Called when an overlay's surface is destroyed, including when the
+    /// view is detached or the overlay is removed.
+    fn overlay_surface_destroyed<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        id: jint,
+        holder: &SurfaceHolder<'local>,
+    ) {
+    }
+
+    /// Called when rendering starts or stops being useful. Rendering is
+    /// active while the view is attached to a visible window and has a
+    /// valid surface, and optionally only while the window has focus; see
+    /// [`View::set_pause_rendering_without_window_focus`]. This is called
+    /// after [`ViewPeer::surface_created`] and before
+    /// [`ViewPeer::surface_destroyed`].
+    ///
+    /// When rendering becomes inactive, any pending frame callback is
+    /// removed, so a peer that renders continuously should post a new one
+    /// with [`View::post_frame_callback`] when it becomes active again.
+    fn on_rendering_active_changed(&mut self, ctx: &mut CallbackCtx, active: bool) {}
+
+    fn do_frame(&mut self, ctx: &mut CallbackCtx, frame_time_nanos: jlong) {}
+
+    fn delayed_callback(&mut self, ctx: &mut CallbackCtx) {}
+
+    /// Called when a timer scheduled with [`View::scheduler`] fires.
+    fn on_timer(&mut self, ctx: &mut CallbackCtx, timer_id: jint) {}
+
+    /// Called when an animation started with [`View::animate`] is canceled,
+    /// either explicitly or by starting another animation of the same
+    /// property. [`ViewPeer::on_animation_end`] is called afterward.
+    fn on_animation_cancel(&mut self, ctx: &mut CallbackCtx, animation_id: jint) {}
+
+    /// Called when an animation started with [`View::animate`] ends,
+    /// whether or not it ran to completion.
+    fn on_animation_end(&mut self, ctx: &mut CallbackCtx, animation_id: jint) {}
+
+    /// Called when a copy requested with [`View::request_pixel_copy`]
+    /// completes. `result` is one of the `PIXEL_COPY_*` constants;
+    /// the bitmap is only valid if it's [`PIXEL_COPY_SUCCESS`], and is
+    /// recycled when this callback returns.
+    #[cfg(feature = "graphics")]
+    fn on_pixel_copy_finished<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        request_id: jint,
+        result: jint,
+        bitmap: &Bitmap<'local>,
+    ) {
+    }
+
+    /// Called for each frame of the host window while frame metrics are
+    /// enabled with [`View::set_frame_metrics_enabled`].
+    /// `drop_count_since_last_invocation` is the number of frames whose
+    /// metrics were dropped because this callback was too slow.
+    fn on_frame_metrics_available<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        metrics: &FrameMetrics<'local>,
+        drop_count_since_last_invocation: jint,
+    ) {
+    }
+
+    /// Called with one of the `THERMAL_STATUS_*` constants when the device's
+    /// thermal status changes, while power monitoring is enabled with
+    /// [`View::set_power_monitoring_enabled`].
+    fn on_thermal_status_changed(&mut self, ctx: &mut CallbackCtx, status: jint) {}
+
+    /// Called when battery saver is turned on or off, while power monitoring
+    /// is enabled with [`View::set_power_monitoring_enabled`].
+    fn on_power_save_mode_changed(&mut self, ctx: &mut CallbackCtx, power_save_mode: bool) {}
+
+    /// Called on the UI thread when the default network changes, while
+    /// network monitoring is enabled with [`View::set_network_monitoring_enabled`].
+    /// To handle changes on another thread, send `status` over a channel.
+    fn on_network_changed(&mut self, ctx: &mut CallbackCtx, status: NetworkStatus) {}
+
+    /// Called when the user changes display settings such as font scale,
+    /// bold text, high contrast text or animation removal, while display
+    /// preferences monitoring is enabled with
+    /// [`View::set_display_preferences_monitoring_enabled`].
+    fn on_display_preferences_changed(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        preferences: DisplayPreferences,
+    ) {
+    }
+
+    /// Called when a hardware keyboard is attached or detached, while
+    /// keyboard monitoring is enabled with
+    /// [`View::set_keyboard_monitoring_enabled`]. Editors may want to
+    /// behave more like desktop editors while one is present.
+    fn on_hardware_keyboard_changed(&mut self, ctx: &mut CallbackCtx, present: bool) {}
+
+    /// Called when the IME is shown or hidden, or its height changes, while
+    /// IME visibility monitoring is enabled with
+    /// [`View::set_ime_visibility_monitoring_enabled`]. `height` is as
+    /// returned by [`View::ime_height`].
+    fn on_ime_visibility_changed(&mut self, ctx: &mut CallbackCtx, visible: bool, height: jint) {}
+
+    /// Called when the layout of a hardware keyboard may have changed,
+    /// while keyboard monitoring is enabled. Any [`crate::KeyCharacterMap`]
+    /// loaded for `device_id` should be loaded again.
+    fn on_keyboard_layout_changed(&mut self, ctx: &mut CallbackCtx, device_id: jint) {}
+
+    /// Called when an input device is connected, while input device
+    /// monitoring is enabled with [`View::set_input_device_monitoring_enabled`].
+    /// Look it up with [`InputDevice::get`](crate::InputDevice::get), for
+    /// example to start showing hover states when a mouse is added.
+    fn on_input_device_added(&mut self, ctx: &mut CallbackCtx, device_id: jint) {}
+
+    /// Called when an input device is disconnected, while input device
+    /// monitoring is enabled. The device can no longer be looked up, so
+    /// peers that care about the kind of device should remember it when
+    /// it's added.
+    fn on_input_device_removed(&mut self, ctx: &mut CallbackCtx, device_id: jint) {}
+
+    /// Called when the properties of an input device change, such as its
+    /// keyboard layout, while input device monitoring is enabled.
+    fn on_input_device_changed(&mut self, ctx: &mut CallbackCtx, device_id: jint) {}
+
+    /// Called when the refresh rate of the view's display changes, while
+    /// monitoring is enabled with [`View::set_refresh_rate_monitoring_enabled`].
+    /// Render loops that pace themselves should adjust their frame interval;
+    /// the display's other supported rates are available from
+    /// [`Display::supported_modes`].
+    fn on_refresh_rate_changed(&mut self, ctx: &mut CallbackCtx, refresh_rate: jfloat) {}
+
+    /// Called when a request started with [`View::start_document_request`]
+    /// completes. `uri` is the chosen document, or `None` if the user
+    /// canceled. Read or write it with [`Context::content_resolver`].
+    fn on_document_result<'local>(
+        &mut self,
+        ctx: &mut CallbackCtx<'local>,
+        request_code: jint,
+        uri: Option<&Uri<'local>>,
+    ) {
+    }
+
+    /// Called when a request started with [`View::request_permissions`]
+    /// completes, with each requested permission and whether it was
+    /// granted. The slice is empty if the request was interrupted.
+    fn on_request_permissions_result(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        request_code: jint,
+        results: &[(String, bool)],
+    ) {
+    }
+
+    /// Called when a [`TextToSpeech`](crate::TextToSpeech) engine created
+    /// for this view has finished initializing. It can't speak until this
+    /// is called with `success` set to `true`.
+    fn on_text_to_speech_init(&mut self, ctx: &mut CallbackCtx, success: bool) {}
+
+    /// Called when an utterance queued with
+    /// [`TextToSpeech::speak`](crate::TextToSpeech::speak) finishes.
+    /// `error` is `true` if it failed or was stopped before completion.
+    fn on_utterance_done(&mut self, ctx: &mut CallbackCtx, utterance_id: &str, error: bool) {}
+
+    /// Called when a [`crate::Popup`] anchored to this view is dismissed,
+    /// whether by [`crate::Popup::dismiss`] or by a touch outside it.
+    fn on_popup_dismissed(&mut self, ctx: &mut CallbackCtx, popup_id: jint) {}
+
+    /// Called when the user picks the replacement at `index` in a
+    /// [`crate::SuggestionsPopup`]. The popup is dismissed afterward.
+    fn on_suggestion_picked(&mut self, ctx: &mut CallbackCtx, popup_id: jint, index: usize) {}
+
+    /// Called when the user picks the delete action in a
+    /// [`crate::SuggestionsPopup`]. The popup is dismissed afterward.
+    fn on_suggestion_delete(&mut self, ctx: &mut CallbackCtx, popup_id: jint) {}
+
+    /// Called when the host, such as the Java `RustViewFragment`, saves
+    /// its instance state. Store anything needed to restore the view's
+    /// content in `out_state`.
+    fn on_save_state<'local>(&mut self, ctx: &mut CallbackCtx<'local>, out_state: &Bundle<'local>) {
+    }
+
+    /// Called when the host restores state saved by
+    /// [`ViewPeer::on_save_state`]. This happens after the peer is
+    /// created but before the view is attached to a window.
+    fn on_restore_state<'local>(&mut self, ctx: &mut CallbackCtx<'local>, state: &Bundle<'local>) {}
+
+    /// Called when the hosting activity or fragment resumes.
+    fn on_host_resume(&mut self, ctx: &mut CallbackCtx) {}
+
+    /// Called when the hosting activity or fragment pauses.
+    fn on_host_pause(&mut self, ctx: &mut CallbackCtx) {}
+
+    /// Called with a message that the Java side sent on `channel` with
+    /// `RustView.sendChannelMessage`. The default replies with null, as
+    /// for a channel with no handler.
+    fn on_channel_message(
+        &mut self,
+        ctx: &mut CallbackCtx,
+        channel: &str,
+        message: &[u8],
+        reply: ChannelReply,
+    ) {
+        reply.send(ctx, None);
+    }
+
+    #[cfg(feature = "accessibility")]
+    fn as_accessibility_node_provider(&mut self) -> Option<&mut dyn AccessibilityNodeProvider> {
+        None
+    }
+
+    #[cfg(feature = "ime")]
+    fn as_input_connection(&mut self) -> Option<&mut dyn InputConnection> {
+        None
+    }
+}
+
+impl dyn ViewPeer {
+    /// The peer as [`Any`], to downcast it to the app's concrete peer type.
+    pub fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
 
 static NEXT_PEER_ID: AtomicI64 = AtomicI64::new(0);
 static PEER_MAP: Mutex<BTreeMap<jlong, SendWrapper<Rc<RefCell<Box<dyn ViewPeer>>>>>> =
     Mutex::new(BTreeMap::new());
+// The views of peers that are attached to a window, for broadcasting
+// to them with `for_each_peer`.
+static PEER_VIEWS: Mutex<BTreeMap<jlong, GlobalRef>> = Mutex::new(BTreeMap::new());
+
+#[cfg_attr(feature = "jni-checks", track_caller)]
+pub(crate) fn with_peer<'local, F, T: Default>(
+    env: JNIEnv<'local>,
+    view: View<'local>,
+    id: jlong,
+    f: F,
+) -> T
+where
+    F: FnOnce(&mut CallbackCtx<'local>, &mut dyn ViewPeer) -> T,
+{
+    #[cfg(feature = "jni-checks")]
+    let name = crate::jni_checks::take_next_callback_name();
+    let map = PEER_MAP.lock().unwrap();
+    let Some(peer) = map.get(&id) else {
+        return T::default();
+    };
+    #[cfg(feature = "thread-checks")]
+    crate::thread_checks::check_peer_thread(id, peer.valid());
+    let peer = Rc::clone(&**peer);
+    drop(map);
+    let handle = WeakPeerHandle::new(id, &peer);
+    let mut peer = peer.borrow_mut();
+    #[cfg(feature = "jni-checks")]
+    let checks = crate::jni_checks::CallbackChecks::enter(&env, id, name);
+    let mut ctx = CallbackCtx::new(env, view, handle);
+    let result = f(&mut ctx, &mut **peer);
+    drop(peer);
+    ctx.finish();
+    #[cfg(feature = "jni-checks")]
+    checks.finish(std::panic::Location::caller());
+    result
+}
+
+peer_callbacks! {
+    fn peer_callback_methods;
+
+    "onMeasureNative" => fn on_measure<'local>(
+        width_spec: jint,
+        height_spec: jint,
+    ) -> JIntArray<'local> |ctx, peer| {
+        let width_spec = MeasureSpec(width_spec);
+        let height_spec = MeasureSpec(height_spec);
+        if let Some((width, height)) = peer.on_measure(ctx, width_spec, height_spec) {
+            let result = ctx.env.new_int_array(2).unwrap();
+            ctx.env
+                .set_int_array_region(&result, 0, &[width, height])
+                .unwrap();
+            result
+        } else {
+            JObject::null().into()
+        }
+    }
+
+    "onLayoutNative" => fn on_layout<'local>(
+        changed: jboolean,
+        left: jint,
+        top: jint,
+        right: jint,
+        bottom: jint,
+    ) |ctx, peer| {
+        peer.on_layout(ctx, changed == JNI_TRUE, left, top, right, bottom);
+    }
+
+    "onKeyUpNative" => fn on_key_up<'local>(
+        key_code: jint,
+        event: KeyEvent<'local>,
+    ) -> jboolean |ctx, peer| {
+        let key_code = Keycode::from_primitive(key_code);
+        #[cfg(feature = "trace")]
+        crate::trace::key_event(ctx, "on_key_up", key_code, &event);
+        as_jboolean(peer.on_key_up(ctx, key_code, &event))
+    }
+
+    "onTrackballEventNative" => fn on_trackball_event<'local>(
+        event: MotionEvent<'local>,
+    ) -> jboolean |ctx, peer| {
+        #[cfg(feature = "trace")]
+        crate::trace::motion_event(ctx, "on_trackball_event", &event);
+        as_jboolean(peer.on_trackball_event(ctx, &event))
+    }
+
+    "onTouchEventNative" => fn on_touch_event<'local>(
+        event: MotionEvent<'local>,
+    ) -> jboolean |ctx, peer| {
+        #[cfg(feature = "trace")]
+        crate::trace::motion_event(ctx, "on_touch_event", &event);
+        as_jboolean(peer.on_touch_event(ctx, &event))
+    }
+
+    "onHoverEventNative" => fn on_hover_event<'local>(
+        event: MotionEvent<'local>,
+    ) -> jboolean |ctx, peer| {
+        #[cfg(feature = "trace")]
+        crate::trace::motion_event(ctx, "on_hover_event", &event);
+        as_jboolean(peer.on_hover_event(ctx, &event))
+    }
+
+    "onApplyWindowInsetsNative" => fn on_apply_window_insets<'local>(
+        insets: WindowInsets<'local>,
+    ) |ctx, peer| {
+        peer.on_apply_window_insets(ctx, &insets);
+    }
+
+    "onSafeContentRectChangedNative" => fn on_safe_content_rect_changed<'local>(
+        left: jint,
+        top: jint,
+        right: jint,
+        bottom: jint,
+    ) |ctx, peer| {
+        let rect = SafeContentRect {
+            left,
+            top,
+            right,
+            bottom,
+        };
+        peer.on_safe_content_rect_changed(ctx, rect);
+    }
+
+    "onMultiWindowModeChangedNative" => fn on_multi_window_mode_changed<'local>(
+        in_multi_window_mode: jboolean,
+    ) |ctx, peer| {
+        peer.on_multi_window_mode_changed(ctx, in_multi_window_mode == JNI_TRUE);
+    }
+
+    "onPictureInPictureModeChangedNative" => fn on_picture_in_picture_mode_changed<'local>(
+        in_picture_in_picture_mode: jboolean,
+    ) |ctx, peer| {
+        peer.on_picture_in_picture_mode_changed(ctx, in_picture_in_picture_mode == JNI_TRUE);
+    }
+
+    "surfaceCreatedNative" => fn surface_created<'local>(
+        holder: SurfaceHolder<'local>,
+    ) |ctx, peer| {
+        #[cfg(feature = "trace")]
+        crate::trace::log(ctx, TraceCategories::SURFACE, format_args!("surface_created"));
+        peer.surface_created(ctx, &holder);
+    }
+
+    "surfaceChangedNative" => fn surface_changed<'local>(
+        holder: SurfaceHolder<'local>,
+        format: jint,
+        width: jint,
+        height: jint,
+    ) |ctx, peer| {
+        #[cfg(feature = "trace")]
+        crate::trace::log(
+            ctx,
+            TraceCategories::SURFACE,
+            format_args!("surface_changed: format {format}, {width}x{height}"),
+        );
+        peer.surface_changed(ctx, &holder, format, width, height);
+    }
+
+    "surfaceDestroyedNative" => fn surface_destroyed<'local>(
+        holder: SurfaceHolder<'local>,
+    ) |ctx, peer| {
+        #[cfg(feature = "trace")]
+        crate::trace::log(ctx, TraceCategories::SURFACE, format_args!("surface_destroyed"));
+        peer.surface_destroyed(ctx, &holder);
+    }
+
+    "onOverlaySurfaceCreatedNative" => fn on_overlay_surface_created<'local>(
+        id: jint,
+        holder: SurfaceHolder<'local>,
+    ) |ctx, peer| {
+        peer.overlay_surface_created(ctx, id, &holder);
+    }
+
+    "onOverlaySurfaceChangedNative" => fn on_overlay_surface_changed<'local>(
+        id: jint,
+        holder: SurfaceHolder<'local>,
+        format: jint,
+        width: jint,
+        height: jint,
+    ) |ctx, peer| {
+        peer.overlay_surface_changed(ctx, id, &holder, format, width, height);
+    }
+
+    "onOverlaySurfaceDestroyedNative" => fn on_overlay_surface_destroyed<'local>(
+        id: jint,
+        holder: SurfaceHolder<'local>,
+    ) |ctx, peer| {
+        peer.overlay_surface_destroyed(ctx, id, &holder);
+    }
+
+    "onRenderingActiveChangedNative" => fn on_rendering_active_changed<'local>(
+        active: jboolean,
+    ) |ctx, peer| {
+        peer.on_rendering_active_changed(ctx, active == JNI_TRUE);
+    }
+
+    "doFrameNative" => fn do_frame<'local>(frame_time_nanos: jlong) |ctx, peer| {
+        peer.do_frame(ctx, frame_time_nanos);
+    }
 
-pub(crate) fn with_peer<'local, F, T: Default>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    id: jlong,
-    f: F,
-) -> T
-where
-    F: FnOnce(&mut CallbackCtx<'local>, &mut dyn ViewPeer) -> T,
-{
-    let map = PEER_MAP.lock().unwrap();
-    let Some(peer) = map.get(&id) else {
-        return T::default();
-    };
-    let peer = Rc::clone(&**peer);
-    drop(map);
-    let mut peer = peer.borrow_mut();
-    let mut ctx = CallbackCtx::new(env, view);
-    let result = f(&mut ctx, &mut **peer);
-    drop(peer);
-    ctx.finish();
-    result
-}
+    "delayedCallbackNative" => fn delayed_callback<'local>() |ctx, peer| {
+        peer.delayed_callback(ctx);
+    }
 
-extern "system" fn on_measure<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    width_spec: jint,
-    height_spec: jint,
-) -> JIntArray<'local> {
-    with_peer(env, view, peer, |ctx, peer| {
-        if let Some((width, height)) = peer.on_measure(ctx, width_spec, height_spec) {
-            let result = ctx.env.new_int_array(2).unwrap();
-            ctx.env
-                .set_int_array_region(&result, 0, &[width, height])
-                .unwrap();
-            result
-        } else {
-            JObject::null().into()
+    "onTimerNative" => fn on_timer<'local>(timer_id: jint) |ctx, peer| {
+        peer.on_timer(ctx, timer_id);
+    }
+
+    "onAnimationCancelNative" => fn on_animation_cancel<'local>(animation_id: jint) |ctx, peer| {
+        peer.on_animation_cancel(ctx, animation_id);
+    }
+
+    "onAnimationEndNative" => fn on_animation_end<'local>(animation_id: jint) |ctx, peer| {
+        peer.on_animation_end(ctx, animation_id);
+    }
+
+    #[cfg(feature = "graphics")]
+    "onPixelCopyFinishedNative" => fn on_pixel_copy_finished<'local>(
+        request_id: jint,
+        result: jint,
+        bitmap: Bitmap<'local>,
+    ) |ctx, peer| {
+        let id = ctx.peer_handle().id();
+        match RequestCompleter::take(id, RequestKind::PixelCopy, request_id) {
+            Some(request) => {
+                request.complete(PixelCopyResult::from_bitmap(&mut ctx.env, result, &bitmap));
+            }
+            None => peer.on_pixel_copy_finished(ctx, request_id, result, &bitmap),
         }
-    })
-}
+    }
 
-extern "system" fn on_layout<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    changed: jboolean,
-    left: jint,
-    top: jint,
-    right: jint,
-    bottom: jint,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.on_layout(ctx, changed == JNI_TRUE, left, top, right, bottom);
-    })
-}
+    "onFrameMetricsAvailableNative" => fn on_frame_metrics_available<'local>(
+        metrics: FrameMetrics<'local>,
+        drop_count_since_last_invocation: jint,
+    ) |ctx, peer| {
+        peer.on_frame_metrics_available(ctx, &metrics, drop_count_since_last_invocation);
+    }
 
-extern "system" fn on_size_changed<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    w: jint,
-    h: jint,
-    oldw: jint,
-    oldh: jint,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.on_size_changed(ctx, w, h, oldw, oldh);
-    })
-}
+    "onThermalStatusChangedNative" => fn on_thermal_status_changed<'local>(
+        status: jint,
+    ) |ctx, peer| {
+        peer.on_thermal_status_changed(ctx, status);
+    }
 
-extern "system" fn on_key_down<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    key_code: jint,
-    event: KeyEvent<'local>,
-) -> jboolean {
-    as_jboolean(with_peer(env, view, peer, |ctx, peer| {
-        peer.on_key_down(ctx, Keycode::from_primitive(key_code), &event)
-    }))
-}
+    "onPowerSaveModeChangedNative" => fn on_power_save_mode_changed<'local>(
+        power_save_mode: jboolean,
+    ) |ctx, peer| {
+        peer.on_power_save_mode_changed(ctx, power_save_mode == JNI_TRUE);
+    }
 
-extern "system" fn on_key_up<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    key_code: jint,
-    event: KeyEvent<'local>,
-) -> jboolean {
-    as_jboolean(with_peer(env, view, peer, |ctx, peer| {
-        peer.on_key_up(ctx, Keycode::from_primitive(key_code), &event)
-    }))
-}
+    "onDisplayPreferencesChangedNative" => fn on_display_preferences_changed<'local>() |ctx, peer| {
+        let context = ctx.view.context(&mut ctx.env);
+        let preferences = DisplayPreferences::query(&mut ctx.env, &context);
+        peer.on_display_preferences_changed(ctx, preferences);
+    }
 
-extern "system" fn on_trackball_event<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    event: MotionEvent<'local>,
-) -> jboolean {
-    as_jboolean(with_peer(env, view, peer, |ctx, peer| {
-        peer.on_trackball_event(ctx, &event)
-    }))
-}
+    "onHardwareKeyboardChangedNative" => fn on_hardware_keyboard_changed<'local>(
+        present: jboolean,
+    ) |ctx, peer| {
+        peer.on_hardware_keyboard_changed(ctx, present == JNI_TRUE);
+    }
 
-extern "system" fn on_touch_event<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    event: MotionEvent<'local>,
-) -> jboolean {
-    as_jboolean(with_peer(env, view, peer, |ctx, peer| {
-        peer.on_touch_event(ctx, &event)
-    }))
-}
+    "onImeVisibilityChangedNative" => fn on_ime_visibility_changed<'local>(
+        visible: jboolean,
+        height: jint,
+    ) |ctx, peer| {
+        peer.on_ime_visibility_changed(ctx, visible == JNI_TRUE, height);
+    }
 
-extern "system" fn on_generic_motion_event<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    event: MotionEvent<'local>,
-) -> jboolean {
-    as_jboolean(with_peer(env, view, peer, |ctx, peer| {
-        peer.on_generic_motion_event(ctx, &event)
-    }))
-}
+    "onKeyboardLayoutChangedNative" => fn on_keyboard_layout_changed<'local>(
+        device_id: jint,
+    ) |ctx, peer| {
+        peer.on_keyboard_layout_changed(ctx, device_id);
+    }
 
-extern "system" fn on_hover_event<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    event: MotionEvent<'local>,
-) -> jboolean {
-    as_jboolean(with_peer(env, view, peer, |ctx, peer| {
-        peer.on_hover_event(ctx, &event)
-    }))
+    "onInputDeviceAddedNative" => fn on_input_device_added<'local>(device_id: jint) |ctx, peer| {
+        peer.on_input_device_added(ctx, device_id);
+    }
+
+    "onInputDeviceRemovedNative" => fn on_input_device_removed<'local>(
+        device_id: jint,
+    ) |ctx, peer| {
+        peer.on_input_device_removed(ctx, device_id);
+    }
+
+    "onInputDeviceChangedNative" => fn on_input_device_changed<'local>(
+        device_id: jint,
+    ) |ctx, peer| {
+        peer.on_input_device_changed(ctx, device_id);
+    }
+
+    "onRefreshRateChangedNative" => fn on_refresh_rate_changed<'local>(
+        refresh_rate: jfloat,
+    ) |ctx, peer| {
+        peer.on_refresh_rate_changed(ctx, refresh_rate);
+    }
+
+    "onDocumentResultNative" => fn on_document_result<'local>(
+        request_code: jint,
+        uri: Uri<'local>,
+    ) |ctx, peer| {
+        let uri = (!uri.0.as_raw().is_null()).then_some(&uri);
+        let id = ctx.peer_handle().id();
+        match RequestCompleter::take(id, RequestKind::Document, request_code) {
+            Some(request) => {
+                request.complete(uri.map(|uri| ctx.env.new_global_ref(&uri.0).unwrap()));
+            }
+            None => peer.on_document_result(ctx, request_code, uri),
+        }
+    }
+
+    "onTextToSpeechInitNative" => fn on_text_to_speech_init<'local>(success: jboolean) |ctx, peer| {
+        peer.on_text_to_speech_init(ctx, success == JNI_TRUE);
+    }
+
+    "onPopupDismissedNative" => fn on_popup_dismissed<'local>(popup_id: jint) |ctx, peer| {
+        peer.on_popup_dismissed(ctx, popup_id);
+    }
+
+    "onSuggestionPickedNative" => fn on_suggestion_picked<'local>(
+        popup_id: jint,
+        index: jint,
+    ) |ctx, peer| {
+        peer.on_suggestion_picked(ctx, popup_id, index as usize);
+    }
+
+    "onSuggestionDeleteNative" => fn on_suggestion_delete<'local>(popup_id: jint) |ctx, peer| {
+        peer.on_suggestion_delete(ctx, popup_id);
+    }
+
+    "onSaveStateNative" => fn on_save_state<'local>(out_state: Bundle<'local>) |ctx, peer| {
+        peer.on_save_state(ctx, &out_state);
+    }
+
+    "onRestoreStateNative" => fn on_restore_state<'local>(state: Bundle<'local>) |ctx, peer| {
+        peer.on_restore_state(ctx, &state);
+    }
+
+    "onHostResumeNative" => fn on_host_resume<'local>() |ctx, peer| {
+        peer.on_host_resume(ctx);
+    }
+
+    "onHostPauseNative" => fn on_host_pause<'local>() |ctx, peer| {
+        peer.on_host_pause(ctx);
+    }
 }
 
-extern "system" fn on_focus_changed<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    gain_focus: jboolean,
-    direction: jint,
-    previously_focused_rect: Rect<'local>,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.on_focus_changed(
-            ctx,
-            gain_focus == JNI_TRUE,
-            direction,
-            (!previously_focused_rect.0.as_raw().is_null()).then_some(&previously_focused_rect),
+native_methods! {
+    fn base_methods;
+
+    "onSizeChangedNative" => fn on_size_changed<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        w: jint,
+        h: jint,
+        oldw: jint,
+        oldh: jint,
+    ) {
+        invalidate_view_state(peer);
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_size_changed(ctx, w, h, oldw, oldh);
+        })
+    }
+
+    "onKeyDownNative" => fn on_key_down<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        key_code: jint,
+        event: KeyEvent<'local>,
+    ) -> jboolean {
+        as_jboolean(with_peer(env, view, peer, |ctx, peer| {
+            let key_code = Keycode::from_primitive(key_code);
+            #[cfg(feature = "trace")]
+            crate::trace::key_event(ctx, "on_key_down", key_code, &event);
+            if peer.on_key_down(ctx, key_code, &event) {
+                return true;
+            }
+            if let Some(key) = MediaKey::from_keycode(key_code) {
+                return peer.on_media_key(ctx, key, &event);
+            }
+            let direction = match key_code {
+                Keycode::DpadUp => FOCUS_UP,
+                Keycode::DpadDown => FOCUS_DOWN,
+                Keycode::DpadLeft => FOCUS_LEFT,
+                Keycode::DpadRight => FOCUS_RIGHT,
+                _ => return false,
+            };
+            peer.on_unhandled_dpad_navigation(ctx, direction, &event)
+        }))
+    }
+
+    "onGenericMotionEventNative" => fn on_generic_motion_event<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        event: MotionEvent<'local>,
+    ) -> jboolean {
+        as_jboolean(with_peer(env, view, peer, |ctx, peer| {
+            #[cfg(feature = "trace")]
+            crate::trace::motion_event(ctx, "on_generic_motion_event", &event);
+            if event.source(&mut ctx.env) == Source::RotaryEncoder {
+                let vc = ctx.view.view_configuration(&mut ctx.env);
+                if let Some(delta) = event.rotary_scroll_delta(&mut ctx.env, &vc)
+                    && peer.on_rotary_input(ctx, delta)
+                {
+                    return true;
+                }
+            }
+            peer.on_generic_motion_event(ctx, &event)
+        }))
+    }
+
+    "onFocusChangedNative" => fn on_focus_changed<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        gain_focus: jboolean,
+        direction: jint,
+        previously_focused_rect: Rect<'local>,
+    ) {
+        invalidate_view_state(peer);
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_focus_changed(
+                ctx,
+                gain_focus == JNI_TRUE,
+                direction,
+                (!previously_focused_rect.0.as_raw().is_null()).then_some(&previously_focused_rect),
+            );
+        })
+    }
+
+    "onWindowFocusChangedNative" => fn on_window_focus_changed<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        has_window_focus: jboolean,
+    ) {
+        invalidate_view_state(peer);
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_window_focus_changed(ctx, has_window_focus == JNI_TRUE);
+        })
+    }
+
+    "onAttachedToWindowNative" => fn on_attached_to_window<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) {
+        invalidate_view_state(peer);
+        #[cfg(feature = "trace")]
+        crate::trace::reload_property();
+        let view_ref = env.new_global_ref(&view.0).unwrap();
+        PEER_VIEWS.lock().unwrap().insert(peer, view_ref);
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.view_config().apply(&mut ctx.env, &ctx.view);
+            peer.on_attached_to_window(ctx);
+        })
+    }
+
+    "onDetachedFromWindowNative" => fn on_detached_from_window<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+    ) {
+        PEER_VIEWS.lock().unwrap().remove(&peer);
+        crate::tasks::drop_tasks(peer);
+        crate::channel::drop_replies(peer);
+        let mut map = PEER_MAP.lock().unwrap();
+        #[cfg(feature = "ime")]
+        clear_text_cache(peer);
+        #[cfg(feature = "ime")]
+        crate::ime_quirks::forget_ime_quirks(peer);
+        invalidate_view_state(peer);
+        let Some(peer_cell) = map.remove(&peer) else {
+            return;
+        };
+        drop(map);
+        let peer_cell = SendWrapper::take(peer_cell);
+        let handle = WeakPeerHandle::new(peer, &peer_cell);
+        #[cfg(feature = "jni-checks")]
+        let (peer_id, checks) = (
+            peer,
+            crate::jni_checks::CallbackChecks::enter(&env, peer, None),
         );
-    })
-}
+        let mut peer = peer_cell.borrow_mut();
+        let mut ctx = CallbackCtx::new(env, view, handle);
+        peer.on_detached_from_window(&mut ctx);
+        drop(peer);
+        ctx.view.remove_frame_callback(&mut ctx.env);
+        ctx.view.remove_delayed_callbacks(&mut ctx.env);
+        ctx.finish();
+        #[cfg(feature = "jni-checks")]
+        {
+            checks.finish(std::panic::Location::caller());
+            drop(peer_cell);
+            crate::jni_checks::check_peer_released(peer_id);
+        }
+    }
 
-extern "system" fn on_window_focus_changed<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    has_window_focus: jboolean,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.on_window_focus_changed(ctx, has_window_focus == JNI_TRUE);
-    })
-}
+    "onWindowVisibilityChangedNative" => fn on_window_visibility_changed<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        visibility: jint,
+    ) {
+        invalidate_view_state(peer);
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_window_visibility_changed(ctx, Visibility::from_primitive(visibility));
+        })
+    }
 
-extern "system" fn on_attached_to_window<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.on_attached_to_window(ctx);
-    })
-}
+    "onFoldingFeaturesChangedNative" => fn on_folding_features_changed<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        features: JIntArray<'local>,
+    ) {
+        let features = FoldingFeature::from_java_array(&mut env, &features);
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_folding_features_changed(ctx, &features);
+        })
+    }
 
-extern "system" fn on_detached_from_window<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-) {
-    let mut map = PEER_MAP.lock().unwrap();
-    let peer = map.remove(&peer).unwrap();
-    drop(map);
-    let mut peer = peer.borrow_mut();
-    let mut ctx = CallbackCtx::new(env, view);
-    peer.on_detached_from_window(&mut ctx);
-    drop(peer);
-    ctx.view.remove_frame_callback(&mut ctx.env);
-    ctx.view.remove_delayed_callbacks(&mut ctx.env);
-    ctx.finish();
-}
+    "onNetworkChangedNative" => fn on_network_changed<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        available: jboolean,
+        validated: jboolean,
+        metered: jboolean,
+    ) {
+        let status = NetworkStatus {
+            available: available == JNI_TRUE,
+            validated: validated == JNI_TRUE,
+            metered: metered == JNI_TRUE,
+        };
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_network_changed(ctx, status);
+        })
+    }
 
-extern "system" fn on_window_visibility_changed<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    visibility: jint,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.on_window_visibility_changed(ctx, visibility);
-    })
+    "onRequestPermissionsResultNative" => fn on_request_permissions_result<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        request_code: jint,
+        permissions: JObjectArray<'local>,
+        grant_results: JIntArray<'local>,
+    ) {
+        let len = env.get_array_length(&grant_results).unwrap();
+        let mut grants = vec![0; len as usize];
+        env.get_int_array_region(&grant_results, 0, &mut grants)
+            .unwrap();
+        let results = grants
+            .into_iter()
+            .enumerate()
+            .map(|(i, grant)| {
+                let permission: JString = env
+                    .get_object_array_element(&permissions, i as jint)
+                    .unwrap()
+                    .into();
+                let permission = env.get_string(&permission).unwrap().into();
+                (permission, grant == PERMISSION_GRANTED)
+            })
+            .collect::<Vec<_>>();
+        if let Some(request) =
+            RequestCompleter::take(peer, RequestKind::Permissions, request_code)
+        {
+            request.complete(results);
+            return;
+        }
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_request_permissions_result(ctx, request_code, &results);
+        })
+    }
+
+    "onUtteranceDoneNative" => fn on_utterance_done<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        peer: jlong,
+        utterance_id: JString<'local>,
+        error: jboolean,
+    ) {
+        let utterance_id = String::from(env.get_string(&utterance_id).unwrap());
+        with_peer(env, view, peer, |ctx, peer| {
+            peer.on_utterance_done(ctx, &utterance_id, error == JNI_TRUE);
+        })
+    }
 }
 
-extern "system" fn surface_created<'local>(
+/// Call `f` with the peer with the given ID, if it's still registered
+/// and is a `P`, from an app-defined native method registered through
+/// [`register_view_class`]. The callback runs just like the crate's own,
+/// including deferred callbacks pushed onto the [`CallbackCtx`]. Returns
+/// the default value if the peer is gone or is of another type.
+pub fn with_peer_ctx_as<'local, P: ViewPeer, T: Default>(
     env: JNIEnv<'local>,
     view: View<'local>,
     peer: jlong,
-    holder: SurfaceHolder<'local>,
-) {
+    f: impl FnOnce(&mut CallbackCtx<'local>, &mut P) -> T,
+) -> T {
     with_peer(env, view, peer, |ctx, peer| {
-        peer.surface_created(ctx, &holder);
+        match peer.as_any().downcast_mut::<P>() {
+            Some(peer) => f(ctx, peer),
+            None => T::default(),
+        }
     })
 }
 
-extern "system" fn surface_changed<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    holder: SurfaceHolder<'local>,
-    format: jint,
-    width: jint,
-    height: jint,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.surface_changed(ctx, &holder, format, width, height);
-    })
+/// Call `f` with the peer with the given ID, outside of any view callback,
+/// such as from an activity's own native methods. This saves keeping a
+/// separate registry of peers to reach app-specific methods. Must be
+/// called on the UI thread. Returns `None` if the peer is gone, isn't a
+/// `P`, or is in the middle of a callback further up the stack, in which
+/// case `f` isn't called.
+pub fn with_peer_as<P: ViewPeer, T>(id: jlong, f: impl FnOnce(&mut P) -> T) -> Option<T> {
+    let map = PEER_MAP.lock().unwrap();
+    let peer = map.get(&id)?;
+    #[cfg(feature = "thread-checks")]
+    crate::thread_checks::check_peer_thread(id, peer.valid());
+    let peer = Rc::clone(&**peer);
+    drop(map);
+    let mut peer = peer.try_borrow_mut().ok()?;
+    let peer = peer.as_any().downcast_mut::<P>()?;
+    Some(f(peer))
 }
 
-extern "system" fn surface_destroyed<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    holder: SurfaceHolder<'local>,
-) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.surface_destroyed(ctx, &holder);
-    })
+pub(crate) fn peer_handle(id: jlong) -> Option<WeakPeerHandle> {
+    let map = PEER_MAP.lock().unwrap();
+    let peer = map.get(&id)?;
+    #[cfg(feature = "thread-checks")]
+    crate::thread_checks::check_peer_thread(id, peer.valid());
+    Some(WeakPeerHandle::new(id, peer))
 }
 
-extern "system" fn do_frame<'local>(
-    env: JNIEnv<'local>,
-    view: View<'local>,
-    peer: jlong,
-    frame_time_nanos: jlong,
+/// Call `f` with the peer of each view that's attached to a window, such
+/// as to pass on a process-wide event like a memory trim or TalkBack being
+/// turned on, from a single listener. Must be called on the UI thread.
+/// Peers in the middle of a callback further up the stack are skipped.
+pub fn for_each_peer<'local>(
+    env: &mut JNIEnv<'local>,
+    mut f: impl FnMut(&mut CallbackCtx<'local>, &mut dyn ViewPeer),
 ) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.do_frame(ctx, frame_time_nanos);
-    })
+    let views = PEER_VIEWS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(id, view)| (*id, view.clone()))
+        .collect::<Vec<_>>();
+    for (id, view) in views {
+        // A peer earlier in the loop may have detached this one.
+        let Some(handle) = peer_handle(id) else {
+            continue;
+        };
+        let view = View(env.new_local_ref(&view).unwrap());
+        // SAFETY: The clone is only used on this thread, within this call.
+        let env = unsafe { env.unsafe_clone() };
+        handle.with(env, view, &mut f);
+    }
 }
 
-extern "system" fn delayed_callback<'local>(env: JNIEnv<'local>, view: View<'local>, peer: jlong) {
-    with_peer(env, view, peer, |ctx, peer| {
-        peer.delayed_callback(ctx);
-    })
+/// Call `f` with the peer of the given view, if it's a `RustView` whose
+/// peer is still registered, and return its result. Returns `None`
+/// without calling `f` otherwise, or if the peer is in the middle of a
+/// callback further up the stack. Must be called on the UI thread.
+pub fn with_view_peer<'local, T>(
+    env: &mut JNIEnv<'local>,
+    view: &JObject<'local>,
+    f: impl FnOnce(&mut CallbackCtx<'local>, &mut dyn ViewPeer) -> T,
+) -> Option<T> {
+    static VIEW_PEER: CachedField =
+        CachedField::new("org/linebender/android/rustview/RustView", "mViewPeer", "J");
+    if view.as_raw().is_null()
+        || !env
+            .is_instance_of(view, "org/linebender/android/rustview/RustView")
+            .unwrap()
+    {
+        return None;
+    }
+    let id = VIEW_PEER.get(env, view).j().unwrap();
+    let handle = peer_handle(id)?;
+    let view = View(env.new_local_ref(view).unwrap());
+    // SAFETY: The clone is only used on this thread, within this call.
+    let env = unsafe { env.unsafe_clone() };
+    handle.with(env, view, f)
 }
 
 pub fn register_view_peer(peer: impl 'static + ViewPeer) -> jlong {
+    #[cfg(feature = "thread-checks")]
+    crate::thread_checks::record_ui_thread();
     let id = NEXT_PEER_ID.fetch_add(1, Ordering::Relaxed);
     let mut map = PEER_MAP.lock().unwrap();
     map.insert(id, SendWrapper::new(Rc::new(RefCell::new(Box::new(peer)))));
     id
 }
 
-pub fn register_view_class<'local, 'other_local>(
-    env: &mut JNIEnv<'local>,
-    class: impl Desc<'local, JClass<'other_local>>,
-    new_peer: for<'a> extern "system" fn(JNIEnv<'a>, View<'a>, Context<'a>) -> jlong,
-) {
+#[cfg(not(feature = "accessibility"))]
+extern "system" fn no_accessibility_node_provider(
+    _env: JNIEnv,
+    _view: View,
+    _peer: jlong,
+) -> jboolean {
+    as_jboolean(false)
+}
+
+#[cfg(not(feature = "ime"))]
+extern "system" fn no_input_connection(
+    _env: JNIEnv,
+    _view: View,
+    _peer: jlong,
+    _out_attrs: JObject,
+) -> jboolean {
+    as_jboolean(false)
+}
+
+pub(crate) fn register_base_natives(env: &mut JNIEnv) {
     static REGISTER_BASE_NATIVES: Once = Once::new();
     REGISTER_BASE_NATIVES.call_once(|| {
+        env.register_native_methods("org/linebender/android/rustview/RustView", &base_methods())
+            .unwrap();
+        env.register_native_methods(
+            "org/linebender/android/rustview/RustView",
+            &peer_callback_methods(),
+        )
+        .unwrap();
+        crate::view_state::register_natives(env);
+        crate::tasks::register_natives(env);
+        crate::channel::register_natives(env);
+        crate::process_state::register_natives(env);
+        #[cfg(feature = "accessibility")]
+        crate::accessibility::register_natives(env);
+        #[cfg(not(feature = "accessibility"))]
+        env.register_native_methods(
+            "org/linebender/android/rustview/RustView",
+            &[NativeMethod {
+                name: "hasAccessibilityNodeProviderNative".into(),
+                sig: "(J)Z".into(),
+                fn_ptr: no_accessibility_node_provider as *mut c_void,
+            }],
+        )
+        .unwrap();
+        #[cfg(feature = "ime")]
+        crate::ime::register_natives(env);
+        #[cfg(not(feature = "ime"))]
         env.register_native_methods(
             "org/linebender/android/rustview/RustView",
-            &[
-                NativeMethod {
-                    name: "onMeasureNative".into(),
-                    sig: "(JII)[I".into(),
-                    fn_ptr: on_measure as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onLayoutNative".into(),
-                    sig: "(JZIIII)V".into(),
-                    fn_ptr: on_layout as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onSizeChangedNative".into(),
-                    sig: "(JIIII)V".into(),
-                    fn_ptr: on_size_changed as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onKeyDownNative".into(),
-                    sig: "(JILandroid/view/KeyEvent;)Z".into(),
-                    fn_ptr: on_key_down as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onKeyUpNative".into(),
-                    sig: "(JILandroid/view/KeyEvent;)Z".into(),
-                    fn_ptr: on_key_up as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onTrackballEventNative".into(),
-                    sig: "(JLandroid/view/MotionEvent;)Z".into(),
-                    fn_ptr: on_trackball_event as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onTouchEventNative".into(),
-                    sig: "(JLandroid/view/MotionEvent;)Z".into(),
-                    fn_ptr: on_touch_event as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onGenericMotionEventNative".into(),
-                    sig: "(JLandroid/view/MotionEvent;)Z".into(),
-                    fn_ptr: on_generic_motion_event as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onHoverEventNative".into(),
-                    sig: "(JLandroid/view/MotionEvent;)Z".into(),
-                    fn_ptr: on_hover_event as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onFocusChangedNative".into(),
-                    sig: "(JZILandroid/graphics/Rect;)V".into(),
-                    fn_ptr: on_focus_changed as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onWindowFocusChangedNative".into(),
-                    sig: "(JZ)V".into(),
-                    fn_ptr: on_window_focus_changed as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onAttachedToWindowNative".into(),
-                    sig: "(J)V".into(),
-                    fn_ptr: on_attached_to_window as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onDetachedFromWindowNative".into(),
-                    sig: "(J)V".into(),
-                    fn_ptr: on_detached_from_window as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onWindowVisibilityChangedNative".into(),
-                    sig: "(JI)V".into(),
-                    fn_ptr: on_window_visibility_changed as *mut c_void,
-                },
-                NativeMethod {
-                    name: "surfaceCreatedNative".into(),
-                    sig: "(JLandroid/view/SurfaceHolder;)V".into(),
-                    fn_ptr: surface_created as *mut c_void,
-                },
-                NativeMethod {
-                    name: "surfaceChangedNative".into(),
-                    sig: "(JLandroid/view/SurfaceHolder;III)V".into(),
-                    fn_ptr: surface_changed as *mut c_void,
-                },
-                NativeMethod {
-                    name: "surfaceDestroyedNative".into(),
-                    sig: "(JLandroid/view/SurfaceHolder;)V".into(),
-                    fn_ptr: surface_destroyed as *mut c_void,
-                },
-                NativeMethod {
-                    name: "doFrameNative".into(),
-                    sig: "(JJ)V".into(),
-                    fn_ptr: do_frame as *mut c_void,
-                },
-                NativeMethod {
-                    name: "delayedCallbackNative".into(),
-                    sig: "(J)V".into(),
-                    fn_ptr: delayed_callback as *mut c_void,
-                },
-                NativeMethod {
-                    name: "hasAccessibilityNodeProviderNative".into(),
-                    sig: "(J)Z".into(),
-                    fn_ptr: has_accessibility_node_provider as *mut c_void,
-                },
-                NativeMethod {
-                    name: "createAccessibilityNodeInfoNative".into(),
-                    sig: "(JI)Landroid/view/accessibility/AccessibilityNodeInfo;".into(),
-                    fn_ptr: create_accessibility_node_info as *mut c_void,
-                },
-                NativeMethod {
-                    name: "accessibilityFindFocusNative".into(),
-                    sig: "(JI)Landroid/view/accessibility/AccessibilityNodeInfo;".into(),
-                    fn_ptr: accessibility_find_focus as *mut c_void,
-                },
-                NativeMethod {
-                    name: "performAccessibilityActionNative".into(),
-                    sig: "(JIILandroid/os/Bundle;)Z".into(),
-                    fn_ptr: perform_accessibility_action as *mut c_void,
-                },
-                NativeMethod {
-                    name: "onCreateInputConnectionNative".into(),
-                    sig: "(JLandroid/view/inputmethod/EditorInfo;)Z".into(),
-                    fn_ptr: on_create_input_connection as *mut c_void,
-                },
-                NativeMethod {
-                    name: "getTextBeforeCursorNative".into(),
-                    sig: "(JI)Ljava/lang/String;".into(),
-                    fn_ptr: get_text_before_cursor as *mut c_void,
-                },
-                NativeMethod {
-                    name: "getTextAfterCursorNative".into(),
-                    sig: "(JI)Ljava/lang/String;".into(),
-                    fn_ptr: get_text_after_cursor as *mut c_void,
-                },
-                NativeMethod {
-                    name: "getSelectedTextNative".into(),
-                    sig: "(J)Ljava/lang/String;".into(),
-                    fn_ptr: get_selected_text as *mut c_void,
-                },
-                NativeMethod {
-                    name: "getCursorCapsModeNative".into(),
-                    sig: "(JI)I".into(),
-                    fn_ptr: get_cursor_caps_mode as *mut c_void,
-                },
-                NativeMethod {
-                    name: "deleteSurroundingTextNative".into(),
-                    sig: "(JII)Z".into(),
-                    fn_ptr: delete_surrounding_text as *mut c_void,
-                },
-                NativeMethod {
-                    name: "deleteSurroundingTextInCodePointsNative".into(),
-                    sig: "(JII)Z".into(),
-                    fn_ptr: delete_surrounding_text_in_code_points as *mut c_void,
-                },
-                NativeMethod {
-                    name: "setComposingTextNative".into(),
-                    sig: "(JLjava/lang/String;I)Z".into(),
-                    fn_ptr: set_composing_text as *mut c_void,
-                },
-                NativeMethod {
-                    name: "setComposingRegionNative".into(),
-                    sig: "(JII)Z".into(),
-                    fn_ptr: set_composing_region as *mut c_void,
-                },
-                NativeMethod {
-                    name: "finishComposingTextNative".into(),
-                    sig: "(J)Z".into(),
-                    fn_ptr: finish_composing_text as *mut c_void,
-                },
-                NativeMethod {
-                    name: "commitTextNative".into(),
-                    sig: "(JLjava/lang/String;I)Z".into(),
-                    fn_ptr: commit_text as *mut c_void,
-                },
-                NativeMethod {
-                    name: "setSelectionNative".into(),
-                    sig: "(JII)Z".into(),
-                    fn_ptr: set_selection as *mut c_void,
-                },
-                NativeMethod {
-                    name: "performEditorActionNative".into(),
-                    sig: "(JI)Z".into(),
-                    fn_ptr: perform_editor_action as *mut c_void,
-                },
-                NativeMethod {
-                    name: "performContextMenuActionNative".into(),
-                    sig: "(JI)Z".into(),
-                    fn_ptr: perform_context_menu_action as *mut c_void,
-                },
-                NativeMethod {
-                    name: "beginBatchEditNative".into(),
-                    sig: "(J)Z".into(),
-                    fn_ptr: begin_batch_edit as *mut c_void,
-                },
-                NativeMethod {
-                    name: "endBatchEditNative".into(),
-                    sig: "(J)Z".into(),
-                    fn_ptr: end_batch_edit as *mut c_void,
-                },
-                NativeMethod {
-                    name: "inputConnectionSendKeyEventNative".into(),
-                    sig: "(JLandroid/view/KeyEvent;)Z".into(),
-                    fn_ptr: input_connection_send_key_event as *mut c_void,
-                },
-                NativeMethod {
-                    name: "inputConnectionClearMetaKeyStatesNative".into(),
-                    sig: "(JI)Z".into(),
-                    fn_ptr: input_connection_clear_meta_key_states as *mut c_void,
-                },
-                NativeMethod {
-                    name: "inputConnectionReportFullscreenModeNative".into(),
-                    sig: "(JZ)Z".into(),
-                    fn_ptr: input_connection_report_fullscreen_mode as *mut c_void,
-                },
-                NativeMethod {
-                    name: "requestCursorUpdatesNative".into(),
-                    sig: "(JI)Z".into(),
-                    fn_ptr: request_cursor_updates as *mut c_void,
-                },
-                NativeMethod {
-                    name: "closeInputConnectionNative".into(),
-                    sig: "(J)V".into(),
-                    fn_ptr: close_input_connection as *mut c_void,
-                },
-            ],
+            &[NativeMethod {
+                name: "onCreateInputConnectionNative".into(),
+                sig: "(JLandroid/view/inputmethod/EditorInfo;)Z".into(),
+                fn_ptr: no_input_connection as *mut c_void,
+            }],
         )
         .unwrap();
     });
-    env.register_native_methods(
-        class,
-        &[NativeMethod {
-            name: "newViewPeer".into(),
-            sig: "(Landroid/content/Context;)J".into(),
-            fn_ptr: new_peer as *mut c_void,
-        }],
-    )
-    .unwrap();
+}
+
+/// Register the native methods of an app's `RustView` subclass.
+/// `new_peer` implements `newViewPeer`. `extra_methods` are any other
+/// native methods the subclass declares, such as a `setDocumentUri` call
+/// from the app's own Java code; their implementations can reach the
+/// peer, given `getViewPeer()` as an argument, through [`with_peer_ctx_as`].
+///
+/// This takes a fourth argument that earlier versions didn't; apps with no
+/// extra native methods pass `Vec::new()`.
+pub fn register_view_class<'local, 'other_local>(
+    env: &mut JNIEnv<'local>,
+    class: impl Desc<'local, JClass<'other_local>>,
+    new_peer: for<'a> extern "system" fn(JNIEnv<'a>, View<'a>, Context<'a>) -> jlong,
+    extra_methods: Vec<NativeMethod>,
+) {
+    register_base_natives(env);
+    let mut methods = vec![NativeMethod {
+        name: "newViewPeer".into(),
+        sig: "(Landroid/content/Context;)J".into(),
+        fn_ptr: new_peer as *mut c_void,
+    }];
+    methods.extend(extra_methods);
+    env.register_native_methods(class, &methods).unwrap();
 }
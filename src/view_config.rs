@@ -0,0 +1,74 @@
+use jni::{JNIEnv, sys::jint};
+use num_enum::IntoPrimitive;
+
+use crate::{jni_cache::*, view::*};
+
+/// `View.LAYER_TYPE_*`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(i32)]
+pub enum LayerType {
+    None = 0,
+    Software = 1,
+    Hardware = 2,
+}
+
+/// `View.IMPORTANT_FOR_ACCESSIBILITY_*`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive)]
+#[repr(i32)]
+pub enum ImportantForAccessibility {
+    Auto = 0,
+    Yes = 1,
+    No = 2,
+    NoHideDescendants = 4,
+}
+
+/// The standard view attributes that a peer usually sets once, returned
+/// by [`ViewPeer::view_config`] and applied each time the view is
+/// attached to a window, just before [`ViewPeer::on_attached_to_window`].
+/// Attributes that are `None` are left as they are, such as set from
+/// Java or a layout file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ViewConfig {
+    pub focusable: Option<bool>,
+    pub focusable_in_touch_mode: Option<bool>,
+    /// Whether the system draws a highlight on the view when it's focused
+    /// and the peer doesn't draw its own.
+    pub default_focus_highlight_enabled: Option<bool>,
+    pub layer_type: Option<LayerType>,
+    pub important_for_accessibility: Option<ImportantForAccessibility>,
+    /// Whether the screen stays on while the view is visible.
+    pub keep_screen_on: Option<bool>,
+}
+
+impl ViewConfig {
+    pub(crate) fn apply<'local>(&self, env: &mut JNIEnv<'local>, view: &View<'local>) {
+        static APPLY_VIEW_CONFIG: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "applyViewConfig",
+            "(IIIIII)V",
+        );
+        fn flag(value: Option<bool>) -> jint {
+            value.map_or(-1, jint::from)
+        }
+        if *self == Self::default() {
+            return;
+        }
+        APPLY_VIEW_CONFIG
+            .call(
+                env,
+                &view.0,
+                &[
+                    flag(self.focusable).into(),
+                    flag(self.focusable_in_touch_mode).into(),
+                    flag(self.default_focus_highlight_enabled).into(),
+                    self.layer_type.map_or(-1, jint::from).into(),
+                    self.important_for_accessibility
+                        .map_or(-1, jint::from)
+                        .into(),
+                    flag(self.keep_screen_on).into(),
+                ],
+            )
+            .v()
+            .unwrap()
+    }
+}
@@ -0,0 +1,74 @@
+//! Typed events from the peer to listeners that the app's Java or Kotlin
+//! code registers with `RustView.addViewEventListener`, so that a
+//! ViewModel can observe the Rust view. The listener interface has a
+//! default method for each event, so Kotlin code can adapt it to a `Flow`
+//! with `callbackFlow`, removing the listener in `awaitClose`.
+//!
+//! Events are delivered on the UI thread after the current callback
+//! returns, so listeners can call back into the view.
+
+use jni::sys::jint;
+
+use crate::{callback_ctx::*, jni_cache::*};
+
+/// Emits events to the view's Java listeners, from
+/// [`CallbackCtx::view_events`].
+pub struct ViewEventEmitter<'a, 'local> {
+    ctx: &'a mut CallbackCtx<'local>,
+}
+
+impl<'local> CallbackCtx<'local> {
+    pub fn view_events(&mut self) -> ViewEventEmitter<'_, 'local> {
+        ViewEventEmitter { ctx: self }
+    }
+}
+
+impl ViewEventEmitter<'_, '_> {
+    /// The height of the view's content changed, in pixels, such as for a
+    /// host that sizes a scroll container or bottom sheet to fit it.
+    pub fn content_height_changed(&mut self, height: jint) {
+        static DISPATCH: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "dispatchContentHeightChanged",
+            "(I)V",
+        );
+        self.ctx.push_dynamic_deferred_callback(move |env, view| {
+            DISPATCH.call(env, &view.0, &[height.into()]).v().unwrap();
+        });
+    }
+
+    /// The selection in the view's text changed. Offsets are in UTF-16
+    /// code units, as Java's strings are.
+    pub fn selection_changed(&mut self, start: jint, end: jint) {
+        static DISPATCH: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "dispatchSelectionChanged",
+            "(II)V",
+        );
+        self.ctx.push_dynamic_deferred_callback(move |env, view| {
+            DISPATCH
+                .call(env, &view.0, &[start.into(), end.into()])
+                .v()
+                .unwrap();
+        });
+    }
+
+    /// The user asked for an action that the host handles, such as
+    /// navigating to another screen or sharing content. The action's name
+    /// is up to the app.
+    pub fn action_requested(&mut self, action: &str) {
+        static DISPATCH: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "dispatchActionRequested",
+            "(Ljava/lang/String;)V",
+        );
+        let action = action.to_string();
+        self.ctx.push_dynamic_deferred_callback(move |env, view| {
+            let action = new_java_string(env, &action);
+            DISPATCH
+                .call(env, &view.0, &[(&action).into()])
+                .v()
+                .unwrap();
+        });
+    }
+}
@@ -0,0 +1,52 @@
+use jni::{JNIEnv, objects::JString, sys::jlong};
+use std::{collections::BTreeMap, sync::Mutex};
+
+use crate::{context::*, native_methods::*, view::*};
+
+/// Creates the peer for a view constructed by name, and returns the ID
+/// from [`register_view_peer`].
+pub type ViewFactory = for<'a> fn(&mut JNIEnv<'a>, &View<'a>, &Context<'a>) -> jlong;
+
+static FACTORIES: Mutex<BTreeMap<String, ViewFactory>> = Mutex::new(BTreeMap::new());
+
+native_methods! {
+    fn factory_methods;
+
+    "newFactoryViewPeer" => fn new_factory_view_peer<'local>(
+        env: JNIEnv<'local>,
+        view: View<'local>,
+        context: Context<'local>,
+        name: JString<'local>,
+    ) -> jlong {
+        let name: String = env.get_string(&name).unwrap().into();
+        let factory = *FACTORIES
+            .lock()
+            .unwrap()
+            .get(&name)
+            .unwrap_or_else(|| panic!("no view factory registered as {name:?}"));
+        factory(&mut env, &view, &context)
+    }
+}
+
+/// Register a peer constructor under a name, so views can be created
+/// with the Java `RustViewFactory.create(context, name)` without
+/// declaring a `RustView` subclass. This suits hosts that construct
+/// views from a factory function, such as Jetpack Compose's
+/// `AndroidView`.
+///
+/// Note that hosts which recycle views may detach a view and attach it
+/// again later. As with any Rust view, the peer is dropped when the view
+/// is detached, so such hosts should create a new view rather than
+/// reusing a detached one.
+pub fn register_view_factory(env: &mut JNIEnv, name: &str, factory: ViewFactory) {
+    let mut factories = FACTORIES.lock().unwrap();
+    if factories.is_empty() {
+        env.register_native_methods(
+            "org/linebender/android/rustview/RustViewFactory$FactoryView",
+            &factory_methods(),
+        )
+        .unwrap();
+        register_base_natives(env);
+    }
+    factories.insert(name.into(), factory);
+}
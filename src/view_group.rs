@@ -0,0 +1,355 @@
+use jni::{
+    JNIEnv, NativeMethod,
+    descriptors::Desc,
+    objects::{JClass, JIntArray, JObject},
+    sys::{JNI_TRUE, jboolean, jint, jlong},
+};
+use send_wrapper::SendWrapper;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    ffi::c_void,
+    rc::Rc,
+    sync::{
+        Mutex, Once,
+        atomic::{AtomicI64, Ordering},
+    },
+};
+
+use crate::{context::*, jni_cache::*, layout::*, native_methods::*};
+
+/// A container view whose platform child views are measured and laid out
+/// by a Rust [`ViewGroupPeer`]. This wraps an instance of the Java
+/// `RustViewGroup` class.
+#[repr(transparent)]
+pub struct ViewGroup<'local>(pub JObject<'local>);
+
+impl<'local> ViewGroup<'local> {
+    pub fn child_count(&self, env: &mut JNIEnv<'local>) -> jint {
+        static GET_CHILD_COUNT: CachedMethod =
+            CachedMethod::new("android/view/ViewGroup", "getChildCount", "()I");
+        GET_CHILD_COUNT.call(env, &self.0, &[]).i().unwrap()
+    }
+
+    /// The child at `index`, or `None` if `index` is out of range.
+    pub fn child_at(&self, env: &mut JNIEnv<'local>, index: jint) -> Option<JObject<'local>> {
+        static GET_CHILD_AT: CachedMethod = CachedMethod::new(
+            "android/view/ViewGroup",
+            "getChildAt",
+            "(I)Landroid/view/View;",
+        );
+        let child = GET_CHILD_AT
+            .call(env, &self.0, &[index.into()])
+            .l()
+            .unwrap();
+        (!child.is_null()).then_some(child)
+    }
+
+    /// Add a platform view as a child at the given index, or at the end
+    /// if `index` is -1.
+    pub fn add_view(
+        &self,
+        env: &mut JNIEnv<'local>,
+        child: &JObject,
+        index: jint,
+        params: &LayoutParams,
+    ) {
+        static ADD_VIEW: CachedMethod = CachedMethod::new(
+            "android/view/ViewGroup",
+            "addView",
+            "(Landroid/view/View;ILandroid/view/ViewGroup$LayoutParams;)V",
+        );
+        ADD_VIEW
+            .call(
+                env,
+                &self.0,
+                &[child.into(), index.into(), (&params.0).into()],
+            )
+            .v()
+            .unwrap()
+    }
+
+    pub fn remove_view(&self, env: &mut JNIEnv<'local>, child: &JObject) {
+        static REMOVE_VIEW: CachedMethod = CachedMethod::new(
+            "android/view/ViewGroup",
+            "removeView",
+            "(Landroid/view/View;)V",
+        );
+        REMOVE_VIEW.call(env, &self.0, &[child.into()]).v().unwrap()
+    }
+
+    pub fn remove_view_at(&self, env: &mut JNIEnv<'local>, index: jint) {
+        static REMOVE_VIEW_AT: CachedMethod =
+            CachedMethod::new("android/view/ViewGroup", "removeViewAt", "(I)V");
+        REMOVE_VIEW_AT
+            .call(env, &self.0, &[index.into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn remove_all_views(&self, env: &mut JNIEnv<'local>) {
+        static REMOVE_ALL_VIEWS: CachedMethod =
+            CachedMethod::new("android/view/ViewGroup", "removeAllViews", "()V");
+        REMOVE_ALL_VIEWS.call(env, &self.0, &[]).v().unwrap()
+    }
+
+    /// Measure a child with explicit measure specs. Call this from
+    /// [`ViewGroupPeer::on_measure_children`].
+    pub fn measure_child(
+        &self,
+        env: &mut JNIEnv<'local>,
+        child: &JObject,
+        width_spec: MeasureSpec,
+        height_spec: MeasureSpec,
+    ) {
+        static MEASURE: CachedMethod = CachedMethod::new("android/view/View", "measure", "(II)V");
+        MEASURE
+            .call(env, child, &[width_spec.0.into(), height_spec.0.into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Measure a child using this group's measure specs and the child's
+    /// own layout params, like the platform's `ViewGroup.measureChild`.
+    pub fn measure_child_with_layout_params(
+        &self,
+        env: &mut JNIEnv<'local>,
+        child: &JObject,
+        parent_width_spec: MeasureSpec,
+        parent_height_spec: MeasureSpec,
+    ) {
+        static MEASURE_CHILD: CachedMethod = CachedMethod::new(
+            "android/view/ViewGroup",
+            "measureChild",
+            "(Landroid/view/View;II)V",
+        );
+        MEASURE_CHILD
+            .call(
+                env,
+                &self.0,
+                &[
+                    child.into(),
+                    parent_width_spec.0.into(),
+                    parent_height_spec.0.into(),
+                ],
+            )
+            .v()
+            .unwrap()
+    }
+
+    /// Return the width and height of a child as determined by its most
+    /// recent measurement.
+    pub fn child_measured_size(&self, env: &mut JNIEnv<'local>, child: &JObject) -> (jint, jint) {
+        static GET_MEASURED_WIDTH: CachedMethod =
+            CachedMethod::new("android/view/View", "getMeasuredWidth", "()I");
+        static GET_MEASURED_HEIGHT: CachedMethod =
+            CachedMethod::new("android/view/View", "getMeasuredHeight", "()I");
+        let width = GET_MEASURED_WIDTH.call(env, child, &[]).i().unwrap();
+        let height = GET_MEASURED_HEIGHT.call(env, child, &[]).i().unwrap();
+        (width, height)
+    }
+
+    /// Return the child's measured state bits, suitable for combining
+    /// and passing as the `child_state` of [`resolve_size_and_state`].
+    pub fn child_measured_state(&self, env: &mut JNIEnv<'local>, child: &JObject) -> jint {
+        static GET_MEASURED_STATE: CachedMethod =
+            CachedMethod::new("android/view/View", "getMeasuredState", "()I");
+        GET_MEASURED_STATE.call(env, child, &[]).i().unwrap()
+    }
+
+    /// Position a child within this group. Call this from
+    /// [`ViewGroupPeer::on_layout_children`]. Coordinates are relative
+    /// to this group.
+    pub fn layout_child(
+        &self,
+        env: &mut JNIEnv<'local>,
+        child: &JObject,
+        left: jint,
+        top: jint,
+        right: jint,
+        bottom: jint,
+    ) {
+        static LAYOUT: CachedMethod = CachedMethod::new("android/view/View", "layout", "(IIII)V");
+        LAYOUT
+            .call(
+                env,
+                child,
+                &[left.into(), top.into(), right.into(), bottom.into()],
+            )
+            .v()
+            .unwrap()
+    }
+
+    pub fn request_layout(&self, env: &mut JNIEnv<'local>) {
+        static REQUEST_LAYOUT: CachedMethod =
+            CachedMethod::new("android/view/View", "requestLayout", "()V");
+        REQUEST_LAYOUT.call(env, &self.0, &[]).v().unwrap()
+    }
+}
+
+#[allow(unused_variables)]
+pub trait ViewGroupPeer {
+    /// Measure the children, typically by calling
+    /// [`ViewGroup::measure_child`] for each of them, and return the
+    /// measured width and height of the group, or `None` to measure all
+    /// children with their layout params and use the platform's default
+    /// size.
+    fn on_measure_children<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        group: &ViewGroup<'local>,
+        width_spec: MeasureSpec,
+        height_spec: MeasureSpec,
+    ) -> Option<(jint, jint)> {
+        None
+    }
+
+    /// Position the children by calling [`ViewGroup::layout_child`]
+    /// for each of them.
+    #[allow(clippy::too_many_arguments)]
+    fn on_layout_children<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        group: &ViewGroup<'local>,
+        changed: bool,
+        left: jint,
+        top: jint,
+        right: jint,
+        bottom: jint,
+    );
+
+    fn on_attached_to_window<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        group: &ViewGroup<'local>,
+    ) {
+    }
+
+    fn on_detached_from_window<'local>(
+        &mut self,
+        env: &mut JNIEnv<'local>,
+        group: &ViewGroup<'local>,
+    ) {
+    }
+}
+
+type GroupPeerRef = SendWrapper<Rc<RefCell<Box<dyn ViewGroupPeer>>>>;
+
+static NEXT_GROUP_PEER_ID: AtomicI64 = AtomicI64::new(0);
+static GROUP_PEER_MAP: Mutex<BTreeMap<jlong, GroupPeerRef>> = Mutex::new(BTreeMap::new());
+
+fn with_group_peer<'local, F, T: Default>(
+    mut env: JNIEnv<'local>,
+    group: ViewGroup<'local>,
+    id: jlong,
+    f: F,
+) -> T
+where
+    F: FnOnce(&mut JNIEnv<'local>, &ViewGroup<'local>, &mut dyn ViewGroupPeer) -> T,
+{
+    let map = GROUP_PEER_MAP.lock().unwrap();
+    let Some(peer) = map.get(&id) else {
+        return T::default();
+    };
+    #[cfg(feature = "thread-checks")]
+    crate::thread_checks::check_peer_thread(id, peer.valid());
+    let peer = Rc::clone(&**peer);
+    drop(map);
+    let mut peer = peer.borrow_mut();
+    f(&mut env, &group, &mut **peer)
+}
+
+native_methods! {
+    fn group_methods;
+
+    "onMeasureNative" => fn on_measure<'local>(
+        env: JNIEnv<'local>,
+        group: ViewGroup<'local>,
+        peer: jlong,
+        width_spec: jint,
+        height_spec: jint,
+    ) -> JIntArray<'local> {
+        with_group_peer(env, group, peer, |env, group, peer| {
+            let width_spec = MeasureSpec(width_spec);
+            let height_spec = MeasureSpec(height_spec);
+            if let Some((width, height)) =
+                peer.on_measure_children(env, group, width_spec, height_spec)
+            {
+                let result = env.new_int_array(2).unwrap();
+                env.set_int_array_region(&result, 0, &[width, height]).unwrap();
+                result
+            } else {
+                JObject::null().into()
+            }
+        })
+    }
+
+    "onLayoutNative" => fn on_layout<'local>(
+        env: JNIEnv<'local>,
+        group: ViewGroup<'local>,
+        peer: jlong,
+        changed: jboolean,
+        left: jint,
+        top: jint,
+        right: jint,
+        bottom: jint,
+    ) {
+        with_group_peer(env, group, peer, |env, group, peer| {
+            peer.on_layout_children(env, group, changed == JNI_TRUE, left, top, right, bottom);
+        })
+    }
+
+    "onAttachedToWindowNative" => fn on_attached_to_window<'local>(
+        env: JNIEnv<'local>,
+        group: ViewGroup<'local>,
+        peer: jlong,
+    ) {
+        with_group_peer(env, group, peer, |env, group, peer| {
+            peer.on_attached_to_window(env, group);
+        })
+    }
+
+    "onDetachedFromWindowNative" => fn on_detached_from_window<'local>(
+        env: JNIEnv<'local>,
+        group: ViewGroup<'local>,
+        peer: jlong,
+    ) {
+        let mut map = GROUP_PEER_MAP.lock().unwrap();
+        let peer = map.remove(&peer).unwrap();
+        drop(map);
+        peer.borrow_mut().on_detached_from_window(&mut env, &group);
+    }
+}
+
+pub fn register_view_group_peer(peer: impl 'static + ViewGroupPeer) -> jlong {
+    #[cfg(feature = "thread-checks")]
+    crate::thread_checks::record_ui_thread();
+    let id = NEXT_GROUP_PEER_ID.fetch_add(1, Ordering::Relaxed);
+    let mut map = GROUP_PEER_MAP.lock().unwrap();
+    map.insert(id, SendWrapper::new(Rc::new(RefCell::new(Box::new(peer)))));
+    id
+}
+
+pub fn register_view_group_class<'local, 'other_local>(
+    env: &mut JNIEnv<'local>,
+    class: impl Desc<'local, JClass<'other_local>>,
+    new_peer: for<'a> extern "system" fn(JNIEnv<'a>, ViewGroup<'a>, Context<'a>) -> jlong,
+) {
+    static REGISTER_BASE_NATIVES: Once = Once::new();
+    REGISTER_BASE_NATIVES.call_once(|| {
+        env.register_native_methods(
+            "org/linebender/android/rustview/RustViewGroup",
+            &group_methods(),
+        )
+        .unwrap();
+    });
+    env.register_native_methods(
+        class,
+        &[NativeMethod {
+            name: "newViewGroupPeer".into(),
+            sig: "(Landroid/content/Context;)J".into(),
+            fn_ptr: new_peer as *mut c_void,
+        }],
+    )
+    .unwrap();
+}
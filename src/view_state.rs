@@ -0,0 +1,113 @@
+use jni::{
+    JNIEnv,
+    objects::JString,
+    sys::{jfloat, jint, jlong},
+};
+use num_enum::FromPrimitive;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{callback_ctx::*, jni_cache::*, native_methods::*, view::*};
+
+/// A snapshot of commonly used view properties, fetched from Java in a
+/// single call by [`CallbackCtx::view_state`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewState {
+    pub width: jint,
+    pub height: jint,
+    /// The view's own visibility.
+    pub visibility: Visibility,
+    pub window_visibility: Visibility,
+    pub is_focused: bool,
+    pub has_window_focus: bool,
+    pub is_attached_to_window: bool,
+    /// The display density, in pixels per density-independent pixel.
+    pub density: jfloat,
+    /// The user's font scale, 1.0 by default.
+    pub font_scale: jfloat,
+    /// The BCP 47 language tag of the user's primary locale.
+    pub locale: String,
+}
+
+// Must match the encoding in RustView.getViewState.
+const FIELD_COUNT: usize = 7;
+const FLAG_FOCUSED: jint = 1 << 0;
+const FLAG_WINDOW_FOCUS: jint = 1 << 1;
+const FLAG_ATTACHED: jint = 1 << 2;
+
+impl ViewState {
+    fn fetch<'local>(env: &mut JNIEnv<'local>, view: &View<'local>) -> Self {
+        static GET_VIEW_STATE: CachedMethod = CachedMethod::new(
+            "org/linebender/android/rustview/RustView",
+            "getViewState",
+            "([I)Ljava/lang/String;",
+        );
+        let array = env.new_int_array(FIELD_COUNT as jint).unwrap();
+        let locale: JString = GET_VIEW_STATE
+            .call(env, &view.0, &[(&array).into()])
+            .l()
+            .unwrap()
+            .into();
+        let mut data = [0; FIELD_COUNT];
+        env.get_int_array_region(&array, 0, &mut data).unwrap();
+        Self {
+            width: data[0],
+            height: data[1],
+            visibility: Visibility::from_primitive(data[2]),
+            window_visibility: Visibility::from_primitive(data[3]),
+            is_focused: data[4] & FLAG_FOCUSED != 0,
+            has_window_focus: data[4] & FLAG_WINDOW_FOCUS != 0,
+            is_attached_to_window: data[4] & FLAG_ATTACHED != 0,
+            density: f32::from_bits(data[5] as u32),
+            font_scale: f32::from_bits(data[6] as u32),
+            locale: env.get_string(&locale).unwrap().into(),
+        }
+    }
+}
+
+thread_local! {
+    // Renderers tend to check the same handful of properties every frame,
+    // so we keep the last snapshot for each peer until one of the
+    // callbacks that can change it arrives.
+    static VIEW_STATE_CACHE: RefCell<BTreeMap<jlong, Rc<ViewState>>> =
+        const { RefCell::new(BTreeMap::new()) };
+}
+
+pub(crate) fn invalidate_view_state(peer: jlong) {
+    VIEW_STATE_CACHE.with_borrow_mut(|cache| cache.remove(&peer));
+}
+
+impl CallbackCtx<'_> {
+    /// The view's current [`ViewState`]. The snapshot is cached until the
+    /// view is resized, changes focus or visibility, is attached or
+    /// detached, or its configuration changes, so calling this every
+    /// frame is cheap.
+    pub fn view_state(&mut self) -> Rc<ViewState> {
+        let peer = self.peer_handle().id();
+        if let Some(state) = VIEW_STATE_CACHE.with_borrow(|cache| cache.get(&peer).cloned()) {
+            return state;
+        }
+        let state = Rc::new(ViewState::fetch(&mut self.env, &self.view));
+        VIEW_STATE_CACHE.with_borrow_mut(|cache| cache.insert(peer, Rc::clone(&state)));
+        state
+    }
+}
+
+native_methods! {
+    fn view_state_methods;
+
+    "invalidateViewStateNative" => fn invalidate_view_state_native<'local>(
+        _env: JNIEnv<'local>,
+        _view: View<'local>,
+        peer: jlong,
+    ) {
+        invalidate_view_state(peer);
+    }
+}
+
+pub(crate) fn register_natives(env: &mut JNIEnv) {
+    env.register_native_methods(
+        "org/linebender/android/rustview/RustView",
+        &view_state_methods(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,189 @@
+//! An adapter, enabled by the `raw-window-handle` feature, for hosting
+//! renderers and event handling written against winit's Android backend in
+//! a view that lives anywhere in an Android view hierarchy.
+//!
+//! [`ViewWindow`] wraps the view's surface and implements the
+//! `raw-window-handle` 0.6 traits, so it can be passed to crates such as
+//! `wgpu` and `glutin` wherever they take a winit window. [`WindowAdapter`]
+//! reads an [`EventStream`] and translates its events into
+//! [`WindowEvent`]s, which follow winit's Android lifecycle: the window is
+//! only usable between [`WindowEvent::Resumed`] and
+//! [`WindowEvent::Suspended`], which here track the view's surface rather
+//! than the activity. winit's own event types aren't used, since they can't
+//! be constructed outside winit, but the variants are named after theirs so
+//! that porting an event loop is mechanical.
+
+use ndk::native_window::NativeWindow;
+use raw_window_handle::{
+    AndroidDisplayHandle, AndroidNdkWindowHandle, DisplayHandle, HandleError, HasDisplayHandle,
+    HasWindowHandle, RawDisplayHandle, RawWindowHandle, WindowHandle,
+};
+use std::sync::mpsc::RecvError;
+use ui_events::{keyboard::KeyboardEvent, pointer::PointerEvent};
+
+#[cfg(feature = "ime")]
+use crate::composition::*;
+use crate::event_stream::*;
+
+/// The view's surface, as a window for renderers.
+#[derive(Clone, Debug)]
+pub struct ViewWindow {
+    window: NativeWindow,
+}
+
+impl ViewWindow {
+    pub fn new(window: NativeWindow) -> Self {
+        Self { window }
+    }
+
+    pub fn native_window(&self) -> &NativeWindow {
+        &self.window
+    }
+
+    /// The size of the surface in physical pixels, like winit's
+    /// `Window::inner_size`.
+    pub fn inner_size(&self) -> dpi::PhysicalSize<u32> {
+        dpi::PhysicalSize::new(self.window.width() as u32, self.window.height() as u32)
+    }
+}
+
+impl HasWindowHandle for ViewWindow {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        let handle = AndroidNdkWindowHandle::new(self.window.ptr().cast());
+        // SAFETY: The window is reference counted, and `self` holds a
+        // reference for as long as the handle borrows it.
+        Ok(unsafe { WindowHandle::borrow_raw(RawWindowHandle::AndroidNdk(handle)) })
+    }
+}
+
+impl HasDisplayHandle for ViewWindow {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        // SAFETY: The Android display handle has no data to outlive.
+        Ok(unsafe {
+            DisplayHandle::borrow_raw(RawDisplayHandle::Android(AndroidDisplayHandle::new()))
+        })
+    }
+}
+
+/// An event translated from a [`ViewEvent`], named after the winit event
+/// it corresponds to.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WindowEvent {
+    /// The surface was created, and the window can be rendered to. This
+    /// corresponds to winit's `ApplicationHandler::resumed`.
+    Resumed(ViewWindow),
+    /// The surface is gone, and rendering resources tied to the window
+    /// should be dropped. This corresponds to winit's
+    /// `ApplicationHandler::suspended`.
+    Suspended,
+    /// The surface was resized, in physical pixels.
+    Resized(dpi::PhysicalSize<u32>),
+    /// The view gained or lost input focus.
+    Focused(bool),
+    /// A pointer event, as `ui-events` gives them to winit-based apps
+    /// through `ui-events-winit`.
+    Pointer(PointerEvent),
+    Keyboard(KeyboardEvent),
+    #[cfg(feature = "ime")]
+    Ime {
+        command: ImeCommand,
+        state: CompositionState,
+    },
+    /// The view was detached from its window, and its peer is gone. This
+    /// corresponds to winit's `WindowEvent::Destroyed`.
+    Destroyed,
+}
+
+/// Translates an [`EventStream`] into [`WindowEvent`]s.
+pub struct WindowAdapter {
+    stream: EventStream,
+    window: Option<ViewWindow>,
+}
+
+impl WindowAdapter {
+    pub fn new(stream: EventStream) -> Self {
+        Self {
+            stream,
+            window: None,
+        }
+    }
+
+    /// The current window, between [`WindowEvent::Resumed`] and
+    /// [`WindowEvent::Suspended`].
+    pub fn window(&self) -> Option<&ViewWindow> {
+        self.window.as_ref()
+    }
+
+    /// The underlying stream, such as for
+    /// [`EventStream::set_ime_state`].
+    pub fn stream(&self) -> &EventStream {
+        &self.stream
+    }
+
+    /// Translate one event, or return `None` if it has no counterpart.
+    pub fn translate(&mut self, event: ViewEvent) -> Option<WindowEvent> {
+        match event {
+            ViewEvent::SurfaceCreated(window) => {
+                let window = ViewWindow::new(window);
+                self.window = Some(window.clone());
+                Some(WindowEvent::Resumed(window))
+            }
+            ViewEvent::SurfaceChanged {
+                window,
+                width,
+                height,
+                ..
+            } => {
+                // A surface can be replaced without being destroyed first,
+                // which a winit app sees as a new window.
+                if self.window.as_ref().map(|w| w.window.ptr()) != Some(window.ptr()) {
+                    let window = ViewWindow::new(window);
+                    self.window = Some(window.clone());
+                    return Some(WindowEvent::Resumed(window));
+                }
+                Some(WindowEvent::Resized(dpi::PhysicalSize::new(
+                    width as u32,
+                    height as u32,
+                )))
+            }
+            ViewEvent::SurfaceDestroyed => {
+                self.window = None;
+                Some(WindowEvent::Suspended)
+            }
+            ViewEvent::Focus { gained } => Some(WindowEvent::Focused(gained)),
+            ViewEvent::Pointer(event) => Some(WindowEvent::Pointer(event)),
+            ViewEvent::Key(event) => Some(WindowEvent::Keyboard(event)),
+            #[cfg(feature = "ime")]
+            ViewEvent::Ime { command, state } => Some(WindowEvent::Ime { command, state }),
+            ViewEvent::Detached => {
+                self.window = None;
+                Some(WindowEvent::Destroyed)
+            }
+            ViewEvent::Attached | ViewEvent::SizeChanged { .. } | ViewEvent::WindowFocus { .. } => {
+                None
+            }
+        }
+    }
+
+    /// Wait for the next event that has a counterpart. Returns an error
+    /// once the peer has been dropped and all of its events have been
+    /// received.
+    pub fn recv(&mut self) -> Result<WindowEvent, RecvError> {
+        loop {
+            let event = self.stream.recv()?;
+            if let Some(event) = self.translate(event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Translate the events that have already arrived, without waiting.
+    pub fn pending(&mut self) -> Vec<WindowEvent> {
+        let events = self.stream.pending().collect::<Vec<_>>();
+        events
+            .into_iter()
+            .filter_map(|event| self.translate(event))
+            .collect()
+    }
+}
@@ -0,0 +1,189 @@
+use jni::{JNIEnv, objects::JObject, sys::jint};
+
+use crate::{binder::*, context::*, jni_cache::*};
+
+/// A panel, such as a dropdown, that appears on top of its attached
+/// application window.
+pub const WINDOW_TYPE_APPLICATION_PANEL: jint = 1000;
+/// A panel displayed on top of another panel, such as a submenu.
+pub const WINDOW_TYPE_APPLICATION_SUB_PANEL: jint = 1002;
+pub const WINDOW_TYPE_APPLICATION_ATTACHED_DIALOG: jint = 1003;
+
+pub const WINDOW_FLAG_NOT_FOCUSABLE: jint = 0x00000008;
+pub const WINDOW_FLAG_NOT_TOUCHABLE: jint = 0x00000010;
+pub const WINDOW_FLAG_NOT_TOUCH_MODAL: jint = 0x00000020;
+pub const WINDOW_FLAG_LAYOUT_NO_LIMITS: jint = 0x00000200;
+pub const WINDOW_FLAG_ALT_FOCUSABLE_IM: jint = 0x00020000;
+pub const WINDOW_FLAG_WATCH_OUTSIDE_TOUCH: jint = 0x00040000;
+
+pub const GRAVITY_LEFT: jint = 0x03;
+pub const GRAVITY_TOP: jint = 0x30;
+
+/// A binding to `WindowManager.LayoutParams`, which describes a window
+/// added with [`WindowManager::add_view`].
+#[repr(transparent)]
+pub struct WindowLayoutParams<'local>(pub JObject<'local>);
+
+impl<'local> WindowLayoutParams<'local> {
+    /// Create params for a window with the given size, which can be a
+    /// size in pixels or one of the `LAYOUT_PARAMS_*` constants, one of
+    /// the `WINDOW_TYPE_*` constants, `WINDOW_FLAG_*` flags, and one of
+    /// the `PIXEL_FORMAT_*` constants. The window is positioned relative
+    /// to the top left of the screen.
+    pub fn new(
+        env: &mut JNIEnv<'local>,
+        width: jint,
+        height: jint,
+        window_type: jint,
+        flags: jint,
+        format: jint,
+    ) -> Self {
+        let params = Self(new_java_object(
+            env,
+            "android/view/WindowManager$LayoutParams",
+            "(IIIII)V",
+            &[
+                width.into(),
+                height.into(),
+                window_type.into(),
+                flags.into(),
+                format.into(),
+            ],
+        ));
+        params.set_gravity(env, GRAVITY_TOP | GRAVITY_LEFT);
+        params
+    }
+
+    pub fn set_size(&self, env: &mut JNIEnv<'local>, width: jint, height: jint) {
+        static WIDTH: CachedField =
+            CachedField::new("android/view/ViewGroup$LayoutParams", "width", "I");
+        static HEIGHT: CachedField =
+            CachedField::new("android/view/ViewGroup$LayoutParams", "height", "I");
+        WIDTH.set(env, &self.0, width.into());
+        HEIGHT.set(env, &self.0, height.into());
+    }
+
+    /// Set the position of the window, in pixels, relative to the edges
+    /// given by the gravity.
+    pub fn set_position(&self, env: &mut JNIEnv<'local>, x: jint, y: jint) {
+        static X: CachedField =
+            CachedField::new("android/view/WindowManager$LayoutParams", "x", "I");
+        static Y: CachedField =
+            CachedField::new("android/view/WindowManager$LayoutParams", "y", "I");
+        X.set(env, &self.0, x.into());
+        Y.set(env, &self.0, y.into());
+    }
+
+    pub fn set_gravity(&self, env: &mut JNIEnv<'local>, gravity: jint) {
+        static GRAVITY: CachedField =
+            CachedField::new("android/view/WindowManager$LayoutParams", "gravity", "I");
+        GRAVITY.set(env, &self.0, gravity.into());
+    }
+
+    pub fn flags(&self, env: &mut JNIEnv<'local>) -> jint {
+        static FLAGS: CachedField =
+            CachedField::new("android/view/WindowManager$LayoutParams", "flags", "I");
+        FLAGS.get(env, &self.0).i().unwrap()
+    }
+
+    pub fn set_flags(&self, env: &mut JNIEnv<'local>, flags: jint) {
+        static FLAGS: CachedField =
+            CachedField::new("android/view/WindowManager$LayoutParams", "flags", "I");
+        FLAGS.set(env, &self.0, flags.into());
+    }
+
+    /// Attach the window to the window identified by `token`, which for
+    /// the panel types must be the [`crate::View::window_token`] of a view
+    /// in the parent window.
+    pub fn set_token(&self, env: &mut JNIEnv<'local>, token: &IBinder<'local>) {
+        static TOKEN: CachedField = CachedField::new(
+            "android/view/WindowManager$LayoutParams",
+            "token",
+            "Landroid/os/IBinder;",
+        );
+        TOKEN.set(env, &self.0, (&token.0).into());
+    }
+
+    pub fn set_title(&self, env: &mut JNIEnv<'local>, title: &str) {
+        static SET_TITLE: CachedMethod = CachedMethod::new(
+            "android/view/WindowManager$LayoutParams",
+            "setTitle",
+            "(Ljava/lang/CharSequence;)V",
+        );
+        let title = new_java_string(env, title);
+        SET_TITLE
+            .call(env, &self.0, &[(&title).into()])
+            .v()
+            .unwrap()
+    }
+}
+
+/// A binding to `WindowManager`, for showing a view, such as another
+/// Rust view, in a separate window above the app's window.
+#[repr(transparent)]
+pub struct WindowManager<'local>(pub JObject<'local>);
+
+impl<'local> WindowManager<'local> {
+    pub fn new(env: &mut JNIEnv<'local>, context: &Context<'local>) -> Self {
+        Self(context.system_service(env, WINDOW_SERVICE))
+    }
+
+    pub fn add_view(
+        &self,
+        env: &mut JNIEnv<'local>,
+        view: &JObject,
+        params: &WindowLayoutParams<'local>,
+    ) {
+        static ADD_VIEW: CachedMethod = CachedMethod::new(
+            "android/view/ViewManager",
+            "addView",
+            "(Landroid/view/View;Landroid/view/ViewGroup$LayoutParams;)V",
+        );
+        ADD_VIEW
+            .call(env, &self.0, &[view.into(), (&params.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    /// Move or resize a window previously added with
+    /// [`WindowManager::add_view`].
+    pub fn update_view_layout(
+        &self,
+        env: &mut JNIEnv<'local>,
+        view: &JObject,
+        params: &WindowLayoutParams<'local>,
+    ) {
+        static UPDATE_VIEW_LAYOUT: CachedMethod = CachedMethod::new(
+            "android/view/ViewManager",
+            "updateViewLayout",
+            "(Landroid/view/View;Landroid/view/ViewGroup$LayoutParams;)V",
+        );
+        UPDATE_VIEW_LAYOUT
+            .call(env, &self.0, &[view.into(), (&params.0).into()])
+            .v()
+            .unwrap()
+    }
+
+    pub fn remove_view(&self, env: &mut JNIEnv<'local>, view: &JObject) {
+        static REMOVE_VIEW: CachedMethod = CachedMethod::new(
+            "android/view/ViewManager",
+            "removeView",
+            "(Landroid/view/View;)V",
+        );
+        REMOVE_VIEW.call(env, &self.0, &[view.into()]).v().unwrap()
+    }
+
+    /// Like [`WindowManager::remove_view`], but detaches the view
+    /// before returning, rather than on a later pass of the event loop.
+    pub fn remove_view_immediate(&self, env: &mut JNIEnv<'local>, view: &JObject) {
+        static REMOVE_VIEW_IMMEDIATE: CachedMethod = CachedMethod::new(
+            "android/view/WindowManager",
+            "removeViewImmediate",
+            "(Landroid/view/View;)V",
+        );
+        REMOVE_VIEW_IMMEDIATE
+            .call(env, &self.0, &[view.into()])
+            .v()
+            .unwrap()
+    }
+}